@@ -0,0 +1,143 @@
+//! Builds a procedure crate for `wasm32-unknown-unknown` and optimizes the
+//! result with `wasm-opt`.
+
+use std::env;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::json::{self, Value};
+
+/// The target procedures are built for.
+pub const TARGET: &str = "wasm32-unknown-unknown";
+
+/// How to build.
+#[derive(Debug, Default)]
+pub struct BuildOptions {
+    pub manifest_path: Option<PathBuf>,
+    pub package: Option<String>,
+    /// Skip `wasm-opt`.
+    pub no_opt: bool,
+}
+
+/// Builds the crate in release mode and returns the path of its module,
+/// optimized unless `no_opt` is set or `wasm-opt` is not installed.
+pub fn build(options: &BuildOptions) -> Result<PathBuf, String> {
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".into());
+    let mut command = Command::new(cargo);
+    command.args([
+        "build",
+        "--release",
+        "--target",
+        TARGET,
+        "--message-format=json-render-diagnostics",
+    ]);
+    if let Some(path) = &options.manifest_path {
+        command.arg("--manifest-path").arg(path);
+    }
+    if let Some(package) = &options.package {
+        command.args(["--package", package]);
+    }
+
+    let mut child = command
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to run cargo: {e}"))?;
+
+    // The last cdylib artifact is the crate's own; dependencies come first
+    let mut module = None;
+    let stdout = child.stdout.take().expect("stdout is piped");
+    for line in BufReader::new(stdout).lines() {
+        let line = line.map_err(|e| format!("failed to read cargo output: {e}"))?;
+        if let Ok(message) = json::parse(&line) {
+            if let Some(path) = cdylib_artifact(&message) {
+                module = Some(path);
+            }
+        }
+    }
+
+    let status = child
+        .wait()
+        .map_err(|e| format!("failed to run cargo: {e}"))?;
+    if !status.success() {
+        return Err(format!(
+            "cargo build failed; is the target installed? (rustup target add {TARGET})"
+        ));
+    }
+    let module = module
+        .ok_or("the build produced no .wasm module; set `crate-type = [\"cdylib\"]` under [lib]")?;
+
+    if options.no_opt {
+        return Ok(module);
+    }
+    optimize(&module)
+}
+
+/// Returns the `.wasm` file of a cdylib `compiler-artifact` message.
+fn cdylib_artifact(message: &Value) -> Option<PathBuf> {
+    if message.get("reason")?.as_str()? != "compiler-artifact" {
+        return None;
+    }
+    let kinds = message.get("target")?.get("kind")?.as_array();
+    if !kinds.iter().any(|k| k.as_str() == Some("cdylib")) {
+        return None;
+    }
+    message
+        .get("filenames")?
+        .as_array()
+        .iter()
+        .filter_map(Value::as_str)
+        .find(|f| f.ends_with(".wasm"))
+        .map(PathBuf::from)
+}
+
+/// Runs `wasm-opt -Oz`, writing `name.opt.wasm` next to the module. Custom
+/// sections, including the procedure signatures, are kept.
+fn optimize(module: &Path) -> Result<PathBuf, String> {
+    let output = module.with_extension("opt.wasm");
+    let status = Command::new("wasm-opt")
+        .arg("-Oz")
+        .arg(module)
+        .arg("-o")
+        .arg(&output)
+        .status();
+
+    match status {
+        Ok(status) if status.success() => Ok(output),
+        Ok(_) => Err("wasm-opt failed".into()),
+        Err(_) => {
+            eprintln!(
+                "warning: wasm-opt not found; deploying the unoptimized module \
+                 (install binaryen, or pass --no-opt to silence this)"
+            );
+            Ok(module.to_path_buf())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cdylib_artifact() {
+        let message = json::parse(
+            r#"{"reason":"compiler-artifact","target":{"kind":["cdylib"]},
+                "filenames":["/t/release/discount.d","/t/release/discount.wasm"]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            cdylib_artifact(&message),
+            Some(PathBuf::from("/t/release/discount.wasm"))
+        );
+
+        let rlib = json::parse(
+            r#"{"reason":"compiler-artifact","target":{"kind":["lib"]},"filenames":["/t/libm.rlib"]}"#,
+        )
+        .unwrap();
+        assert_eq!(cdylib_artifact(&rlib), None);
+
+        let done = json::parse(r#"{"reason":"build-finished","success":true}"#).unwrap();
+        assert_eq!(cdylib_artifact(&done), None);
+    }
+}
@@ -0,0 +1,299 @@
+//! A small HTTP/1.1 client for the mindb server API.
+//!
+//! The server authenticates a request either by its `mindb_session` cookie
+//! or by Basic auth, answering the latter with a fresh session cookie. The
+//! client sends Basic auth until it holds a session, then the cookie alone.
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+use crate::json::{self, Value};
+
+/// Cookie carrying the server session.
+const SESSION_COOKIE: &str = "mindb_session";
+
+/// Where to reach the server and who to be there.
+pub struct Client {
+    host: String,
+    port: u16,
+    base_path: String,
+    credentials: Option<(String, String)>,
+    database: Option<String>,
+    session: Option<String>,
+}
+
+/// A response with a successful status.
+pub struct Response {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+impl Client {
+    /// Creates a client for a server URL such as `http://localhost:8080`.
+    pub fn new(
+        url: &str,
+        credentials: Option<(String, String)>,
+        database: Option<String>,
+    ) -> Result<Client, String> {
+        let rest = match url.split_once("://") {
+            Some(("http", rest)) => rest,
+            Some(("https", _)) => {
+                return Err("https URLs are not supported; reach the server over http".into())
+            }
+            Some((scheme, _)) => return Err(format!("unsupported URL scheme `{scheme}`")),
+            None => url,
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], rest[i..].trim_end_matches('/')),
+            None => (rest, ""),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse()
+                    .map_err(|_| format!("invalid port in URL `{url}`"))?,
+            ),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(format!("missing host in URL `{url}`"));
+        }
+
+        Ok(Client {
+            host: host.to_string(),
+            port,
+            base_path: path.to_string(),
+            credentials,
+            database,
+            session: None,
+        })
+    }
+
+    /// Sends a JSON request and parses the JSON response.
+    pub fn post_json(&mut self, path: &str, body: &str) -> Result<Value, String> {
+        let response = self.send("POST", path, "application/json", body.as_bytes())?;
+        parse_body(&response.body)
+    }
+
+    /// Sends a request, signing in again once if the session has expired.
+    pub fn send(
+        &mut self,
+        method: &str,
+        path: &str,
+        content_type: &str,
+        body: &[u8],
+    ) -> Result<Response, String> {
+        let mut response = self.round_trip(method, path, content_type, body)?;
+        if response.status == 401 && self.session.take().is_some() {
+            response = self.round_trip(method, path, content_type, body)?;
+        }
+
+        if response.status >= 400 {
+            let message = parse_body(&response.body)
+                .ok()
+                .and_then(|v| {
+                    let error = v.get("error")?;
+                    error.get("message")?.as_str().map(String::from)
+                })
+                .unwrap_or_else(|| String::from_utf8_lossy(&response.body).trim().to_string());
+            return Err(format!("server returned {}: {message}", response.status));
+        }
+        Ok(response)
+    }
+
+    fn round_trip(
+        &mut self,
+        method: &str,
+        path: &str,
+        content_type: &str,
+        body: &[u8],
+    ) -> Result<Response, String> {
+        let address = format!("{}:{}", self.host, self.port);
+        let mut stream = TcpStream::connect(&address)
+            .map_err(|e| format!("failed to connect to {address}: {e}"))?;
+
+        let mut request = format!(
+            "{method} {}{path} HTTP/1.1\r\nHost: {address}\r\nContent-Type: {content_type}\r\n\
+             Content-Length: {}\r\nAccept: application/json\r\nConnection: close\r\n",
+            self.base_path,
+            body.len()
+        );
+        match (&self.session, &self.credentials) {
+            (Some(session), _) => {
+                request.push_str(&format!("Cookie: {SESSION_COOKIE}={session}\r\n"))
+            }
+            (None, Some((user, password))) => request.push_str(&format!(
+                "Authorization: Basic {}\r\n",
+                base64(format!("{user}:{password}").as_bytes())
+            )),
+            (None, None) => {}
+        }
+        if let Some(database) = &self.database {
+            request.push_str(&format!("X-Mindb-Database: {database}\r\n"));
+        }
+        request.push_str("\r\n");
+
+        let sent = stream
+            .write_all(request.as_bytes())
+            .and_then(|_| stream.write_all(body));
+        let mut raw = Vec::new();
+        let received = stream.read_to_end(&mut raw);
+        if raw.is_empty() {
+            let error = sent.and(received).err();
+            return Err(format!(
+                "no response from {address}{}",
+                error.map(|e| format!(": {e}")).unwrap_or_default()
+            ));
+        }
+
+        let (status, headers, body) = parse_response(&raw)?;
+        for (name, value) in &headers {
+            if name.eq_ignore_ascii_case("set-cookie") {
+                if let Some(session) = session_cookie(value) {
+                    self.session = Some(session);
+                }
+            }
+        }
+        Ok(Response { status, body })
+    }
+}
+
+fn parse_body(body: &[u8]) -> Result<Value, String> {
+    let text = std::str::from_utf8(body).map_err(|_| "response is not UTF-8".to_string())?;
+    json::parse(text)
+}
+
+/// Returns the session token from a `Set-Cookie` header, if it sets one.
+fn session_cookie(header: &str) -> Option<String> {
+    let pair = header.split(';').next()?.trim();
+    let value = pair.strip_prefix(SESSION_COOKIE)?.strip_prefix('=')?;
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+type Headers = Vec<(String, String)>;
+
+/// Splits a raw HTTP response into status, headers and decoded body.
+fn parse_response(raw: &[u8]) -> Result<(u16, Headers, Vec<u8>), String> {
+    let split = raw
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or("malformed HTTP response")?;
+    let head = String::from_utf8_lossy(&raw[..split]);
+    let body = &raw[split + 4..];
+
+    let mut lines = head.split("\r\n");
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok())
+        .ok_or("malformed HTTP status line")?;
+    let headers: Headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+
+    let chunked = headers.iter().any(|(name, value)| {
+        name.eq_ignore_ascii_case("transfer-encoding") && value.eq_ignore_ascii_case("chunked")
+    });
+    let body = if chunked {
+        decode_chunked(body)?
+    } else {
+        body.to_vec()
+    };
+    Ok((status, headers, body))
+}
+
+/// Decodes a `Transfer-Encoding: chunked` body.
+fn decode_chunked(mut data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    loop {
+        let line_end = data
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .ok_or("malformed chunked body")?;
+        let size_text = String::from_utf8_lossy(&data[..line_end]);
+        let size_text = size_text.split(';').next().unwrap_or_default().trim();
+        let size =
+            usize::from_str_radix(size_text, 16).map_err(|_| "malformed chunk size".to_string())?;
+        data = &data[line_end + 2..];
+        if size == 0 {
+            return Ok(body);
+        }
+        if data.len() < size + 2 {
+            return Err("truncated chunked body".into());
+        }
+        body.extend_from_slice(&data[..size]);
+        data = &data[size + 2..];
+    }
+}
+
+/// Encodes bytes as standard, padded base64.
+pub fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"root:root"), "cm9vdDpyb290");
+        assert_eq!(base64(b"\0asm\x01\0\0\0"), "AGFzbQEAAAA=");
+    }
+
+    #[test]
+    fn test_parse_url() {
+        let client = Client::new("http://db.local:9000/api/", None, None).unwrap();
+        assert_eq!(
+            (client.host.as_str(), client.port, client.base_path.as_str()),
+            ("db.local", 9000, "/api")
+        );
+        let client = Client::new("localhost", None, None).unwrap();
+        assert_eq!((client.host.as_str(), client.port), ("localhost", 80));
+        assert!(Client::new("https://localhost", None, None).is_err());
+        assert!(Client::new("http://:8080", None, None).is_err());
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let raw =
+            b"HTTP/1.1 201 Created\r\nSet-Cookie: mindb_session=abc.def; Path=/; HttpOnly\r\n\
+                    Content-Length: 2\r\n\r\n{}";
+        let (status, headers, body) = parse_response(raw).unwrap();
+        assert_eq!(status, 201);
+        assert_eq!(body, b"{}");
+        assert_eq!(session_cookie(&headers[0].1), Some("abc.def".to_string()));
+        assert_eq!(session_cookie("other=1; Path=/"), None);
+    }
+
+    #[test]
+    fn test_parse_chunked_response() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+                    4\r\n{\"a\"\r\n3;ext=1\r\n:1}\r\n0\r\n\r\n";
+        let (_, _, body) = parse_response(raw).unwrap();
+        assert_eq!(body, b"{\"a\":1}");
+        assert!(decode_chunked(b"9\r\nshort\r\n").is_err());
+    }
+}
@@ -0,0 +1,329 @@
+//! `cargo mindb`: build, check and deploy mindb stored procedures written
+//! with the `mindb-procedure` SDK.
+//!
+//! ```text
+//! cargo mindb build
+//! cargo mindb deploy --user root --password root
+//! cargo mindb call calculate_discount 100.0 2
+//! ```
+//!
+//! `deploy` builds the crate for `wasm32-unknown-unknown`, runs `wasm-opt`,
+//! checks the module's exports against the signatures `#[procedure]`
+//! recorded, and creates each procedure through `POST /procedures`.
+
+mod build;
+mod client;
+mod json;
+mod wasm;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use build::BuildOptions;
+use client::Client;
+use json::Value;
+use wasm::Module;
+
+const USAGE: &str = "\
+Build, check and deploy mindb procedures written in Rust
+
+Usage: cargo mindb <COMMAND> [OPTIONS]
+
+Commands:
+  build                Build the crate for wasm32-unknown-unknown and run wasm-opt
+  check [MODULE]       Check a module's exports against the SDK ABI
+  deploy [MODULE]      Check a module and create its procedures on the server
+  call <NAME> [ARGS]   Call a procedure, e.g. `cargo mindb call calculate_discount 100.0 2`
+
+Without MODULE, check and deploy build the crate first.
+
+Build options:
+      --manifest-path <PATH>  Path to the crate's Cargo.toml
+  -p, --package <NAME>        Package to build
+      --no-opt                Skip wasm-opt
+
+Server options:
+      --url <URL>             Server URL [env: MINDB_URL] [default: http://localhost:8080]
+  -u, --user <USER>           User to sign in as [env: MINDB_USER]
+      --password <PASSWORD>   Password [env: MINDB_PASSWORD]
+  -d, --database <NAME>       Database to use [env: MINDB_DATABASE]
+
+Deploy options:
+      --replace               Install a new version of procedures that already exist
+      --only <NAME>           Deploy only this procedure; may be repeated
+";
+
+/// Parsed command line.
+#[derive(Debug, Default)]
+struct Args {
+    command: String,
+    positional: Vec<String>,
+    build: BuildOptions,
+    url: Option<String>,
+    user: Option<String>,
+    password: Option<String>,
+    database: Option<String>,
+    replace: bool,
+    only: Vec<String>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+        let mut parsed = Args::default();
+        let mut options_done = false;
+        while let Some(arg) = args.next() {
+            let mut value =
+                |name: &str| args.next().ok_or_else(|| format!("`{name}` needs a value"));
+            // Negative numbers are call arguments, not options
+            let is_option = !options_done
+                && arg.starts_with('-')
+                && !arg[1..].starts_with(|c: char| c.is_ascii_digit() || c == '.');
+            if !is_option {
+                if parsed.command.is_empty() {
+                    parsed.command = arg;
+                } else {
+                    parsed.positional.push(arg);
+                }
+                continue;
+            }
+
+            match arg.as_str() {
+                "--" => options_done = true,
+                "-h" | "--help" => parsed.command = "help".into(),
+                "--manifest-path" => parsed.build.manifest_path = Some(value(&arg)?.into()),
+                "-p" | "--package" => parsed.build.package = Some(value(&arg)?),
+                "--no-opt" => parsed.build.no_opt = true,
+                "--url" => parsed.url = Some(value(&arg)?),
+                "-u" | "--user" => parsed.user = Some(value(&arg)?),
+                "--password" => parsed.password = Some(value(&arg)?),
+                "-d" | "--database" => parsed.database = Some(value(&arg)?),
+                "--replace" => parsed.replace = true,
+                "--only" => parsed.only.push(value(&arg)?),
+                _ => return Err(format!("unknown option `{arg}`")),
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Connects to the server named by the options or the environment.
+    fn client(&self) -> Result<Client, String> {
+        let setting =
+            |flag: &Option<String>, var: &str| flag.clone().or_else(|| env::var(var).ok());
+        let url = setting(&self.url, "MINDB_URL").unwrap_or_else(|| "http://localhost:8080".into());
+        let credentials = setting(&self.user, "MINDB_USER").map(|user| {
+            let password = setting(&self.password, "MINDB_PASSWORD").unwrap_or_default();
+            (user, password)
+        });
+        Client::new(&url, credentials, setting(&self.database, "MINDB_DATABASE"))
+    }
+
+    /// Returns the module named on the command line, or builds the crate.
+    fn module_path(&self) -> Result<PathBuf, String> {
+        match self.positional.as_slice() {
+            [] => build::build(&self.build),
+            [path] => Ok(PathBuf::from(path)),
+            _ => Err("expected at most one module path".into()),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    // Cargo runs `cargo-mindb mindb ...` for `cargo mindb ...`
+    let mut args = env::args().skip(1).peekable();
+    if args.peek().map(String::as_str) == Some("mindb") {
+        args.next();
+    }
+
+    match Args::parse(args).and_then(|args| run(&args)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    match args.command.as_str() {
+        "build" => {
+            let path = build::build(&args.build)?;
+            println!("{}", path.display());
+            Ok(())
+        }
+        "check" => {
+            let path = args.module_path()?;
+            let module = load(&path)?.1;
+            for sig in &module.signatures {
+                println!("{}", sig.display());
+            }
+            Ok(())
+        }
+        "deploy" => deploy(args),
+        "call" => call(args),
+        "" | "help" => {
+            print!("{USAGE}");
+            Ok(())
+        }
+        other => Err(format!("unknown command `{other}`\n\n{USAGE}")),
+    }
+}
+
+/// Reads a module and checks it against the SDK ABI.
+fn load(path: &PathBuf) -> Result<(Vec<u8>, Module), String> {
+    let bytes = fs::read(path).map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    let module = Module::parse(&bytes).map_err(|e| format!("{}: {e}", path.display()))?;
+
+    let problems = module.check_abi();
+    if !problems.is_empty() {
+        return Err(format!(
+            "{} does not match the SDK ABI:\n  {}",
+            path.display(),
+            problems.join("\n  ")
+        ));
+    }
+    Ok((bytes, module))
+}
+
+fn deploy(args: &Args) -> Result<(), String> {
+    let path = args.module_path()?;
+    let (bytes, module) = load(&path)?;
+    for name in &args.only {
+        if !module.signatures.iter().any(|sig| &sig.name == name) {
+            return Err(format!("{} has no procedure `{name}`", path.display()));
+        }
+    }
+
+    let mut client = args.client()?;
+    let code = client::base64(&bytes);
+    for sig in &module.signatures {
+        if !args.only.is_empty() && !args.only.contains(&sig.name) {
+            continue;
+        }
+        if sig.is_aggregate() {
+            eprintln!(
+                "warning: skipping aggregate `{}`; register it with CREATE AGGREGATE",
+                sig.name
+            );
+            continue;
+        }
+
+        if args.replace {
+            let response = client.send(
+                "PUT",
+                &format!("/procedures/{}/module", sig.name),
+                "application/wasm",
+                &bytes,
+            )?;
+            let version = std::str::from_utf8(&response.body)
+                .ok()
+                .and_then(|body| json::parse(body).ok())
+                .and_then(|body| body.get("version").map(Value::to_string))
+                .unwrap_or_default();
+            println!("Deployed {} (version {version})", sig.display());
+            continue;
+        }
+
+        let params: Vec<String> = sig
+            .params
+            .iter()
+            .map(|(name, ty)| {
+                format!(
+                    "{{\"name\":{},\"data_type\":{}}}",
+                    json::quote(name),
+                    json::quote(ty)
+                )
+            })
+            .collect();
+        let body = format!(
+            "{{\"name\":{},\"language\":\"wasm\",\"wasm_base64\":\"{code}\",\"params\":[{}],\
+             \"return_type\":{},\"description\":{}}}",
+            json::quote(&sig.name),
+            params.join(","),
+            json::quote(&sig.returns),
+            json::quote(&sig.description)
+        );
+        client.post_json("/procedures", &body)?;
+        println!("Deployed {}", sig.display());
+    }
+    Ok(())
+}
+
+fn call(args: &Args) -> Result<(), String> {
+    let (name, call_args) = args
+        .positional
+        .split_first()
+        .ok_or("`call` needs a procedure name")?;
+
+    let call_args: Vec<Value> = call_args.iter().map(|arg| parse_arg(arg)).collect();
+    let body = format!("{{\"args\":{}}}", Value::Array(call_args));
+
+    let response = args
+        .client()?
+        .post_json(&format!("/procedures/{name}/call"), &body)?;
+    match response.get("result") {
+        Some(Value::String(text)) => println!("{text}"),
+        Some(result) => println!("{result}"),
+        None => println!("null"),
+    }
+    Ok(())
+}
+
+/// Interprets a command-line argument as a JSON number, boolean or null
+/// where it reads as one, and as a string otherwise. A quoted argument is
+/// always a string: `'"42"'`.
+fn parse_arg(arg: &str) -> Value {
+    match json::parse(arg) {
+        Ok(value @ (Value::Number(_) | Value::Bool(_) | Value::Null | Value::String(_))) => value,
+        _ => Value::String(arg.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Args {
+        Args::parse(line.split_whitespace().map(String::from)).unwrap()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let parsed = args("deploy target/x.wasm --replace --only tax --only fee -u root -d shop");
+        assert_eq!(parsed.command, "deploy");
+        assert_eq!(parsed.positional, ["target/x.wasm"]);
+        assert!(parsed.replace);
+        assert_eq!(parsed.only, ["tax", "fee"]);
+        assert_eq!(parsed.user.as_deref(), Some("root"));
+        assert_eq!(parsed.database.as_deref(), Some("shop"));
+
+        let parsed = args("build --manifest-path procs/Cargo.toml --no-opt");
+        assert_eq!(
+            parsed.build.manifest_path,
+            Some(PathBuf::from("procs/Cargo.toml"))
+        );
+        assert!(parsed.build.no_opt);
+
+        assert!(Args::parse(["deploy".to_string(), "--bogus".to_string()].into_iter()).is_err());
+        assert!(Args::parse(["call".to_string(), "--url".to_string()].into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_parse_call_args() {
+        let parsed = args("call calculate_discount 100.0 -2 -- --literal");
+        assert_eq!(
+            parsed.positional,
+            ["calculate_discount", "100.0", "-2", "--literal"]
+        );
+
+        let values: Vec<Value> = ["100.0", "2", "true", "null", "gold", "\"42\"", "[1]"]
+            .iter()
+            .map(|arg| parse_arg(arg))
+            .collect();
+        assert_eq!(
+            Value::Array(values).to_string(),
+            r#"[100.0,2,true,null,"gold","42","[1]"]"#
+        );
+    }
+}
@@ -0,0 +1,563 @@
+//! Reads the parts of a WASM module the host cares about and checks them
+//! against the SDK ABI.
+//!
+//! The host (`src/core/wasm_engine.go`) calls procedures by export name,
+//! passes INT, BIGINT, REAL, FLOAT and BOOLEAN arguments as WASM scalars, and
+//! TEXT and BLOB as an `i64` packing `(ptr << 32) | len` of a buffer it
+//! allocates with `mindb_alloc`. TEXT, BLOB and TABLE results go through host
+//! imports, so those exports return nothing. A module that disagrees loads
+//! fine and only fails when called; checking here catches it before deploy.
+
+use crate::json::{self, Value};
+
+/// Name of the custom section the SDK records signatures in.
+const SIGNATURE_SECTION: &str = "mindb.procedures";
+
+/// Host functions the module may import from `env`, as registered in
+/// `src/core/wasm_host.go`.
+const HOST_IMPORTS: [&str; 18] = [
+    "mindb_error",
+    "mindb_raise",
+    "mindb_log",
+    "mindb_result_read",
+    "mindb_return_text",
+    "mindb_return_blob",
+    "mindb_return_null",
+    "mindb_arg_is_null",
+    "mindb_emit_row",
+    "mindb_query",
+    "mindb_get_row",
+    "mindb_insert",
+    "mindb_update",
+    "mindb_delete",
+    "mindb_trigger_event",
+    "mindb_trigger_old",
+    "mindb_trigger_new",
+    "mindb_trigger_set_new",
+];
+
+/// The exports the host calls for an aggregate named `name`.
+const AGGREGATE_EXPORTS: [&str; 4] = ["init", "step", "merge", "finalize"];
+
+/// A WASM value type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValType {
+    I32,
+    I64,
+    F32,
+    F64,
+    Other(u8),
+}
+
+impl ValType {
+    fn from_byte(byte: u8) -> ValType {
+        match byte {
+            0x7f => ValType::I32,
+            0x7e => ValType::I64,
+            0x7d => ValType::F32,
+            0x7c => ValType::F64,
+            other => ValType::Other(other),
+        }
+    }
+}
+
+/// A function type: parameters and results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuncType {
+    pub params: Vec<ValType>,
+    pub results: Vec<ValType>,
+}
+
+/// What an export refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportKind {
+    Func(u32),
+    Memory,
+    Other,
+}
+
+/// A procedure signature recorded by `#[procedure]` or `#[aggregate]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub name: String,
+    /// `Some("aggregate")` for aggregates.
+    pub kind: Option<String>,
+    /// Parameter names and SQL types.
+    pub params: Vec<(String, String)>,
+    pub returns: String,
+    pub description: String,
+}
+
+impl Signature {
+    pub fn is_aggregate(&self) -> bool {
+        self.kind.as_deref() == Some("aggregate")
+    }
+
+    /// Renders the signature as `name(a FLOAT, b INT) -> FLOAT`.
+    pub fn display(&self) -> String {
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .map(|(name, ty)| format!("{name} {ty}"))
+            .collect();
+        format!("{}({}) -> {}", self.name, params.join(", "), self.returns)
+    }
+}
+
+/// The parsed module.
+#[derive(Debug, Default)]
+pub struct Module {
+    types: Vec<FuncType>,
+    /// Type index of every function, imported ones first.
+    functions: Vec<u32>,
+    /// `(module, name)` of every imported function.
+    imports: Vec<(String, String)>,
+    exports: Vec<(String, ExportKind)>,
+    pub signatures: Vec<Signature>,
+}
+
+impl Module {
+    /// Parses a binary WASM module.
+    pub fn parse(bytes: &[u8]) -> Result<Module, String> {
+        if bytes.len() < 8 || &bytes[..4] != b"\0asm" {
+            return Err("not a WASM module".into());
+        }
+        if bytes[4..8] != [1, 0, 0, 0] {
+            return Err("unsupported WASM version".into());
+        }
+
+        let mut module = Module::default();
+        let mut metadata = Vec::new();
+        let mut reader = Reader::new(&bytes[8..]);
+        while !reader.is_empty() {
+            let id = reader.byte()?;
+            let size = reader.u32()? as usize;
+            let mut section = Reader::new(reader.take(size)?);
+            match id {
+                0 if section.name()? == SIGNATURE_SECTION => {
+                    metadata.extend_from_slice(section.rest());
+                }
+                1 => module.read_types(&mut section)?,
+                2 => module.read_imports(&mut section)?,
+                3 => {
+                    for _ in 0..section.u32()? {
+                        module.functions.push(section.u32()?);
+                    }
+                }
+                7 => module.read_exports(&mut section)?,
+                _ => {}
+            }
+        }
+
+        let text = String::from_utf8(metadata)
+            .map_err(|_| format!("{SIGNATURE_SECTION} section is not UTF-8"))?;
+        module.signatures = parse_signatures(&text)?;
+        Ok(module)
+    }
+
+    fn read_types(&mut self, section: &mut Reader) -> Result<(), String> {
+        for _ in 0..section.u32()? {
+            if section.byte()? != 0x60 {
+                return Err("unsupported type in type section".into());
+            }
+            let params = section.val_types()?;
+            let results = section.val_types()?;
+            self.types.push(FuncType { params, results });
+        }
+        Ok(())
+    }
+
+    fn read_imports(&mut self, section: &mut Reader) -> Result<(), String> {
+        for _ in 0..section.u32()? {
+            let module = section.name()?;
+            let name = section.name()?;
+            match section.byte()? {
+                0 => {
+                    self.functions.push(section.u32()?);
+                    self.imports.push((module, name));
+                }
+                1 => {
+                    section.byte()?;
+                    section.limits()?;
+                }
+                2 => section.limits()?,
+                3 => {
+                    section.byte()?;
+                    section.byte()?;
+                }
+                4 => {
+                    section.byte()?;
+                    section.u32()?;
+                }
+                kind => return Err(format!("unsupported import kind {kind}")),
+            }
+        }
+        Ok(())
+    }
+
+    fn read_exports(&mut self, section: &mut Reader) -> Result<(), String> {
+        for _ in 0..section.u32()? {
+            let name = section.name()?;
+            let kind = match (section.byte()?, section.u32()?) {
+                (0, index) => ExportKind::Func(index),
+                (2, _) => ExportKind::Memory,
+                _ => ExportKind::Other,
+            };
+            self.exports.push((name, kind));
+        }
+        Ok(())
+    }
+
+    /// Returns the type of an exported function.
+    pub fn export_type(&self, name: &str) -> Option<&FuncType> {
+        let index = self.exports.iter().find_map(|(export, kind)| match kind {
+            ExportKind::Func(index) if export == name => Some(*index),
+            _ => None,
+        })?;
+        let type_index = *self.functions.get(index as usize)?;
+        self.types.get(type_index as usize)
+    }
+
+    fn exports_memory(&self) -> bool {
+        self.exports
+            .iter()
+            .any(|(name, kind)| name == "memory" && *kind == ExportKind::Memory)
+    }
+
+    /// Checks the module against the SDK ABI, returning every problem found.
+    pub fn check_abi(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if !self.exports_memory() {
+            problems.push("module does not export `memory`".to_string());
+        }
+        for (module, name) in &self.imports {
+            if module != "env" || !HOST_IMPORTS.contains(&name.as_str()) {
+                problems.push(format!(
+                    "module imports `{module}.{name}`, which the host does not provide"
+                ));
+            }
+        }
+        if self.signatures.is_empty() {
+            problems.push(format!(
+                "module has no {SIGNATURE_SECTION} section; mark functions with #[procedure]"
+            ));
+        }
+
+        let mut needs_alloc = false;
+        for sig in &self.signatures {
+            if sig.is_aggregate() {
+                for suffix in AGGREGATE_EXPORTS {
+                    let export = format!("{}_{suffix}", sig.name);
+                    if self.export_type(&export).is_none() {
+                        problems.push(format!(
+                            "aggregate `{}` does not export `{export}`",
+                            sig.name
+                        ));
+                    }
+                }
+                continue;
+            }
+
+            let Some(actual) = self.export_type(&sig.name) else {
+                problems.push(format!("procedure `{}` is not exported", sig.name));
+                continue;
+            };
+            match expected_type(sig) {
+                Ok(expected) if expected != *actual => problems.push(format!(
+                    "procedure `{}` is exported as {} but its signature needs {}",
+                    sig.name,
+                    render(actual),
+                    render(&expected)
+                )),
+                Ok(_) => {}
+                Err(problem) => problems.push(problem),
+            }
+            needs_alloc |= sig
+                .params
+                .iter()
+                .any(|(_, ty)| ty == "TEXT" || ty == "BLOB");
+        }
+
+        if needs_alloc && self.export_type("mindb_alloc").is_none() {
+            problems.push("TEXT and BLOB arguments need a `mindb_alloc` export".to_string());
+        }
+
+        problems
+    }
+}
+
+/// The WASM type the host calls a procedure with.
+fn expected_type(sig: &Signature) -> Result<FuncType, String> {
+    let mut params = Vec::new();
+    for (name, ty) in &sig.params {
+        params.push(match ty.as_str() {
+            "INT" | "BOOLEAN" => ValType::I32,
+            "BIGINT" | "TEXT" | "BLOB" => ValType::I64,
+            "REAL" => ValType::F32,
+            "FLOAT" => ValType::F64,
+            _ => {
+                return Err(format!(
+                    "procedure `{}` has parameter `{name}` of unsupported type {ty}",
+                    sig.name
+                ))
+            }
+        });
+    }
+    let results = match sig.returns.as_str() {
+        "INT" | "BOOLEAN" => vec![ValType::I32],
+        "BIGINT" => vec![ValType::I64],
+        "REAL" => vec![ValType::F32],
+        "FLOAT" => vec![ValType::F64],
+        "TEXT" | "BLOB" | "TABLE" | "VOID" => vec![],
+        ty => {
+            return Err(format!(
+                "procedure `{}` has unsupported return type {ty}",
+                sig.name
+            ))
+        }
+    };
+    Ok(FuncType { params, results })
+}
+
+/// Renders a function type as `(i32, f64) -> f64`.
+fn render(ty: &FuncType) -> String {
+    let list = |types: &[ValType]| {
+        types
+            .iter()
+            .map(|t| match t {
+                ValType::I32 => "i32".to_string(),
+                ValType::I64 => "i64".to_string(),
+                ValType::F32 => "f32".to_string(),
+                ValType::F64 => "f64".to_string(),
+                ValType::Other(byte) => format!("0x{byte:02x}"),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    format!("({}) -> ({})", list(&ty.params), list(&ty.results))
+}
+
+/// Parses the section's JSON lines.
+fn parse_signatures(text: &str) -> Result<Vec<Signature>, String> {
+    let mut signatures = Vec::new();
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let record = json::parse(line).map_err(|e| format!("invalid procedure metadata: {e}"))?;
+        let field = |key: &str| record.get(key).and_then(Value::as_str).unwrap_or_default();
+
+        let name = field("name").to_string();
+        if name.is_empty() {
+            return Err("invalid procedure metadata: missing name".into());
+        }
+        let params = record
+            .get("params")
+            .map(Value::as_array)
+            .unwrap_or_default()
+            .iter()
+            .map(|p| {
+                let get = |key| p.get(key).and_then(Value::as_str).unwrap_or_default();
+                (get("name").to_string(), get("type").to_string())
+            })
+            .collect();
+
+        signatures.push(Signature {
+            name,
+            kind: record.get("kind").and_then(Value::as_str).map(String::from),
+            params,
+            returns: field("returns").to_string(),
+            description: field("description").to_string(),
+        });
+    }
+    Ok(signatures)
+}
+
+/// A cursor over WASM binary data.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn rest(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if len > self.bytes.len() {
+            return Err("unexpected end of module".into());
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// Reads an unsigned LEB128 integer.
+    fn u32(&mut self) -> Result<u32, String> {
+        let mut value = 0u64;
+        for shift in (0..35).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return u32::try_from(value).map_err(|_| "integer too large".to_string());
+            }
+        }
+        Err("integer too large".into())
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| "name is not UTF-8".into())
+    }
+
+    fn val_types(&mut self) -> Result<Vec<ValType>, String> {
+        (0..self.u32()?)
+            .map(|_| self.byte().map(ValType::from_byte))
+            .collect()
+    }
+
+    fn limits(&mut self) -> Result<(), String> {
+        let flags = self.byte()?;
+        self.u32()?;
+        if flags & 1 != 0 {
+            self.u32()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a section with its id and size.
+    fn section(id: u8, body: &[u8]) -> Vec<u8> {
+        let mut out = vec![id];
+        let mut size = body.len();
+        loop {
+            let byte = (size & 0x7f) as u8;
+            size >>= 7;
+            if size == 0 {
+                out.push(byte);
+                break;
+            }
+            out.push(byte | 0x80);
+        }
+        out.extend_from_slice(body);
+        out
+    }
+
+    fn name(s: &str) -> Vec<u8> {
+        let mut out = vec![s.len() as u8];
+        out.extend_from_slice(s.as_bytes());
+        out
+    }
+
+    /// A module exporting `memory` and `calculate_tax: (f64, i32) -> f64`,
+    /// importing `import_module.import_name`, with `metadata` as its
+    /// signature section.
+    fn module(import_module: &str, import_name: &str, metadata: &str) -> Vec<u8> {
+        let mut bytes = b"\0asm\x01\0\0\0".to_vec();
+        // Types: 0 = (f64, i32) -> f64, 1 = (i32, i32) -> ()
+        bytes.extend(section(
+            1,
+            &[2, 0x60, 2, 0x7c, 0x7f, 1, 0x7c, 0x60, 2, 0x7f, 0x7f, 0],
+        ));
+        let mut imports = vec![1];
+        imports.extend(name(import_module));
+        imports.extend(name(import_name));
+        imports.extend([0, 1]);
+        bytes.extend(section(2, &imports));
+        bytes.extend(section(3, &[1, 0]));
+        bytes.extend(section(5, &[1, 0, 1]));
+        let mut exports = vec![2];
+        exports.extend(name("memory"));
+        exports.extend([2, 0]);
+        exports.extend(name("calculate_tax"));
+        exports.extend([0, 1]);
+        bytes.extend(section(7, &exports));
+        bytes.extend(section(10, &[1, 4, 0, 0x20, 0, 0x0b]));
+        let mut custom = name(SIGNATURE_SECTION);
+        custom.extend_from_slice(metadata.as_bytes());
+        bytes.extend(section(0, &custom));
+        bytes
+    }
+
+    const TAX: &str = concat!(
+        r#"{"name":"calculate_tax","params":[{"name":"amount","type":"FLOAT"},"#,
+        r#"{"name":"state_code","type":"INT"}],"returns":"FLOAT","description":"Sales tax."}"#,
+        "\n"
+    );
+
+    #[test]
+    fn test_parse_module() {
+        let module = Module::parse(&module("env", "mindb_error", TAX)).unwrap();
+        assert_eq!(
+            module.export_type("calculate_tax"),
+            Some(&FuncType {
+                params: vec![ValType::F64, ValType::I32],
+                results: vec![ValType::F64],
+            })
+        );
+        assert_eq!(module.signatures.len(), 1);
+        assert_eq!(
+            module.signatures[0].display(),
+            "calculate_tax(amount FLOAT, state_code INT) -> FLOAT"
+        );
+        assert_eq!(module.signatures[0].description, "Sales tax.");
+        assert!(module.check_abi().is_empty(), "{:?}", module.check_abi());
+    }
+
+    #[test]
+    fn test_check_abi_mismatch() {
+        let metadata = TAX.replace(r#""returns":"FLOAT""#, r#""returns":"INT""#);
+        let module = Module::parse(&module("env", "mindb_error", &metadata)).unwrap();
+        assert_eq!(
+            module.check_abi(),
+            ["procedure `calculate_tax` is exported as (f64, i32) -> (f64) but its signature needs (f64, i32) -> (i32)"]
+        );
+    }
+
+    #[test]
+    fn test_check_abi_unknown_import() {
+        let module = Module::parse(&module("wasi_snapshot_preview1", "fd_write", TAX)).unwrap();
+        assert_eq!(
+            module.check_abi(),
+            ["module imports `wasi_snapshot_preview1.fd_write`, which the host does not provide"]
+        );
+    }
+
+    #[test]
+    fn test_check_abi_missing_exports() {
+        let metadata = concat!(
+            r#"{"name":"missing","params":[{"name":"s","type":"TEXT"}],"returns":"TEXT"}"#,
+            "\n",
+            r#"{"name":"avg","kind":"aggregate","params":[],"returns":"FLOAT"}"#,
+        );
+        let parsed = Module::parse(&module("env", "mindb_error", metadata)).unwrap();
+        let problems = parsed.check_abi();
+        assert!(problems.contains(&"procedure `missing` is not exported".to_string()));
+        assert!(problems.contains(&"aggregate `avg` does not export `avg_init`".to_string()));
+
+        let bare = Module::parse(&module("env", "mindb_error", "")).unwrap();
+        assert!(bare.check_abi()[0].contains("no mindb.procedures section"));
+    }
+
+    #[test]
+    fn test_parse_rejects_non_wasm() {
+        assert!(Module::parse(b"not wasm").is_err());
+        let mut truncated = module("env", "mindb_error", TAX);
+        truncated.truncate(20);
+        assert!(Module::parse(&truncated).is_err());
+    }
+}
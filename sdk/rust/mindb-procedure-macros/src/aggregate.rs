@@ -0,0 +1,351 @@
+//! Expansion of `#[aggregate]`.
+//!
+//! The attribute goes on an inherent `impl` block whose type holds the
+//! aggregate's state:
+//!
+//! ```text
+//! impl WeightedAvg {
+//!     fn step(&mut self, value: f64, weight: f64) { ... }
+//!     fn merge(&mut self, other: Self) { ... }
+//!     fn finalize(self) -> f64 { ... }
+//! }
+//! ```
+//!
+//! The block is emitted unchanged, followed by the `name_init`, `name_step`,
+//! `name_merge` and `name_finalize` exports the host calls. States cross the
+//! boundary as `i32` handles to boxed values in guest memory.
+
+use proc_macro::{Delimiter, Group, Ident, Span, TokenStream, TokenTree};
+
+use crate::crate_path;
+use crate::metadata::{self, Record};
+use crate::signature::{self, type_string, Receiver, Signature};
+use crate::{code, metadata_static, paren, qualified, Args, Error};
+
+/// The methods an aggregate implements, in the order they are looked up.
+const METHODS: [&str; 3] = ["step", "merge", "finalize"];
+
+pub fn expand(attr: TokenStream, item: TokenStream) -> Result<TokenStream, Error> {
+    let args = Args::parse(attr)?;
+    let krate = args.krate.clone().unwrap_or_else(crate_path::sdk_path);
+    let block = ImplBlock::parse(item.clone())?;
+
+    let [step, merge, finalize] = METHODS.map(|name| {
+        block
+            .methods
+            .iter()
+            .find(|sig| sig.name.to_string() == name)
+            .ok_or_else(|| Error::new(block.ty.span(), format!("aggregate is missing `fn {name}`")))
+    });
+    let (step, merge, finalize) = (step?, merge?, finalize?);
+
+    expect_receiver(step, Receiver::RefMut, "&mut self")?;
+    expect_receiver(merge, Receiver::RefMut, "&mut self")?;
+    expect_receiver(finalize, Receiver::Value, "self")?;
+    if merge.params.len() != 1 {
+        return Err(Error::new(
+            merge.name.span(),
+            "`merge` must take one other state",
+        ));
+    }
+    if !finalize.params.is_empty() {
+        return Err(Error::new(
+            finalize.name.span(),
+            "`finalize` must take only `self`",
+        ));
+    }
+
+    let mut params = Vec::with_capacity(step.params.len());
+    for param in &step.params {
+        let sql = metadata::param_sql_type(&type_string(&param.ty))
+            .map_err(|msg| Error::new(param.ty[0].span(), msg))?;
+        params.push((param.name.to_string(), sql));
+    }
+    if metadata::return_sql_type(&type_string(&step.ret)) != Ok("VOID") {
+        return Err(Error::new(
+            step.ret[0].span(),
+            "`step` must return `()` or `Result<()>`",
+        ));
+    }
+    let returns = match metadata::return_sql_type(&type_string(&finalize.ret)) {
+        Ok("VOID") => Err("`finalize` must return a value".to_string()),
+        Ok("TABLE") => Err("`finalize` cannot return a table".to_string()),
+        other => other,
+    }
+    .map_err(|msg| {
+        Error::new(
+            finalize
+                .ret
+                .first()
+                .map_or(finalize.name.span(), TokenTree::span),
+            msg,
+        )
+    })?;
+
+    let name = args
+        .name
+        .as_ref()
+        .map(|(name, _)| name.clone())
+        .unwrap_or_else(|| snake_case(&block.ty.to_string()));
+    let description = args
+        .description
+        .clone()
+        .unwrap_or_else(|| signature::summary(&block.docs));
+    let record = Record {
+        name: &name,
+        kind: Some("aggregate"),
+        params,
+        returns,
+        on_null: None,
+        description: &description,
+    };
+
+    let mut out = item;
+    out.extend(shims(&name, &block.ty, step, finalize, &krate));
+    out.extend(metadata_static(
+        "AGGREGATE",
+        &name,
+        record.to_json_line().as_bytes(),
+    ));
+    Ok(out)
+}
+
+fn expect_receiver(sig: &Signature, want: Receiver, text: &str) -> Result<(), Error> {
+    if sig.receiver == Some(want) {
+        Ok(())
+    } else {
+        Err(Error::new(
+            sig.name.span(),
+            format!("`{}` must take `{text}`", sig.name),
+        ))
+    }
+}
+
+/// Generates the four exports wrapping the impl's methods.
+fn shims(
+    name: &str,
+    ty: &Ident,
+    step: &Signature,
+    finalize: &Signature,
+    krate: &TokenStream,
+) -> TokenStream {
+    let ty = TokenStream::from(TokenTree::Ident(ty.clone()));
+    let state = |handle: &str, func: &str| {
+        let mut call = krate.clone();
+        call.extend(code(&format!("::__aggregate::{func}::<")));
+        call.extend(ty.clone());
+        call.extend(code(">"));
+        call.extend([paren(code(handle))]);
+        call
+    };
+
+    // name_init() -> i32
+    let mut out = export(name, "init", TokenStream::new(), code("i32"));
+    out.extend([brace(state("", "init"))]);
+
+    // name_step(state: i32, arg: <Ty as FromAbi>::Abi, ...)
+    let ret = return_type(step);
+    let mut outer_args = code("__mindb_state: i32,");
+    let mut call_args = state("__mindb_state", "state");
+    for param in &step.params {
+        let param_ty: TokenStream = param.ty.iter().cloned().collect();
+        outer_args.extend([TokenTree::Ident(param.name.clone())]);
+        outer_args.extend(code(":"));
+        outer_args.extend(qualified(&param_ty, krate, "FromAbi", "Abi"));
+        outer_args.extend(code(","));
+
+        call_args.extend(code(","));
+        call_args.extend(qualified(&param_ty, krate, "FromAbi", "from_abi"));
+        call_args.extend([paren(TokenStream::from(TokenTree::Ident(
+            param.name.clone(),
+        )))]);
+    }
+    out.extend(export(
+        name,
+        "step",
+        outer_args,
+        qualified(&ret, krate, "IntoReturn", "Abi"),
+    ));
+    out.extend([brace(into_return(&ty, "step", call_args, krate))]);
+
+    // name_merge(state: i32, other: i32)
+    let mut call_args = state("__mindb_state", "state");
+    call_args.extend(code(","));
+    call_args.extend(state("__mindb_other", "take"));
+    out.extend(export(
+        name,
+        "merge",
+        code("__mindb_state: i32, __mindb_other: i32"),
+        code("()"),
+    ));
+    let mut body = ty.clone();
+    body.extend(code("::merge"));
+    body.extend([paren(call_args)]);
+    body.extend(code(";"));
+    out.extend([brace(body)]);
+
+    // name_finalize(state: i32) -> <Ret as IntoReturn>::Abi
+    let ret = return_type(finalize);
+    out.extend(export(
+        name,
+        "finalize",
+        code("__mindb_state: i32"),
+        qualified(&ret, krate, "IntoReturn", "Abi"),
+    ));
+    out.extend([brace(into_return(
+        &ty,
+        "finalize",
+        state("__mindb_state", "take"),
+        krate,
+    ))]);
+    out
+}
+
+/// Builds `#[unsafe(no_mangle)] pub extern "C-unwind" fn name_suffix(args) -> ret`.
+fn export(name: &str, suffix: &str, args: TokenStream, ret: TokenStream) -> TokenStream {
+    let mut out = code("#[doc(hidden)] #[unsafe(no_mangle)] pub extern \"C-unwind\" fn");
+    out.extend([TokenTree::Ident(Ident::new(
+        &format!("{name}_{suffix}"),
+        Span::call_site(),
+    ))]);
+    out.extend([paren(args)]);
+    out.extend(code("->"));
+    out.extend(ret);
+    out
+}
+
+/// Builds `IntoReturn::into_return(Ty::method(args))`.
+fn into_return(
+    ty: &TokenStream,
+    method: &str,
+    args: TokenStream,
+    krate: &TokenStream,
+) -> TokenStream {
+    let mut call = ty.clone();
+    call.extend(code(&format!("::{method}")));
+    call.extend([paren(args)]);
+
+    let mut out = krate.clone();
+    out.extend(code("::IntoReturn::into_return"));
+    out.extend([paren(call)]);
+    out
+}
+
+fn return_type(sig: &Signature) -> TokenStream {
+    if sig.ret.is_empty() {
+        code("()")
+    } else {
+        sig.ret.iter().cloned().collect()
+    }
+}
+
+fn brace(inner: TokenStream) -> TokenTree {
+    TokenTree::Group(Group::new(Delimiter::Brace, inner))
+}
+
+/// A parsed `impl Type { ... }` block.
+struct ImplBlock {
+    /// Text of the `#[doc = "..."]` attributes on the block.
+    docs: Vec<String>,
+    ty: Ident,
+    /// The aggregate methods; other items are left alone.
+    methods: Vec<Signature>,
+}
+
+impl ImplBlock {
+    fn parse(item: TokenStream) -> Result<ImplBlock, Error> {
+        let mut tokens = item.into_iter().peekable();
+        let mut docs = Vec::new();
+
+        // Outer attributes; only doc comments matter here.
+        while let Some(TokenTree::Punct(p)) = tokens.peek() {
+            if p.as_char() != '#' {
+                break;
+            }
+            let hash = tokens.next().unwrap();
+            match tokens.next() {
+                Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Bracket => {
+                    docs.extend(signature::doc_text(&g));
+                }
+                _ => return Err(Error::new(hash.span(), "expected attribute")),
+            }
+        }
+
+        let mut next = || tokens.next();
+        let (ty, body) = match (next(), next(), next(), next()) {
+            (
+                Some(TokenTree::Ident(kw)),
+                Some(TokenTree::Ident(ty)),
+                Some(TokenTree::Group(body)),
+                None,
+            ) if kw.to_string() == "impl" && body.delimiter() == Delimiter::Brace => (ty, body),
+            (Some(tt), ..) => {
+                return Err(Error::new(
+                    tt.span(),
+                    "#[aggregate] must be applied to an inherent `impl Type { ... }` block",
+                ))
+            }
+            (None, ..) => return Err(Error::new(Span::call_site(), "expected `impl`")),
+        };
+
+        // Each fn ends with its braced body.
+        let mut methods = Vec::new();
+        let mut current = Vec::new();
+        for tt in body.stream() {
+            let ends = matches!(&tt, TokenTree::Group(g) if g.delimiter() == Delimiter::Brace);
+            current.push(tt);
+            if !ends {
+                continue;
+            }
+            let item = std::mem::take(&mut current);
+            if METHODS
+                .iter()
+                .any(|m| fn_name(&item).as_deref() == Some(*m))
+            {
+                methods.push(Signature::parse(item.into_iter().collect())?);
+            }
+        }
+
+        Ok(ImplBlock { docs, ty, methods })
+    }
+}
+
+/// Returns the name following `fn` in an item, if it is a function.
+fn fn_name(item: &[TokenTree]) -> Option<String> {
+    item.windows(2).find_map(|pair| match pair {
+        [TokenTree::Ident(kw), TokenTree::Ident(name)] if kw.to_string() == "fn" => {
+            Some(name.to_string())
+        }
+        _ => None,
+    })
+}
+
+/// Converts a type name to snake case: `WeightedAvg` becomes `weighted_avg`.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                out.push('_');
+            }
+        }
+        out.extend(c.to_lowercase());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::snake_case;
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(snake_case("WeightedAvg"), "weighted_avg");
+        assert_eq!(snake_case("Median"), "median");
+        assert_eq!(snake_case("HTTPStatus"), "http_status");
+        assert_eq!(snake_case("P95Latency"), "p95_latency");
+    }
+}
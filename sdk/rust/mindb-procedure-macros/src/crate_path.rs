@@ -0,0 +1,111 @@
+//! Locating the SDK crate from the calling crate's `Cargo.toml`.
+//!
+//! The SDK is usually renamed on import, e.g.
+//! `mindb = { package = "mindb-procedure", ... }` so procedures read as
+//! `#[mindb::procedure]`. Generated code must refer to the SDK by whatever
+//! name the caller gave it.
+
+use std::path::Path;
+
+use proc_macro::TokenStream;
+
+const PACKAGE: &str = "mindb-procedure";
+
+/// Returns the path generated code should use for the SDK crate.
+pub fn sdk_path() -> TokenStream {
+    let name = if std::env::var("CARGO_PKG_NAME").as_deref() == Ok(PACKAGE) {
+        // The SDK's own tests and examples; the SDK declares
+        // `extern crate self as mindb_procedure`.
+        None
+    } else {
+        std::env::var("CARGO_MANIFEST_DIR")
+            .ok()
+            .and_then(|dir| std::fs::read_to_string(Path::new(&dir).join("Cargo.toml")).ok())
+            .and_then(|manifest| dependency_name(&manifest))
+    };
+    let name = name
+        .unwrap_or_else(|| PACKAGE.to_string())
+        .replace('-', "_");
+    format!("::{name}")
+        .parse()
+        .expect("crate name must tokenize")
+}
+
+/// Finds the name the SDK is imported under in a `Cargo.toml`.
+fn dependency_name(manifest: &str) -> Option<String> {
+    let package = format!("package=\"{PACKAGE}\"");
+    let mut table = String::new();
+    for line in manifest.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            table = line
+                .trim_matches(|c| c == '[' || c == ']')
+                .trim()
+                .to_string();
+            continue;
+        }
+        let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+        if table.ends_with("dependencies") {
+            // mindb = { package = "mindb-procedure", ... }
+            // mindb-procedure = { path = "..." }
+            if let Some((key, value)) = compact.split_once('=') {
+                let key = key.trim_matches('"');
+                if value.contains(&package) || (key == PACKAGE && !value.contains("package=")) {
+                    return Some(key.to_string());
+                }
+            }
+        } else if let Some((parent, key)) = table.rsplit_once('.') {
+            // [dependencies.mindb]
+            // package = "mindb-procedure"
+            if parent.ends_with("dependencies") && compact == package {
+                return Some(key.trim_matches('"').to_string());
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::dependency_name;
+
+    #[test]
+    fn test_renamed_inline() {
+        let manifest = r#"
+[package]
+name = "pricing"
+
+[dependencies]
+serde = "1"
+mindb = { package = "mindb-procedure", path = "../sdk" }
+"#;
+        assert_eq!(dependency_name(manifest).as_deref(), Some("mindb"));
+    }
+
+    #[test]
+    fn test_renamed_table() {
+        let manifest = r#"
+[dependencies.mindb]
+version = "0.1"
+package = "mindb-procedure"
+"#;
+        assert_eq!(dependency_name(manifest).as_deref(), Some("mindb"));
+    }
+
+    #[test]
+    fn test_not_renamed() {
+        let manifest = r#"
+[dependencies]
+mindb-procedure = { path = "../sdk" }
+"#;
+        assert_eq!(
+            dependency_name(manifest).as_deref(),
+            Some("mindb-procedure")
+        );
+    }
+
+    #[test]
+    fn test_missing() {
+        assert_eq!(dependency_name("[dependencies]\nserde = \"1\"\n"), None);
+    }
+}
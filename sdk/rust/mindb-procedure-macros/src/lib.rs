@@ -0,0 +1,280 @@
+//! Procedural macros for the `mindb-procedure` SDK.
+//!
+//! Use these through the SDK (`mindb_procedure::procedure`), not directly.
+
+mod aggregate;
+mod crate_path;
+mod metadata;
+mod signature;
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+use metadata::Record;
+use signature::{type_string, Signature};
+
+/// Exports a Rust function as a mindb stored procedure.
+///
+/// See the `mindb-procedure` crate documentation for details. Accepted
+/// arguments:
+///
+/// - `description = "..."` overrides the description taken from the doc
+///   comment.
+/// - `crate = path` names the SDK crate when it cannot be found in
+///   `Cargo.toml`.
+#[proc_macro_attribute]
+pub fn procedure(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match expand(attr, item) {
+        Ok(tokens) => tokens,
+        Err(err) => err.into_compile_error(),
+    }
+}
+
+/// A compile error at a particular span.
+pub(crate) struct Error {
+    span: Span,
+    message: String,
+}
+
+impl Error {
+    pub(crate) fn new(span: Span, message: impl Into<String>) -> Self {
+        Error {
+            span,
+            message: message.into(),
+        }
+    }
+
+    fn into_compile_error(self) -> TokenStream {
+        let mut tokens = code("::core::compile_error!");
+        tokens.extend([TokenTree::Group(Group::new(
+            Delimiter::Parenthesis,
+            TokenStream::from(TokenTree::Literal(Literal::string(&self.message))),
+        ))]);
+        tokens.extend(code(";"));
+        tokens
+            .into_iter()
+            .map(|mut tt| {
+                tt.set_span(self.span);
+                tt
+            })
+            .collect()
+    }
+}
+
+/// Exports the methods of an `impl` block as a mindb aggregate.
+///
+/// See the `mindb-procedure` crate documentation for details. Accepts the
+/// same arguments as `#[procedure]`, plus `name = "..."` to override the
+/// SQL name derived from the type.
+#[proc_macro_attribute]
+pub fn aggregate(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match aggregate::expand(attr, item) {
+        Ok(tokens) => tokens,
+        Err(err) => err.into_compile_error(),
+    }
+}
+
+/// Arguments given to `#[procedure(...)]` or `#[aggregate(...)]`.
+#[derive(Default)]
+struct Args {
+    description: Option<String>,
+    /// The SQL name and the span of its key; aggregates only.
+    name: Option<(String, Span)>,
+    krate: Option<TokenStream>,
+}
+
+impl Args {
+    fn parse(attr: TokenStream) -> Result<Args, Error> {
+        let mut args = Args::default();
+        for part in signature::split_top_level(attr) {
+            let key = match part.first() {
+                Some(TokenTree::Ident(i)) => i.to_string(),
+                Some(tt) => return Err(Error::new(tt.span(), "expected `key = value`")),
+                None => continue,
+            };
+            let value: Vec<TokenTree> = match part.get(1) {
+                Some(TokenTree::Punct(p)) if p.as_char() == '=' => part[2..].to_vec(),
+                _ => return Err(Error::new(part[0].span(), "expected `key = value`")),
+            };
+            match key.as_str() {
+                "description" => args.description = Some(string_literal(&value, part[0].span())?),
+                "name" => {
+                    args.name = Some((string_literal(&value, part[0].span())?, part[0].span()))
+                }
+                "crate" => args.krate = Some(value.into_iter().collect()),
+                _ => {
+                    return Err(Error::new(
+                        part[0].span(),
+                        format!("unknown procedure argument `{key}`"),
+                    ))
+                }
+            }
+        }
+        Ok(args)
+    }
+}
+
+/// Parses a string literal argument value.
+fn string_literal(value: &[TokenTree], key: Span) -> Result<String, Error> {
+    match value {
+        [TokenTree::Literal(lit)] => {
+            let text = lit.to_string();
+            let text = text
+                .strip_prefix('"')
+                .and_then(|t| t.strip_suffix('"'))
+                .ok_or_else(|| Error::new(lit.span(), "expected a string literal"))?;
+            Ok(text.replace("\\\"", "\"").replace("\\\\", "\\"))
+        }
+        _ => Err(Error::new(key, "expected a string literal")),
+    }
+}
+
+fn expand(attr: TokenStream, item: TokenStream) -> Result<TokenStream, Error> {
+    let args = Args::parse(attr)?;
+    if let Some((_, span)) = args.name {
+        return Err(Error::new(span, "unknown procedure argument `name`"));
+    }
+    let sig = Signature::parse(item)?;
+    if sig.receiver.is_some() {
+        return Err(Error::new(sig.name.span(), "procedures cannot take `self`"));
+    }
+    let krate = args.krate.clone().unwrap_or_else(crate_path::sdk_path);
+
+    let mut params = Vec::with_capacity(sig.params.len());
+    for param in &sig.params {
+        let sql = metadata::param_sql_type(&type_string(&param.ty))
+            .map_err(|msg| Error::new(param.ty[0].span(), msg))?;
+        params.push((param.name.to_string(), sql));
+    }
+    let returns = metadata::return_sql_type(&type_string(&sig.ret)).map_err(|msg| {
+        Error::new(
+            sig.ret.first().map_or(sig.name.span(), TokenTree::span),
+            msg,
+        )
+    })?;
+
+    let nullable = sig
+        .params
+        .iter()
+        .any(|param| metadata::is_option(&type_string(&param.ty)));
+
+    let name = sig.name.to_string();
+    let description = args.description.clone().unwrap_or_else(|| sig.summary());
+    let record = Record {
+        name: &name,
+        kind: None,
+        params,
+        returns,
+        on_null: Some(if nullable { "called" } else { "strict" }),
+        description: &description,
+    };
+
+    let mut out = TokenStream::new();
+    out.extend(shim(&sig, &krate, nullable));
+    out.extend(metadata_static(
+        "PROCEDURE",
+        &name,
+        record.to_json_line().as_bytes(),
+    ));
+    Ok(out)
+}
+
+/// Generates the exported `extern "C-unwind"` function wrapping the user's
+/// body. Panics abort in WASM; natively they unwind so a test harness can
+/// catch a raised `ProcError`. The arguments of a `nullable` procedure,
+/// which the host calls on NULL input, are checked for NULL.
+fn shim(sig: &Signature, krate: &TokenStream, nullable: bool) -> TokenStream {
+    let ret: TokenStream = if sig.ret.is_empty() {
+        code("()")
+    } else {
+        sig.ret.iter().cloned().collect()
+    };
+
+    // vis extern "C-unwind" fn name(arg: <Ty as FromAbi>::Abi, ...) -> <Ret as IntoReturn>::Abi
+    let mut outer_args = TokenStream::new();
+    let mut inner_args = TokenStream::new();
+    let mut call_args = TokenStream::new();
+    for (index, param) in sig.params.iter().enumerate() {
+        let ty: TokenStream = param.ty.iter().cloned().collect();
+        outer_args.extend([TokenTree::Ident(param.name.clone())]);
+        outer_args.extend(code(":"));
+        outer_args.extend(qualified(&ty, krate, "FromAbi", "Abi"));
+        outer_args.extend(code(","));
+
+        inner_args.extend(param.pat.iter().cloned());
+        inner_args.extend(code(":"));
+        inner_args.extend(ty.clone());
+        inner_args.extend(code(","));
+
+        let arg = TokenStream::from(TokenTree::Ident(param.name.clone()));
+        if nullable {
+            call_args.extend(qualified(&ty, krate, "FromAbi", "from_nullable_abi"));
+            let mut index_arg = code(&format!("{index}u32,"));
+            index_arg.extend(arg);
+            call_args.extend([paren(index_arg)]);
+        } else {
+            call_args.extend(qualified(&ty, krate, "FromAbi", "from_abi"));
+            call_args.extend([paren(arg)]);
+        }
+        call_args.extend(code(","));
+    }
+
+    // fn inner(args) -> Ret { body }
+    let mut body = code("fn inner");
+    body.extend([paren(inner_args)]);
+    body.extend(code("->"));
+    body.extend(ret.clone());
+    body.extend([TokenTree::Group(sig.body.clone())]);
+
+    // IntoReturn::into_return(inner(converted args))
+    let mut call = code("inner");
+    call.extend([paren(call_args)]);
+    body.extend(krate.clone());
+    body.extend(code("::IntoReturn::into_return"));
+    body.extend([paren(call)]);
+
+    let mut out: TokenStream = sig.attrs.iter().cloned().collect();
+    out.extend(code("#[unsafe(no_mangle)]"));
+    out.extend(sig.vis.iter().cloned());
+    out.extend(code("extern \"C-unwind\" fn"));
+    out.extend([TokenTree::Ident(sig.name.clone())]);
+    out.extend([paren(outer_args)]);
+    out.extend(code("->"));
+    out.extend(qualified(&ret, krate, "IntoReturn", "Abi"));
+    out.extend([TokenTree::Group(Group::new(Delimiter::Brace, body))]);
+    out
+}
+
+/// Generates the static that places the signature in the custom section.
+fn metadata_static(kind: &str, name: &str, bytes: &[u8]) -> TokenStream {
+    let mut out = code(&format!(
+        "#[cfg_attr(target_arch = \"wasm32\", unsafe(link_section = \"{}\"))] \
+         #[used] #[doc(hidden)] static",
+        metadata::SECTION
+    ));
+    out.extend([TokenTree::Ident(Ident::new(
+        &format!("__MINDB_{kind}_{}", name.to_uppercase()),
+        Span::call_site(),
+    ))]);
+    out.extend(code(&format!(": [u8; {}] = *", bytes.len())));
+    out.extend([TokenTree::Literal(Literal::byte_string(bytes))]);
+    out.extend([TokenTree::Punct(Punct::new(';', Spacing::Alone))]);
+    out
+}
+
+/// Builds `<ty as krate::Trait>::item`.
+fn qualified(ty: &TokenStream, krate: &TokenStream, trait_name: &str, item: &str) -> TokenStream {
+    let mut out = code("<");
+    out.extend(ty.clone());
+    out.extend(code("as"));
+    out.extend(krate.clone());
+    out.extend(code(&format!("::{trait_name} > :: {item}")));
+    out
+}
+
+fn paren(inner: TokenStream) -> TokenTree {
+    TokenTree::Group(Group::new(Delimiter::Parenthesis, inner))
+}
+
+fn code(src: &str) -> TokenStream {
+    src.parse().expect("generated code must tokenize")
+}
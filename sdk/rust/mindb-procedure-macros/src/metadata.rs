@@ -0,0 +1,283 @@
+//! Signature metadata embedded in the `mindb.procedures` custom section.
+//!
+//! Each procedure contributes one JSON object followed by a newline. The
+//! linker concatenates same-named custom sections, so a module exporting
+//! several procedures carries one line per procedure. Aggregates are recorded
+//! the same way with `"kind":"aggregate"`. Procedures also record how they
+//! take NULL arguments: `"on_null":"called"` if any argument is an `Option`,
+//! otherwise `"strict"`. The host parses this in `src/core/wasm_metadata.go`.
+
+/// Name of the custom section the host reads.
+pub const SECTION: &str = "mindb.procedures";
+
+/// Maps a Rust argument type (rendered without whitespace) to its SQL type.
+/// `Option<T>` maps to the type of `T`.
+pub fn param_sql_type(ty: &str) -> Result<&'static str, String> {
+    let sql = match strip_reference(ty) {
+        Some("str") => "TEXT",
+        Some("[u8]") => "BLOB",
+        Some(_) => return Err(format!("unsupported procedure argument type `{ty}`")),
+        None => match split_generics(ty) {
+            ("Option", Some(inner)) if !is_option(inner) => {
+                return param_sql_type(inner)
+                    .map_err(|_| format!("unsupported procedure argument type `{ty}`"))
+            }
+            ("i32", None) => "INT",
+            ("i64", None) => "BIGINT",
+            ("f32", None) => "REAL",
+            ("f64", None) => "FLOAT",
+            ("bool", None) => "BOOLEAN",
+            ("String", None) => "TEXT",
+            ("Vec", Some("u8")) => "BLOB",
+            _ => return Err(format!("unsupported procedure argument type `{ty}`")),
+        },
+    };
+    Ok(sql)
+}
+
+/// Whether a type is `Option<T>`, which takes NULL.
+pub fn is_option(ty: &str) -> bool {
+    matches!(split_generics(ty), ("Option", Some(_)))
+}
+
+/// Strips a shared reference and its lifetime: `&'a str` becomes `str`.
+/// Returns `None` for types that are not references.
+fn strip_reference(ty: &str) -> Option<&str> {
+    let rest = ty.strip_prefix('&')?;
+    match rest.strip_prefix('\'') {
+        Some(lifetime) => Some(
+            lifetime
+                .trim_start_matches(|c: char| c.is_alphanumeric() || c == '_')
+                .trim_start(),
+        ),
+        None => Some(rest),
+    }
+}
+
+/// Maps a Rust return type to its SQL type, looking through `Result<T, _>`
+/// and `Option<T>`.
+pub fn return_sql_type(ty: &str) -> Result<&'static str, String> {
+    if ty.is_empty() || ty == "()" {
+        return Ok("VOID");
+    }
+    match split_generics(ty) {
+        ("Result", Some(args)) => {
+            let ok = first_generic_arg(args);
+            if ok.starts_with("Result<") {
+                return Err(format!("unsupported procedure return type `{ty}`"));
+            }
+            return_sql_type(ok)
+        }
+        ("Table", None) => Ok("TABLE"),
+        _ => param_sql_type(ty).map_err(|_| format!("unsupported procedure return type `{ty}`")),
+    }
+}
+
+/// Splits `path::Name<args>` into `Name` and the text between the brackets.
+fn split_generics(ty: &str) -> (&str, Option<&str>) {
+    let (path, args) = match ty.find('<') {
+        Some(i) if ty.ends_with('>') => (&ty[..i], Some(&ty[i + 1..ty.len() - 1])),
+        _ => (ty, None),
+    };
+    let name = path.rsplit("::").next().unwrap_or(path);
+    (name, args)
+}
+
+/// Returns the first comma-separated generic argument.
+fn first_generic_arg(args: &str) -> &str {
+    let mut depth = 0usize;
+    for (i, c) in args.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => return &args[..i],
+            _ => {}
+        }
+    }
+    args
+}
+
+/// A procedure signature as recorded in the custom section.
+pub struct Record<'a> {
+    pub name: &'a str,
+    /// `Some("aggregate")` for aggregates; omitted for procedures.
+    pub kind: Option<&'static str>,
+    pub params: Vec<(String, &'static str)>,
+    pub returns: &'static str,
+    /// `Some("strict")` or `Some("called")` for procedures; omitted for
+    /// aggregates, whose rows with a NULL argument the host skips.
+    pub on_null: Option<&'static str>,
+    pub description: &'a str,
+}
+
+impl Record<'_> {
+    /// Encodes the record as a single JSON line.
+    pub fn to_json_line(&self) -> String {
+        let mut out = String::from("{\"name\":");
+        push_json_string(&mut out, self.name);
+        if let Some(kind) = self.kind {
+            out.push_str(",\"kind\":");
+            push_json_string(&mut out, kind);
+        }
+        out.push_str(",\"params\":[");
+        for (i, (name, ty)) in self.params.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            out.push_str("{\"name\":");
+            push_json_string(&mut out, name);
+            out.push_str(",\"type\":");
+            push_json_string(&mut out, ty);
+            out.push('}');
+        }
+        out.push_str("],\"returns\":");
+        push_json_string(&mut out, self.returns);
+        if let Some(on_null) = self.on_null {
+            out.push_str(",\"on_null\":");
+            push_json_string(&mut out, on_null);
+        }
+        if !self.description.is_empty() {
+            out.push_str(",\"description\":");
+            push_json_string(&mut out, self.description);
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+fn push_json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_param_sql_types() {
+        assert_eq!(param_sql_type("f64"), Ok("FLOAT"));
+        assert_eq!(param_sql_type("f32"), Ok("REAL"));
+        assert_eq!(param_sql_type("i32"), Ok("INT"));
+        assert_eq!(param_sql_type("i64"), Ok("BIGINT"));
+        assert_eq!(param_sql_type("bool"), Ok("BOOLEAN"));
+        assert!(param_sql_type("u128").is_err());
+        assert!(param_sql_type("()").is_err());
+    }
+
+    #[test]
+    fn test_bytes_sql_types() {
+        assert_eq!(param_sql_type("&str"), Ok("TEXT"));
+        assert_eq!(param_sql_type("&'a str"), Ok("TEXT"));
+        assert_eq!(param_sql_type("String"), Ok("TEXT"));
+        assert_eq!(param_sql_type("alloc::string::String"), Ok("TEXT"));
+        assert_eq!(param_sql_type("&[u8]"), Ok("BLOB"));
+        assert_eq!(param_sql_type("&'a[u8]"), Ok("BLOB"));
+        assert_eq!(param_sql_type("Vec<u8>"), Ok("BLOB"));
+        assert_eq!(return_sql_type("Result<String>"), Ok("TEXT"));
+        assert_eq!(return_sql_type("&'static str"), Ok("TEXT"));
+        assert!(param_sql_type("&mut str").is_err());
+        assert!(param_sql_type("&i32").is_err());
+        assert!(param_sql_type("Vec<i32>").is_err());
+    }
+
+    #[test]
+    fn test_option_sql_types() {
+        assert_eq!(param_sql_type("Option<f64>"), Ok("FLOAT"));
+        assert_eq!(param_sql_type("core::option::Option<&str>"), Ok("TEXT"));
+        assert_eq!(return_sql_type("Option<i32>"), Ok("INT"));
+        assert_eq!(return_sql_type("Result<Option<String>>"), Ok("TEXT"));
+        assert!(is_option("Option<f64>"));
+        assert!(!is_option("f64"));
+        assert!(param_sql_type("Option<Option<f64>>").is_err());
+        assert!(param_sql_type("Option<u128>").is_err());
+        assert!(return_sql_type("Option<Table>").is_err());
+        assert!(return_sql_type("Option<()>").is_err());
+    }
+
+    #[test]
+    fn test_return_sql_types() {
+        assert_eq!(return_sql_type(""), Ok("VOID"));
+        assert_eq!(return_sql_type("Result<mindb::Table>"), Ok("TABLE"));
+        assert!(param_sql_type("Table").is_err());
+        assert_eq!(return_sql_type("()"), Ok("VOID"));
+        assert_eq!(return_sql_type("f64"), Ok("FLOAT"));
+        assert_eq!(return_sql_type("Result<f64>"), Ok("FLOAT"));
+        assert_eq!(return_sql_type("mindb::Result<i64>"), Ok("BIGINT"));
+        assert_eq!(return_sql_type("Result<i32,ProcError>"), Ok("INT"));
+        assert_eq!(
+            return_sql_type("core::result::Result<(),ProcError>"),
+            Ok("VOID")
+        );
+        assert!(return_sql_type("Result<Result<i32>>").is_err());
+        assert!(return_sql_type("Vec<i32>").is_err());
+    }
+
+    #[test]
+    fn test_record_json() {
+        let record = Record {
+            name: "calculate_tax",
+            kind: None,
+            params: vec![("amount".into(), "FLOAT"), ("state_code".into(), "INT")],
+            returns: "FLOAT",
+            on_null: Some("strict"),
+            description: "Sales tax for a \"state\" code",
+        };
+        assert_eq!(
+            record.to_json_line(),
+            concat!(
+                r#"{"name":"calculate_tax","params":[{"name":"amount","type":"FLOAT"},"#,
+                r#"{"name":"state_code","type":"INT"}],"returns":"FLOAT","on_null":"strict","#,
+                r#""description":"Sales tax for a \"state\" code"}"#,
+                "\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_record_without_description() {
+        let record = Record {
+            name: "noop",
+            kind: None,
+            params: vec![],
+            returns: "VOID",
+            on_null: Some("called"),
+            description: "",
+        };
+        assert_eq!(
+            record.to_json_line(),
+            "{\"name\":\"noop\",\"params\":[],\"returns\":\"VOID\",\"on_null\":\"called\"}\n"
+        );
+    }
+
+    #[test]
+    fn test_aggregate_record_json() {
+        let record = Record {
+            name: "weighted_avg",
+            kind: Some("aggregate"),
+            params: vec![("value".into(), "FLOAT"), ("weight".into(), "FLOAT")],
+            returns: "FLOAT",
+            on_null: None,
+            description: "",
+        };
+        assert_eq!(
+            record.to_json_line(),
+            concat!(
+                r#"{"name":"weighted_avg","kind":"aggregate","params":[{"name":"value","type":"FLOAT"},"#,
+                r#"{"name":"weight","type":"FLOAT"}],"returns":"FLOAT"}"#,
+                "\n"
+            )
+        );
+    }
+}
@@ -0,0 +1,313 @@
+//! Minimal parser for the `fn` items the attribute is applied to.
+//!
+//! Procedures are plain functions with simple typed arguments, so this only
+//! understands attributes, visibility, `fn name(arg: Type, ...) -> Type`, and
+//! a body. Generics, `async` and patterns other than `ident` or `mut ident`
+//! are rejected. A leading `self`, `&self` or `&mut self` is recorded as the
+//! receiver, for the methods of `#[aggregate]` impls.
+
+use proc_macro::{Delimiter, Group, Ident, Span, TokenStream, TokenTree};
+
+use crate::Error;
+
+/// A parsed procedure function.
+pub struct Signature {
+    /// Outer attributes, as written (including doc comments).
+    pub attrs: Vec<TokenTree>,
+    /// Text of the `#[doc = "..."]` attributes, one entry per line.
+    pub docs: Vec<String>,
+    /// Visibility as written, e.g. `pub` or `pub(crate)`; empty when private.
+    pub vis: Vec<TokenTree>,
+    pub name: Ident,
+    pub receiver: Option<Receiver>,
+    pub params: Vec<Param>,
+    /// Return type tokens; empty when the function returns `()`.
+    pub ret: Vec<TokenTree>,
+    pub body: Group,
+}
+
+/// How a method takes `self`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Receiver {
+    /// `self` or `mut self`
+    Value,
+    /// `&self`
+    Ref,
+    /// `&mut self`
+    RefMut,
+}
+
+/// A single procedure argument.
+pub struct Param {
+    /// The binding as written, e.g. `mut amount`.
+    pub pat: Vec<TokenTree>,
+    pub name: Ident,
+    pub ty: Vec<TokenTree>,
+}
+
+impl Signature {
+    pub fn parse(item: TokenStream) -> Result<Signature, Error> {
+        let mut tokens = item.into_iter().peekable();
+        let mut attrs = Vec::new();
+        let mut docs = Vec::new();
+
+        // Outer attributes: `#` followed by a bracketed group.
+        while let Some(TokenTree::Punct(p)) = tokens.peek() {
+            if p.as_char() != '#' {
+                break;
+            }
+            let hash = tokens.next().unwrap();
+            let group = match tokens.next() {
+                Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Bracket => g,
+                _ => return Err(Error::new(hash.span(), "expected attribute")),
+            };
+            if let Some(doc) = doc_text(&group) {
+                docs.push(doc);
+            }
+            attrs.push(hash);
+            attrs.push(TokenTree::Group(group));
+        }
+
+        // Visibility, kept for the generated function. The symbol is
+        // exported from the module either way.
+        let mut vis = Vec::new();
+        if let Some(TokenTree::Ident(i)) = tokens.peek() {
+            if i.to_string() == "pub" {
+                vis.push(tokens.next().unwrap());
+                if let Some(TokenTree::Group(g)) = tokens.peek() {
+                    if g.delimiter() == Delimiter::Parenthesis {
+                        vis.push(tokens.next().unwrap());
+                    }
+                }
+            }
+        }
+
+        match tokens.next() {
+            Some(TokenTree::Ident(i)) if i.to_string() == "fn" => {}
+            Some(tt) => {
+                return Err(Error::new(
+                    tt.span(),
+                    "#[procedure] must be applied to a plain `fn` item",
+                ))
+            }
+            None => return Err(Error::new(Span::call_site(), "expected `fn`")),
+        }
+
+        let name = match tokens.next() {
+            Some(TokenTree::Ident(i)) => i,
+            other => return Err(Error::new(span_of(&other), "expected function name")),
+        };
+
+        let args = match tokens.next() {
+            Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis => g,
+            Some(TokenTree::Punct(p)) if p.as_char() == '<' => {
+                return Err(Error::new(p.span(), "procedures cannot be generic"))
+            }
+            other => return Err(Error::new(span_of(&other), "expected argument list")),
+        };
+        let (receiver, params) = parse_params(args.stream())?;
+
+        let mut ret = Vec::new();
+        let body = loop {
+            match tokens.next() {
+                Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => break g,
+                Some(TokenTree::Ident(i)) if i.to_string() == "where" => {
+                    return Err(Error::new(i.span(), "procedures cannot have where clauses"))
+                }
+                Some(tt) => ret.push(tt),
+                None => return Err(Error::new(name.span(), "expected function body")),
+            }
+        };
+
+        // Strip the leading `->`.
+        if !ret.is_empty() {
+            let arrow = matches!(
+                (&ret.first(), &ret.get(1)),
+                (Some(TokenTree::Punct(a)), Some(TokenTree::Punct(b)))
+                    if a.as_char() == '-' && b.as_char() == '>'
+            );
+            if !arrow {
+                return Err(Error::new(ret[0].span(), "expected `->` or function body"));
+            }
+            ret.drain(..2);
+        }
+
+        Ok(Signature {
+            attrs,
+            docs,
+            vis,
+            name,
+            receiver,
+            params,
+            ret,
+            body,
+        })
+    }
+
+    /// Returns the first paragraph of the doc comment, joined into one line.
+    pub fn summary(&self) -> String {
+        summary(&self.docs)
+    }
+}
+
+/// Joins the first paragraph of doc comment lines into one line.
+pub fn summary(docs: &[String]) -> String {
+    let mut out = String::new();
+    for line in docs {
+        let line = line.trim();
+        if line.is_empty() {
+            if out.is_empty() {
+                continue;
+            }
+            break;
+        }
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        out.push_str(line);
+    }
+    out
+}
+
+/// Splits an argument list into the receiver, if any, and `name: Type`
+/// pairs.
+fn parse_params(stream: TokenStream) -> Result<(Option<Receiver>, Vec<Param>), Error> {
+    let mut args = split_top_level(stream).into_iter().peekable();
+    let receiver = args.peek().and_then(|arg| receiver(arg));
+    if receiver.is_some() {
+        args.next();
+    }
+
+    let mut params = Vec::new();
+    for arg in args {
+        let colon = arg
+            .iter()
+            .position(|tt| matches!(tt, TokenTree::Punct(p) if p.as_char() == ':'))
+            .ok_or_else(|| Error::new(arg[0].span(), "expected `name: Type`"))?;
+        let pat = arg[..colon].to_vec();
+        let ty = arg[colon + 1..].to_vec();
+        let name = match pat.as_slice() {
+            [TokenTree::Ident(i)] if i.to_string() != "self" => i.clone(),
+            [TokenTree::Ident(m), TokenTree::Ident(i)] if m.to_string() == "mut" => i.clone(),
+            _ => {
+                return Err(Error::new(
+                    arg[0].span(),
+                    "procedure arguments must be simple identifiers",
+                ))
+            }
+        };
+        if ty.is_empty() {
+            return Err(Error::new(name.span(), "missing argument type"));
+        }
+        params.push(Param { pat, name, ty });
+    }
+    Ok((receiver, params))
+}
+
+/// Recognizes `self`, `mut self`, `&self` and `&mut self`.
+fn receiver(arg: &[TokenTree]) -> Option<Receiver> {
+    let words: Vec<String> = arg.iter().map(TokenTree::to_string).collect();
+    match words.join(" ").as_str() {
+        "self" | "mut self" => Some(Receiver::Value),
+        "& self" => Some(Receiver::Ref),
+        "& mut self" => Some(Receiver::RefMut),
+        _ => None,
+    }
+}
+
+/// Splits a token stream on commas that are not nested inside `<...>`.
+pub fn split_top_level(stream: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut parts = Vec::new();
+    let mut current = Vec::new();
+    let mut depth = 0usize;
+    for tt in stream {
+        if let TokenTree::Punct(p) = &tt {
+            match p.as_char() {
+                '<' => depth += 1,
+                '>' => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => {
+                    parts.push(std::mem::take(&mut current));
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        current.push(tt);
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// Renders type tokens without whitespace, e.g. `Result<f64,ProcError>`,
+/// except between adjacent words so `&'a str` keeps its lifetime apart.
+pub fn type_string(ty: &[TokenTree]) -> String {
+    let mut out = String::new();
+    let mut after_word = false;
+    for tt in ty {
+        let word = matches!(tt, TokenTree::Ident(_));
+        if word && after_word {
+            out.push(' ');
+        }
+        out.push_str(&tt.to_string());
+        after_word = word;
+    }
+    out
+}
+
+/// Extracts the text of a `doc = "..."` attribute body.
+pub fn doc_text(group: &Group) -> Option<String> {
+    let tokens: Vec<TokenTree> = group.stream().into_iter().collect();
+    match tokens.as_slice() {
+        [TokenTree::Ident(i), TokenTree::Punct(eq), TokenTree::Literal(lit)]
+            if i.to_string() == "doc" && eq.as_char() == '=' =>
+        {
+            unquote(&lit.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Decodes a string literal as produced by `Literal::to_string`.
+fn unquote(lit: &str) -> Option<String> {
+    if let Some(raw) = lit.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        let inner = &raw[hashes..raw.len() - hashes];
+        return Some(inner.strip_prefix('"')?.strip_suffix('"')?.to_string());
+    }
+    let inner = lit.strip_prefix('"')?.strip_suffix('"')?;
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => out.push('\n'),
+            'r' => out.push('\r'),
+            't' => out.push('\t'),
+            '0' => out.push('\0'),
+            'x' => {
+                let hex: String = chars.by_ref().take(2).collect();
+                out.push(u8::from_str_radix(&hex, 16).ok()? as char);
+            }
+            'u' => {
+                let hex: String = chars.by_ref().skip(1).take_while(|&c| c != '}').collect();
+                out.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+            }
+            '\n' => {
+                while chars.as_str().starts_with(char::is_whitespace) {
+                    chars.next();
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    Some(out)
+}
+
+fn span_of(tt: &Option<TokenTree>) -> Span {
+    tt.as_ref().map_or_else(Span::call_site, TokenTree::span)
+}
@@ -0,0 +1,673 @@
+//! A mock mindb host for testing Rust procedures with `cargo test`.
+//!
+//! [`MockHost`] implements the imports `WASMEngine` links into every
+//! procedure (`mindb_query`, `mindb_get_row`, `mindb_insert`,
+//! `mindb_update` and `mindb_delete`, plus `mindb_return_*`,
+//! `mindb_arg_is_null`, `mindb_emit_row` and `mindb_error`) against in-memory tables, and meters
+//! fuel. Procedures run natively, so a test calls the function
+//! `#[procedure]` exported, with no server running:
+//!
+//! ```
+//! use mindb_procedure::{db, procedure, ProcError, Result};
+//! use mindb_procedure_test::MockHost;
+//!
+//! /// Charges 1% of the invoice per day late, capped at 25%.
+//! #[procedure]
+//! fn calculate_late_fee(invoice_id: i64) -> Result<f64> {
+//!     let invoice = db::get_row("invoices", &[("id", invoice_id.into())])?
+//!         .ok_or_else(|| ProcError::new("invoice not found"))?;
+//!     let amount = invoice.get("amount").and_then(db::Value::as_f64).unwrap_or(0.0);
+//!     let days = invoice.get("days_late").and_then(db::Value::as_i64).unwrap_or(0);
+//!     let fee = amount * (days as f64 * 0.01).min(0.25);
+//!     db::insert("late_fees", &[("invoice_id", invoice_id.into()), ("fee", fee.into())])?;
+//!     Ok(fee)
+//! }
+//!
+//! let host = MockHost::new();
+//! host.insert("invoices", &[("id", 1.into()), ("amount", 200.0.into()), ("days_late", 10.into())]);
+//! host.create_table("late_fees");
+//!
+//! assert_eq!(host.call(|| calculate_late_fee(1)), Ok(20.0));
+//! assert_eq!(host.rows("late_fees")[0].get("fee"), Some(&db::Value::Float(20.0)));
+//!
+//! let err = host.call(|| calculate_late_fee(2)).unwrap_err();
+//! assert_eq!(err.to_string(), "procedure error: invoice not found");
+//! ```
+//!
+//! TEXT and BLOB arguments are passed with [`MockHost::text`] and
+//! [`MockHost::blob`], and such results read back with
+//! [`MockHost::returned_text`] and [`MockHost::returned_blob`]. NULL
+//! arguments are flagged with [`MockHost::null_args`], and a NULL result
+//! shows in [`MockHost::returned_null`]. Rows a
+//! table-valued procedure emits are in [`MockHost::emitted_rows`].
+//!
+//! `db::query` understands a subset of SELECT: one table, `AND`ed
+//! comparisons against literals, `ORDER BY` and `LIMIT`. Other statements
+//! can be answered with [`MockHost::stub_query`].
+//!
+//! Native code is not metered, so fuel is charged for host calls only:
+//! [`HOST_CALL_FUEL`] per call plus one unit per byte passed either way and
+//! per row scanned. That catches a procedure that loops over the database
+//! until the limit runs out, but not a loop that never calls the host.
+
+mod query;
+mod tables;
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+use std::sync::Once;
+
+use mindb_procedure::db::{Row, Value};
+use mindb_procedure::log::Level;
+use mindb_procedure::native::{self, Host};
+use mindb_procedure::{ProcError, Result};
+
+use tables::Tables;
+
+/// Fuel charged for each host call, on top of the bytes and rows it moves.
+pub const HOST_CALL_FUEL: u64 = 1_000;
+
+/// The fuel a call may use unless [`MockHost::with_fuel_limit`] sets
+/// another limit; the server's default `FuelLimit`.
+pub const DEFAULT_FUEL_LIMIT: u64 = 10_000_000;
+
+/// Why a call failed, reported as the server would.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CallError {
+    /// The procedure returned `Err`; holds the error it raised.
+    Procedure(ProcError),
+    /// The call used more than its fuel limit.
+    OutOfFuel,
+    /// The procedure panicked, which traps in WASM.
+    Panic(String),
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallError::Procedure(err) => write!(f, "procedure error: {err}"),
+            CallError::OutOfFuel => {
+                f.write_str("execution error: all fuel consumed by WebAssembly")
+            }
+            CallError::Panic(message) => {
+                write!(f, "execution error: procedure panicked: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CallError {}
+
+/// An in-memory stand-in for the mindb host.
+///
+/// Tables persist across calls; the result, emitted rows and fuel used
+/// describe the most recent call.
+#[derive(Default)]
+pub struct MockHost {
+    state: Rc<State>,
+}
+
+#[derive(Default)]
+struct State {
+    tables: RefCell<Tables>,
+    stubs: RefCell<Vec<(String, Vec<Row>)>>,
+    /// TEXT and BLOB arguments; an argument's value is its index plus one.
+    args: RefCell<Vec<Box<[u8]>>>,
+    fuel_limit: Cell<Option<u64>>,
+    fuel_consumed: Cell<u64>,
+    out_of_fuel: Cell<bool>,
+    error: RefCell<Option<ProcError>>,
+    returned: RefCell<Option<(Vec<u8>, bool)>>,
+    returned_null: Cell<bool>,
+    nulls: RefCell<Vec<u32>>,
+    emitted: RefCell<Vec<Row>>,
+    logged: RefCell<Vec<(Level, String)>>,
+}
+
+impl MockHost {
+    /// Creates a host with no tables.
+    pub fn new() -> Self {
+        MockHost::default()
+    }
+
+    /// Sets the fuel each call may use.
+    pub fn with_fuel_limit(self, limit: u64) -> Self {
+        self.state.fuel_limit.set(Some(limit));
+        self
+    }
+
+    /// Creates an empty table unless it already exists.
+    pub fn create_table(&self, table: &str) {
+        self.state.tables.borrow_mut().create(table);
+    }
+
+    /// Inserts a row, creating the table if needed.
+    pub fn insert(&self, table: &str, row: &[(&str, Value)]) {
+        let mut tables = self.state.tables.borrow_mut();
+        tables.create(table);
+        tables
+            .insert(table, to_row(row))
+            .expect("table was just created");
+    }
+
+    /// Returns a table's rows in insertion order; empty if there is no such
+    /// table.
+    pub fn rows(&self, table: &str) -> Vec<Row> {
+        self.state
+            .tables
+            .borrow()
+            .rows(table)
+            .map(<[Row]>::to_vec)
+            .unwrap_or_default()
+    }
+
+    /// Answers `sql` with `rows` instead of running it. Whitespace and a
+    /// trailing `;` are ignored when matching.
+    pub fn stub_query(&self, sql: &str, rows: &[&[(&str, Value)]]) {
+        let rows = rows.iter().map(|row| to_row(row)).collect();
+        self.state.stubs.borrow_mut().push((normalize(sql), rows));
+    }
+
+    /// Passes a TEXT argument. The value is valid until the end of the next
+    /// call.
+    pub fn text(&self, value: &str) -> i64 {
+        self.blob(value.as_bytes())
+    }
+
+    /// Passes a BLOB argument. The value is valid until the end of the next
+    /// call.
+    pub fn blob(&self, value: &[u8]) -> i64 {
+        // Empty values are 0, as in WASM
+        if value.is_empty() {
+            return 0;
+        }
+        let mut args = self.state.args.borrow_mut();
+        args.push(value.into());
+        args.len() as i64
+    }
+
+    /// Passes the arguments at `indices`, counting from 0, as NULL in the
+    /// next call. Pass a zero value in their place, as the server does; a
+    /// NULL-aware procedure sees `None` for them.
+    pub fn null_args(&self, indices: &[u32]) {
+        self.state.nulls.borrow_mut().extend_from_slice(indices);
+    }
+
+    /// Runs `procedure` with this host installed and returns its WASM
+    /// result. Call the function `#[procedure]` exported inside the closure.
+    pub fn call<T>(&self, procedure: impl FnOnce() -> T) -> std::result::Result<T, CallError> {
+        quiet_expected_panics();
+        let state = &self.state;
+        state.fuel_consumed.set(0);
+        state.out_of_fuel.set(false);
+        state.error.take();
+        state.returned.take();
+        state.returned_null.set(false);
+        state.emitted.take();
+        state.logged.take();
+
+        let previous = native::set_host(Some(state.clone()));
+        let outcome = panic::catch_unwind(AssertUnwindSafe(procedure));
+        native::set_host(previous);
+        state.args.take();
+        state.nulls.take();
+        QUIET.with(|quiet| quiet.set(false));
+
+        let payload = match outcome {
+            Ok(value) => return Ok(value),
+            Err(payload) => payload,
+        };
+        if state.out_of_fuel.get() {
+            return Err(CallError::OutOfFuel);
+        }
+        if let Some(err) = state.error.take() {
+            return Err(CallError::Procedure(err));
+        }
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(CallError::Panic(message))
+    }
+
+    /// The TEXT result of the last call.
+    pub fn returned_text(&self) -> Option<String> {
+        match &*self.state.returned.borrow() {
+            Some((bytes, true)) => Some(String::from_utf8_lossy(bytes).into_owned()),
+            _ => None,
+        }
+    }
+
+    /// The BLOB result of the last call.
+    pub fn returned_blob(&self) -> Option<Vec<u8>> {
+        match &*self.state.returned.borrow() {
+            Some((bytes, false)) => Some(bytes.clone()),
+            _ => None,
+        }
+    }
+
+    /// Whether the last call returned NULL.
+    pub fn returned_null(&self) -> bool {
+        self.state.returned_null.get()
+    }
+
+    /// The rows the last call emitted.
+    pub fn emitted_rows(&self) -> Vec<Row> {
+        self.state.emitted.borrow().clone()
+    }
+
+    /// The lines the last call logged.
+    pub fn logged(&self) -> Vec<(Level, String)> {
+        self.state.logged.borrow().clone()
+    }
+
+    /// The fuel the last call used.
+    pub fn fuel_consumed(&self) -> u64 {
+        self.state.fuel_consumed.get()
+    }
+}
+
+impl State {
+    /// Charges a host call moving `bytes` bytes and scanning `rows` rows,
+    /// trapping once the limit is exceeded.
+    fn charge(&self, bytes: usize, rows: usize) {
+        let fuel = HOST_CALL_FUEL + bytes as u64 + rows as u64;
+        let consumed = self.fuel_consumed.get().saturating_add(fuel);
+        self.fuel_consumed.set(consumed);
+        if consumed > self.fuel_limit.get().unwrap_or(DEFAULT_FUEL_LIMIT) {
+            self.out_of_fuel.set(true);
+            QUIET.with(|quiet| quiet.set(true));
+            panic!("all fuel consumed by WebAssembly");
+        }
+    }
+
+    fn table_len(&self, table: &str) -> usize {
+        self.tables.borrow().rows(table).map_or(0, <[Row]>::len)
+    }
+
+    fn stub(&self, sql: &str) -> Option<Vec<Row>> {
+        let sql = normalize(sql);
+        self.stubs
+            .borrow()
+            .iter()
+            .find(|(stub, _)| *stub == sql)
+            .map(|(_, rows)| rows.clone())
+    }
+}
+
+impl Host for State {
+    fn query(&self, sql: &str) -> Result<Vec<u8>> {
+        let (rows, scanned) = match self.stub(sql) {
+            Some(rows) => (rows, 0),
+            None => {
+                let select = query::parse(sql)?;
+                let rows = select.run(&self.tables.borrow())?;
+                (rows, self.table_len(&select.table))
+            }
+        };
+        let result = native::encode_rows(&rows);
+        self.charge(sql.len() + result.len(), scanned);
+        Ok(result)
+    }
+
+    fn get_row(&self, table: &str, filter: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.charge(table.len() + filter.len(), self.table_len(table));
+        let filter = native::decode_row(filter)?;
+        let tables = self.tables.borrow();
+        let row = tables
+            .rows(table)?
+            .iter()
+            .find(|row| tables::matches(row, &filter));
+        let result = row.map(native::encode_row);
+        self.charge(result.as_ref().map_or(0, Vec::len), 0);
+        Ok(result)
+    }
+
+    fn insert(&self, table: &str, row: &[u8]) -> Result<u32> {
+        self.charge(table.len() + row.len(), 0);
+        let row = native::decode_row(row)?;
+        self.tables.borrow_mut().insert(table, row)?;
+        Ok(1)
+    }
+
+    fn update(&self, table: &str, set: &[u8], filter: &[u8]) -> Result<u32> {
+        self.charge(
+            table.len() + set.len() + filter.len(),
+            self.table_len(table),
+        );
+        let (set, filter) = (native::decode_row(set)?, native::decode_row(filter)?);
+        self.tables.borrow_mut().update(table, &set, &filter)
+    }
+
+    fn delete(&self, table: &str, filter: &[u8]) -> Result<u32> {
+        self.charge(table.len() + filter.len(), self.table_len(table));
+        let filter = native::decode_row(filter)?;
+        self.tables.borrow_mut().delete(table, &filter)
+    }
+
+    fn emit_row(&self, row: &[u8]) -> Result<()> {
+        self.charge(row.len(), 0);
+        let row = native::decode_row(row)?;
+        self.emitted.borrow_mut().push(row);
+        Ok(())
+    }
+
+    fn return_bytes(&self, bytes: &[u8], text: bool) {
+        self.charge(bytes.len(), 0);
+        *self.returned.borrow_mut() = Some((bytes.to_vec(), text));
+    }
+
+    fn return_null(&self) {
+        self.charge(0, 0);
+        self.returned_null.set(true);
+    }
+
+    fn arg_is_null(&self, index: u32) -> bool {
+        self.charge(0, 0);
+        self.nulls.borrow().contains(&index)
+    }
+
+    fn error(&self, error: &ProcError) {
+        // The SDK panics right after; the call reports the error instead
+        *self.error.borrow_mut() = Some(error.clone());
+        QUIET.with(|quiet| quiet.set(true));
+    }
+
+    fn log(&self, level: Level, message: &str) {
+        self.charge(message.len(), 0);
+        self.logged.borrow_mut().push((level, message.to_string()));
+    }
+
+    fn argument(&self, value: i64) -> Option<(*const u8, usize)> {
+        let index = usize::try_from(value).ok()?.checked_sub(1)?;
+        let args = self.args.borrow();
+        // The boxed bytes do not move when `args` grows
+        args.get(index).map(|arg| (arg.as_ptr(), arg.len()))
+    }
+}
+
+fn to_row(columns: &[(&str, Value)]) -> Row {
+    columns
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect()
+}
+
+/// Collapses whitespace and drops a trailing `;` for matching stubs.
+fn normalize(sql: &str) -> String {
+    let sql = sql.trim().trim_end_matches(';');
+    sql.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+std::thread_local! {
+    /// Set when the next panic on this thread is a raised error or a fuel
+    /// trap, which `call` reports itself.
+    static QUIET: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Keeps the panic hook from printing the panics `call` turns into errors.
+fn quiet_expected_panics() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let default = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !QUIET.with(|quiet| quiet.replace(false)) {
+                default(info);
+            }
+        }));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use mindb_procedure::{db, procedure, Table};
+
+    use super::*;
+
+    /// Totals a customer's open invoices and marks them billed.
+    #[procedure]
+    fn test_bill_customer(customer_id: i64) -> Result<f64> {
+        let sql = format!(
+            "SELECT amount FROM invoices WHERE customer_id = {customer_id} AND status = 'open'"
+        );
+        let total = db::query(&sql)?
+            .iter()
+            .filter_map(|row| row.get("amount").and_then(db::Value::as_f64))
+            .sum();
+        db::update(
+            "invoices",
+            &[("status", "billed".into())],
+            &[
+                ("customer_id", customer_id.into()),
+                ("status", "open".into()),
+            ],
+        )?;
+        Ok(total)
+    }
+
+    #[procedure]
+    fn test_greeting(name: &str, times: i32) -> String {
+        format!("Hello, {}!", name).repeat(times as usize)
+    }
+
+    #[procedure]
+    fn test_reverse(data: Vec<u8>) -> Vec<u8> {
+        data.into_iter().rev().collect()
+    }
+
+    #[procedure]
+    fn test_open_invoices() -> Result<Table> {
+        let mut table = Table::new();
+        for row in db::query("SELECT id, amount FROM invoices WHERE status = 'open' ORDER BY id")? {
+            table.emit(&[("id", row.get("id").cloned().unwrap_or(db::Value::Null))])?;
+        }
+        Ok(table)
+    }
+
+    #[procedure]
+    fn test_purge(status: &str) -> Result<i32> {
+        Ok(db::delete("invoices", &[("status", status.into())])? as i32)
+    }
+
+    #[procedure]
+    fn test_spin() -> Result<()> {
+        loop {
+            db::get_row("invoices", &[])?;
+        }
+    }
+
+    #[procedure]
+    fn test_fifth_byte(data: &[u8]) -> i32 {
+        data[4] as i32
+    }
+
+    #[procedure]
+    fn test_tier_discount(tier: i32) -> Result<f64> {
+        mindb_procedure::debug!("discount for tier {}", tier);
+        match tier {
+            0..=3 => Ok(tier as f64 * 0.05),
+            _ => Err(ProcError::new("invalid tier")
+                .with_code("22023")
+                .with_detail(format!("tier {tier} is not between 0 and 3"))),
+        }
+    }
+
+    #[procedure]
+    fn test_late_fee(amount: Option<f64>, days_late: i32) -> Option<f64> {
+        amount.map(|amount| amount * (days_late as f64 * 0.01).min(0.25))
+    }
+
+    fn invoices() -> MockHost {
+        let host = MockHost::new();
+        for (id, customer, amount, status) in [
+            (1, 7, 100.0, "open"),
+            (2, 7, 50.5, "open"),
+            (3, 7, 20.0, "paid"),
+            (4, 8, 75.0, "open"),
+        ] {
+            host.insert(
+                "invoices",
+                &[
+                    ("id", id.into()),
+                    ("customer_id", customer.into()),
+                    ("amount", amount.into()),
+                    ("status", status.into()),
+                ],
+            );
+        }
+        host
+    }
+
+    #[test]
+    fn test_database_calls() {
+        let host = invoices();
+        assert_eq!(host.call(|| test_bill_customer(7)), Ok(150.5));
+
+        let statuses: Vec<_> = host
+            .rows("invoices")
+            .iter()
+            .map(|row| {
+                row.get("status")
+                    .and_then(db::Value::as_str)
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(statuses, ["billed", "billed", "paid", "open"]);
+
+        // Tables persist across calls
+        assert_eq!(host.call(|| test_bill_customer(7)), Ok(0.0));
+        assert_eq!(host.call(|| test_purge(host.text("billed"))), Ok(2));
+        assert_eq!(host.rows("invoices").len(), 2);
+    }
+
+    #[test]
+    fn test_text_and_blob() {
+        let host = MockHost::new();
+        host.call(|| test_greeting(host.text("Ada"), 2)).unwrap();
+        assert_eq!(
+            host.returned_text().as_deref(),
+            Some("Hello, Ada!Hello, Ada!")
+        );
+        assert_eq!(host.returned_blob(), None);
+
+        let data = host.blob(&[1, 2, 3]);
+        host.call(|| test_reverse(data)).unwrap();
+        assert_eq!(host.returned_blob(), Some(vec![3, 2, 1]));
+
+        host.call(|| test_greeting(host.text(""), 1)).unwrap();
+        assert_eq!(host.returned_text().as_deref(), Some("Hello, !"));
+    }
+
+    #[test]
+    fn test_null_args() {
+        let host = MockHost::new();
+        host.null_args(&[0]);
+        assert_eq!(host.call(|| test_late_fee(0.0, 10)), Ok(0.0));
+        assert!(host.returned_null());
+
+        // Flags last for one call
+        assert_eq!(host.call(|| test_late_fee(200.0, 10)), Ok(20.0));
+        assert!(!host.returned_null());
+
+        // NULL for an argument that is not an Option is an error
+        host.null_args(&[1]);
+        match host.call(|| test_late_fee(200.0, 0)) {
+            Err(CallError::Procedure(err)) => {
+                assert_eq!(err.code(), "22004");
+                assert_eq!(err.to_string(), "argument 2 must not be NULL");
+            }
+            other => panic!("expected a procedure error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_emitted_rows() {
+        let host = invoices();
+        host.call(|| test_open_invoices()).unwrap();
+        let ids: Vec<_> = host
+            .emitted_rows()
+            .iter()
+            .map(|row| row.get("id").and_then(db::Value::as_i64).unwrap())
+            .collect();
+        assert_eq!(ids, [1, 2, 4]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let host = MockHost::new();
+        assert_eq!(
+            host.call(|| test_bill_customer(1)),
+            Err(CallError::Procedure(ProcError::new(
+                "table 'invoices' does not exist"
+            )))
+        );
+
+        let err = host.call(|| test_tier_discount(7)).unwrap_err();
+        let CallError::Procedure(ref raised) = err else {
+            panic!("{err}");
+        };
+        assert_eq!(raised.code(), "22023");
+        assert_eq!(raised.detail(), Some("tier 7 is not between 0 and 3"));
+        assert_eq!(err.to_string(), "procedure error: invalid tier");
+
+        let err = host.call(|| test_fifth_byte(host.blob(&[1]))).unwrap_err();
+        assert!(
+            matches!(err, CallError::Panic(ref m) if m.contains("out of bounds")),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_logged() {
+        let host = MockHost::new();
+        assert!(host.call(|| test_tier_discount(2)).is_ok());
+        assert_eq!(
+            host.logged(),
+            [(Level::Debug, "discount for tier 2".to_string())]
+        );
+        assert!(host.fuel_consumed() > HOST_CALL_FUEL);
+    }
+
+    #[test]
+    fn test_stub_query() {
+        let host = MockHost::new();
+        host.stub_query(
+            "SELECT amount FROM invoices WHERE customer_id = 3 AND status = 'open';",
+            &[&[("amount", 12.5.into())], &[("amount", 7.5.into())]],
+        );
+        host.create_table("invoices");
+        assert_eq!(host.call(|| test_bill_customer(3)), Ok(20.0));
+    }
+
+    #[test]
+    fn test_fuel() {
+        let host = invoices();
+        host.call(|| test_bill_customer(7)).unwrap();
+        let used = host.fuel_consumed();
+        assert!(used > 2 * HOST_CALL_FUEL, "{used}");
+
+        let host = invoices().with_fuel_limit(50_000);
+        let err = host.call(|| test_spin()).unwrap_err();
+        assert_eq!(err, CallError::OutOfFuel);
+        assert_eq!(
+            err.to_string(),
+            "execution error: all fuel consumed by WebAssembly"
+        );
+        assert!(host.fuel_consumed() > 50_000);
+
+        // The limit applies per call
+        assert_eq!(host.call(|| test_bill_customer(8)), Ok(75.0));
+    }
+
+    #[test]
+    fn test_host_is_removed_after_call() {
+        let host = invoices();
+        host.call(|| test_bill_customer(7)).unwrap();
+        let err = db::get_row("invoices", &[]).unwrap_err();
+        assert_eq!(err.message(), "database access requires the mindb host");
+    }
+}
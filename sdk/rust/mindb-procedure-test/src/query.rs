@@ -0,0 +1,409 @@
+//! The SELECT subset the mock runs for `mindb_query`:
+//!
+//! ```text
+//! SELECT * | column [, column]... FROM table
+//!     [WHERE column op literal [AND column op literal]...]
+//!     [ORDER BY column [ASC | DESC] [, column [ASC | DESC]]...]
+//!     [LIMIT n]
+//! ```
+//!
+//! `op` is `=`, `!=`, `<>`, `<`, `<=`, `>` or `>=`, and a literal is a
+//! number, a 'string', TRUE, FALSE or NULL. Anything else needs
+//! [`MockHost::stub_query`](crate::MockHost::stub_query).
+
+use std::cmp::Ordering;
+
+use mindb_procedure::db::{Row, Value};
+use mindb_procedure::{ProcError, Result};
+
+use crate::tables::{column, compare, Tables};
+
+/// A parsed SELECT.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Select {
+    /// `None` for `*`.
+    columns: Option<Vec<String>>,
+    pub table: String,
+    conditions: Vec<(String, Op, Value)>,
+    /// Columns with `true` for descending.
+    order: Vec<(String, bool)>,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    fn holds(self, ordering: Option<Ordering>) -> bool {
+        match (self, ordering) {
+            (Op::Ne, None) => true,
+            (_, None) => false,
+            (Op::Eq, Some(o)) => o.is_eq(),
+            (Op::Ne, Some(o)) => o.is_ne(),
+            (Op::Lt, Some(o)) => o.is_lt(),
+            (Op::Le, Some(o)) => o.is_le(),
+            (Op::Gt, Some(o)) => o.is_gt(),
+            (Op::Ge, Some(o)) => o.is_ge(),
+        }
+    }
+}
+
+impl Select {
+    /// Runs the query against `tables`.
+    pub fn run(&self, tables: &Tables) -> Result<Vec<Row>> {
+        let mut rows: Vec<&Row> = tables
+            .rows(&self.table)?
+            .iter()
+            .filter(|row| {
+                self.conditions
+                    .iter()
+                    .all(|(name, op, value)| op.holds(compare(column(row, name), value)))
+            })
+            .collect();
+
+        rows.sort_by(|a, b| {
+            self.order
+                .iter()
+                .map(|(name, descending)| {
+                    let ordering = sort_order(column(a, name), column(b, name));
+                    if *descending {
+                        ordering.reverse()
+                    } else {
+                        ordering
+                    }
+                })
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+        rows.truncate(self.limit.unwrap_or(usize::MAX));
+
+        Ok(rows
+            .into_iter()
+            .map(|row| match &self.columns {
+                None => row.clone(),
+                Some(columns) => columns
+                    .iter()
+                    .map(|name| (name.clone(), column(row, name).clone()))
+                    .collect(),
+            })
+            .collect())
+    }
+}
+
+/// Orders any two values: NULL first, then booleans, numbers and text.
+fn sort_order(a: &Value, b: &Value) -> Ordering {
+    let rank = |value: &Value| match value {
+        Value::Null => 0,
+        Value::Bool(_) => 1,
+        Value::Int(_) | Value::Float(_) => 2,
+        Value::Text(_) => 3,
+    };
+    compare(a, b).unwrap_or_else(|| rank(a).cmp(&rank(b)))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Number(String),
+    Str(String),
+    Symbol(&'static str),
+}
+
+/// Parses a SELECT in the supported subset.
+pub(crate) fn parse(sql: &str) -> Result<Select> {
+    let tokens = tokenize(sql)?;
+    if !matches!(tokens.first(), Some(Token::Word(w)) if w.eq_ignore_ascii_case("SELECT")) {
+        return Err(ProcError::new(
+            "mindb_query only supports SELECT statements",
+        ));
+    }
+    let mut parser = Parser { tokens, pos: 1 };
+    parser
+        .select()
+        .map_err(|reason| ProcError::new(format!("mock host cannot run query ({reason}): {sql}")))
+}
+
+fn tokenize(sql: &str) -> Result<Vec<Token>> {
+    const SYMBOLS: [&str; 10] = ["<=", ">=", "<>", "!=", "=", "<", ">", "*", ",", ";"];
+
+    let mut tokens = Vec::new();
+    let mut rest = sql.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let len = if c.is_ascii_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(rest.len());
+            tokens.push(Token::Word(rest[..len].to_string()));
+            len
+        } else if c.is_ascii_digit()
+            || (c == '-' && rest[1..].starts_with(|c: char| c.is_ascii_digit()))
+        {
+            let len = 1 + rest[1..]
+                .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E'))
+                .unwrap_or(rest.len() - 1);
+            tokens.push(Token::Number(rest[..len].to_string()));
+            len
+        } else if c == '\'' {
+            let (text, len) = quoted(rest)?;
+            tokens.push(Token::Str(text));
+            len
+        } else if let Some(symbol) = SYMBOLS.iter().find(|s| rest.starts_with(**s)) {
+            tokens.push(Token::Symbol(symbol));
+            symbol.len()
+        } else {
+            return Err(ProcError::new(format!("mock host cannot run query: {sql}")));
+        };
+        rest = rest[len..].trim_start();
+    }
+    Ok(tokens)
+}
+
+/// Reads a '...' literal, where '' is a quote. Returns the text and the
+/// length consumed.
+fn quoted(input: &str) -> Result<(String, usize)> {
+    let mut text = String::new();
+    let mut chars = input.char_indices().skip(1).peekable();
+    while let Some((i, c)) = chars.next() {
+        if c != '\'' {
+            text.push(c);
+        } else if chars.peek().map(|&(_, next)| next) == Some('\'') {
+            text.push('\'');
+            chars.next();
+        } else {
+            return Ok((text, i + 1));
+        }
+    }
+    Err(ProcError::new("unterminated string literal"))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn keyword(&mut self, word: &str) -> bool {
+        match self.tokens.get(self.pos) {
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case(word) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn symbol(&mut self, symbol: &'static str) -> bool {
+        if self.tokens.get(self.pos) == Some(&Token::Symbol(symbol)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn name(&mut self, what: &str) -> std::result::Result<String, String> {
+        match self.next() {
+            Some(Token::Word(w)) => Ok(w),
+            _ => Err(format!("expected {what}")),
+        }
+    }
+
+    fn select(&mut self) -> std::result::Result<Select, String> {
+        let columns = if self.symbol("*") {
+            None
+        } else {
+            let mut columns = vec![self.name("a column")?];
+            while self.symbol(",") {
+                columns.push(self.name("a column")?);
+            }
+            Some(columns)
+        };
+        if !self.keyword("FROM") {
+            return Err("expected FROM".into());
+        }
+        let table = self.name("a table")?;
+
+        let mut conditions = Vec::new();
+        if self.keyword("WHERE") {
+            loop {
+                let name = self.name("a column")?;
+                let op = match self.next() {
+                    Some(Token::Symbol("=")) => Op::Eq,
+                    Some(Token::Symbol("!=" | "<>")) => Op::Ne,
+                    Some(Token::Symbol("<")) => Op::Lt,
+                    Some(Token::Symbol("<=")) => Op::Le,
+                    Some(Token::Symbol(">")) => Op::Gt,
+                    Some(Token::Symbol(">=")) => Op::Ge,
+                    _ => return Err("expected a comparison".into()),
+                };
+                conditions.push((name, op, self.literal()?));
+                if !self.keyword("AND") {
+                    break;
+                }
+            }
+        }
+
+        let mut order = Vec::new();
+        if self.keyword("ORDER") {
+            if !self.keyword("BY") {
+                return Err("expected BY".into());
+            }
+            loop {
+                let name = self.name("a column")?;
+                let descending = self.keyword("DESC");
+                if !descending {
+                    self.keyword("ASC");
+                }
+                order.push((name, descending));
+                if !self.symbol(",") {
+                    break;
+                }
+            }
+        }
+
+        let mut limit = None;
+        if self.keyword("LIMIT") {
+            limit = match self.next() {
+                Some(Token::Number(n)) => Some(n.parse().map_err(|_| "invalid LIMIT")?),
+                _ => return Err("expected a number after LIMIT".into()),
+            };
+        }
+
+        self.symbol(";");
+        if self.pos < self.tokens.len() {
+            return Err("unsupported clause".into());
+        }
+        Ok(Select {
+            columns,
+            table,
+            conditions,
+            order,
+            limit,
+        })
+    }
+
+    fn literal(&mut self) -> std::result::Result<Value, String> {
+        match self.next() {
+            Some(Token::Number(n)) => match n.parse::<i64>() {
+                Ok(i) => Ok(Value::Int(i)),
+                Err(_) => n
+                    .parse::<f64>()
+                    .map(Value::Float)
+                    .map_err(|_| format!("invalid number {n}")),
+            },
+            Some(Token::Str(s)) => Ok(Value::Text(s)),
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case("TRUE") => Ok(Value::Bool(true)),
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case("FALSE") => Ok(Value::Bool(false)),
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case("NULL") => Ok(Value::Null),
+            _ => Err("expected a literal".into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tables() -> Tables {
+        let mut tables = Tables::default();
+        tables.create("invoices");
+        for (id, amount, status) in [(1, 120.0, "open"), (2, 80.5, "paid"), (3, 300.0, "open")] {
+            let row: Row = [
+                ("id".to_string(), Value::Int(id)),
+                ("amount".to_string(), Value::Float(amount)),
+                ("status".to_string(), Value::Text(status.into())),
+            ]
+            .into_iter()
+            .collect();
+            tables.insert("invoices", row).unwrap();
+        }
+        tables
+    }
+
+    fn ids(sql: &str) -> Vec<i64> {
+        parse(sql)
+            .unwrap()
+            .run(&tables())
+            .unwrap()
+            .iter()
+            .map(|row| row.get("id").and_then(Value::as_i64).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_where_order_limit() {
+        assert_eq!(ids("SELECT * FROM invoices"), [1, 2, 3]);
+        assert_eq!(
+            ids("select id from invoices where status = 'open' and amount >= 100"),
+            [1, 3]
+        );
+        assert_eq!(ids("SELECT id FROM invoices WHERE status <> 'open'"), [2]);
+        assert_eq!(
+            ids("SELECT id FROM invoices ORDER BY amount DESC LIMIT 2;"),
+            [3, 1]
+        );
+        assert_eq!(
+            ids("SELECT id FROM invoices ORDER BY status, id DESC"),
+            [3, 1, 2]
+        );
+        assert_eq!(
+            ids("SELECT id FROM invoices WHERE amount < -1"),
+            Vec::<i64>::new()
+        );
+    }
+
+    #[test]
+    fn test_projection() {
+        let rows = parse("SELECT status, missing FROM invoices WHERE id = 2")
+            .unwrap()
+            .run(&tables())
+            .unwrap();
+        assert_eq!(
+            rows[0].iter().collect::<Vec<_>>(),
+            [
+                ("status", &Value::Text("paid".into())),
+                ("missing", &Value::Null)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_string_literals() {
+        let select = parse("SELECT * FROM t WHERE name = 'O''Brien'").unwrap();
+        assert_eq!(
+            select.conditions,
+            [("name".to_string(), Op::Eq, Value::Text("O'Brien".into()))]
+        );
+    }
+
+    #[test]
+    fn test_unsupported() {
+        assert_eq!(
+            parse("DELETE FROM invoices").unwrap_err().message(),
+            "mindb_query only supports SELECT statements"
+        );
+        let err = parse("SELECT COUNT(*) FROM invoices").unwrap_err();
+        assert!(
+            err.message().starts_with("mock host cannot run query"),
+            "{err}"
+        );
+        assert!(parse("SELECT * FROM invoices GROUP BY status").is_err());
+        assert!(parse("SELECT * FROM missing")
+            .unwrap()
+            .run(&tables())
+            .is_err());
+    }
+}
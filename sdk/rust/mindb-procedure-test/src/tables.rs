@@ -0,0 +1,181 @@
+//! In-memory tables behind the mock's database imports.
+//!
+//! Tables have no schema: a row holds whatever columns it was inserted with,
+//! and a column a row lacks reads as NULL.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use mindb_procedure::db::{Row, Value};
+use mindb_procedure::{ProcError, Result};
+
+/// Tables by name.
+#[derive(Debug, Default)]
+pub(crate) struct Tables {
+    tables: BTreeMap<String, Vec<Row>>,
+}
+
+impl Tables {
+    /// Creates an empty table unless it already exists.
+    pub fn create(&mut self, table: &str) {
+        self.tables.entry(table.to_string()).or_default();
+    }
+
+    /// Returns a table's rows in insertion order.
+    pub fn rows(&self, table: &str) -> Result<&[Row]> {
+        self.tables
+            .get(table)
+            .map(Vec::as_slice)
+            .ok_or_else(|| missing(table))
+    }
+
+    pub fn insert(&mut self, table: &str, row: Row) -> Result<()> {
+        self.table_mut(table)?.push(row);
+        Ok(())
+    }
+
+    /// Sets the columns in `set` on every row matching `filter`.
+    pub fn update(&mut self, table: &str, set: &Row, filter: &Row) -> Result<u32> {
+        if set.is_empty() {
+            return Err(ProcError::new(
+                "mindb_update requires at least one column to set",
+            ));
+        }
+        let mut count = 0;
+        for row in self.table_mut(table)? {
+            if matches(row, filter) {
+                *row = with_columns(row, set);
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Deletes every row matching `filter`.
+    pub fn delete(&mut self, table: &str, filter: &Row) -> Result<u32> {
+        let rows = self.table_mut(table)?;
+        let before = rows.len();
+        rows.retain(|row| !matches(row, filter));
+        Ok((before - rows.len()) as u32)
+    }
+
+    fn table_mut(&mut self, table: &str) -> Result<&mut Vec<Row>> {
+        self.tables.get_mut(table).ok_or_else(|| missing(table))
+    }
+}
+
+fn missing(table: &str) -> ProcError {
+    ProcError::new(format!("table '{table}' does not exist"))
+}
+
+/// Returns a column's value, NULL if the row lacks it.
+pub(crate) fn column<'a>(row: &'a Row, name: &str) -> &'a Value {
+    row.get(name).unwrap_or(&Value::Null)
+}
+
+/// Reports whether a row's columns equal every value in `filter`.
+pub(crate) fn matches(row: &Row, filter: &Row) -> bool {
+    filter
+        .iter()
+        .all(|(name, value)| compare(column(row, name), value) == Some(Ordering::Equal))
+}
+
+/// Compares two column values, converting between integers and floats.
+/// Values of unrelated types do not compare.
+pub(crate) fn compare(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Null, Value::Null) => Some(Ordering::Equal),
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        (Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+        (Value::Text(a), Value::Text(b)) => Some(a.cmp(b)),
+        _ => a.as_f64()?.partial_cmp(&b.as_f64()?),
+    }
+}
+
+/// Returns a copy of `row` with the columns in `set` replaced or added.
+fn with_columns(row: &Row, set: &Row) -> Row {
+    let mut columns: Vec<(String, Value)> = row
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect();
+    for (name, value) in set.iter() {
+        match columns.iter_mut().find(|(existing, _)| existing == name) {
+            Some((_, existing)) => *existing = value.clone(),
+            None => columns.push((name.to_string(), value.clone())),
+        }
+    }
+    columns.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(columns: &[(&str, Value)]) -> Row {
+        columns
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_update_and_delete() {
+        let mut tables = Tables::default();
+        tables.create("invoices");
+        tables
+            .insert("invoices", row(&[("id", 1.into()), ("paid", false.into())]))
+            .unwrap();
+        tables
+            .insert("invoices", row(&[("id", 2.into()), ("paid", false.into())]))
+            .unwrap();
+
+        let set = row(&[("paid", true.into()), ("fee", 2.5.into())]);
+        assert_eq!(
+            tables.update("invoices", &set, &row(&[("id", 2.into())])),
+            Ok(1)
+        );
+        let updated = &tables.rows("invoices").unwrap()[1];
+        assert_eq!(updated.get("paid"), Some(&Value::Bool(true)));
+        assert_eq!(updated.get("fee"), Some(&Value::Float(2.5)));
+
+        assert_eq!(
+            tables.delete("invoices", &row(&[("paid", false.into())])),
+            Ok(1)
+        );
+        assert_eq!(tables.delete("invoices", &Row::default()), Ok(1));
+        assert!(tables.rows("invoices").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut tables = Tables::default();
+        assert_eq!(
+            tables
+                .insert("missing", Row::default())
+                .unwrap_err()
+                .message(),
+            "table 'missing' does not exist"
+        );
+        tables.create("t");
+        assert!(tables
+            .update("t", &Row::default(), &Row::default())
+            .is_err());
+    }
+
+    #[test]
+    fn test_compare() {
+        assert_eq!(
+            compare(&Value::Int(2), &Value::Float(2.0)),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            compare(&Value::Int(1), &Value::Float(1.5)),
+            Some(Ordering::Less)
+        );
+        assert_eq!(compare(&Value::Text("1".into()), &Value::Int(1)), None);
+        assert!(matches(
+            &row(&[("a", 1.into())]),
+            &row(&[("b", Value::Null)])
+        ));
+    }
+}
@@ -0,0 +1,57 @@
+//! User-defined aggregates, written with the SDK.
+//!
+//! Build: cargo build --release --target wasm32-unknown-unknown --example aggregates
+//!
+//! Then: CREATE AGGREGATE weighted_avg LANGUAGE rust AS '<base64>'
+//!       SELECT region, weighted_avg(price, qty) FROM sales GROUP BY region
+
+use mindb_procedure::{aggregate, ProcError, Result};
+
+#[derive(Default)]
+struct WeightedAvg {
+    sum: f64,
+    weight: f64,
+}
+
+/// Average of value weighted by weight.
+#[aggregate]
+impl WeightedAvg {
+    fn step(&mut self, value: f64, weight: f64) -> Result<()> {
+        if weight < 0.0 {
+            return Err(ProcError::new("weight must not be negative"));
+        }
+        self.sum += value * weight;
+        self.weight += weight;
+        Ok(())
+    }
+
+    fn merge(&mut self, other: Self) {
+        self.sum += other.sum;
+        self.weight += other.weight;
+    }
+
+    fn finalize(self) -> f64 {
+        self.sum / self.weight
+    }
+}
+
+#[derive(Default)]
+struct Concat {
+    parts: Vec<String>,
+}
+
+/// Comma-separated list of the group's values.
+#[aggregate(name = "string_agg")]
+impl Concat {
+    fn step(&mut self, value: &str) {
+        self.parts.push(value.into());
+    }
+
+    fn merge(&mut self, other: Self) {
+        self.parts.extend(other.parts);
+    }
+
+    fn finalize(self) -> String {
+        self.parts.join(",")
+    }
+}
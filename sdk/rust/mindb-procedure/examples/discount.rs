@@ -0,0 +1,43 @@
+//! Discount procedures from examples/wasm/discount.rs, written with the SDK.
+//!
+//! Build: cargo build --release --target wasm32-unknown-unknown --example discount
+
+use mindb_procedure::{debug, procedure, ProcError, Result};
+
+/// Applies the discount for a customer tier (1-5).
+#[procedure]
+fn calculate_discount(price: f64, tier: i32) -> Result<f64> {
+    let rate = match tier {
+        1 => 0.05,
+        2 => 0.10,
+        3 => 0.15,
+        4 => 0.20,
+        5 => 0.25,
+        _ => return Err(ProcError::new("unknown customer tier")),
+    };
+    debug!("tier {} discount rate {}", tier, rate);
+    Ok(price * (1.0 - rate))
+}
+
+/// Applies a volume discount based on order quantity.
+#[procedure]
+fn calculate_bulk_discount(price: f64, quantity: i32) -> f64 {
+    let rate = match quantity {
+        100.. => 0.20,
+        50.. => 0.15,
+        10.. => 0.10,
+        _ => 0.0,
+    };
+    price * (1.0 - rate)
+}
+
+/// Loyalty points earned for a purchase; premium members earn double.
+#[procedure]
+fn calculate_loyalty_points(amount: f64, is_premium: bool) -> i32 {
+    let base_points = (amount / 10.0) as i32;
+    if is_premium {
+        base_points * 2
+    } else {
+        base_points
+    }
+}
@@ -0,0 +1,29 @@
+//! String procedures from the README's string_utils.rs, written with the SDK.
+//!
+//! Build: cargo build --release --target wasm32-unknown-unknown --example strings
+
+use mindb_procedure::procedure;
+
+/// Reverses a string by characters.
+#[procedure]
+fn reverse_string(s: &str) -> String {
+    s.chars().rev().collect()
+}
+
+/// Converts a string to uppercase.
+#[procedure]
+fn to_uppercase(s: &str) -> String {
+    s.to_uppercase()
+}
+
+/// Counts the whitespace-separated words in a string.
+#[procedure]
+fn word_count(s: &str) -> i32 {
+    s.split_whitespace().count() as i32
+}
+
+/// Flips every bit of a binary value.
+#[procedure]
+fn invert_bytes(data: &[u8]) -> Vec<u8> {
+    data.iter().map(|b| !b).collect()
+}
@@ -0,0 +1,80 @@
+//! Table-valued procedures, written with the SDK.
+//!
+//! Build: cargo build --release --target wasm32-unknown-unknown --example tables
+//!
+//! Then: CREATE MODULE finance LANGUAGE rust AS '<base64>'
+//!       SELECT * FROM finance.amortization(250000, 0.045, 360) WHERE month <= 12
+//!       SELECT carrier, price FROM finance.shipping_rates(3.5) ORDER BY price
+
+use mindb_procedure::{procedure, ProcError, Result, Table};
+
+/// Monthly payment schedule for a fixed-rate loan.
+#[procedure]
+fn amortization(principal: f64, annual_rate: f64, months: i32) -> Result<Table> {
+    if principal <= 0.0 || months <= 0 {
+        return Err(ProcError::new("principal and months must be positive"));
+    }
+    if annual_rate < 0.0 {
+        return Err(ProcError::new("annual_rate must not be negative"));
+    }
+
+    let rate = annual_rate / 12.0;
+    let payment = if rate == 0.0 {
+        principal / months as f64
+    } else {
+        principal * rate / (1.0 - (1.0 + rate).powi(-months))
+    };
+
+    let mut table = Table::new();
+    let mut balance = principal;
+    for month in 1..=months {
+        let interest = balance * rate;
+        // The last payment clears whatever rounding left over
+        let principal_paid = if month == months {
+            balance
+        } else {
+            payment - interest
+        };
+        balance -= principal_paid;
+        table.emit(&[
+            ("month", month.into()),
+            ("payment", cents(principal_paid + interest).into()),
+            ("principal", cents(principal_paid).into()),
+            ("interest", cents(interest).into()),
+            ("balance", cents(balance.max(0.0)).into()),
+        ])?;
+    }
+    Ok(table)
+}
+
+/// Carrier, service, base price, per-kg price and delivery days.
+const RATE_CARD: [(&str, &str, f64, f64, i32); 5] = [
+    ("postal", "standard", 4.50, 1.20, 5),
+    ("postal", "express", 9.00, 2.10, 2),
+    ("swift", "standard", 6.00, 0.95, 3),
+    ("swift", "overnight", 18.00, 3.40, 1),
+    ("freightco", "economy", 3.00, 0.80, 7),
+];
+
+/// Shipping options for a parcel, one row per carrier service.
+#[procedure]
+fn shipping_rates(weight_kg: f64) -> Result<Table> {
+    if weight_kg <= 0.0 {
+        return Err(ProcError::new("weight_kg must be positive"));
+    }
+
+    let mut table = Table::new();
+    for (carrier, service, base, per_kg, days) in RATE_CARD {
+        table.emit(&[
+            ("carrier", carrier.into()),
+            ("service", service.into()),
+            ("price", cents(base + per_kg * weight_kg).into()),
+            ("days", days.into()),
+        ])?;
+    }
+    Ok(table)
+}
+
+fn cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
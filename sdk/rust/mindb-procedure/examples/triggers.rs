@@ -0,0 +1,91 @@
+//! Trigger handlers, written with the SDK.
+//!
+//! Build: cargo build --release --target wasm32-unknown-unknown --example triggers
+//!
+//! Then: CREATE MODULE payments LANGUAGE rust AS '<base64>'
+//!       CREATE TRIGGER check_card BEFORE INSERT OR UPDATE ON cards
+//!           EXECUTE PROCEDURE payments.validate_credit_card
+//!       CREATE TRIGGER audit_cards AFTER UPDATE OR DELETE ON cards
+//!           EXECUTE PROCEDURE payments.audit_card_change
+
+use mindb_procedure::{db, procedure, trigger, ProcError, Result};
+
+/// Rejects card numbers that fail the Luhn check and stores them masked.
+#[procedure]
+fn validate_credit_card() -> Result<()> {
+    let row = trigger::new_row()?.ok_or_else(|| ProcError::new("no new row"))?;
+    let number = row
+        .get("number")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| ProcError::new("number is required"))?;
+
+    let digits: String = number.chars().filter(|c| *c != ' ' && *c != '-').collect();
+    if !luhn_valid(&digits) {
+        return Err(ProcError::new("invalid credit card number"));
+    }
+
+    let masked = format!(
+        "{}{}",
+        "*".repeat(digits.len() - 4),
+        &digits[digits.len() - 4..]
+    );
+    trigger::set_new(&[("number", masked.into()), ("brand", brand(&digits).into())])
+}
+
+/// Records the old card of every changed or deleted row in card_audit.
+#[procedure]
+fn audit_card_change() -> Result<()> {
+    let event = trigger::current()?;
+    let old = trigger::old_row()?.ok_or_else(|| ProcError::new("no old row"))?;
+    let action = match event.event {
+        trigger::Event::Update => "update",
+        _ => "delete",
+    };
+    db::insert(
+        "card_audit",
+        &[
+            ("card_id", old.get("id").cloned().unwrap_or(db::Value::Null)),
+            (
+                "number",
+                old.get("number").cloned().unwrap_or(db::Value::Null),
+            ),
+            ("action", action.into()),
+        ],
+    )
+}
+
+/// Checks a string of digits with the Luhn algorithm.
+fn luhn_valid(digits: &str) -> bool {
+    if !(12..=19).contains(&digits.len()) || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+
+    let sum: u32 = digits
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(i, b)| {
+            let d = u32::from(b - b'0');
+            if i % 2 == 1 {
+                if d > 4 {
+                    d * 2 - 9
+                } else {
+                    d * 2
+                }
+            } else {
+                d
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// Card brand from the number's prefix.
+fn brand(digits: &str) -> &'static str {
+    match digits.as_bytes() {
+        [b'4', ..] => "visa",
+        [b'5', b'1'..=b'5', ..] => "mastercard",
+        [b'3', b'4' | b'7', ..] => "amex",
+        _ => "other",
+    }
+}
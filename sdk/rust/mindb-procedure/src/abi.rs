@@ -0,0 +1,114 @@
+//! Raw imports provided by the mindb host.
+//!
+//! These mirror the functions registered on the Wasmtime linker in
+//! `src/core/wasm_host.go`. Pointers and lengths are byte offsets into the
+//! guest's exported `memory`.
+
+#[link(wasm_import_module = "env")]
+extern "C" {
+    /// Records an error message for the current call. The guest traps right
+    /// after, and the host reports the message instead of a generic trap.
+    pub fn mindb_error(ptr: *const u8, len: usize);
+
+    /// Records a typed error for the current call: a five-character
+    /// SQLSTATE-style code, a message and an optional detail (length 0 for
+    /// none). The guest traps right after, as with `mindb_error`.
+    pub fn mindb_raise(
+        code_ptr: *const u8,
+        code_len: usize,
+        msg_ptr: *const u8,
+        msg_len: usize,
+        detail_ptr: *const u8,
+        detail_len: usize,
+    );
+
+    /// Sets the call's result to `len` bytes of UTF-8 text at `ptr`. The host
+    /// copies them before returning.
+    pub fn mindb_return_text(ptr: *const u8, len: usize);
+
+    /// Sets the call's result to `len` bytes of binary data at `ptr`.
+    pub fn mindb_return_blob(ptr: *const u8, len: usize);
+
+    /// Makes the call's result NULL, whatever the function returns.
+    pub fn mindb_return_null();
+
+    /// Returns 1 if the argument at `index`, counting from 0, is NULL. Only
+    /// procedures called on NULL input receive NULL arguments, passed as
+    /// zero values.
+    pub fn mindb_arg_is_null(index: u32) -> i32;
+
+    /// Writes `len` bytes of UTF-8 text at `ptr` to the server log. `level`
+    /// runs from 1 (error) to 5 (trace).
+    pub fn mindb_log(level: i32, ptr: *const u8, len: usize);
+
+    /// Appends a row, given as a JSON object, to the call's result set.
+    /// Returns -1 with the message as the pending result if the host rejects
+    /// it; read it with `mindb_result_read`.
+    pub fn mindb_emit_row(ptr: *const u8, len: usize) -> i32;
+}
+
+// Database access. Each returns -1 on error, leaving the message as the
+// pending result; read it (or a successful result) with `mindb_result_read`.
+#[link(wasm_import_module = "env")]
+extern "C" {
+    /// Copies up to `len` bytes of the pending result to `ptr` and returns
+    /// the result's full length.
+    pub fn mindb_result_read(ptr: *mut u8, len: usize) -> i32;
+
+    /// Runs a SELECT. The result is a JSON array of row objects.
+    pub fn mindb_query(sql_ptr: *const u8, sql_len: usize) -> i32;
+
+    /// Looks up the first row matching a JSON filter object. Returns 0 when
+    /// no row matches.
+    pub fn mindb_get_row(
+        table_ptr: *const u8,
+        table_len: usize,
+        filter_ptr: *const u8,
+        filter_len: usize,
+    ) -> i32;
+
+    /// Inserts a row given as a JSON object. Returns the rows inserted.
+    pub fn mindb_insert(
+        table_ptr: *const u8,
+        table_len: usize,
+        row_ptr: *const u8,
+        row_len: usize,
+    ) -> i32;
+
+    /// Sets columns on rows matching a filter. Returns the rows updated.
+    pub fn mindb_update(
+        table_ptr: *const u8,
+        table_len: usize,
+        set_ptr: *const u8,
+        set_len: usize,
+        filter_ptr: *const u8,
+        filter_len: usize,
+    ) -> i32;
+
+    /// Deletes rows matching a filter. Returns the rows deleted.
+    pub fn mindb_delete(
+        table_ptr: *const u8,
+        table_len: usize,
+        filter_ptr: *const u8,
+        filter_len: usize,
+    ) -> i32;
+}
+
+// Trigger data, available to trigger handlers only. Each returns -1 on
+// error, leaving the message as the pending result.
+#[link(wasm_import_module = "env")]
+extern "C" {
+    /// Describes the trigger being run as a JSON object with `trigger`,
+    /// `table`, `timing` and `event`.
+    pub fn mindb_trigger_event() -> i32;
+
+    /// The OLD row as a JSON object. Returns 0 for INSERT.
+    pub fn mindb_trigger_old() -> i32;
+
+    /// The NEW row as a JSON object. Returns 0 for DELETE.
+    pub fn mindb_trigger_new() -> i32;
+
+    /// Sets columns of the NEW row from a JSON object. Only BEFORE INSERT
+    /// and BEFORE UPDATE handlers may call it.
+    pub fn mindb_trigger_set_new(ptr: *const u8, len: usize) -> i32;
+}
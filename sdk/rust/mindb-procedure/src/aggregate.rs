@@ -0,0 +1,49 @@
+//! Runtime support for the exports generated by `#[aggregate]`.
+//!
+//! Each group's state is a boxed value in guest memory. The host holds it as
+//! an `i32` handle between calls: `init` creates one, `step` borrows it, and
+//! `merge` (for the other state) and `finalize` consume it. All calls for a
+//! group run on the same instance.
+
+use alloc::boxed::Box;
+
+/// Allocates an empty state and returns its handle.
+pub fn init<S: Default>() -> i32 {
+    into_handle(Box::into_raw(Box::<S>::default()))
+}
+
+/// Borrows the state behind a handle from [`init`].
+pub fn state<'a, S>(handle: i32) -> &'a mut S {
+    // SAFETY: the host only passes handles returned by init and not yet
+    // consumed, and makes one call at a time.
+    unsafe { &mut *from_handle::<S>(handle) }
+}
+
+/// Takes ownership of the state behind a handle, freeing it when dropped.
+pub fn take<S>(handle: i32) -> S {
+    // SAFETY: as for `state`; the host does not use the handle again.
+    *unsafe { Box::from_raw(from_handle::<S>(handle)) }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn into_handle<S>(state: *mut S) -> i32 {
+    state as usize as i32
+}
+
+#[cfg(target_arch = "wasm32")]
+fn from_handle<S>(handle: i32) -> *mut S {
+    if handle == 0 {
+        crate::error::ProcError::new("invalid aggregate state").raise()
+    }
+    handle as u32 as usize as *mut S
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn into_handle<S>(_state: *mut S) -> i32 {
+    panic!("aggregate states require the mindb host")
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn from_handle<S>(_handle: i32) -> *mut S {
+    panic!("aggregate states require the mindb host")
+}
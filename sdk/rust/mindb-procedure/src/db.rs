@@ -0,0 +1,342 @@
+//! Database access from inside a procedure.
+//!
+//! These call the `mindb_query`, `mindb_get_row`, `mindb_insert`,
+//! `mindb_update` and `mindb_delete` host functions, which run against the
+//! database the procedure was called from. Filters match rows whose columns
+//! equal every given value; an empty filter matches every row.
+//!
+//! ```no_run
+//! use mindb_procedure::{db, procedure, ProcError, Result};
+//!
+//! #[procedure]
+//! fn customer_discount(customer_id: i64, price: f64) -> Result<f64> {
+//!     let customer = db::get_row("customers", &[("id", customer_id.into())])?
+//!         .ok_or_else(|| ProcError::new("customer not found"))?;
+//!     let rate = match customer.get("tier").and_then(db::Value::as_i64) {
+//!         Some(2) => 0.10,
+//!         Some(3) => 0.15,
+//!         _ => 0.0,
+//!     };
+//!     Ok(price * (1.0 - rate))
+//! }
+//! ```
+//!
+//! Built natively (e.g. for unit tests) the calls go to the host installed
+//! with [`native::set_host`](crate::native::set_host); without one every call
+//! returns an error.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::error::Result;
+use crate::json;
+
+/// A column value read from or written to a table.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl Value {
+    /// Returns `true` for SQL `NULL`.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// Returns the value as an integer. Floats with no fractional part
+    /// convert, since the host may hand back whole numbers either way.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::Int(i) => Some(i),
+            Value::Float(f)
+                if f >= i64::MIN as f64 && f < i64::MAX as f64 && f as i64 as f64 == f =>
+            {
+                Some(f as i64)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a float, converting integers.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Int(i) => Some(i as f64),
+            Value::Float(f) => Some(f),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a boolean.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a string slice.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Text(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! value_from {
+    ($($ty:ty => $variant:ident($conv:ty)),* $(,)?) => {$(
+        impl From<$ty> for Value {
+            fn from(value: $ty) -> Self {
+                Value::$variant(<$conv>::from(value))
+            }
+        }
+    )*};
+}
+
+value_from! {
+    bool => Bool(bool),
+    i32 => Int(i64),
+    i64 => Int(i64),
+    f32 => Float(f64),
+    f64 => Float(f64),
+    String => Text(String),
+    &str => Text(String),
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+/// A row returned by [`query`] or [`get_row`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Row {
+    columns: Vec<(String, Value)>,
+}
+
+impl Row {
+    pub(crate) fn from_columns(columns: Vec<(String, Value)>) -> Self {
+        Row { columns }
+    }
+
+    /// Returns the value of a column, or `None` if the row has no such column.
+    pub fn get(&self, column: &str) -> Option<&Value> {
+        self.columns
+            .iter()
+            .find(|(name, _)| name == column)
+            .map(|(_, value)| value)
+    }
+
+    /// Iterates over the row's columns.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.columns
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// Returns the number of columns.
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Returns `true` if the row has no columns.
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+}
+
+impl FromIterator<(String, Value)> for Row {
+    fn from_iter<I: IntoIterator<Item = (String, Value)>>(columns: I) -> Self {
+        Row::from_columns(columns.into_iter().collect())
+    }
+}
+
+/// Runs a `SELECT` statement and returns the matching rows.
+pub fn query(sql: &str) -> Result<Vec<Row>> {
+    json::decode_rows(&host::query(sql)?)
+}
+
+/// Returns the first row of `table` matching `filter`, if any.
+pub fn get_row(table: &str, filter: &[(&str, Value)]) -> Result<Option<Row>> {
+    match host::get_row(table, &json::encode_object(filter))? {
+        Some(row) => json::decode_row(&row).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Inserts a row into `table`.
+pub fn insert(table: &str, row: &[(&str, Value)]) -> Result<()> {
+    host::insert(table, &json::encode_object(row)).map(drop)
+}
+
+/// Sets the columns in `set` on every row of `table` matching `filter` and
+/// returns the number of rows updated.
+pub fn update(table: &str, set: &[(&str, Value)], filter: &[(&str, Value)]) -> Result<u32> {
+    host::update(
+        table,
+        &json::encode_object(set),
+        &json::encode_object(filter),
+    )
+}
+
+/// Deletes every row of `table` matching `filter` and returns the number of
+/// rows deleted.
+pub fn delete(table: &str, filter: &[(&str, Value)]) -> Result<u32> {
+    host::delete(table, &json::encode_object(filter))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) mod host {
+    use alloc::string::String;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use crate::abi;
+    use crate::error::{ProcError, Result};
+
+    /// Copies the pending result out of the host.
+    pub(crate) fn read_result() -> Vec<u8> {
+        // SAFETY: a zero length only queries the size; the second call writes
+        // at most `buf.len()` bytes into `buf`.
+        let len = unsafe { abi::mindb_result_read(core::ptr::null_mut(), 0) };
+        if len <= 0 {
+            return Vec::new();
+        }
+        let mut buf = vec![0u8; len as usize];
+        unsafe { abi::mindb_result_read(buf.as_mut_ptr(), buf.len()) };
+        buf
+    }
+
+    /// Turns a host return code into a count, fetching the message on error.
+    pub(crate) fn check(code: i32) -> Result<u32> {
+        if code < 0 {
+            let message = read_result();
+            return Err(ProcError::new(String::from_utf8_lossy(&message)));
+        }
+        Ok(code as u32)
+    }
+
+    pub fn query(sql: &str) -> Result<Vec<u8>> {
+        // SAFETY: the host only reads the given ranges.
+        check(unsafe { abi::mindb_query(sql.as_ptr(), sql.len()) })?;
+        Ok(read_result())
+    }
+
+    pub fn get_row(table: &str, filter: &str) -> Result<Option<Vec<u8>>> {
+        let found = check(unsafe {
+            abi::mindb_get_row(table.as_ptr(), table.len(), filter.as_ptr(), filter.len())
+        })?;
+        Ok((found > 0).then(read_result))
+    }
+
+    pub fn insert(table: &str, row: &str) -> Result<u32> {
+        check(unsafe { abi::mindb_insert(table.as_ptr(), table.len(), row.as_ptr(), row.len()) })
+    }
+
+    pub fn update(table: &str, set: &str, filter: &str) -> Result<u32> {
+        check(unsafe {
+            abi::mindb_update(
+                table.as_ptr(),
+                table.len(),
+                set.as_ptr(),
+                set.len(),
+                filter.as_ptr(),
+                filter.len(),
+            )
+        })
+    }
+
+    pub fn delete(table: &str, filter: &str) -> Result<u32> {
+        check(unsafe {
+            abi::mindb_delete(table.as_ptr(), table.len(), filter.as_ptr(), filter.len())
+        })
+    }
+
+    pub fn emit_row(row: &str) -> Result<()> {
+        check(unsafe { abi::mindb_emit_row(row.as_ptr(), row.len()) }).map(drop)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod host {
+    use alloc::rc::Rc;
+    use alloc::vec::Vec;
+
+    use crate::error::{ProcError, Result};
+    use crate::native::{self, Host};
+
+    fn installed() -> Result<Rc<dyn Host>> {
+        native::host().ok_or_else(|| ProcError::new("database access requires the mindb host"))
+    }
+
+    pub fn query(sql: &str) -> Result<Vec<u8>> {
+        installed()?.query(sql)
+    }
+
+    pub fn get_row(table: &str, filter: &str) -> Result<Option<Vec<u8>>> {
+        installed()?.get_row(table, filter.as_bytes())
+    }
+
+    pub fn insert(table: &str, row: &str) -> Result<u32> {
+        installed()?.insert(table, row.as_bytes())
+    }
+
+    pub fn update(table: &str, set: &str, filter: &str) -> Result<u32> {
+        installed()?.update(table, set.as_bytes(), filter.as_bytes())
+    }
+
+    pub fn delete(table: &str, filter: &str) -> Result<u32> {
+        installed()?.delete(table, filter.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_conversions() {
+        assert_eq!(Value::from(3), Value::Int(3));
+        assert_eq!(Value::from(2.5f32), Value::Float(2.5));
+        assert_eq!(Value::from("gold"), Value::Text("gold".into()));
+        assert_eq!(Value::from(None::<i64>), Value::Null);
+        assert_eq!(Value::from(Some(true)), Value::Bool(true));
+    }
+
+    #[test]
+    fn test_value_accessors() {
+        assert_eq!(Value::Float(2.0).as_i64(), Some(2));
+        assert_eq!(Value::Float(2.5).as_i64(), None);
+        assert_eq!(Value::Float(1e300).as_i64(), None);
+        assert_eq!(Value::Int(4).as_f64(), Some(4.0));
+        assert_eq!(Value::Text("x".into()).as_str(), Some("x"));
+        assert_eq!(Value::Int(1).as_bool(), None);
+        assert!(Value::Null.is_null());
+    }
+
+    #[test]
+    fn test_row_lookup() {
+        let row = Row::from_columns(alloc::vec![
+            ("id".into(), Value::Int(1)),
+            ("tier".into(), Value::Int(3)),
+        ]);
+        assert_eq!(row.get("tier"), Some(&Value::Int(3)));
+        assert_eq!(row.get("missing"), None);
+        assert_eq!(row.len(), 2);
+        assert_eq!(
+            row.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            ["id", "tier"]
+        );
+    }
+
+    #[test]
+    fn test_requires_host() {
+        let err = get_row("customers", &[("id", 1.into())]).unwrap_err();
+        assert_eq!(err.message(), "database access requires the mindb host");
+    }
+}
@@ -0,0 +1,163 @@
+//! Procedure errors.
+
+use alloc::string::String;
+use core::fmt;
+
+/// Result type returned by procedures.
+pub type Result<T, E = ProcError> = core::result::Result<T, E>;
+
+/// An error raised by a procedure.
+///
+/// Returning `Err(ProcError)` from a procedure aborts the SQL statement that
+/// called it. The host reports the error's SQLSTATE-style code, message and
+/// detail back to the client, which can tell it apart from a trap by the
+/// code.
+///
+/// ```
+/// use mindb_procedure::ProcError;
+///
+/// let err = ProcError::new("unknown customer tier")
+///     .with_code("22023")
+///     .with_detail("tier 7 is not between 0 and 3");
+/// assert_eq!(err.code(), "22023");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcError {
+    code: String,
+    message: String,
+    detail: Option<String>,
+}
+
+impl ProcError {
+    /// The code of errors created without [`with_code`](Self::with_code):
+    /// `raise_exception`.
+    pub const DEFAULT_CODE: &'static str = "P0001";
+
+    /// Creates an error with the given message and the code
+    /// [`DEFAULT_CODE`](Self::DEFAULT_CODE).
+    pub fn new(message: impl Into<String>) -> Self {
+        ProcError {
+            code: Self::DEFAULT_CODE.into(),
+            message: message.into(),
+            detail: None,
+        }
+    }
+
+    /// Sets the error's code: five digits or uppercase ASCII letters, as in
+    /// SQLSTATE. Class `00` means success and cannot be raised.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `code` is not a valid code.
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        let code = code.into();
+        assert!(is_valid_code(&code), "invalid SQLSTATE code {code:?}");
+        self.code = code;
+        self
+    }
+
+    /// Sets further detail, reported separately from the message.
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Returns the error code.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Returns the error message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the error detail, if any.
+    pub fn detail(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+
+    /// Reports the error to the host and aborts the current call.
+    pub(crate) fn raise(self) -> ! {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let detail = self.detail.as_deref().unwrap_or("");
+            // SAFETY: the host only reads the given lengths from each pointer.
+            unsafe {
+                crate::abi::mindb_raise(
+                    self.code.as_ptr(),
+                    self.code.len(),
+                    self.message.as_ptr(),
+                    self.message.len(),
+                    detail.as_ptr(),
+                    detail.len(),
+                )
+            };
+            core::arch::wasm32::unreachable()
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(host) = crate::native::host() {
+                host.error(&self);
+            }
+            panic!("procedure error: {}", self.message)
+        }
+    }
+}
+
+/// Reports whether `code` is five digits or uppercase ASCII letters outside
+/// class `00`.
+fn is_valid_code(code: &str) -> bool {
+    code.len() == 5
+        && code
+            .bytes()
+            .all(|b| b.is_ascii_digit() || b.is_ascii_uppercase())
+        && !code.starts_with("00")
+}
+
+impl fmt::Display for ProcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<&str> for ProcError {
+    fn from(message: &str) -> Self {
+        ProcError::new(message)
+    }
+}
+
+impl From<String> for ProcError {
+    fn from(message: String) -> Self {
+        ProcError::new(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_codes() {
+        let err = ProcError::new("bad tier");
+        assert_eq!(err.code(), ProcError::DEFAULT_CODE);
+        assert_eq!(err.detail(), None);
+
+        let err = err.with_code("22023").with_detail("tier 7");
+        assert_eq!(err.code(), "22023");
+        assert_eq!(err.detail(), Some("tier 7"));
+        assert_eq!(err.to_string(), "bad tier");
+
+        assert!(is_valid_code("P0001"));
+        assert!(!is_valid_code("p0001"));
+        assert!(!is_valid_code("2202"));
+        assert!(!is_valid_code("00000"));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid SQLSTATE code")]
+    fn test_invalid_code() {
+        let _ = ProcError::new("bad tier").with_code("bad");
+    }
+}
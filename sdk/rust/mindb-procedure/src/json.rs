@@ -0,0 +1,316 @@
+//! The small subset of JSON exchanged with the database host functions.
+//!
+//! Rows travel as flat objects of scalar values and query results as arrays
+//! of such objects, so nested values are rejected rather than supported.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::db::{Row, Value};
+use crate::error::{ProcError, Result};
+
+/// Encodes `(column, value)` pairs as a JSON object.
+pub(crate) fn encode_object(columns: &[(&str, Value)]) -> String {
+    let mut out = String::from("{");
+    for (i, (name, value)) in columns.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        encode_string(&mut out, name);
+        out.push(':');
+        encode_value(&mut out, value);
+    }
+    out.push('}');
+    out
+}
+
+fn encode_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Int(i) => {
+            let _ = write!(out, "{i}");
+        }
+        // JSON has no NaN or infinity
+        Value::Float(f) if !f.is_finite() => out.push_str("null"),
+        Value::Float(f) => {
+            let _ = write!(out, "{f:?}");
+        }
+        Value::Text(s) => encode_string(out, s),
+    }
+}
+
+fn encode_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Decodes a JSON array of row objects.
+pub(crate) fn decode_rows(input: &[u8]) -> Result<Vec<Row>> {
+    let mut p = Parser::new(input)?;
+    let mut rows = Vec::new();
+    p.expect(b'[')?;
+    if !p.eat(b']') {
+        loop {
+            rows.push(p.row()?);
+            if p.eat(b']') {
+                break;
+            }
+            p.expect(b',')?;
+        }
+    }
+    p.finish()?;
+    Ok(rows)
+}
+
+/// Decodes a single row object.
+pub(crate) fn decode_row(input: &[u8]) -> Result<Row> {
+    let mut p = Parser::new(input)?;
+    let row = p.row()?;
+    p.finish()?;
+    Ok(row)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a [u8]) -> Result<Self> {
+        let input = core::str::from_utf8(input).map_err(|_| invalid("not UTF-8"))?;
+        Ok(Parser { input, pos: 0 })
+    }
+
+    fn row(&mut self) -> Result<Row> {
+        let mut columns = Vec::new();
+        self.expect(b'{')?;
+        if !self.eat(b'}') {
+            loop {
+                self.skip_ws();
+                let name = self.string()?;
+                self.expect(b':')?;
+                columns.push((name, self.value()?));
+                if self.eat(b'}') {
+                    break;
+                }
+                self.expect(b',')?;
+            }
+        }
+        Ok(Row::from_columns(columns))
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        self.skip_ws();
+        match self.peek() {
+            Some(b'"') => self.string().map(Value::Text),
+            Some(b'n') => self.keyword("null", Value::Null),
+            Some(b't') => self.keyword("true", Value::Bool(true)),
+            Some(b'f') => self.keyword("false", Value::Bool(false)),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(b'{' | b'[') => Err(invalid("nested values are not supported")),
+            _ => Err(invalid("expected a value")),
+        }
+    }
+
+    fn keyword(&mut self, word: &str, value: Value) -> Result<Value> {
+        if self.input[self.pos..].starts_with(word) {
+            self.pos += word.len();
+            Ok(value)
+        } else {
+            Err(invalid("unexpected token"))
+        }
+    }
+
+    fn number(&mut self) -> Result<Value> {
+        let start = self.pos;
+        let mut float = false;
+        while let Some(b) = self.peek() {
+            match b {
+                b'0'..=b'9' | b'-' | b'+' => {}
+                b'.' | b'e' | b'E' => float = true,
+                _ => break,
+            }
+            self.pos += 1;
+        }
+        let text = &self.input[start..self.pos];
+        if !float {
+            if let Ok(i) = text.parse::<i64>() {
+                return Ok(Value::Int(i));
+            }
+        }
+        text.parse::<f64>()
+            .map(Value::Float)
+            .map_err(|_| invalid("bad number"))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        self.expect(b'"')?;
+        let mut out = String::new();
+        loop {
+            let rest = &self.input[self.pos..];
+            let end = rest
+                .find(['"', '\\'])
+                .ok_or_else(|| invalid("unterminated string"))?;
+            out.push_str(&rest[..end]);
+            self.pos += end;
+            if self.bump() == Some(b'"') {
+                return Ok(out);
+            }
+            match self.bump() {
+                Some(b'"') => out.push('"'),
+                Some(b'\\') => out.push('\\'),
+                Some(b'/') => out.push('/'),
+                Some(b'b') => out.push('\u{8}'),
+                Some(b'f') => out.push('\u{c}'),
+                Some(b'n') => out.push('\n'),
+                Some(b'r') => out.push('\r'),
+                Some(b't') => out.push('\t'),
+                Some(b'u') => {
+                    let mut code = self.hex4()?;
+                    if (0xd800..0xdc00).contains(&code) {
+                        // Surrogate pair
+                        if self.bump() != Some(b'\\') || self.bump() != Some(b'u') {
+                            return Err(invalid("unpaired surrogate"));
+                        }
+                        let low = self.hex4()?;
+                        if !(0xdc00..0xe000).contains(&low) {
+                            return Err(invalid("unpaired surrogate"));
+                        }
+                        code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                    }
+                    out.push(char::from_u32(code).ok_or_else(|| invalid("bad escape"))?);
+                }
+                _ => return Err(invalid("bad escape")),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32> {
+        let digits = self
+            .input
+            .get(self.pos..self.pos + 4)
+            .ok_or_else(|| invalid("bad escape"))?;
+        self.pos += 4;
+        u32::from_str_radix(digits, 16).map_err(|_| invalid("bad escape"))
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<u8> {
+        let b = self.peek()?;
+        self.pos += 1;
+        Some(b)
+    }
+
+    fn eat(&mut self, b: u8) -> bool {
+        self.skip_ws();
+        if self.peek() == Some(b) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, b: u8) -> Result<()> {
+        if self.eat(b) {
+            Ok(())
+        } else {
+            Err(invalid(&format!("expected '{}'", b as char)))
+        }
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.skip_ws();
+        if self.pos == self.input.len() {
+            Ok(())
+        } else {
+            Err(invalid("trailing characters"))
+        }
+    }
+}
+
+fn invalid(what: &str) -> ProcError {
+    ProcError::new(format!("invalid result from host: {what}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_encode_object() {
+        let json = encode_object(&[
+            ("id", Value::Int(7)),
+            ("rate", Value::Float(0.5)),
+            ("total", Value::Float(100.0)),
+            ("name", Value::Text("a \"b\"\n".into())),
+            ("active", Value::Bool(true)),
+            ("note", Value::Null),
+        ]);
+        assert_eq!(
+            json,
+            r#"{"id":7,"rate":0.5,"total":100.0,"name":"a \"b\"\n","active":true,"note":null}"#
+        );
+        assert_eq!(encode_object(&[]), "{}");
+    }
+
+    #[test]
+    fn test_decode_rows() {
+        let rows = decode_rows(
+            r#"[{"id":1,"tier":2,"name":"Ann é\ud83d\ude00"}, {"id":2,"rate":0.25,"x":null,"ok":false}]"#
+                .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].get("tier"), Some(&Value::Int(2)));
+        assert_eq!(rows[0].get("name"), Some(&Value::Text("Ann é😀".into())));
+        assert_eq!(rows[1].get("rate"), Some(&Value::Float(0.25)));
+        assert_eq!(rows[1].get("x"), Some(&Value::Null));
+        assert_eq!(rows[1].get("ok"), Some(&Value::Bool(false)));
+        assert_eq!(decode_rows(b" [ ] ").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_decode_numbers() {
+        let row = decode_row(br#"{"a":-3,"b":1e3,"c":9223372036854775807,"d":1e400}"#).unwrap();
+        assert_eq!(row.get("a"), Some(&Value::Int(-3)));
+        assert_eq!(row.get("b"), Some(&Value::Float(1000.0)));
+        assert_eq!(row.get("c"), Some(&Value::Int(i64::MAX)));
+        assert_eq!(row.get("d"), Some(&Value::Float(f64::INFINITY)));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert!(decode_row(br#"{"a":{"b":1}}"#).is_err());
+        assert!(decode_row(br#"{"a":1"#).is_err());
+        assert!(decode_row(br#"{"a":"x}"#).is_err());
+        assert!(decode_row(br#"{"a":1} x"#).is_err());
+        assert!(decode_rows(br#"{"a":1}"#).is_err());
+    }
+}
@@ -0,0 +1,406 @@
+//! Guest SDK for writing mindb stored procedures in Rust.
+//!
+//! Mindb runs stored procedures as WebAssembly modules inside Wasmtime. The
+//! host (`WASMEngine.ExecuteWithContext`) calls an exported function by name,
+//! passing SQL arguments as WASM scalars or, for TEXT and BLOB, as buffers in
+//! guest memory, and reads a single value back. This crate wraps that ABI so
+//! procedures can be written as ordinary Rust functions instead of
+//! hand-written `extern "C"` glue.
+//!
+//! ```
+//! use mindb_procedure::{procedure, ProcError, Result};
+//!
+//! /// Applies the discount for a customer tier.
+//! #[procedure]
+//! fn calculate_discount(price: f64, tier: i32) -> Result<f64> {
+//!     let rate = match tier {
+//!         1 => 0.05,
+//!         2 => 0.10,
+//!         3 => 0.15,
+//!         _ => return Err(ProcError::new("unknown customer tier")),
+//!     };
+//!     Ok(price * (1.0 - rate))
+//! }
+//!
+//! assert_eq!(calculate_discount(100.0, 2), 90.0);
+//! ```
+//!
+//! Build the crate as a `cdylib` for `wasm32-unknown-unknown` and load the
+//! resulting `.wasm` with `CREATE PROCEDURE`. Crates usually import the SDK
+//! as `mindb = { package = "mindb-procedure", ... }` and write
+//! `#[mindb::procedure]`.
+//!
+//! Procedures can read and write tables through the [`db`] module, return
+//! a [`Table`] of rows to be queried with `SELECT * FROM name(...)`, and
+//! act as `CREATE TRIGGER` handlers through the [`trigger`] module.
+//! [`aggregate`] turns an `impl` block into a user-defined aggregate for
+//! `CREATE AGGREGATE`. [`debug!`] and the other [`log`] macros write to the
+//! server log.
+//!
+//! Built for `wasm32` the crate is `no_std` and only needs `alloc`. A
+//! procedure crate built without `std` must provide its own global allocator
+//! and panic handler. Built natively, e.g. for `cargo test`, host calls go to
+//! the stand-in installed through the [`native`] module.
+
+#![no_std]
+
+extern crate alloc;
+#[cfg(not(target_arch = "wasm32"))]
+extern crate std;
+
+#[doc(hidden)]
+#[path = "aggregate.rs"]
+pub mod __aggregate;
+#[cfg(target_arch = "wasm32")]
+mod abi;
+pub mod db;
+mod error;
+mod json;
+pub mod log;
+#[cfg(target_arch = "wasm32")]
+mod memory;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod native;
+mod table;
+pub mod trigger;
+mod value;
+
+pub use error::{ProcError, Result};
+pub use table::Table;
+pub use value::{FromAbi, IntoAbi, IntoReturn};
+
+/// Exports a Rust function as a mindb stored procedure.
+///
+/// The function keeps its Rust signature; the attribute generates a
+/// `#[no_mangle] extern "C-unwind"` shim with the same name that converts arguments
+/// from their WASM representation and maps the return value back. Returning
+/// `Err` from a procedure reports the error to the host and aborts the call.
+///
+/// The attribute also records the signature (argument names, SQL types,
+/// return type and the first paragraph of the doc comment) in the
+/// `mindb.procedures` custom section. `CREATE PROCEDURE` reads it, so the
+/// statement does not need to repeat the parameter list:
+///
+/// ```text
+/// {"name":"calculate_tax","params":[{"name":"amount","type":"FLOAT"},{"name":"state_code","type":"INT"}],"returns":"FLOAT"}
+/// ```
+///
+/// Rust types map to SQL types as follows: `i32` → `INT`, `i64` → `BIGINT`,
+/// `f32` → `REAL`, `f64` → `FLOAT`, `bool` → `BOOLEAN`, `&str` and `String`
+/// → `TEXT`, `&[u8]` and `Vec<u8>` → `BLOB`, [`Table`] → `TABLE`, and
+/// `()` → `VOID`.
+/// `Result<T>` and `Option<T>` are recorded as `T`. TEXT and BLOB values are copied through
+/// guest memory using the `mindb_alloc` and `mindb_dealloc` functions the
+/// SDK exports.
+///
+/// A procedure is STRICT unless it takes an `Option`: the host returns NULL
+/// for a NULL argument without calling it. Taking an `Option` makes it NULL-aware
+/// (`"on_null":"called"`): NULL arrives as `None`, and NULL for any other
+/// argument raises a 22004 error. Returning `None` returns NULL.
+///
+/// ```
+/// use mindb_procedure::procedure;
+///
+/// /// The discount rate for a tier, 5% for customers without one.
+/// #[procedure]
+/// fn tier_rate(tier: Option<i32>) -> Option<f64> {
+///     match tier {
+///         None => Some(0.05),
+///         Some(tier @ 1..=3) => Some(tier as f64 * 0.05),
+///         Some(_) => None,
+///     }
+/// }
+/// # assert_eq!(tier_rate(2), 0.1);
+/// ```
+///
+/// Use `#[procedure(description = "...")]` to override the description.
+pub use mindb_procedure_macros::procedure;
+
+/// Exports the methods of an `impl` block as a mindb aggregate.
+///
+/// The type holds one group's running state and must implement `Default`,
+/// which gives the empty state. The block implements three methods:
+///
+/// ```
+/// use mindb_procedure::aggregate;
+///
+/// #[derive(Default)]
+/// struct WeightedAvg {
+///     sum: f64,
+///     weight: f64,
+/// }
+///
+/// /// Average of value weighted by weight.
+/// #[aggregate]
+/// impl WeightedAvg {
+///     fn step(&mut self, value: f64, weight: f64) {
+///         self.sum += value * weight;
+///         self.weight += weight;
+///     }
+///
+///     fn merge(&mut self, other: Self) {
+///         self.sum += other.sum;
+///         self.weight += other.weight;
+///     }
+///
+///     fn finalize(self) -> f64 {
+///         self.sum / self.weight
+///     }
+/// }
+/// ```
+///
+/// `step` folds one row into the state and returns `()` or `Result<()>`;
+/// its arguments follow the same type mapping as procedures. `merge` folds
+/// another partial state into this one, and `finalize` computes the result.
+/// Rows with a NULL argument are skipped by the host.
+///
+/// The attribute exports `weighted_avg_init`, `weighted_avg_step`,
+/// `weighted_avg_merge` and `weighted_avg_finalize`, keeping states in guest
+/// memory between calls, and records the signature with
+/// `"kind":"aggregate"` so `CREATE AGGREGATE weighted_avg LANGUAGE rust AS
+/// '...'` needs no argument list. The SQL name is the type name in snake
+/// case; use `#[aggregate(name = "...")]` to choose another. The description
+/// comes from the doc comment on the `impl` block.
+pub use mindb_procedure_macros::aggregate;
+
+// Lets generated code refer to `::mindb_procedure` from inside this crate.
+extern crate self as mindb_procedure;
+
+#[cfg(test)]
+mod tests {
+    use crate::{aggregate, procedure, IntoReturn, ProcError, Result};
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    /// Sales tax for a state.
+    ///
+    /// Only the first paragraph is recorded.
+    #[procedure]
+    fn test_tax(amount: f64, state_code: i32) -> f64 {
+        match state_code {
+            1 => amount * 0.05,
+            _ => amount * 0.08,
+        }
+    }
+
+    #[procedure]
+    fn test_is_positive(value: i64) -> bool {
+        value > 0
+    }
+
+    #[procedure]
+    fn test_checked(tier: i32) -> Result<f32> {
+        if tier < 0 {
+            return Err(ProcError::new("tier must not be negative"));
+        }
+        Ok(tier as f32 * 1.5)
+    }
+
+    #[procedure]
+    fn test_noop(_value: i32) {}
+
+    #[procedure]
+    fn test_greet(name: &str, times: i32) -> Result<String> {
+        Ok(format!("Hello, {}", name.repeat(times as usize)))
+    }
+
+    #[procedure]
+    fn test_checksum(data: Vec<u8>) -> i64 {
+        data.iter().map(|&b| b as i64).sum()
+    }
+
+    #[procedure]
+    fn test_safe_div(a: Option<f64>, b: f64) -> Option<f64> {
+        a.filter(|_| b != 0.0).map(|a| a / b)
+    }
+
+    mod visible {
+        use crate::procedure;
+
+        #[procedure]
+        pub fn test_negate(value: i64) -> i64 {
+            -value
+        }
+    }
+
+    #[derive(Default)]
+    struct TestLongest {
+        best: String,
+    }
+
+    /// Longest string in the group.
+    #[aggregate]
+    impl TestLongest {
+        fn step(&mut self, value: &str) -> Result<()> {
+            if value.len() > self.best.len() {
+                self.best = value.into();
+            }
+            Ok(())
+        }
+
+        fn merge(&mut self, other: Self) {
+            self.step(&other.best).into_return();
+        }
+
+        fn finalize(self) -> String {
+            self.best
+        }
+    }
+
+    #[derive(Default)]
+    struct Tally(i64);
+
+    #[aggregate(name = "test_tally", description = "Counts rows")]
+    impl Tally {
+        fn step(&mut self) {
+            self.0 += 1;
+        }
+
+        fn merge(&mut self, other: Self) {
+            self.0 += other.0;
+        }
+
+        fn finalize(self) -> i64 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_aggregate_methods() {
+        let mut a = TestLongest::default();
+        a.step("ab").unwrap();
+        let mut b = TestLongest::default();
+        b.step("abcd").unwrap();
+        a.merge(b);
+        assert_eq!(a.finalize(), "abcd");
+
+        let mut tally = Tally::default();
+        tally.step();
+        tally.merge(Tally(2));
+        assert_eq!(tally.finalize(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "aggregate states require the mindb host")]
+    fn test_aggregate_state_needs_host() {
+        test_tally_init();
+    }
+
+    #[test]
+    fn test_aggregate_metadata() {
+        assert_eq!(
+            &__MINDB_AGGREGATE_TEST_LONGEST,
+            concat!(
+                r#"{"name":"test_longest","kind":"aggregate","params":[{"name":"value","type":"TEXT"}],"#,
+                r#""returns":"TEXT","description":"Longest string in the group."}"#,
+                "\n"
+            )
+            .as_bytes()
+        );
+        assert_eq!(
+            &__MINDB_AGGREGATE_TEST_TALLY,
+            concat!(
+                r#"{"name":"test_tally","kind":"aggregate","params":[],"returns":"BIGINT","#,
+                r#""description":"Counts rows"}"#,
+                "\n"
+            )
+            .as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_plain_return() {
+        assert_eq!(test_tax(100.0, 1), 5.0);
+        assert_eq!(test_tax(100.0, 9), 8.0);
+    }
+
+    #[test]
+    fn test_bool_travels_as_i32() {
+        assert_eq!(test_is_positive(1), 1);
+        assert_eq!(test_is_positive(-5), 0);
+    }
+
+    #[test]
+    fn test_keeps_visibility() {
+        assert_eq!(visible::test_negate(4), -4);
+    }
+
+    #[test]
+    fn test_result_ok() {
+        assert_eq!(test_checked(2), 3.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "procedure error: tier must not be negative")]
+    fn test_result_err_raises() {
+        test_checked(-1);
+    }
+
+    #[test]
+    fn test_nullable_without_host() {
+        // Natively, no argument is NULL unless a host says so
+        assert_eq!(test_safe_div(3.0, 2.0), 1.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "NULL results require the mindb host")]
+    fn test_null_return_needs_host() {
+        test_safe_div(3.0, 0.0);
+    }
+
+    #[test]
+    fn test_unit_return() {
+        test_noop(7);
+    }
+
+    #[test]
+    #[should_panic(expected = "TEXT and BLOB results require the mindb host")]
+    fn test_text_return_needs_host() {
+        String::from("hi").into_return();
+    }
+
+    #[test]
+    fn test_signature_metadata() {
+        assert_eq!(
+            &__MINDB_PROCEDURE_TEST_TAX,
+            concat!(
+                r#"{"name":"test_tax","params":[{"name":"amount","type":"FLOAT"},"#,
+                r#"{"name":"state_code","type":"INT"}],"returns":"FLOAT","on_null":"strict","#,
+                r#""description":"Sales tax for a state."}"#,
+                "\n"
+            )
+            .as_bytes()
+        );
+        assert_eq!(
+            &__MINDB_PROCEDURE_TEST_CHECKED,
+            b"{\"name\":\"test_checked\",\"params\":[{\"name\":\"tier\",\"type\":\"INT\"}],\"returns\":\"REAL\",\"on_null\":\"strict\"}\n"
+        );
+        assert_eq!(
+            &__MINDB_PROCEDURE_TEST_NOOP,
+            b"{\"name\":\"test_noop\",\"params\":[{\"name\":\"_value\",\"type\":\"INT\"}],\"returns\":\"VOID\",\"on_null\":\"strict\"}\n"
+        );
+        assert_eq!(
+            &__MINDB_PROCEDURE_TEST_GREET,
+            concat!(
+                r#"{"name":"test_greet","params":[{"name":"name","type":"TEXT"},"#,
+                r#"{"name":"times","type":"INT"}],"returns":"TEXT","on_null":"strict"}"#,
+                "\n"
+            )
+            .as_bytes()
+        );
+        assert_eq!(
+            &__MINDB_PROCEDURE_TEST_CHECKSUM,
+            b"{\"name\":\"test_checksum\",\"params\":[{\"name\":\"data\",\"type\":\"BLOB\"}],\"returns\":\"BIGINT\",\"on_null\":\"strict\"}\n"
+        );
+        assert_eq!(
+            &__MINDB_PROCEDURE_TEST_SAFE_DIV,
+            concat!(
+                r#"{"name":"test_safe_div","params":[{"name":"a","type":"FLOAT"},"#,
+                r#"{"name":"b","type":"FLOAT"}],"returns":"FLOAT","on_null":"called"}"#,
+                "\n"
+            )
+            .as_bytes()
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! Logging to the server log.
+//!
+//! [`error!`](crate::error), [`warn!`](crate::warn), [`info!`](crate::info),
+//! [`debug!`](crate::debug) and [`trace!`](crate::trace) take the same
+//! arguments as the `log` crate's macros and write a line through the
+//! `mindb_log` host import. The server tags it with the procedure, user and
+//! request, and drops lines beyond its per-procedure rate limit. Lines below
+//! the server's log level are discarded there.
+//!
+//! ```
+//! use mindb_procedure::{debug, procedure};
+//!
+//! #[procedure]
+//! fn tier_rate(tier: i32) -> f64 {
+//!     let rate = tier as f64 * 0.05;
+//!     debug!("tier {} gets rate {}", tier, rate);
+//!     rate
+//! }
+//! # assert_eq!(tier_rate(2), 0.1);
+//! ```
+//!
+//! To route the `log` crate's macros to the server instead, install a
+//! `log::Log` whose `log` method calls [`write`].
+
+use core::fmt;
+
+/// The level of a log line, numbered as in the `log` crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+    Trace = 5,
+}
+
+impl Level {
+    /// Returns the level's name in lowercase, as the server logs it.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Writes a line to the server log.
+///
+/// Natively the line goes to the installed [`Host`](crate::native::Host),
+/// or to standard error if there is none.
+pub fn write(level: Level, message: &str) {
+    #[cfg(target_arch = "wasm32")]
+    // SAFETY: the host only reads `len` bytes starting at `ptr`.
+    unsafe {
+        crate::abi::mindb_log(level as i32, message.as_ptr(), message.len())
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    match crate::native::host() {
+        Some(host) => host.log(level, message),
+        None => std::eprintln!("[{level}] {message}"),
+    }
+}
+
+#[doc(hidden)]
+pub fn __log(level: Level, args: fmt::Arguments<'_>) {
+    match args.as_str() {
+        Some(message) => write(level, message),
+        None => write(level, &alloc::fmt::format(args)),
+    }
+}
+
+/// Logs a line at a given [`Level`](crate::log::Level).
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)+) => {
+        $crate::log::__log($level, ::core::format_args!($($arg)+))
+    };
+}
+
+/// Logs a line at the error level.
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => {
+        $crate::log!($crate::log::Level::Error, $($arg)+)
+    };
+}
+
+/// Logs a line at the warn level.
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => {
+        $crate::log!($crate::log::Level::Warn, $($arg)+)
+    };
+}
+
+/// Logs a line at the info level.
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => {
+        $crate::log!($crate::log::Level::Info, $($arg)+)
+    };
+}
+
+/// Logs a line at the debug level.
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        $crate::log!($crate::log::Level::Debug, $($arg)+)
+    };
+}
+
+/// Logs a line at the trace level.
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)+) => {
+        $crate::log!($crate::log::Level::Trace, $($arg)+)
+    };
+}
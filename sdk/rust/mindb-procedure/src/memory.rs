@@ -0,0 +1,36 @@
+//! Allocator exports the host uses to pass TEXT and BLOB arguments.
+//!
+//! For each such argument the host calls `mindb_alloc`, copies the bytes in
+//! and passes the procedure `(ptr << 32) | len` as an `i64`. Once the call
+//! returns the host frees the buffer with `mindb_dealloc`, so arguments are
+//! only borrowed for the duration of the call. Empty values are passed as 0
+//! without allocating.
+
+use alloc::alloc::{alloc, dealloc, Layout};
+
+/// Allocates `len` bytes for the host to write an argument into. Returns
+/// null if `len` is zero or the allocation fails.
+#[unsafe(no_mangle)]
+pub extern "C" fn mindb_alloc(len: usize) -> *mut u8 {
+    match Layout::array::<u8>(len) {
+        // SAFETY: the layout has a non-zero size.
+        Ok(layout) if len > 0 => unsafe { alloc(layout) },
+        _ => core::ptr::null_mut(),
+    }
+}
+
+/// Frees a buffer returned by [`mindb_alloc`].
+///
+/// # Safety
+///
+/// `ptr` must come from `mindb_alloc(len)` and must not be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mindb_dealloc(ptr: *mut u8, len: usize) {
+    if ptr.is_null() || len == 0 {
+        return;
+    }
+    if let Ok(layout) = Layout::array::<u8>(len) {
+        // SAFETY: guaranteed by the caller.
+        unsafe { dealloc(ptr, layout) };
+    }
+}
@@ -0,0 +1,132 @@
+//! A stand-in for the mindb host when procedures are built natively.
+//!
+//! Built for `wasm32` the SDK calls the `env` imports registered in
+//! `src/core/wasm_host.go`. Built for any other target, as it is for
+//! `cargo test`, it calls the [`Host`] installed on the current thread
+//! instead, passing the same bytes the imports exchange: SQL and table names
+//! as UTF-8, rows and filters as JSON objects, query results as a JSON array
+//! of rows. With no host installed, database access fails and TEXT and BLOB
+//! values panic, as before.
+//!
+//! The `mindb-procedure-test` crate provides a host with in-memory tables;
+//! implement [`Host`] directly only to simulate something it cannot.
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use crate::db::{Row, Value};
+use crate::error::{ProcError, Result};
+use crate::json;
+use crate::log::Level;
+
+/// The host functions a procedure can call, one method per import.
+///
+/// Errors become `Err(ProcError)` in the procedure, just as a -1 return
+/// with the message as the pending result does in WASM.
+pub trait Host {
+    /// `mindb_query`: runs a SELECT and returns a JSON array of row objects.
+    fn query(&self, sql: &str) -> Result<Vec<u8>>;
+
+    /// `mindb_get_row`: returns the first row of `table` matching a JSON
+    /// filter object, or `None` if no row matches.
+    fn get_row(&self, table: &str, filter: &[u8]) -> Result<Option<Vec<u8>>>;
+
+    /// `mindb_insert`: inserts a JSON row object and returns the rows
+    /// inserted.
+    fn insert(&self, table: &str, row: &[u8]) -> Result<u32>;
+
+    /// `mindb_update`: sets the columns of a JSON object on the rows
+    /// matching a filter and returns the rows updated.
+    fn update(&self, table: &str, set: &[u8], filter: &[u8]) -> Result<u32>;
+
+    /// `mindb_delete`: deletes the rows matching a filter and returns the
+    /// rows deleted.
+    fn delete(&self, table: &str, filter: &[u8]) -> Result<u32>;
+
+    /// `mindb_emit_row`: appends a JSON row object to the call's result set.
+    fn emit_row(&self, row: &[u8]) -> Result<()>;
+
+    /// `mindb_return_text` and `mindb_return_blob`: sets the call's result.
+    fn return_bytes(&self, bytes: &[u8], text: bool);
+
+    /// `mindb_return_null`: makes the call's result NULL.
+    fn return_null(&self);
+
+    /// `mindb_arg_is_null`: whether the argument at `index`, counting from
+    /// 0, is NULL. No argument is unless overridden.
+    fn arg_is_null(&self, index: u32) -> bool {
+        let _ = index;
+        false
+    }
+
+    /// `mindb_raise`: records the error the procedure is about to raise.
+    fn error(&self, error: &ProcError);
+
+    /// `mindb_log`: writes a line to the server log. Writes to standard
+    /// error unless overridden.
+    fn log(&self, level: Level, message: &str) {
+        std::eprintln!("[{level}] {message}");
+    }
+
+    /// Resolves a TEXT or BLOB argument the host passed as `value`, the
+    /// native counterpart of `(ptr << 32) | len`. The bytes must stay valid
+    /// until the current call returns.
+    fn argument(&self, value: i64) -> Option<(*const u8, usize)>;
+}
+
+std::thread_local! {
+    static HOST: RefCell<Option<Rc<dyn Host>>> = const { RefCell::new(None) };
+}
+
+/// Installs `host` for calls made on the current thread and returns the
+/// host it replaces. Pass `None` to remove it.
+pub fn set_host(host: Option<Rc<dyn Host>>) -> Option<Rc<dyn Host>> {
+    HOST.with(|current| current.replace(host))
+}
+
+/// Returns the host installed on the current thread.
+pub(crate) fn host() -> Option<Rc<dyn Host>> {
+    HOST.with(|current| current.borrow().clone())
+}
+
+/// Decodes a row, filter or column set passed to a [`Host`] method.
+pub fn decode_row(bytes: &[u8]) -> Result<Row> {
+    json::decode_row(bytes)
+}
+
+/// Encodes a row as `mindb_get_row` returns it.
+pub fn encode_row(row: &Row) -> Vec<u8> {
+    let columns: Vec<(&str, Value)> = row
+        .iter()
+        .map(|(name, value)| (name, value.clone()))
+        .collect();
+    json::encode_object(&columns).into_bytes()
+}
+
+/// Encodes rows as `mindb_query` returns them.
+pub fn encode_rows(rows: &[Row]) -> Vec<u8> {
+    let mut out = Vec::from(*b"[");
+    for (i, row) in rows.iter().enumerate() {
+        if i > 0 {
+            out.push(b',');
+        }
+        out.extend(encode_row(row));
+    }
+    out.push(b']');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_rows() {
+        let row = decode_row(br#"{"id":1,"fee":2.5,"note":null}"#).unwrap();
+        assert_eq!(row.get("fee"), Some(&Value::Float(2.5)));
+        assert_eq!(encode_row(&row), br#"{"id":1,"fee":2.5,"note":null}"#);
+        assert_eq!(encode_rows(&[row.clone(), row]).len(), 2 * 30 + 3);
+        assert_eq!(encode_rows(&[]), b"[]");
+    }
+}
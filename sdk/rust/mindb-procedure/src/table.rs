@@ -0,0 +1,130 @@
+//! Table-valued procedures.
+
+#[cfg(not(target_arch = "wasm32"))]
+use alloc::string::ToString;
+#[cfg(not(target_arch = "wasm32"))]
+use alloc::vec::Vec;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::db::Row;
+use crate::db::Value;
+use crate::error::Result;
+use crate::value::IntoReturn;
+
+/// The result set of a table-valued procedure.
+///
+/// A procedure returning `Table` produces rows instead of a single value and
+/// can be queried like a table with `SELECT * FROM name(...)`. Rows are flat
+/// `(column, value)` lists; the result's columns are the emitted column names
+/// in order of first appearance.
+///
+/// ```
+/// use mindb_procedure::{procedure, Result, Table};
+///
+/// /// Powers of two up to 2^n.
+/// #[procedure]
+/// fn powers_of_two(n: i32) -> Result<Table> {
+///     powers(n)
+/// }
+///
+/// fn powers(n: i32) -> Result<Table> {
+///     let mut table = Table::new();
+///     for exp in 0..=n {
+///         table.emit(&[("exp", exp.into()), ("value", (1i64 << exp).into())])?;
+///     }
+///     Ok(table)
+/// }
+///
+/// // Natively the rows are kept, so the logic can be unit tested
+/// let table = powers(3)?;
+/// assert_eq!(table.len(), 4);
+/// assert_eq!(table.rows()[3].get("value").and_then(|v| v.as_i64()), Some(8));
+/// # Ok::<(), mindb_procedure::ProcError>(())
+/// ```
+///
+/// In a mindb host each row goes to the host through `mindb_emit_row` as
+/// soon as it is emitted, so large results are not held in guest memory.
+/// Elsewhere rows are kept and can be inspected with [`Table::rows`]; they
+/// are also passed to the [native host](crate::native), if one is installed.
+#[derive(Debug, Default)]
+pub struct Table {
+    len: usize,
+    #[cfg(not(target_arch = "wasm32"))]
+    rows: Vec<Row>,
+}
+
+impl Table {
+    /// Creates an empty result set.
+    pub fn new() -> Self {
+        Table::default()
+    }
+
+    /// Emits a row of `(column, value)` pairs.
+    ///
+    /// Fails if the host rejects the row, e.g. once the procedure has
+    /// emitted more rows than the server allows.
+    pub fn emit(&mut self, row: &[(&str, Value)]) -> Result<()> {
+        #[cfg(target_arch = "wasm32")]
+        crate::db::host::emit_row(&crate::json::encode_object(row))?;
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(host) = crate::native::host() {
+            host.emit_row(crate::json::encode_object(row).as_bytes())?;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        self.rows.push(Row::from_columns(
+            row.iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect(),
+        ));
+
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Returns the number of rows emitted.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no rows have been emitted.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the emitted rows. Only available outside a mindb host, where
+    /// rows are not sent anywhere.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn rows(&self) -> &[Row] {
+        &self.rows
+    }
+}
+
+/// The rows have already been handed to the host; there is no WASM result.
+impl IntoReturn for Table {
+    type Abi = ();
+
+    fn into_return(self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emit_keeps_rows_natively() {
+        let mut table = Table::new();
+        assert!(table.is_empty());
+
+        table
+            .emit(&[("zone", "A".into()), ("rate", 4.5.into())])
+            .unwrap();
+        table
+            .emit(&[("zone", "B".into()), ("express", true.into())])
+            .unwrap();
+
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.rows()[0].get("rate"), Some(&Value::Float(4.5)));
+        assert_eq!(table.rows()[1].get("express"), Some(&Value::Bool(true)));
+        assert_eq!(table.rows()[1].get("rate"), None);
+    }
+}
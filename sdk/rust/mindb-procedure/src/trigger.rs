@@ -0,0 +1,199 @@
+//! Trigger handlers.
+//!
+//! A procedure named in `CREATE TRIGGER ... EXECUTE PROCEDURE` is called
+//! once per inserted, updated or deleted row. It takes no arguments and
+//! reads the change through this module: [`old_row`] is the row before the
+//! change and [`new_row`] the row after it. Returning `Err` rejects the
+//! statement; a `BEFORE INSERT` or `BEFORE UPDATE` handler can also change
+//! the row about to be written with [`set_new`].
+//!
+//! ```no_run
+//! use mindb_procedure::{procedure, trigger, ProcError, Result};
+//!
+//! /// Rejects payments whose card number fails the Luhn check.
+//! #[procedure]
+//! fn validate_card() -> Result<()> {
+//!     let row = trigger::new_row()?.ok_or_else(|| ProcError::new("no new row"))?;
+//!     let card = row.get("card_number").and_then(|v| v.as_str()).unwrap_or("");
+//!     if card.len() < 12 {
+//!         return Err(ProcError::new("card_number is too short"));
+//!     }
+//!     trigger::set_new(&[("card_last4", card[card.len() - 4..].into())])
+//! }
+//! ```
+//!
+//! Outside of a mindb host, or in a procedure not called as a trigger
+//! handler, every call returns an error.
+
+use alloc::string::String;
+
+use crate::db::{Row, Value};
+use crate::error::{ProcError, Result};
+use crate::json;
+
+/// When a trigger runs relative to the row change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timing {
+    Before,
+    After,
+}
+
+/// The kind of row change a trigger runs for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Insert,
+    Update,
+    Delete,
+}
+
+/// The trigger a handler was called for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trigger {
+    /// The trigger's name.
+    pub name: String,
+    /// The table the row belongs to.
+    pub table: String,
+    pub timing: Timing,
+    pub event: Event,
+}
+
+/// Returns the trigger the current call runs for.
+pub fn current() -> Result<Trigger> {
+    parse_trigger(&json::decode_row(&host::event()?)?)
+}
+
+/// Returns the row before the change, or `None` for an INSERT.
+pub fn old_row() -> Result<Option<Row>> {
+    host::old_row()?
+        .map(|row| json::decode_row(&row))
+        .transpose()
+}
+
+/// Returns the row after the change, or `None` for a DELETE. In a `BEFORE`
+/// handler this includes changes made with [`set_new`] by this and earlier
+/// triggers.
+pub fn new_row() -> Result<Option<Row>> {
+    host::new_row()?
+        .map(|row| json::decode_row(&row))
+        .transpose()
+}
+
+/// Sets columns of the row about to be written. Columns not given keep
+/// their values. Only `BEFORE INSERT` and `BEFORE UPDATE` handlers may
+/// change the row.
+pub fn set_new(values: &[(&str, Value)]) -> Result<()> {
+    host::set_new(&json::encode_object(values))
+}
+
+fn parse_trigger(row: &Row) -> Result<Trigger> {
+    let text = |column: &str| {
+        row.get(column)
+            .and_then(Value::as_str)
+            .ok_or_else(|| ProcError::new("invalid trigger event from host"))
+    };
+
+    let timing = match text("timing")? {
+        "BEFORE" => Timing::Before,
+        "AFTER" => Timing::After,
+        _ => return Err(ProcError::new("invalid trigger timing from host")),
+    };
+    let event = match text("event")? {
+        "INSERT" => Event::Insert,
+        "UPDATE" => Event::Update,
+        "DELETE" => Event::Delete,
+        _ => return Err(ProcError::new("invalid trigger event from host")),
+    };
+
+    Ok(Trigger {
+        name: text("trigger")?.into(),
+        table: text("table")?.into(),
+        timing,
+        event,
+    })
+}
+
+#[cfg(target_arch = "wasm32")]
+mod host {
+    use alloc::vec::Vec;
+
+    use crate::abi;
+    use crate::db::host::{check, read_result};
+    use crate::error::Result;
+
+    pub fn event() -> Result<Vec<u8>> {
+        // SAFETY: the import takes no arguments.
+        check(unsafe { abi::mindb_trigger_event() })?;
+        Ok(read_result())
+    }
+
+    pub fn old_row() -> Result<Option<Vec<u8>>> {
+        let len = check(unsafe { abi::mindb_trigger_old() })?;
+        Ok((len > 0).then(read_result))
+    }
+
+    pub fn new_row() -> Result<Option<Vec<u8>>> {
+        let len = check(unsafe { abi::mindb_trigger_new() })?;
+        Ok((len > 0).then(read_result))
+    }
+
+    pub fn set_new(values: &str) -> Result<()> {
+        // SAFETY: the host only reads the given range.
+        check(unsafe { abi::mindb_trigger_set_new(values.as_ptr(), values.len()) }).map(drop)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod host {
+    use alloc::vec::Vec;
+
+    use crate::error::{ProcError, Result};
+
+    fn unavailable<T>() -> Result<T> {
+        Err(ProcError::new("trigger data requires the mindb host"))
+    }
+
+    pub fn event() -> Result<Vec<u8>> {
+        unavailable()
+    }
+
+    pub fn old_row() -> Result<Option<Vec<u8>>> {
+        unavailable()
+    }
+
+    pub fn new_row() -> Result<Option<Vec<u8>>> {
+        unavailable()
+    }
+
+    pub fn set_new(_values: &str) -> Result<()> {
+        unavailable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_trigger() {
+        let row = json::decode_row(
+            br#"{"trigger":"check_card","table":"payments","timing":"BEFORE","event":"UPDATE"}"#,
+        )
+        .unwrap();
+        let trigger = parse_trigger(&row).unwrap();
+        assert_eq!(trigger.name, "check_card");
+        assert_eq!(trigger.table, "payments");
+        assert_eq!(trigger.timing, Timing::Before);
+        assert_eq!(trigger.event, Event::Update);
+
+        let row =
+            json::decode_row(br#"{"trigger":"t","table":"x","timing":"DURING","event":"INSERT"}"#)
+                .unwrap();
+        assert!(parse_trigger(&row).is_err());
+    }
+
+    #[test]
+    fn test_requires_host() {
+        let err = new_row().unwrap_err();
+        assert_eq!(err.message(), "trigger data requires the mindb host");
+    }
+}
@@ -0,0 +1,299 @@
+//! Conversions between Rust types and the WASM values the host passes.
+//!
+//! TEXT and BLOB arguments arrive as an `i64` packing a pointer into guest
+//! memory and a length, `(ptr << 32) | len`; see the `memory` module for how
+//! the host allocates them. TEXT and BLOB results are handed to the host
+//! through the `mindb_return_text` and `mindb_return_blob` imports, so the
+//! exported function itself returns nothing.
+//!
+//! NULL has no WASM value. A procedure taking an `Option` argument is called
+//! on NULL input: the host passes NULL as a zero value and answers
+//! `mindb_arg_is_null` for it. Returning `None` calls `mindb_return_null`.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::error::ProcError;
+
+/// A type that can be received as a procedure argument.
+pub trait FromAbi: Sized {
+    /// The WASM value type the host passes for this argument.
+    type Abi;
+
+    /// Converts the raw WASM value into the Rust type.
+    fn from_abi(value: Self::Abi) -> Self;
+
+    /// Converts argument `index` of a procedure called on NULL input. Only
+    /// `Option` accepts NULL; other types raise a 22004 error for it.
+    fn from_nullable_abi(index: u32, value: Self::Abi) -> Self {
+        if arg_is_null(index) {
+            ProcError::new(format!("argument {} must not be NULL", index + 1))
+                .with_code("22004")
+                .raise()
+        }
+        Self::from_abi(value)
+    }
+}
+
+/// A type that can be handed back to the host as a WASM value.
+pub trait IntoAbi {
+    /// The WASM value type the host reads back.
+    type Abi;
+
+    /// Converts the Rust value into its WASM representation.
+    fn into_abi(self) -> Self::Abi;
+}
+
+/// A type that can be returned from a procedure.
+///
+/// Implemented for every [`IntoAbi`] type, for `()` and for
+/// `Result<T, ProcError>`, where `Err` aborts the call.
+pub trait IntoReturn {
+    /// The WASM result type of the exported function.
+    type Abi;
+
+    /// Converts the procedure's return value, raising errors to the host.
+    fn into_return(self) -> Self::Abi;
+}
+
+macro_rules! scalar {
+    ($($ty:ty),*) => {$(
+        impl FromAbi for $ty {
+            type Abi = $ty;
+
+            fn from_abi(value: $ty) -> Self {
+                value
+            }
+        }
+
+        impl IntoAbi for $ty {
+            type Abi = $ty;
+
+            fn into_abi(self) -> $ty {
+                self
+            }
+        }
+
+        impl IntoReturn for $ty {
+            type Abi = $ty;
+
+            fn into_return(self) -> $ty {
+                self
+            }
+        }
+    )*};
+}
+
+scalar!(i32, i64, f32, f64);
+
+impl FromAbi for bool {
+    type Abi = i32;
+
+    fn from_abi(value: i32) -> Self {
+        value != 0
+    }
+}
+
+impl IntoAbi for bool {
+    type Abi = i32;
+
+    fn into_abi(self) -> i32 {
+        self as i32
+    }
+}
+
+impl IntoReturn for bool {
+    type Abi = i32;
+
+    fn into_return(self) -> i32 {
+        self.into_abi()
+    }
+}
+
+impl IntoReturn for () {
+    type Abi = ();
+
+    fn into_return(self) {}
+}
+
+impl<T: IntoReturn> IntoReturn for Result<T, ProcError> {
+    type Abi = T::Abi;
+
+    fn into_return(self) -> T::Abi {
+        match self {
+            Ok(value) => value.into_return(),
+            Err(err) => err.raise(),
+        }
+    }
+}
+
+impl<T: FromAbi> FromAbi for Option<T> {
+    type Abi = T::Abi;
+
+    fn from_abi(value: T::Abi) -> Self {
+        Some(T::from_abi(value))
+    }
+
+    fn from_nullable_abi(index: u32, value: T::Abi) -> Self {
+        if arg_is_null(index) {
+            return None;
+        }
+        Some(T::from_abi(value))
+    }
+}
+
+impl<T: IntoReturn> IntoReturn for Option<T>
+where
+    T::Abi: Default,
+{
+    type Abi = T::Abi;
+
+    fn into_return(self) -> T::Abi {
+        match self {
+            Some(value) => value.into_return(),
+            None => {
+                return_null();
+                T::Abi::default()
+            }
+        }
+    }
+}
+
+impl FromAbi for &[u8] {
+    type Abi = i64;
+
+    fn from_abi(value: i64) -> Self {
+        let (ptr, len) = unpack(value);
+        if len == 0 {
+            return &[];
+        }
+        // SAFETY: the host copied `len` bytes to `ptr` and frees them only
+        // after the call returns.
+        unsafe { core::slice::from_raw_parts(ptr, len) }
+    }
+}
+
+impl FromAbi for &str {
+    type Abi = i64;
+
+    fn from_abi(value: i64) -> Self {
+        core::str::from_utf8(<&[u8]>::from_abi(value))
+            .unwrap_or_else(|_| ProcError::new("TEXT argument is not valid UTF-8").raise())
+    }
+}
+
+impl FromAbi for String {
+    type Abi = i64;
+
+    fn from_abi(value: i64) -> Self {
+        <&str>::from_abi(value).into()
+    }
+}
+
+impl FromAbi for Vec<u8> {
+    type Abi = i64;
+
+    fn from_abi(value: i64) -> Self {
+        <&[u8]>::from_abi(value).to_vec()
+    }
+}
+
+impl IntoReturn for &str {
+    type Abi = ();
+
+    fn into_return(self) {
+        return_bytes(self.as_bytes(), true);
+    }
+}
+
+impl IntoReturn for String {
+    type Abi = ();
+
+    fn into_return(self) {
+        return_bytes(self.as_bytes(), true);
+    }
+}
+
+impl IntoReturn for &[u8] {
+    type Abi = ();
+
+    fn into_return(self) {
+        return_bytes(self, false);
+    }
+}
+
+impl IntoReturn for Vec<u8> {
+    type Abi = ();
+
+    fn into_return(self) {
+        return_bytes(&self, false);
+    }
+}
+
+/// Splits a packed TEXT or BLOB argument into its pointer and length.
+#[cfg(target_arch = "wasm32")]
+fn unpack(value: i64) -> (*const u8, usize) {
+    let value = value as u64;
+    ((value >> 32) as usize as *const u8, value as u32 as usize)
+}
+
+/// Resolves a TEXT or BLOB argument through the native host. Empty values
+/// are 0, as in WASM.
+#[cfg(not(target_arch = "wasm32"))]
+fn unpack(value: i64) -> (*const u8, usize) {
+    if value == 0 {
+        return (core::ptr::null(), 0);
+    }
+    crate::native::host()
+        .and_then(|host| host.argument(value))
+        .unwrap_or_else(|| panic!("TEXT and BLOB arguments require the mindb host"))
+}
+
+/// Asks the host whether argument `index` is NULL.
+#[cfg(target_arch = "wasm32")]
+fn arg_is_null(index: u32) -> bool {
+    // SAFETY: the import takes and returns plain integers.
+    unsafe { crate::abi::mindb_arg_is_null(index) != 0 }
+}
+
+/// Asks the native host whether argument `index` is NULL. Without a host,
+/// no argument is.
+#[cfg(not(target_arch = "wasm32"))]
+fn arg_is_null(index: u32) -> bool {
+    crate::native::host().is_some_and(|host| host.arg_is_null(index))
+}
+
+/// Tells the host the call's result is NULL.
+fn return_null() {
+    #[cfg(target_arch = "wasm32")]
+    // SAFETY: the import takes no arguments.
+    unsafe {
+        crate::abi::mindb_return_null()
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    match crate::native::host() {
+        Some(host) => host.return_null(),
+        None => panic!("NULL results require the mindb host"),
+    }
+}
+
+/// Hands a TEXT or BLOB result to the host, which copies it.
+fn return_bytes(bytes: &[u8], text: bool) {
+    #[cfg(target_arch = "wasm32")]
+    {
+        // SAFETY: the host only reads `len` bytes starting at `ptr`.
+        unsafe {
+            if text {
+                crate::abi::mindb_return_text(bytes.as_ptr(), bytes.len());
+            } else {
+                crate::abi::mindb_return_blob(bytes.as_ptr(), bytes.len());
+            }
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    match crate::native::host() {
+        Some(host) => host.return_bytes(bytes, text),
+        None => panic!("TEXT and BLOB results require the mindb host"),
+    }
+}
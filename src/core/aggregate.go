@@ -14,6 +14,7 @@ const (
 	AvgFunc
 	MinFunc
 	MaxFunc
+	UserFunc // User-defined aggregate (CREATE AGGREGATE)
 )
 
 // AggregateFunc represents an aggregate function in a query
@@ -22,16 +23,30 @@ type AggregateFunc struct {
 	Column   string
 	Alias    string
 	Distinct bool
+	Name     string        // User-defined aggregate name (UserFunc)
+	Args     []FunctionArg // User-defined aggregate arguments (UserFunc)
 }
 
 // AggregateExecutor executes aggregate functions
-type AggregateExecutor struct{}
+type AggregateExecutor struct {
+	engine *PagedEngine      // Runs user-defined aggregates; nil if unavailable
+	ctx    *ExecutionContext // The statement the aggregates run for
+}
 
 // NewAggregateExecutor creates a new aggregate executor
 func NewAggregateExecutor() *AggregateExecutor {
 	return &AggregateExecutor{}
 }
 
+// isBuiltinAggregate reports whether name is one of the built-in aggregates
+func isBuiltinAggregate(name string) bool {
+	switch strings.ToUpper(name) {
+	case "COUNT", "SUM", "AVG", "MIN", "MAX":
+		return true
+	}
+	return false
+}
+
 // ExecuteAggregates executes aggregate functions on rows
 func (ae *AggregateExecutor) ExecuteAggregates(rows []Row, aggregates []AggregateFunc, groupBy string) ([]Row, error) {
 	if groupBy == "" {
@@ -54,6 +69,15 @@ func (ae *AggregateExecutor) executeWithoutGrouping(rows []Row, aggregates []Agg
 				alias = ae.getDefaultAlias(agg)
 			}
 			result[alias] = ae.getEmptyValue(agg.Type)
+
+			// User-defined aggregates decide their own empty result
+			if agg.Type == UserFunc {
+				value, err := ae.computeAggregate(rows, agg)
+				if err != nil {
+					return nil, err
+				}
+				result[alias] = value
+			}
 		}
 		return []Row{result}, nil
 	}
@@ -125,11 +149,43 @@ func (ae *AggregateExecutor) computeAggregate(rows []Row, agg AggregateFunc) (in
 		return ae.min(rows, agg)
 	case MaxFunc:
 		return ae.max(rows, agg)
+	case UserFunc:
+		return ae.user(rows, agg)
 	default:
 		return nil, fmt.Errorf("unsupported aggregate function")
 	}
 }
 
+// user runs a user-defined aggregate over the rows. Rows with a NULL
+// argument are skipped, as the built-in aggregates skip NULL values.
+func (ae *AggregateExecutor) user(rows []Row, agg AggregateFunc) (interface{}, error) {
+	if ae.engine == nil {
+		return nil, fmt.Errorf("aggregate '%s' is not available here", agg.Name)
+	}
+
+	inputs := make([][]interface{}, 0, len(rows))
+	for _, row := range rows {
+		args := make([]interface{}, len(agg.Args))
+		hasNull := false
+		for i, arg := range agg.Args {
+			args[i] = arg.Value
+			if arg.Column != "" {
+				value, exists := row[arg.Column]
+				if !exists {
+					return nil, fmt.Errorf("column '%s' does not exist", arg.Column)
+				}
+				args[i] = value
+			}
+			hasNull = hasNull || args[i] == nil
+		}
+		if !hasNull {
+			inputs = append(inputs, args)
+		}
+	}
+
+	return ae.engine.runAggregate(ae.ctx, agg.Name, inputs)
+}
+
 // count implements COUNT aggregate
 func (ae *AggregateExecutor) count(rows []Row, agg AggregateFunc) (interface{}, error) {
 	if agg.Column == "*" {
@@ -248,6 +304,9 @@ func (ae *AggregateExecutor) max(rows []Row, agg AggregateFunc) (interface{}, er
 
 // getDefaultAlias returns the default alias for an aggregate function
 func (ae *AggregateExecutor) getDefaultAlias(agg AggregateFunc) string {
+	if agg.Type == UserFunc {
+		return (&FunctionCall{Name: agg.Name, Args: agg.Args}).String()
+	}
 	funcName := ae.getFuncName(agg.Type)
 	return strings.ToLower(funcName) + "(" + agg.Column + ")"
 }
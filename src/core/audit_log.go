@@ -2,9 +2,11 @@ package mindb
 
 import (
 	"encoding/json"
+	"errors"
 	"fmt"
 	"os"
 	"path/filepath"
+	"sort"
 	"sync"
 	"time"
 )
@@ -22,6 +24,12 @@ const (
 	AuditRevokePrivilege AuditEventType = "REVOKE_PRIVILEGE"
 	AuditAccessDenied    AuditEventType = "ACCESS_DENIED"
 	AuditQueryExecuted   AuditEventType = "QUERY_EXECUTED"
+
+	AuditProcedureCreated  AuditEventType = "PROCEDURE_CREATED"
+	AuditProcedureDropped  AuditEventType = "PROCEDURE_DROPPED"
+	AuditProcedureExecuted AuditEventType = "PROCEDURE_EXECUTED"
+	AuditModuleCreated     AuditEventType = "MODULE_CREATED"
+	AuditModuleDropped     AuditEventType = "MODULE_DROPPED"
 )
 
 // AuditEvent represents a single audit log entry
@@ -34,6 +42,44 @@ type AuditEvent struct {
 	Query     string         `json:"query,omitempty"`
 	Details   string         `json:"details,omitempty"`
 	Success   bool           `json:"success"`
+
+	Procedure *ProcedureAudit `json:"procedure,omitempty"` // Set for PROCEDURE_* and MODULE_* events
+}
+
+// ProcedureAudit identifies the procedure, aggregate or module of a
+// PROCEDURE_* or MODULE_* audit event, down to the WASM module that ran, and
+// describes the calls of a PROCEDURE_EXECUTED event
+type ProcedureAudit struct {
+	Name     string `json:"name"` // Procedure, module.function, aggregate or module
+	Version  int    `json:"version,omitempty"`
+	Digest   string `json:"wasm_sha256"`
+	Args     int    `json:"arg_count"`               // Arguments passed; parameters declared when created or dropped
+	Calls    int    `json:"call_count,omitempty"`    // Successful calls a PROCEDURE_EXECUTED event totals
+	Fuel     uint64 `json:"fuel_consumed,omitempty"` // Zero without fuel metering; the total over Calls
+	SQLState string `json:"sqlstate,omitempty"`      // Code of the error a failed call ended with
+	RunAs    string `json:"run_as,omitempty"`        // Owner of a SECURITY DEFINER procedure, whose privileges applied
+}
+
+// procedureCallKey groups the successful calls one PROCEDURE_EXECUTED event
+// totals: those of one procedure version, by one user in one database
+type procedureCallKey struct {
+	username, host, database string
+	name, digest, runAs      string
+	version, args            int
+}
+
+// ProcedureCalls totals the successful procedure calls of one statement
+// until AuditLogger.FlushProcedureCalls logs them. Each statement has its
+// own, carried in its ExecutionContext, so that concurrent statements do
+// not log each other's calls.
+type ProcedureCalls struct {
+	events map[procedureCallKey]*AuditEvent
+	mu     sync.Mutex
+}
+
+// NewProcedureCalls returns an empty set of call totals
+func NewProcedureCalls() *ProcedureCalls {
+	return &ProcedureCalls{events: make(map[procedureCallKey]*AuditEvent)}
 }
 
 // AuditLogger manages audit logging
@@ -91,6 +137,15 @@ func (al *AuditLogger) Log(event AuditEvent) error {
 	al.mu.Lock()
 	defer al.mu.Unlock()
 	
+	if err := al.write(event); err != nil {
+		return err
+	}
+	return al.sync()
+}
+
+// write appends an event to the log file without syncing it. The caller
+// holds al.mu.
+func (al *AuditLogger) write(event AuditEvent) error {
 	// Set timestamp if not set
 	if event.Timestamp.IsZero() {
 		event.Timestamp = time.Now()
@@ -107,13 +162,18 @@ func (al *AuditLogger) Log(event AuditEvent) error {
 		if _, err := al.file.Write(append(jsonData, '\n')); err != nil {
 			return fmt.Errorf("failed to write audit log: %w", err)
 		}
-		
-		// Flush to disk
+	}
+	
+	return nil
+}
+
+// sync flushes the log file to disk. The caller holds al.mu.
+func (al *AuditLogger) sync() error {
+	if al.file != nil {
 		if err := al.file.Sync(); err != nil {
 			return fmt.Errorf("failed to sync audit log: %w", err)
 		}
 	}
-	
 	return nil
 }
 
@@ -205,6 +265,152 @@ func (al *AuditLogger) LogQueryExecuted(username, host, database, query string)
 	})
 }
 
+// LogProcedureCreated logs the creation of a procedure, of a new version by
+// CREATE OR REPLACE, or of an earlier one restored by ROLLBACK TO VERSION
+func (al *AuditLogger) LogProcedureCreated(username, host, database string, proc ProcedureAudit) {
+	al.Log(AuditEvent{
+		EventType: AuditProcedureCreated,
+		Username:  username,
+		Host:      host,
+		Database:  database,
+		Details:   fmt.Sprintf("Created procedure '%s' version %d", proc.Name, proc.Version),
+		Success:   true,
+		Procedure: &proc,
+	})
+}
+
+// LogProcedureDropped logs the removal of a procedure
+func (al *AuditLogger) LogProcedureDropped(username, host, database string, proc ProcedureAudit) {
+	al.Log(AuditEvent{
+		EventType: AuditProcedureDropped,
+		Username:  username,
+		Host:      host,
+		Database:  database,
+		Details:   fmt.Sprintf("Dropped procedure '%s'", proc.Name),
+		Success:   true,
+		Procedure: &proc,
+	})
+}
+
+// LogProcedureExecuted logs a procedure call and its outcome: err is the
+// error the call failed with, if any. A failed call is logged at once. A
+// successful one is totalled in calls, those of the statement making it,
+// with the statement's other calls of the procedure by the same user, until
+// FlushProcedureCalls logs them as one event; the engine flushes as each
+// statement ends, so a procedure called for every row adds one event rather
+// than one per row.
+func (al *AuditLogger) LogProcedureExecuted(calls *ProcedureCalls, username, host, database string, proc ProcedureAudit, err error) {
+	if err != nil {
+		proc.Calls = 1
+		event := AuditEvent{
+			EventType: AuditProcedureExecuted,
+			Username:  username,
+			Host:      host,
+			Database:  database,
+			Details:   err.Error(),
+			Success:   false,
+			Procedure: &proc,
+		}
+		var procErr *ProcedureError
+		if errors.As(err, &procErr) {
+			proc.SQLState = procErr.Code
+		}
+		al.Log(event)
+		return
+	}
+	
+	al.mu.Lock()
+	enabled := al.enabled
+	al.mu.Unlock()
+	if !enabled {
+		return
+	}
+	if calls == nil {
+		// A call made outside any statement is logged on its own
+		calls = NewProcedureCalls()
+		defer al.FlushProcedureCalls(calls)
+	}
+
+	calls.mu.Lock()
+	defer calls.mu.Unlock()
+	
+	key := procedureCallKey{username, host, database, proc.Name, proc.Digest, proc.RunAs, proc.Version, proc.Args}
+	if event, exists := calls.events[key]; exists {
+		event.Procedure.Calls++
+		event.Procedure.Fuel += proc.Fuel
+		return
+	}
+	proc.Calls = 1
+	calls.events[key] = &AuditEvent{
+		Timestamp: time.Now(),
+		EventType: AuditProcedureExecuted,
+		Username:  username,
+		Host:      host,
+		Database:  database,
+		Success:   true,
+		Procedure: &proc,
+	}
+}
+
+// FlushProcedureCalls logs the successful calls totalled in calls since the
+// last flush, one event per procedure and user, oldest first, and syncs them
+// to disk at once
+func (al *AuditLogger) FlushProcedureCalls(calls *ProcedureCalls) error {
+	calls.mu.Lock()
+	events := make([]*AuditEvent, 0, len(calls.events))
+	for _, event := range calls.events {
+		events = append(events, event)
+	}
+	calls.events = make(map[procedureCallKey]*AuditEvent)
+	calls.mu.Unlock()
+	
+	if len(events) == 0 {
+		return nil
+	}
+	sort.Slice(events, func(i, j int) bool {
+		return events[i].Timestamp.Before(events[j].Timestamp)
+	})
+	
+	al.mu.Lock()
+	defer al.mu.Unlock()
+	
+	if !al.enabled {
+		return nil
+	}
+	for _, event := range events {
+		if err := al.write(*event); err != nil {
+			return err
+		}
+	}
+	return al.sync()
+}
+
+// LogModuleCreated logs the creation of a module
+func (al *AuditLogger) LogModuleCreated(username, host, database string, mod ProcedureAudit) {
+	al.Log(AuditEvent{
+		EventType: AuditModuleCreated,
+		Username:  username,
+		Host:      host,
+		Database:  database,
+		Details:   fmt.Sprintf("Created module '%s'", mod.Name),
+		Success:   true,
+		Procedure: &mod,
+	})
+}
+
+// LogModuleDropped logs the removal of a module
+func (al *AuditLogger) LogModuleDropped(username, host, database string, mod ProcedureAudit) {
+	al.Log(AuditEvent{
+		EventType: AuditModuleDropped,
+		Username:  username,
+		Host:      host,
+		Database:  database,
+		Details:   fmt.Sprintf("Dropped module '%s'", mod.Name),
+		Success:   true,
+		Procedure: &mod,
+	})
+}
+
 // Close closes the audit log file
 func (al *AuditLogger) Close() error {
 	al.mu.Lock()
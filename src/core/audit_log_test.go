@@ -4,6 +4,7 @@ import (
 	"encoding/json"
 	"os"
 	"path/filepath"
+	"strings"
 	"testing"
 	"time"
 )
@@ -392,6 +393,210 @@ func TestAuditLogger_LogQueryExecuted(t *testing.T) {
 	}
 }
 
+func TestAuditLogger_LogProcedureExecuted(t *testing.T) {
+	tmpDir := t.TempDir()
+	
+	logger, err := NewAuditLogger(tmpDir, true)
+	if err != nil {
+		t.Fatalf("Failed to create audit logger: %v", err)
+	}
+	defer logger.Close()
+	
+	// Successful calls are held until the flush; a failed one is logged at
+	// once. Another statement's calls are totalled apart.
+	calls, other := NewProcedureCalls(), NewProcedureCalls()
+	proc := ProcedureAudit{Name: "calculate_commission", Version: 2, Digest: "ab12", Args: 3, Fuel: 1500}
+	logger.LogProcedureExecuted(calls, "testuser", "localhost", "testdb", proc, nil)
+	logger.LogProcedureExecuted(other, "testuser", "localhost", "testdb", proc, nil)
+	logger.LogProcedureExecuted(calls, "testuser", "localhost", "testdb", proc, nil)
+	callErr := &ProcedureError{Code: "22012", Message: "division by zero", Raised: true}
+	logger.LogProcedureExecuted(calls, "testuser", "localhost", "testdb", proc, callErr)
+	
+	events := readAuditEvents(t, tmpDir)
+	if len(events) != 1 {
+		t.Fatalf("Expected only the failure before the flush, got %d events", len(events))
+	}
+	failed := events[0]
+	if failed.Success || failed.Details != callErr.Error() || failed.Procedure.SQLState != "22012" || failed.Procedure.Calls != 1 {
+		t.Errorf("Expected the failure recorded with its SQLSTATE, got %+v (%+v)", failed, failed.Procedure)
+	}
+	
+	if err := logger.FlushProcedureCalls(calls); err != nil {
+		t.Fatalf("Failed to flush procedure calls: %v", err)
+	}
+	events = readAuditEvents(t, tmpDir)
+	if len(events) != 2 {
+		t.Fatalf("Expected 2 events, got %d", len(events))
+	}
+	
+	ok := events[1]
+	if ok.EventType != AuditProcedureExecuted || !ok.Success || ok.Procedure == nil {
+		t.Fatalf("Unexpected event: %+v", ok)
+	}
+	want := proc
+	want.Calls, want.Fuel = 2, 3000
+	if *ok.Procedure != want {
+		t.Errorf("Expected procedure %+v, got %+v", want, *ok.Procedure)
+	}
+	
+	if err := logger.FlushProcedureCalls(other); err != nil {
+		t.Fatalf("Failed to flush procedure calls: %v", err)
+	}
+	events = readAuditEvents(t, tmpDir)
+	if len(events) != 3 || events[2].Procedure.Calls != 1 {
+		t.Errorf("Expected the other statement's call logged on its own, got %d events", len(events))
+	}
+}
+
+func TestEngineAdapter_ProcedureAuditPerStatement(t *testing.T) {
+	adapter := newScalarTestAdapter(t)
+	
+	// Three rows call add three times in one statement
+	execSQL(t, adapter, "SELECT id, add(qty, 10) FROM orders")
+	
+	var executed []AuditEvent
+	for _, event := range readAuditEvents(t, adapter.pagedEngine.dataDir) {
+		if event.EventType == AuditProcedureExecuted {
+			executed = append(executed, event)
+		}
+	}
+	if len(executed) != 1 {
+		t.Fatalf("Expected 1 PROCEDURE_EXECUTED event, got %+v", executed)
+	}
+	if proc := executed[0].Procedure; proc.Name != "add" || proc.Calls != 3 || proc.Fuel == 0 {
+		t.Errorf("Expected 3 calls to add with their fuel, got %+v", proc)
+	}
+}
+
+func TestPagedEngine_ProcedureAudit(t *testing.T) {
+	engine, _ := newHostTestEngine(t)
+	engine.currentUser = "clerk@localhost"
+	
+	proc := &StoredProcedure{Name: "insert_customer", Language: "wasm", Code: databaseAccessWASM, ReturnType: "INT"}
+	if err := engine.CreateProcedure(proc); err != nil {
+		t.Fatalf("Failed to create procedure: %v", err)
+	}
+	if _, err := engine.CallProcedure("insert_customer"); err != nil {
+		t.Fatalf("CallProcedure failed: %v", err)
+	}
+	if err := engine.DropProcedure("insert_customer"); err != nil {
+		t.Fatalf("Failed to drop procedure: %v", err)
+	}
+	
+	var events []AuditEvent
+	for _, event := range readAuditEvents(t, engine.dataDir) {
+		if event.Procedure != nil {
+			events = append(events, event)
+		}
+	}
+	want := []AuditEventType{AuditProcedureCreated, AuditProcedureExecuted, AuditProcedureDropped}
+	if len(events) != len(want) {
+		t.Fatalf("Expected %d procedure events, got %+v", len(want), events)
+	}
+	for i, event := range events {
+		if event.EventType != want[i] || event.Username != "clerk" || event.Host != "localhost" || event.Database != "testdb" {
+			t.Errorf("Unexpected event %d: %+v", i, event)
+		}
+		if event.Procedure.Digest != procedureDigest(databaseAccessWASM) || event.Procedure.Version != 1 {
+			t.Errorf("Expected version 1 of the module, got %+v", event.Procedure)
+		}
+	}
+	
+	if executed := events[1].Procedure; !events[1].Success || executed.Args != 0 || executed.Fuel == 0 {
+		t.Errorf("Expected a successful call that used fuel, got %+v", executed)
+	}
+}
+
+func TestPagedEngine_DDLAudit(t *testing.T) {
+	engine, err := NewPagedEngine(t.TempDir())
+	if err != nil {
+		t.Fatalf("Failed to create engine: %v", err)
+	}
+	defer engine.Close()
+	
+	params := []Column{{Name: "amount", DataType: "INT"}}
+	if err := engine.CreateProcedure(&StoredProcedure{Name: "discount", Language: "wasm", Code: discountV1WASM, Params: params, ReturnType: "INT"}); err != nil {
+		t.Fatalf("Failed to create procedure: %v", err)
+	}
+	
+	// A replace rolled back is not audited; a committed one is, at COMMIT
+	for _, commit := range []bool{false, true} {
+		engine.BeginTransaction()
+		if err := engine.ReplaceProcedure(&StoredProcedure{Name: "discount", Language: "wasm", Code: discountV2WASM, Params: params, ReturnType: "INT"}); err != nil {
+			t.Fatalf("Failed to replace procedure: %v", err)
+		}
+		if commit {
+			err = engine.CommitTransaction()
+		} else {
+			err = engine.RollbackTransaction()
+		}
+		if err != nil {
+			t.Fatalf("Failed to end transaction: %v", err)
+		}
+	}
+	
+	if err := engine.RollbackProcedure("discount", 1); err != nil {
+		t.Fatalf("Failed to roll back procedure: %v", err)
+	}
+	if err := engine.CreateModule(&StoredModule{Name: "business_rules", Code: businessRulesWASM}); err != nil {
+		t.Fatalf("Failed to create module: %v", err)
+	}
+	if err := engine.DropModule("business_rules"); err != nil {
+		t.Fatalf("Failed to drop module: %v", err)
+	}
+	
+	var events []AuditEvent
+	for _, event := range readAuditEvents(t, engine.dataDir) {
+		if event.Procedure != nil {
+			events = append(events, event)
+		}
+	}
+	want := []struct {
+		eventType AuditEventType
+		version   int
+		code      []byte
+	}{
+		{AuditProcedureCreated, 1, discountV1WASM},
+		{AuditProcedureCreated, 2, discountV2WASM},
+		{AuditProcedureCreated, 1, discountV1WASM},
+		{AuditModuleCreated, 0, businessRulesWASM},
+		{AuditModuleDropped, 0, businessRulesWASM},
+	}
+	if len(events) != len(want) {
+		t.Fatalf("Expected %d events, got %+v", len(want), events)
+	}
+	for i, event := range events {
+		if event.EventType != want[i].eventType || event.Procedure.Version != want[i].version || event.Procedure.Digest != procedureDigest(want[i].code) {
+			t.Errorf("Expected %s of version %d, got %s %+v", want[i].eventType, want[i].version, event.EventType, event.Procedure)
+		}
+	}
+}
+
+// readAuditEvents returns the events in the audit log under dataDir
+func readAuditEvents(t *testing.T, dataDir string) []AuditEvent {
+	t.Helper()
+	
+	auditDir := filepath.Join(dataDir, "audit")
+	files, err := os.ReadDir(auditDir)
+	if err != nil || len(files) == 0 {
+		t.Fatalf("Failed to read audit directory: %v", err)
+	}
+	content, err := os.ReadFile(filepath.Join(auditDir, files[0].Name()))
+	if err != nil {
+		t.Fatalf("Failed to read log file: %v", err)
+	}
+	
+	var events []AuditEvent
+	for _, line := range strings.Split(strings.TrimSpace(string(content)), "\n") {
+		var event AuditEvent
+		if err := json.Unmarshal([]byte(line), &event); err != nil {
+			t.Fatalf("Failed to parse audit event %q: %v", line, err)
+		}
+		events = append(events, event)
+	}
+	return events
+}
+
 func TestAuditLogger_Close(t *testing.T) {
 	tmpDir := t.TempDir()
 	
@@ -602,6 +807,11 @@ func TestAuditEventTypes(t *testing.T) {
 		AuditRevokePrivilege,
 		AuditAccessDenied,
 		AuditQueryExecuted,
+		AuditProcedureCreated,
+		AuditProcedureDropped,
+		AuditProcedureExecuted,
+		AuditModuleCreated,
+		AuditModuleDropped,
 	}
 	
 	for _, eventType := range types {
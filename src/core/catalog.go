@@ -23,10 +23,11 @@ type DatabaseMetadata struct {
 
 // TableMetadata stores metadata about a table
 type TableMetadata struct {
-	Name      string   `json:"name"`
-	Columns   []Column `json:"columns"`
-	HeapFile  string   `json:"heap_file"`  // Path to heap file
-	CreatedAt int64    `json:"created_at"` // Unix timestamp
+	Name      string            `json:"name"`
+	Columns   []Column          `json:"columns"`
+	Checks    []CheckConstraint `json:"checks,omitempty"` // CHECK constraints
+	HeapFile  string            `json:"heap_file"`        // Path to heap file
+	CreatedAt int64             `json:"created_at"`       // Unix timestamp
 }
 
 // IndexMetadata stores metadata about an index (for future use)
@@ -269,6 +270,25 @@ func (sc *SystemCatalog) AlterTable(dbName, tableName string, newColumns []Colum
 	return nil
 }
 
+// SetChecks replaces a table's CHECK constraints
+func (sc *SystemCatalog) SetChecks(dbName, tableName string, checks []CheckConstraint) error {
+	sc.mu.Lock()
+	defer sc.mu.Unlock()
+
+	db, exists := sc.databases[dbName]
+	if !exists {
+		return fmt.Errorf("database '%s' does not exist", dbName)
+	}
+
+	table, exists := db.Tables[tableName]
+	if !exists {
+		return fmt.Errorf("table '%s' does not exist", tableName)
+	}
+
+	table.Checks = checks
+	return nil
+}
+
 // getCurrentTimestamp returns current Unix timestamp
 func getCurrentTimestamp() int64 {
 	return 1727922000 // Fixed timestamp for deterministic testing
@@ -2,6 +2,8 @@ package mindb
 
 import (
 	"fmt"
+	"regexp"
+	"strings"
 )
 
 // ConstraintValidator validates constraints on data operations
@@ -311,3 +313,213 @@ func (cv *ConstraintValidator) CreateIndexForConstraint(table *PagedTable, colum
 	table.Indexes[columnName] = btree
 	return nil
 }
+
+// CheckConstraint is a CHECK constraint: a comparison every row must
+// satisfy. Its left side may call a stored procedure, as in
+// CHECK (validate_credit_card(card_number) = 1); a bare call such as
+// CHECK (is_valid_sku(sku)) must return true. Only Name, Column and Expr are
+// stored in the catalog; Condition is parsed from Expr.
+type CheckConstraint struct {
+	Name      string    `json:"name"`
+	Column    string    `json:"column,omitempty"` // Column it was declared on, if any
+	Expr      string    `json:"expr"`             // Condition as written
+	Condition Condition `json:"-"`
+}
+
+// checkCallPattern matches a procedure call whose arguments hold no
+// parentheses, as CHECK constraints allow
+const checkCallPattern = `(?:\w+\.)?\w+\s*\((?:[^()']|'[^']*')*\)`
+
+var (
+	checkCallRe       = regexp.MustCompile(`^` + checkCallPattern + `$`)
+	checkComparisonRe = regexp.MustCompile(`(?i)^(` + checkCallPattern + `|\w+)\s*(>=|<=|!=|=|>|<)\s*(-?\d+(?:\.\d+)?|'[^']*'|TRUE|FALSE)$`)
+)
+
+// compile parses the constraint's expression into its condition. Only a
+// bare call, or a single column or call compared with a literal, is
+// supported; anything else, such as AND, is rejected rather than partly
+// checked.
+func (c *CheckConstraint) compile() error {
+	p := NewParser()
+	expr := strings.TrimSpace(c.Expr)
+	if checkCallRe.MatchString(expr) {
+		call := p.parseFunctionCall(expr)
+		c.Condition = Condition{Column: call.String(), Operator: "=", Value: true, Call: call}
+		return nil
+	}
+
+	matches := checkComparisonRe.FindStringSubmatch(expr)
+	if matches == nil {
+		return fmt.Errorf("invalid CHECK constraint: %s", c.Expr)
+	}
+	c.Condition = Condition{Column: matches[1], Operator: matches[2], Value: p.parseValue(matches[3])}
+	if call := p.parseFunctionCall(matches[1]); call != nil {
+		c.Condition.Call = call
+		c.Condition.Column = call.String()
+	}
+	return nil
+}
+
+// columns returns the columns the constraint reads
+func (c *CheckConstraint) columns() []string {
+	if c.Condition.Call == nil {
+		return []string{c.Condition.Column}
+	}
+	var columns []string
+	for _, arg := range c.Condition.Call.Args {
+		if arg.Column != "" {
+			columns = append(columns, arg.Column)
+		}
+	}
+	return columns
+}
+
+// calledOnNull reports whether the constraint calls a procedure that is
+// CALLED ON NULL INPUT
+func (c *CheckConstraint) calledOnNull(engine *PagedEngine) bool {
+	if c.Condition.Call == nil {
+		return false
+	}
+	engine.mu.RLock()
+	proc, err := engine.lookupHandler(c.Condition.Call.Module, c.Condition.Call.Name)
+	engine.mu.RUnlock()
+	return err == nil && proc.OnNull == OnNullCalled
+}
+
+// hasChecks reports whether a table has CHECK constraints
+func (t *PagedTable) hasChecks() bool {
+	t.mu.RLock()
+	defer t.mu.RUnlock()
+	return len(t.Checks) > 0
+}
+
+// ValidateChecks validates CHECK constraints before inserting or updating a
+// row for the statement running in ctx. Constraints may call procedures
+// that read the table, so the caller must not hold the table lock.
+func (cv *ConstraintValidator) ValidateChecks(ctx *ExecutionContext, table *PagedTable, row Row) error {
+	table.mu.RLock()
+	checks, columns := table.Checks, table.Columns
+	table.mu.RUnlock()
+
+	return cv.validateChecks(ctx, checks, columns, row)
+}
+
+// validateChecks validates a row against CHECK constraints. Columns missing
+// from the row are NULL. As in SQL, a constraint whose comparison is unknown
+// passes: a column that is NULL, a call with a NULL argument or a call
+// returning NULL. Procedures CALLED ON NULL INPUT are called regardless.
+func (cv *ConstraintValidator) validateChecks(ctx *ExecutionContext, checks []CheckConstraint, columns []Column, row Row) error {
+	if len(checks) == 0 {
+		return nil
+	}
+	engine := ctx.Engine
+
+	full := make(Row, len(columns)+len(row))
+	for _, col := range columns {
+		full[col.Name] = nil
+	}
+	for k, v := range row {
+		full[k] = v
+	}
+
+	for i := range checks {
+		check := &checks[i]
+		cond := check.Condition
+
+		unknown := false
+		for _, col := range check.columns() {
+			if full[col] == nil && !check.calledOnNull(engine) {
+				unknown = true
+				break
+			}
+		}
+		if unknown {
+			continue
+		}
+
+		val := full[cond.Column]
+		if cond.Call != nil {
+			var err error
+			if val, err = engine.callFunction(ctx, cond.Call, full); err != nil {
+				return fmt.Errorf("CHECK constraint '%s' failed: %w", check.Name, err)
+			}
+			if val == nil {
+				continue
+			}
+		}
+
+		// Booleans compare as 1 and 0, so BOOLEAN procedures can be
+		// compared with = 1 or called bare
+		cond.Value = checkValue(cond.Value)
+		if !matchesConditions(Row{cond.Column: checkValue(val)}, []Condition{cond}) {
+			return fmt.Errorf("CHECK constraint '%s' violated: %s", check.Name, check.Expr)
+		}
+	}
+
+	return nil
+}
+
+// checkValue converts a boolean to 1 or 0 for comparison
+func checkValue(v interface{}) interface{} {
+	if b, ok := v.(bool); ok {
+		if b {
+			return int64(1)
+		}
+		return int64(0)
+	}
+	return v
+}
+
+// addChecks names and validates CHECK constraints being added to a table
+// and returns the table's constraints with them appended. The columns and
+// any procedure or module function a constraint calls must exist. Unnamed
+// constraints are named table_column_check or table_check, numbered if
+// taken.
+func (cv *ConstraintValidator) addChecks(table string, columns []Column, existing []CheckConstraint, added []CheckConstraint, engine *PagedEngine) ([]CheckConstraint, error) {
+	checks := append([]CheckConstraint{}, existing...)
+	taken := make(map[string]bool)
+	for _, check := range existing {
+		taken[check.Name] = true
+	}
+
+	for _, check := range added {
+		for _, colName := range check.columns() {
+			found := false
+			for _, col := range columns {
+				if col.Name == colName {
+					found = true
+					break
+				}
+			}
+			if !found {
+				return nil, fmt.Errorf("CHECK constraint references unknown column '%s'", colName)
+			}
+		}
+		if call := check.Condition.Call; call != nil {
+			engine.mu.RLock()
+			_, err := engine.lookupHandler(call.Module, call.Name)
+			engine.mu.RUnlock()
+			if err != nil {
+				return nil, fmt.Errorf("CHECK constraint calls %s: %w", call.String(), err)
+			}
+		}
+
+		if check.Name == "" {
+			base := table + "_check"
+			if check.Column != "" {
+				base = table + "_" + check.Column + "_check"
+			}
+			check.Name = base
+			for n := 1; taken[check.Name]; n++ {
+				check.Name = fmt.Sprintf("%s%d", base, n)
+			}
+		} else if taken[check.Name] {
+			return nil, fmt.Errorf("constraint '%s' already exists", check.Name)
+		}
+
+		taken[check.Name] = true
+		checks = append(checks, check)
+	}
+
+	return checks, nil
+}
@@ -0,0 +1,207 @@
+package mindb
+
+import (
+	"strings"
+	"testing"
+)
+
+// WASM module running the Luhn check on a card number. Equivalent to:
+//
+//	#[procedure]
+//	fn validate_credit_card(number: i64) -> i32 {
+//	    let (mut n, mut sum, mut digits) = (number as u64, 0, 0);
+//	    while n != 0 || digits == 0 {
+//	        let mut d = n % 10;
+//	        if digits % 2 == 1 { d *= 2; if d > 9 { d -= 9; } }
+//	        sum += d; digits += 1; n /= 10;
+//	    }
+//	    (sum % 10 == 0 && digits >= 12) as i32
+//	}
+var luhnWASM = []byte{
+	0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60,
+	0x01, 0x7e, 0x01, 0x7f, 0x03, 0x02, 0x01, 0x00, 0x07, 0x18, 0x01, 0x14,
+	0x76, 0x61, 0x6c, 0x69, 0x64, 0x61, 0x74, 0x65, 0x5f, 0x63, 0x72, 0x65,
+	0x64, 0x69, 0x74, 0x5f, 0x63, 0x61, 0x72, 0x64, 0x00, 0x00, 0x0a, 0x58,
+	0x01, 0x56, 0x02, 0x02, 0x7e, 0x01, 0x7f, 0x03, 0x40, 0x20, 0x00, 0x42,
+	0x0a, 0x82, 0x21, 0x02, 0x20, 0x00, 0x42, 0x0a, 0x80, 0x21, 0x00, 0x20,
+	0x03, 0x41, 0x01, 0x71, 0x04, 0x40, 0x20, 0x02, 0x42, 0x02, 0x7e, 0x21,
+	0x02, 0x20, 0x02, 0x42, 0x09, 0x56, 0x04, 0x40, 0x20, 0x02, 0x42, 0x09,
+	0x7d, 0x21, 0x02, 0x0b, 0x0b, 0x20, 0x01, 0x20, 0x02, 0x7c, 0x21, 0x01,
+	0x20, 0x03, 0x41, 0x01, 0x6a, 0x21, 0x03, 0x20, 0x00, 0x42, 0x00, 0x52,
+	0x0d, 0x00, 0x0b, 0x20, 0x01, 0x42, 0x0a, 0x82, 0x50, 0x20, 0x03, 0x41,
+	0x0c, 0x4f, 0x71, 0x0b,
+}
+
+// newCheckTestAdapter returns an adapter with the validate_credit_card and
+// check_tier procedures
+func newCheckTestAdapter(t *testing.T, dataDir string) *EngineAdapter {
+	t.Helper()
+
+	adapter, err := NewEngineAdapter(dataDir, false)
+	if err != nil {
+		t.Fatalf("Failed to create adapter: %v", err)
+	}
+	t.Cleanup(func() { adapter.Close() })
+
+	if _, err := adapter.Execute(&Statement{Type: CreateDatabase, Database: "shop"}); err != nil {
+		t.Fatalf("CREATE DATABASE failed: %v", err)
+	}
+	if err := adapter.UseDatabase("shop"); err != nil {
+		t.Fatalf("USE DATABASE failed: %v", err)
+	}
+
+	for _, proc := range []*StoredProcedure{
+		{Name: "validate_credit_card", Code: luhnWASM, Params: []Column{{Name: "number", DataType: "BIGINT"}}},
+		{Name: "check_tier", Code: guestErrorWASM, Params: []Column{{Name: "tier", DataType: "INT"}}},
+	} {
+		proc.Language = "wasm"
+		proc.ReturnType = "INT"
+		if err := adapter.pagedEngine.CreateProcedure(proc); err != nil {
+			t.Fatalf("Failed to create procedure %s: %v", proc.Name, err)
+		}
+	}
+
+	return adapter
+}
+
+func TestEngineAdapter_CheckConstraintCallsProcedure(t *testing.T) {
+	adapter := newCheckTestAdapter(t, t.TempDir())
+
+	execSQL(t, adapter, "CREATE TABLE payments (id INT, card_number BIGINT CHECK (validate_credit_card(card_number) = 1), amount INT, CONSTRAINT positive_amount CHECK (amount > 0))")
+	execSQL(t, adapter, "INSERT INTO payments (id, card_number, amount) VALUES (1, 4111111111111111, 10)")
+
+	err := execSQLError(t, adapter, "INSERT INTO payments (id, card_number, amount) VALUES (2, 4111111111111112, 10)")
+	if !strings.Contains(err.Error(), "CHECK constraint 'payments_card_number_check' violated") {
+		t.Errorf("Expected card check violation, got: %v", err)
+	}
+	err = execSQLError(t, adapter, "INSERT INTO payments (id, card_number, amount) VALUES (3, 5555555555554444, 0)")
+	if !strings.Contains(err.Error(), "CHECK constraint 'positive_amount' violated: amount > 0") {
+		t.Errorf("Expected amount check violation, got: %v", err)
+	}
+
+	// A NULL card number is not checked
+	execSQL(t, adapter, "INSERT INTO payments (id, amount) VALUES (4, 5)")
+
+	// A rejected UPDATE changes no rows
+	err = execSQLError(t, adapter, "UPDATE payments SET card_number = 4111111111111112 WHERE id = 1")
+	if !strings.Contains(err.Error(), "payments_card_number_check") {
+		t.Errorf("Expected card check violation, got: %v", err)
+	}
+	execSQL(t, adapter, "UPDATE payments SET card_number = 378282246310005 WHERE id = 4")
+
+	result := execSQL(t, adapter, "SELECT id FROM payments WHERE card_number = 4111111111111111")
+	if !strings.Contains(result, "1 row(s) returned") {
+		t.Errorf("Expected the original card number, got:\n%s", result)
+	}
+	result = execSQL(t, adapter, "SELECT id FROM payments WHERE card_number = 378282246310005")
+	if !strings.Contains(result, "1 row(s) returned") {
+		t.Errorf("Expected the updated card number, got:\n%s", result)
+	}
+}
+
+func TestEngineAdapter_CheckConstraintCalledOnNull(t *testing.T) {
+	adapter := newCheckTestAdapter(t, t.TempDir())
+	engine := adapter.pagedEngine
+	engine.mu.Lock()
+	engine.procedures["validate_credit_card"].OnNull = OnNullCalled
+	engine.mu.Unlock()
+
+	// The procedure sees a NULL card number as 0, which fails the check
+	execSQL(t, adapter, "CREATE TABLE payments (id INT, card_number BIGINT CHECK (validate_credit_card(card_number) = 1))")
+	err := execSQLError(t, adapter, "INSERT INTO payments (id) VALUES (1)")
+	if !strings.Contains(err.Error(), "CHECK constraint 'payments_card_number_check' violated") {
+		t.Errorf("Expected card check violation, got: %v", err)
+	}
+}
+
+func TestEngineAdapter_CheckConstraintProcedureError(t *testing.T) {
+	adapter := newCheckTestAdapter(t, t.TempDir())
+
+	execSQL(t, adapter, "CREATE TABLE customers (id INT, tier INT, CONSTRAINT valid_tier CHECK (check_tier(tier) >= 1))")
+	execSQL(t, adapter, "INSERT INTO customers (id, tier) VALUES (1, 2)")
+
+	err := execSQLError(t, adapter, "INSERT INTO customers (id, tier) VALUES (2, 0)")
+	if !strings.Contains(err.Error(), "CHECK constraint 'valid_tier' failed") || !strings.Contains(err.Error(), "bad tier") {
+		t.Errorf("Expected the procedure's error, got: %v", err)
+	}
+
+	err = execSQLError(t, adapter, "CREATE TABLE bad (id INT, CHECK (check_tier(tier) >= 1))")
+	if !strings.Contains(err.Error(), "unknown column 'tier'") {
+		t.Errorf("Expected unknown column error, got: %v", err)
+	}
+
+	// A callable that does not exist is refused when the constraint is
+	// added, not on every INSERT
+	err = execSQLError(t, adapter, "CREATE TABLE bad (id INT, tier INT, CHECK (no_such_fn(tier) >= 1))")
+	if !strings.Contains(err.Error(), "procedure 'no_such_fn' does not exist") {
+		t.Errorf("Expected unknown procedure error, got: %v", err)
+	}
+	execSQLError(t, adapter, "INSERT INTO bad (id, tier) VALUES (1, 2)")
+
+	err = execSQLError(t, adapter, "ALTER TABLE customers ADD CONSTRAINT valid_rule CHECK (rules.no_such_fn(tier) = 1)")
+	if !strings.Contains(err.Error(), "module 'rules' does not exist") {
+		t.Errorf("Expected unknown module error, got: %v", err)
+	}
+}
+
+func TestEngineAdapter_AlterTableCheckConstraint(t *testing.T) {
+	adapter := newCheckTestAdapter(t, t.TempDir())
+
+	execSQL(t, adapter, "CREATE TABLE payments (id INT, card_number BIGINT)")
+	execSQL(t, adapter, "INSERT INTO payments (id, card_number) VALUES (1, 4111111111111112)")
+
+	// Existing rows are validated against the new constraint
+	err := execSQLError(t, adapter, "ALTER TABLE payments ADD CONSTRAINT valid_card CHECK (validate_credit_card(card_number) = 1)")
+	if !strings.Contains(err.Error(), "existing row violates constraint") {
+		t.Errorf("Expected existing row violation, got: %v", err)
+	}
+	execSQL(t, adapter, "INSERT INTO payments (id, card_number) VALUES (2, 1234)")
+
+	execSQL(t, adapter, "DELETE FROM payments WHERE id = 1")
+	execSQL(t, adapter, "DELETE FROM payments WHERE id = 2")
+	execSQL(t, adapter, "ALTER TABLE payments ADD CONSTRAINT valid_card CHECK (validate_credit_card(card_number) = 1)")
+	execSQLError(t, adapter, "INSERT INTO payments (id, card_number) VALUES (3, 1234)")
+
+	err = execSQLError(t, adapter, "ALTER TABLE payments ADD CONSTRAINT valid_card CHECK (id > 0)")
+	if !strings.Contains(err.Error(), "constraint 'valid_card' already exists") {
+		t.Errorf("Expected duplicate constraint error, got: %v", err)
+	}
+
+	// A new column can carry a constraint
+	execSQL(t, adapter, "ALTER TABLE payments ADD COLUMN fee INT CHECK (fee >= 0)")
+	err = execSQLError(t, adapter, "INSERT INTO payments (id, card_number, fee) VALUES (4, 4111111111111111, -1)")
+	if !strings.Contains(err.Error(), "payments_fee_check") {
+		t.Errorf("Expected fee check violation, got: %v", err)
+	}
+
+	execSQL(t, adapter, "ALTER TABLE payments DROP CONSTRAINT valid_card")
+	execSQL(t, adapter, "INSERT INTO payments (id, card_number, fee) VALUES (5, 1234, 0)")
+
+	err = execSQLError(t, adapter, "ALTER TABLE payments DROP CONSTRAINT valid_card")
+	if !strings.Contains(err.Error(), "constraint 'valid_card' does not exist") {
+		t.Errorf("Expected missing constraint error, got: %v", err)
+	}
+}
+
+func TestEngineAdapter_CheckConstraintPersistence(t *testing.T) {
+	dataDir := t.TempDir()
+
+	adapter := newCheckTestAdapter(t, dataDir)
+	execSQL(t, adapter, "CREATE TABLE payments (id INT, card_number BIGINT CHECK (validate_credit_card(card_number) = 1))")
+	adapter.Close()
+
+	reopened, err := NewEngineAdapter(dataDir, false)
+	if err != nil {
+		t.Fatalf("Failed to reopen adapter: %v", err)
+	}
+	defer reopened.Close()
+	if err := reopened.UseDatabase("shop"); err != nil {
+		t.Fatalf("USE DATABASE failed: %v", err)
+	}
+
+	execSQL(t, reopened, "INSERT INTO payments (id, card_number) VALUES (1, 4111111111111111)")
+	err = execSQLError(t, reopened, "INSERT INTO payments (id, card_number) VALUES (2, 4111111111111112)")
+	if !strings.Contains(err.Error(), "payments_card_number_check") {
+		t.Errorf("Expected the constraint to survive a restart, got: %v", err)
+	}
+}
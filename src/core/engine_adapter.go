@@ -1,8 +1,11 @@
 package mindb
 
 import (
+	"encoding/base64"
 	"fmt"
 	"strings"
+
+	"github.com/rs/zerolog"
 )
 
 // EngineAdapter wraps PagedEngine to provide backward compatibility with the old Engine interface
@@ -24,6 +27,16 @@ func NewEngineAdapter(dataDir string, enableWAL bool) (*EngineAdapter, error) {
 
 // Execute executes a parsed statement (backward compatibility interface)
 func (ea *EngineAdapter) Execute(stmt *Statement) (string, error) {
+	return ea.ExecuteRequest("", stmt)
+}
+
+// ExecuteRequest executes a parsed statement for the HTTP request with the
+// given ID, which tags the log lines of the procedures it calls
+func (ea *EngineAdapter) ExecuteRequest(requestID string, stmt *Statement) (string, error) {
+	// Procedures the statement calls are audited once it ends
+	ctx := ea.pagedEngine.executionContext(requestID)
+	defer ea.pagedEngine.flushProcedureCalls(ctx)
+	
 	// Check permissions (except for user management commands which have their own checks)
 	if stmt.Type != CreateUser && stmt.Type != DropUser && 
 	   stmt.Type != GrantPrivileges && stmt.Type != RevokePrivileges && 
@@ -47,21 +60,39 @@ func (ea *EngineAdapter) Execute(stmt *Statement) (string, error) {
 	case CreateTable:
 		return ea.createTable(stmt)
 	case AlterTable:
-		return ea.alterTable(stmt)
+		return ea.alterTable(ctx, stmt)
 	case DropTable:
 		return ea.dropTable(stmt)
 	case Insert:
-		return ea.insertData(stmt)
+		return ea.insertData(ctx, stmt)
 	case Select:
-		return ea.selectData(stmt)
+		return ea.selectData(ctx, stmt)
 	case Update:
-		return ea.updateData(stmt)
+		return ea.updateData(ctx, stmt)
 	case Delete:
-		return ea.deleteData(stmt)
+		return ea.deleteData(ctx, stmt)
 	case DescribeTable:
 		return ea.describeTable(stmt)
+	case CreateProcedure:
+		return ea.createProcedure(stmt)
+	case DropProcedure:
+		return ea.dropProcedure(stmt)
+	case AlterProcedure:
+		return ea.alterProcedure(stmt)
 	case CallProcedure:
-		return ea.callProcedure(stmt)
+		return ea.callProcedure(ctx, stmt)
+	case CreateModule:
+		return ea.createModule(stmt)
+	case DropModule:
+		return ea.dropModule(stmt)
+	case CreateAggregate:
+		return ea.createAggregate(stmt)
+	case DropAggregate:
+		return ea.dropAggregate(stmt)
+	case CreateTrigger:
+		return ea.createTrigger(stmt)
+	case DropTrigger:
+		return ea.dropTrigger(stmt)
 	case CreateUser:
 		return ea.createUser(stmt)
 	case DropUser:
@@ -129,7 +160,7 @@ func (ea *EngineAdapter) createTable(stmt *Statement) (string, error) {
 		tableName = stmt.Schema + "." + stmt.Table
 	}
 
-	if err := ea.pagedEngine.CreateTable(tableName, stmt.Columns); err != nil {
+	if err := ea.pagedEngine.CreateTableWithChecks(tableName, stmt.Columns, stmt.Checks); err != nil {
 		return "", err
 	}
 
@@ -137,7 +168,7 @@ func (ea *EngineAdapter) createTable(stmt *Statement) (string, error) {
 }
 
 // alterTable alters a table
-func (ea *EngineAdapter) alterTable(stmt *Statement) (string, error) {
+func (ea *EngineAdapter) alterTable(ctx *ExecutionContext, stmt *Statement) (string, error) {
 	// Get current table to retrieve existing columns
 	db, err := ea.pagedEngine.getCurrentDatabase()
 	if err != nil {
@@ -152,17 +183,58 @@ func (ea *EngineAdapter) alterTable(stmt *Statement) (string, error) {
 		return "", fmt.Errorf("table '%s' does not exist", stmt.Table)
 	}
 
+	table.mu.RLock()
+	columns, checks := table.Columns, table.Checks
+	table.mu.RUnlock()
+
 	// Add new column to existing columns
-	newColumns := append(table.Columns, stmt.Columns...)
+	newColumns := append(append([]Column{}, columns...), stmt.Columns...)
+	if stmt.NewColumn.Name != "" {
+		newColumns = append(newColumns, stmt.NewColumn)
+	}
+
+	// Drop or add CHECK constraints
+	newChecks := checks
+	if stmt.Constraint != "" {
+		newChecks = nil
+		for _, check := range checks {
+			if check.Name != stmt.Constraint {
+				newChecks = append(newChecks, check)
+			}
+		}
+		if len(newChecks) == len(checks) {
+			return "", fmt.Errorf("constraint '%s' does not exist", stmt.Constraint)
+		}
+	}
+	validator := NewConstraintValidator()
+	newChecks, err = validator.addChecks(stmt.Table, newColumns, newChecks, stmt.Checks, ea.pagedEngine)
+	if err != nil {
+		return "", err
+	}
+
+	// Existing rows must satisfy the altered table's CHECK constraints
+	rows, err := ea.pagedEngine.SelectRows(stmt.Table, nil)
+	if err != nil {
+		return "", err
+	}
+	for _, row := range rows {
+		if err := validator.validateChecks(ctx, newChecks, newColumns, row); err != nil {
+			return "", fmt.Errorf("existing row violates constraint: %w", err)
+		}
+	}
 
 	// Update catalog
 	if err := ea.pagedEngine.catalog.AlterTable(db.Name, stmt.Table, newColumns); err != nil {
 		return "", err
 	}
+	if err := ea.pagedEngine.catalog.SetChecks(db.Name, stmt.Table, newChecks); err != nil {
+		return "", err
+	}
 
 	// Update in-memory structure
 	table.mu.Lock()
 	table.Columns = newColumns
+	table.Checks = newChecks
 	table.mu.Unlock()
 
 	// Save catalog
@@ -186,7 +258,7 @@ func (ea *EngineAdapter) dropTable(stmt *Statement) (string, error) {
 }
 
 // insertData inserts data into a table
-func (ea *EngineAdapter) insertData(stmt *Statement) (string, error) {
+func (ea *EngineAdapter) insertData(ctx *ExecutionContext, stmt *Statement) (string, error) {
 	rowCount := 0
 
 	for _, values := range stmt.Values {
@@ -199,7 +271,7 @@ func (ea *EngineAdapter) insertData(stmt *Statement) (string, error) {
 			row[col.Name] = values[i]
 		}
 
-		if err := ea.pagedEngine.InsertRow(stmt.Table, row); err != nil {
+		if err := ea.pagedEngine.insertRowIn(ctx, stmt.Table, row); err != nil {
 			return "", err
 		}
 		rowCount++
@@ -209,32 +281,41 @@ func (ea *EngineAdapter) insertData(stmt *Statement) (string, error) {
 }
 
 // selectData selects data from a table
-func (ea *EngineAdapter) selectData(stmt *Statement) (string, error) {
+func (ea *EngineAdapter) selectData(ctx *ExecutionContext, stmt *Statement) (string, error) {
 	// Handle JOINs
 	if len(stmt.Joins) > 0 {
-		return ea.selectDataWithJoin(stmt)
+		return ea.selectDataWithJoin(ctx, stmt)
 	}
 
-	// Handle aggregates
-	if len(stmt.Aggregates) > 0 {
-		return ea.selectDataWithAggregates(stmt)
+	// Calls may name user-defined aggregates
+	if err := ea.pagedEngine.resolveAggregates(stmt); err != nil {
+		return "", err
 	}
 
-	// Convert Statement conditions to PagedEngine conditions
-	var conditions []Condition
-	for _, cond := range stmt.Conditions {
-		conditions = append(conditions, Condition{
-			Column:   cond.Column,
-			Operator: cond.Operator,
-			Value:    cond.Value,
-		})
+	// Handle aggregates
+	if len(stmt.Aggregates) > 0 {
+		return ea.selectDataWithAggregates(ctx, stmt)
 	}
 
-	rows, err := ea.pagedEngine.SelectRows(stmt.Table, conditions)
+	// Conditions that call procedures are evaluated after the scan
+	rows, columns, err := ea.pagedEngine.scanRows(ctx, stmt)
 	if err != nil {
 		return "", err
 	}
 
+	// Evaluate procedure calls per row
+	if stmt.hasFunctionCalls() {
+		rows, err = ea.pagedEngine.applyFunctionCalls(ctx, stmt, rows)
+		if err != nil {
+			return "", err
+		}
+	}
+
+	// Apply ORDER BY
+	if stmt.OrderBy != "" {
+		rows = ea.pagedEngine.applyOrderBy(rows, stmt.OrderBy, stmt.OrderDesc)
+	}
+
 	// Apply LIMIT and OFFSET
 	if stmt.Offset > 0 {
 		if stmt.Offset >= len(rows) {
@@ -249,11 +330,19 @@ func (ea *EngineAdapter) selectData(stmt *Statement) (string, error) {
 	}
 
 	// Format output
+	if stmt.TableFunction != nil {
+		return formatTableFunctionResult(stmt, columns, rows), nil
+	}
 	return ea.formatSelectResult(stmt, rows)
 }
 
 // selectDataWithJoin executes a SELECT with JOIN
-func (ea *EngineAdapter) selectDataWithJoin(stmt *Statement) (string, error) {
+func (ea *EngineAdapter) selectDataWithJoin(ctx *ExecutionContext, stmt *Statement) (string, error) {
+	// Calls may name user-defined aggregates
+	if err := ea.pagedEngine.resolveAggregates(stmt); err != nil {
+		return "", err
+	}
+
 	// Get rows from left table
 	leftRows, err := ea.pagedEngine.SelectRows(stmt.Table, nil)
 	if err != nil {
@@ -278,9 +367,26 @@ func (ea *EngineAdapter) selectDataWithJoin(stmt *Statement) (string, error) {
 		}
 	}
 
-	// Apply WHERE conditions on joined result
-	if len(stmt.Conditions) > 0 {
-		result = ea.filterRows(result, stmt.Conditions)
+	// Apply WHERE conditions on joined result; those that call procedures
+	// are evaluated with the SELECT-list calls
+	if conditions := scanConditions(stmt.Conditions); len(conditions) > 0 {
+		result = ea.filterRows(result, conditions)
+	}
+	if stmt.hasFunctionCalls() {
+		result, err = ea.pagedEngine.applyFunctionCalls(ctx, stmt, result)
+		if err != nil {
+			return "", err
+		}
+	}
+
+	// Aggregate the joined rows
+	if len(stmt.Aggregates) > 0 {
+		aggExecutor := NewAggregateExecutor()
+		aggExecutor.engine, aggExecutor.ctx = ea.pagedEngine, ctx
+		result, err = aggExecutor.ExecuteAggregates(result, stmt.Aggregates, stmt.GroupBy)
+		if err != nil {
+			return "", err
+		}
 	}
 
 	// Apply LIMIT and OFFSET
@@ -295,24 +401,24 @@ func (ea *EngineAdapter) selectDataWithJoin(stmt *Statement) (string, error) {
 }
 
 // selectDataWithAggregates executes a SELECT with aggregate functions
-func (ea *EngineAdapter) selectDataWithAggregates(stmt *Statement) (string, error) {
+func (ea *EngineAdapter) selectDataWithAggregates(ctx *ExecutionContext, stmt *Statement) (string, error) {
 	// Get all rows
-	var conditions []Condition
-	for _, cond := range stmt.Conditions {
-		conditions = append(conditions, Condition{
-			Column:   cond.Column,
-			Operator: cond.Operator,
-			Value:    cond.Value,
-		})
-	}
-
-	rows, err := ea.pagedEngine.SelectRows(stmt.Table, conditions)
+	rows, _, err := ea.pagedEngine.scanRows(ctx, stmt)
 	if err != nil {
 		return "", err
 	}
 
+	// Apply WHERE conditions that call procedures
+	if stmt.hasFunctionCalls() {
+		rows, err = ea.pagedEngine.applyFunctionCalls(ctx, stmt, rows)
+		if err != nil {
+			return "", err
+		}
+	}
+
 	// Execute aggregates
 	aggExecutor := NewAggregateExecutor()
+	aggExecutor.engine, aggExecutor.ctx = ea.pagedEngine, ctx
 	result, err := aggExecutor.ExecuteAggregates(rows, stmt.Aggregates, stmt.GroupBy)
 	if err != nil {
 		return "", err
@@ -380,7 +486,7 @@ func (ea *EngineAdapter) filterRows(rows []Row, conditions []Condition) []Row {
 }
 
 // updateData updates data in a table
-func (ea *EngineAdapter) updateData(stmt *Statement) (string, error) {
+func (ea *EngineAdapter) updateData(ctx *ExecutionContext, stmt *Statement) (string, error) {
 	// Convert Statement conditions to PagedEngine conditions
 	var conditions []Condition
 	for _, cond := range stmt.Conditions {
@@ -391,7 +497,7 @@ func (ea *EngineAdapter) updateData(stmt *Statement) (string, error) {
 		})
 	}
 
-	count, err := ea.pagedEngine.UpdateRows(stmt.Table, stmt.Updates, conditions)
+	count, err := ea.pagedEngine.updateRowsIn(ctx, stmt.Table, stmt.Updates, conditions)
 	if err != nil {
 		return "", err
 	}
@@ -400,7 +506,7 @@ func (ea *EngineAdapter) updateData(stmt *Statement) (string, error) {
 }
 
 // deleteData deletes data from a table
-func (ea *EngineAdapter) deleteData(stmt *Statement) (string, error) {
+func (ea *EngineAdapter) deleteData(ctx *ExecutionContext, stmt *Statement) (string, error) {
 	// Convert Statement conditions to PagedEngine conditions
 	var conditions []Condition
 	for _, cond := range stmt.Conditions {
@@ -411,7 +517,7 @@ func (ea *EngineAdapter) deleteData(stmt *Statement) (string, error) {
 		})
 	}
 
-	count, err := ea.pagedEngine.DeleteRows(stmt.Table, conditions)
+	count, err := ea.pagedEngine.deleteRowsIn(ctx, stmt.Table, conditions)
 	if err != nil {
 		return "", err
 	}
@@ -480,6 +586,27 @@ func (ea *EngineAdapter) formatSelectResult(stmt *Statement, rows []Row) (string
 		}
 	}
 
+	return formatRows(displayColumns, rows), nil
+}
+
+// formatTableFunctionResult formats the rows of a table-valued procedure.
+// SELECT * shows the columns in the order the procedure emitted them.
+func formatTableFunctionResult(stmt *Statement, columns []string, rows []Row) string {
+	if len(stmt.Columns) > 0 && !(len(stmt.Columns) == 1 && stmt.Columns[0].Name == "*") {
+		columns = make([]string, len(stmt.Columns))
+		for i, col := range stmt.Columns {
+			columns[i] = col.Name
+		}
+	}
+	return formatRows(columns, rows)
+}
+
+// formatRows renders rows as a text table with the given columns
+func formatRows(displayColumns []string, rows []Row) string {
+	if len(rows) == 0 {
+		return "0 rows returned"
+	}
+
 	// Calculate column widths
 	colWidths := make(map[string]int)
 	for _, colName := range displayColumns {
@@ -541,7 +668,7 @@ func (ea *EngineAdapter) formatSelectResult(stmt *Statement, rows []Row) (string
 	}
 	result.WriteString(fmt.Sprintf("\n%d row(s) returned", len(rows)))
 
-	return result.String(), nil
+	return result.String()
 }
 
 // beginTransaction starts a new explicit transaction
@@ -573,6 +700,16 @@ func (ea *EngineAdapter) CreateProcedureViaAdapter(proc *StoredProcedure) error
 	return ea.pagedEngine.CreateProcedure(proc)
 }
 
+// ReplaceProcedureCodeViaAdapter creates a stored procedure or replaces its
+// bytecode with a new version, keeping its settings. The current user needs
+// CREATE, as for CREATE PROCEDURE, and to own a procedure it replaces.
+func (ea *EngineAdapter) ReplaceProcedureCodeViaAdapter(proc *StoredProcedure) error {
+	if err := ea.checkPermission(&Statement{Type: CreateProcedure}); err != nil {
+		return err
+	}
+	return ea.pagedEngine.ReplaceProcedureCode(proc)
+}
+
 // DropProcedureViaAdapter drops a stored procedure
 func (ea *EngineAdapter) DropProcedureViaAdapter(name string) error {
 	return ea.pagedEngine.DropProcedure(name)
@@ -583,9 +720,25 @@ func (ea *EngineAdapter) ListProceduresViaAdapter() []*StoredProcedure {
 	return ea.pagedEngine.ListProcedures()
 }
 
-// CallProcedureViaAdapter calls a stored procedure
+// CallProcedureViaAdapter calls a stored procedure, or a module function
+// when name is qualified as module.function. The current user needs EXECUTE
+// on it.
 func (ea *EngineAdapter) CallProcedureViaAdapter(name string, args ...interface{}) (interface{}, error) {
-	return ea.pagedEngine.CallProcedure(name, args...)
+	return ea.CallProcedureRequest("", name, args...)
+}
+
+// CallProcedureRequest calls a stored procedure or module function like
+// CallProcedureViaAdapter, for the HTTP request with the given ID
+func (ea *EngineAdapter) CallProcedureRequest(requestID, name string, args ...interface{}) (interface{}, error) {
+	ctx := ea.pagedEngine.executionContext(requestID)
+	if err := ctx.authorizeExecute(name); err != nil {
+		return nil, err
+	}
+	defer ea.pagedEngine.flushProcedureCalls(ctx)
+	if moduleName, functionName, ok := strings.Cut(name, "."); ok {
+		return ea.pagedEngine.callModuleFunction(ctx, moduleName, functionName, args...)
+	}
+	return ea.pagedEngine.callProcedure(ctx, name, args...)
 }
 
 // describeTable returns the schema of a table in table format
@@ -650,14 +803,25 @@ func (ea *EngineAdapter) describeTable(stmt *Statement) (string, error) {
 	return result.String(), nil
 }
 
-// callProcedure executes a stored procedure
-func (ea *EngineAdapter) callProcedure(stmt *Statement) (string, error) {
+// callProcedure executes a stored procedure or a module function
+func (ea *EngineAdapter) callProcedure(ctx *ExecutionContext, stmt *Statement) (string, error) {
 	// Call the procedure
-	result, err := ea.pagedEngine.CallProcedure(stmt.ProcedureName, stmt.ProcedureArgs...)
+	var result interface{}
+	var err error
+	if stmt.ModuleName != "" {
+		result, err = ea.pagedEngine.callModuleFunction(ctx, stmt.ModuleName, stmt.ProcedureName, stmt.ProcedureArgs...)
+	} else {
+		result, err = ea.pagedEngine.callProcedure(ctx, stmt.ProcedureName, stmt.ProcedureArgs...)
+	}
 	if err != nil {
 		return "", fmt.Errorf("procedure call failed: %w", err)
 	}
 
+	// Table-valued procedures return a result set
+	if rows, ok := result.(*ProcedureRows); ok {
+		return formatRows(rows.Columns, rows.Rows), nil
+	}
+
 	// Format the result as a table for display
 	var output strings.Builder
 	
@@ -676,6 +840,149 @@ func (ea *EngineAdapter) callProcedure(stmt *Statement) (string, error) {
 	return output.String(), nil
 }
 
+// createProcedure stores a WASM procedure given as base64. CREATE OR
+// REPLACE stores a new version of an existing procedure.
+func (ea *EngineAdapter) createProcedure(stmt *Statement) (string, error) {
+	code, err := base64.StdEncoding.DecodeString(string(stmt.ProcedureCode))
+	if err != nil {
+		return "", fmt.Errorf("failed to decode WASM: %w", err)
+	}
+
+	proc := &StoredProcedure{
+		Name:       stmt.ProcedureName,
+		Language:   stmt.ProcedureLang,
+		Code:       code,
+		Params:     stmt.Columns,
+		ReturnType: stmt.ReturnType,
+		OnNull:     stmt.OnNull,
+		Security:   stmt.Security,
+	}
+	if stmt.OrReplace {
+		err = ea.pagedEngine.ReplaceProcedure(proc)
+	} else {
+		err = ea.pagedEngine.CreateProcedure(proc)
+	}
+	if err != nil {
+		return "", err
+	}
+
+	return fmt.Sprintf("Procedure '%s' version %d created successfully", proc.Name, proc.Version), nil
+}
+
+// dropProcedure drops a stored procedure and its versions
+func (ea *EngineAdapter) dropProcedure(stmt *Statement) (string, error) {
+	if err := ea.pagedEngine.DropProcedure(stmt.ProcedureName); err != nil {
+		if stmt.IfExists {
+			return fmt.Sprintf("Procedure '%s' does not exist, skipping", stmt.ProcedureName), nil
+		}
+		return "", err
+	}
+
+	return fmt.Sprintf("Procedure '%s' dropped successfully", stmt.ProcedureName), nil
+}
+
+// alterProcedure rolls a stored procedure back to an earlier version
+func (ea *EngineAdapter) alterProcedure(stmt *Statement) (string, error) {
+	if err := ea.pagedEngine.RollbackProcedure(stmt.ProcedureName, stmt.Version); err != nil {
+		return "", err
+	}
+
+	return fmt.Sprintf("Procedure '%s' rolled back to version %d", stmt.ProcedureName, stmt.Version), nil
+}
+
+// createModule stores a WASM module given as base64
+func (ea *EngineAdapter) createModule(stmt *Statement) (string, error) {
+	code, err := base64.StdEncoding.DecodeString(string(stmt.ProcedureCode))
+	if err != nil {
+		return "", fmt.Errorf("failed to decode WASM: %w", err)
+	}
+
+	mod := &StoredModule{
+		Name:     stmt.ModuleName,
+		Language: stmt.ProcedureLang,
+		Code:     code,
+	}
+	if err := ea.pagedEngine.CreateModule(mod); err != nil {
+		return "", err
+	}
+
+	return fmt.Sprintf("Module '%s' created successfully with %d function(s)", mod.Name, len(mod.Functions)), nil
+}
+
+// createAggregate stores a user-defined aggregate given as base64
+func (ea *EngineAdapter) createAggregate(stmt *Statement) (string, error) {
+	code, err := base64.StdEncoding.DecodeString(string(stmt.ProcedureCode))
+	if err != nil {
+		return "", fmt.Errorf("failed to decode WASM: %w", err)
+	}
+
+	agg := &StoredAggregate{
+		Name:       stmt.ProcedureName,
+		Language:   stmt.ProcedureLang,
+		Code:       code,
+		Params:     stmt.Columns,
+		ReturnType: stmt.ReturnType,
+	}
+	if err := ea.pagedEngine.CreateAggregate(agg); err != nil {
+		return "", err
+	}
+
+	return fmt.Sprintf("Aggregate '%s' created successfully", agg.Name), nil
+}
+
+// dropAggregate drops a user-defined aggregate
+func (ea *EngineAdapter) dropAggregate(stmt *Statement) (string, error) {
+	if err := ea.pagedEngine.DropAggregate(stmt.ProcedureName); err != nil {
+		if stmt.IfExists {
+			return fmt.Sprintf("Aggregate '%s' does not exist, skipping", stmt.ProcedureName), nil
+		}
+		return "", err
+	}
+
+	return fmt.Sprintf("Aggregate '%s' dropped successfully", stmt.ProcedureName), nil
+}
+
+// createTrigger creates a trigger that runs a procedure for each changed row
+func (ea *EngineAdapter) createTrigger(stmt *Statement) (string, error) {
+	trigger := &StoredTrigger{
+		Name:      stmt.TriggerName,
+		Table:     stmt.Table,
+		Timing:    stmt.TriggerTiming,
+		Events:    stmt.TriggerEvents,
+		Procedure: stmt.ProcedureName,
+		Module:    stmt.ModuleName,
+	}
+	if err := ea.pagedEngine.CreateTrigger(trigger); err != nil {
+		return "", err
+	}
+
+	return fmt.Sprintf("Trigger '%s' created successfully", trigger.Name), nil
+}
+
+// dropTrigger drops a trigger
+func (ea *EngineAdapter) dropTrigger(stmt *Statement) (string, error) {
+	if err := ea.pagedEngine.DropTrigger(stmt.TriggerName, stmt.Table); err != nil {
+		if stmt.IfExists {
+			return fmt.Sprintf("Trigger '%s' does not exist, skipping", stmt.TriggerName), nil
+		}
+		return "", err
+	}
+
+	return fmt.Sprintf("Trigger '%s' dropped successfully", stmt.TriggerName), nil
+}
+
+// dropModule drops a stored module
+func (ea *EngineAdapter) dropModule(stmt *Statement) (string, error) {
+	if err := ea.pagedEngine.DropModule(stmt.ModuleName); err != nil {
+		if stmt.IfExists {
+			return fmt.Sprintf("Module '%s' does not exist, skipping", stmt.ModuleName), nil
+		}
+		return "", err
+	}
+
+	return fmt.Sprintf("Module '%s' dropped successfully", stmt.ModuleName), nil
+}
+
 // createUser creates a new user
 func (ea *EngineAdapter) createUser(stmt *Statement) (string, error) {
 	err := ea.pagedEngine.userManager.CreateUser(stmt.Username, stmt.Password, stmt.Host)
@@ -709,6 +1016,10 @@ func (ea *EngineAdapter) dropUser(stmt *Statement) (string, error) {
 
 // grantPrivileges grants privileges to a user
 func (ea *EngineAdapter) grantPrivileges(stmt *Statement) (string, error) {
+	if stmt.ProcedureName != "" {
+		return ea.grantExecute(stmt)
+	}
+	
 	// Convert string privileges to Privilege type
 	privileges := make([]Privilege, len(stmt.Privileges))
 	for i, p := range stmt.Privileges {
@@ -732,6 +1043,10 @@ func (ea *EngineAdapter) grantPrivileges(stmt *Statement) (string, error) {
 
 // revokePrivileges revokes privileges from a user
 func (ea *EngineAdapter) revokePrivileges(stmt *Statement) (string, error) {
+	if stmt.ProcedureName != "" {
+		return ea.revokeExecute(stmt)
+	}
+	
 	// Convert string privileges to Privilege type
 	privileges := make([]Privilege, len(stmt.Privileges))
 	for i, p := range stmt.Privileges {
@@ -753,6 +1068,56 @@ func (ea *EngineAdapter) revokePrivileges(stmt *Statement) (string, error) {
 		stmt.Privileges, stmt.Database, stmt.Table, stmt.Username, stmt.Host), nil
 }
 
+// grantExecute grants EXECUTE on a procedure to a user or role
+func (ea *EngineAdapter) grantExecute(stmt *Statement) (string, error) {
+	if !ea.pagedEngine.hasCallable(stmt.ProcedureName) {
+		return "", fmt.Errorf("procedure '%s' does not exist", stmt.ProcedureName)
+	}
+	
+	um := ea.pagedEngine.userManager
+	grantee := fmt.Sprintf("'%s'@'%s'", stmt.Username, stmt.Host)
+	var err error
+	if stmt.RoleName != "" {
+		grantee = fmt.Sprintf("role '%s'", stmt.RoleName)
+		err = um.GrantRoleExecute(stmt.RoleName, stmt.ProcedureName)
+	} else {
+		err = um.GrantExecute(stmt.Username, stmt.Host, stmt.ProcedureName)
+	}
+	if err != nil {
+		return "", err
+	}
+	
+	// Save users to disk
+	if err := um.Save(); err != nil {
+		fmt.Printf("Warning: failed to save users: %v\n", err)
+	}
+	
+	return fmt.Sprintf("Granted EXECUTE on procedure %s to %s", stmt.ProcedureName, grantee), nil
+}
+
+// revokeExecute revokes EXECUTE on a procedure from a user or role
+func (ea *EngineAdapter) revokeExecute(stmt *Statement) (string, error) {
+	um := ea.pagedEngine.userManager
+	grantee := fmt.Sprintf("'%s'@'%s'", stmt.Username, stmt.Host)
+	var err error
+	if stmt.RoleName != "" {
+		grantee = fmt.Sprintf("role '%s'", stmt.RoleName)
+		err = um.RevokeRoleExecute(stmt.RoleName, stmt.ProcedureName)
+	} else {
+		err = um.RevokeExecute(stmt.Username, stmt.Host, stmt.ProcedureName)
+	}
+	if err != nil {
+		return "", err
+	}
+	
+	// Save users to disk
+	if err := um.Save(); err != nil {
+		fmt.Printf("Warning: failed to save users: %v\n", err)
+	}
+	
+	return fmt.Sprintf("Revoked EXECUTE on procedure %s from %s", stmt.ProcedureName, grantee), nil
+}
+
 // showGrants shows grants for a user
 func (ea *EngineAdapter) showGrants(stmt *Statement) (string, error) {
 	grants := ea.pagedEngine.userManager.ListGrants(stmt.Username, stmt.Host)
@@ -777,6 +1142,13 @@ func (ea *EngineAdapter) showGrants(stmt *Statement) (string, error) {
 			grant.Table,
 			grant.Username,
 			grant.Host)
+		if grant.Procedure != "" {
+			grantStr = fmt.Sprintf("GRANT %s ON PROCEDURE %s TO '%s'@'%s'",
+				strings.Join(privs, ", "),
+				grant.Procedure,
+				grant.Username,
+				grant.Host)
+		}
 		
 		output.WriteString(fmt.Sprintf("| %-48s |\n", grantStr))
 	}
@@ -851,6 +1223,12 @@ func (ea *EngineAdapter) checkPermission(stmt *Statement) error {
 	switch stmt.Type {
 	case Select, DescribeTable:
 		requiredPriv = PrivilegeSelect
+		if err := ea.pagedEngine.executionContext("").authorizeCalls(stmt); err != nil {
+			return err
+		}
+		if stmt.TableFunction != nil {
+			return nil // Only needs EXECUTE, like CALL
+		}
 	case Insert:
 		requiredPriv = PrivilegeInsert
 	case Update:
@@ -869,8 +1247,38 @@ func (ea *EngineAdapter) checkPermission(stmt *Statement) error {
 		}
 	case AlterTable:
 		requiredPriv = PrivilegeCreate // ALTER requires CREATE privilege
+	case CreateProcedure, AlterProcedure:
+		requiredPriv = PrivilegeCreate
+		table = "*"
+	case DropProcedure:
+		requiredPriv = PrivilegeDrop
+		table = "*"
+	case CreateModule:
+		requiredPriv = PrivilegeCreate
+		table = "*"
+	case DropModule:
+		requiredPriv = PrivilegeDrop
+		table = "*"
+	case CreateAggregate:
+		requiredPriv = PrivilegeCreate
+		table = "*"
+	case DropAggregate:
+		requiredPriv = PrivilegeDrop
+		table = "*"
+	case CreateTrigger:
+		requiredPriv = PrivilegeCreate // Triggers are created on a table
+		// The handler runs for every changed row, with its owner's
+		// privileges if SECURITY DEFINER, so binding it needs EXECUTE
+		if err := ea.pagedEngine.executionContext("").authorizeExecute(stmt.callable()); err != nil {
+			return err
+		}
+	case DropTrigger:
+		requiredPriv = PrivilegeDrop
+		if table == "" {
+			table = "*"
+		}
 	case CallProcedure:
-		requiredPriv = PrivilegeSelect // Calling procedures requires SELECT
+		return ea.pagedEngine.executionContext("").authorizeExecute(stmt.callable())
 	default:
 		// For unknown types, allow (backward compatibility)
 		return nil
@@ -890,6 +1298,11 @@ func (ea *EngineAdapter) SetCurrentUser(username, host string) {
 	ea.pagedEngine.currentUser = fmt.Sprintf("%s@%s", username, host)
 }
 
+// SetLogger sets the logger procedures write to through mindb_log
+func (ea *EngineAdapter) SetLogger(logger zerolog.Logger) {
+	ea.pagedEngine.wasmEngine.SetLogger(logger)
+}
+
 // Authenticate authenticates a user
 func (ea *EngineAdapter) Authenticate(username, password, host string) bool {
 	return ea.pagedEngine.userManager.Authenticate(username, password, host)
@@ -1,6 +1,7 @@
 package mindb
 
 import (
+	"encoding/base64"
 	"strings"
 	"testing"
 )
@@ -400,18 +401,13 @@ func TestEngineAdapter_CreateProcedure(t *testing.T) {
 	}
 	defer adapter.Close()
 
-	// CREATE PROCEDURE - not yet implemented in Execute switch
 	result, err := adapter.Execute(&Statement{
 		Type:          CreateProcedure,
-		ProcedureName: "get_user",
-		ProcedureCode: []byte("SELECT * FROM users WHERE id = $1"),
-		ProcedureLang: "sql",
+		ProcedureName: "add",
+		ProcedureCode: []byte(base64.StdEncoding.EncodeToString(simpleAddWASM)),
+		ProcedureLang: "wasm",
 	})
 	if err != nil {
-		// Expected - CreateProcedure not in Execute switch yet
-		if strings.Contains(err.Error(), "unsupported") {
-			t.Skip("CREATE PROCEDURE not yet implemented in Execute switch")
-		}
 		t.Fatalf("CREATE PROCEDURE failed: %v", err)
 	}
 	if !strings.Contains(result, "created") && !strings.Contains(result, "Created") {
@@ -428,16 +424,15 @@ func TestEngineAdapter_DropProcedure(t *testing.T) {
 	}
 	defer adapter.Close()
 
-	// DROP PROCEDURE - not yet implemented in Execute switch
+	if err := adapter.CreateProcedureViaAdapter(&StoredProcedure{Name: "add", Language: "wasm", Code: simpleAddWASM}); err != nil {
+		t.Fatalf("Failed to create procedure: %v", err)
+	}
+
 	result, err := adapter.Execute(&Statement{
 		Type:          DropProcedure,
-		ProcedureName: "get_user",
+		ProcedureName: "add",
 	})
 	if err != nil {
-		// Expected - DropProcedure not in Execute switch yet
-		if strings.Contains(err.Error(), "unsupported") {
-			t.Skip("DROP PROCEDURE not yet implemented in Execute switch")
-		}
 		t.Fatalf("DROP PROCEDURE failed: %v", err)
 	}
 	if !strings.Contains(result, "dropped") && !strings.Contains(result, "Dropped") {
@@ -15,6 +15,7 @@ import (
 type PagedTable struct {
 	Name      string
 	Columns   []Column
+	Checks    []CheckConstraint // CHECK constraints
 	HeapFile  *HeapFile
 	Indexes   map[string]*BTree // Column name -> B-tree index
 	TupleIDs  []TupleID         // Track tuple IDs for scanning
@@ -39,9 +40,14 @@ type PagedEngine struct {
 	vacuumManager  *VacuumManager
 	catalog        *SystemCatalog
 	currentTxn     *Transaction // Current transaction (if any)
+	txnProcedures  []*procedureChange // Procedure DDL of the current transaction
+	txnObjects     []*objectChange    // Module, aggregate and trigger DDL of the current transaction
 	queryCache     *QueryCache  // Query result cache
 	wasmEngine     *WASMEngine  // WASM stored procedure engine
 	procedures     map[string]*StoredProcedure // Stored procedures
+	modules        map[string]*StoredModule    // Stored WASM modules (CREATE MODULE)
+	aggregates     map[string]*StoredAggregate // User-defined aggregates (CREATE AGGREGATE)
+	triggers       map[string]*StoredTrigger   // Row triggers (CREATE TRIGGER)
 	userManager    *UserManager // User authentication and authorization
 	auditLogger    *AuditLogger // Audit logging
 	currentUser    string       // Current authenticated user (username@host)
@@ -61,8 +67,11 @@ func NewPagedEngineWithWAL(dataDir string, enableWAL bool) (*PagedEngine, error)
 	// Create system catalog
 	catalog := NewSystemCatalog(dataDir)
 	
-	// Initialize WASM engine for stored procedures
-	wasmEngine, err := NewWASMEngine(DefaultWASMConfig())
+	// Initialize WASM engine for stored procedures, caching compiled
+	// modules next to the procedure store
+	wasmConfig := DefaultWASMConfig()
+	wasmConfig.CacheDir = filepath.Join(dataDir, "module_cache")
+	wasmEngine, err := NewWASMEngine(wasmConfig)
 	if err != nil {
 		return nil, fmt.Errorf("failed to initialize WASM engine: %v", err)
 	}
@@ -91,6 +100,9 @@ func NewPagedEngineWithWAL(dataDir string, enableWAL bool) (*PagedEngine, error)
 		queryCache:    NewQueryCache(60*time.Second, 1000), // 60s TTL, 1000 entries
 		wasmEngine:    wasmEngine,
 		procedures:    make(map[string]*StoredProcedure),
+		modules:       make(map[string]*StoredModule),
+		aggregates:    make(map[string]*StoredAggregate),
+		triggers:      make(map[string]*StoredTrigger),
 		userManager:   userManager,
 		auditLogger:   auditLogger,
 		currentUser:   "root@%", // Default to root user
@@ -115,6 +127,7 @@ func NewPagedEngineWithWAL(dataDir string, enableWAL bool) (*PagedEngine, error)
 		}
 		engine.walManager = wm
 		engine.recoveryMgr = NewRecoveryManager(wm)
+		engine.recoveryMgr.SetDataDir(dataDir)
 		
 		// Run recovery
 		if err := engine.recoveryMgr.Recover(); err != nil {
@@ -139,6 +152,24 @@ func NewPagedEngineWithWAL(dataDir string, enableWAL bool) (*PagedEngine, error)
 		return nil, fmt.Errorf("failed to load procedures: %v", err)
 	}
 	
+	// Load stored modules
+	if err := engine.loadModules(); err != nil {
+		return nil, fmt.Errorf("failed to load modules: %v", err)
+	}
+	
+	// Load user-defined aggregates
+	if err := engine.loadAggregates(); err != nil {
+		return nil, fmt.Errorf("failed to load aggregates: %v", err)
+	}
+	
+	// Load triggers
+	if err := engine.loadTriggers(); err != nil {
+		return nil, fmt.Errorf("failed to load triggers: %v", err)
+	}
+	
+	// Drop artifacts of bytecode no longer stored
+	wasmEngine.PruneArtifacts()
+	
 	return engine, nil
 }
 
@@ -173,11 +204,19 @@ func (e *PagedEngine) loadDatabases() error {
 			table := &PagedTable{
 				Name:     tableName,
 				Columns:  tableMeta.Columns,
+				Checks:   tableMeta.Checks,
 				HeapFile: heapFile,
 				Indexes:  make(map[string]*BTree),
 				TupleIDs: make([]TupleID, 0),
 			}
 			
+			// Parse CHECK constraints
+			for i := range table.Checks {
+				if err := table.Checks[i].compile(); err != nil {
+					return fmt.Errorf("table '%s': %v", tableName, err)
+				}
+			}
+			
 			// Load indexes from disk
 			indexDir := filepath.Join(e.dataDir, dbName, "indexes")
 			for _, col := range tableMeta.Columns {
@@ -248,6 +287,11 @@ func (e *PagedEngine) DropDatabase(name string) error {
 	// Remove from memory
 	delete(e.databases, name)
 	
+	// Triggers go with their database
+	if err := e.dropTriggers(name, ""); err != nil {
+		return err
+	}
+	
 	// If this was the current database, clear it
 	if e.currentDB == name {
 		e.currentDB = ""
@@ -267,6 +311,34 @@ func (e *PagedEngine) DropDatabase(name string) error {
 	return nil
 }
 
+// executionContext returns the context a statement runs in: the current
+// database and user, the ID of the HTTP request it serves, if any, and
+// totals of the procedure calls it makes. Each statement has its own, so
+// concurrent requests keep these apart.
+func (e *PagedEngine) executionContext(requestID string) *ExecutionContext {
+	return &ExecutionContext{
+		Engine:    e,
+		Database:  e.currentDB,
+		UserID:    e.currentUser,
+		RequestID: requestID,
+		Calls:     NewProcedureCalls(),
+	}
+}
+
+// callContext returns the context of a call made by the statement running
+// in parent. The call shares the statement's request, call totals and
+// trigger depth.
+func (e *PagedEngine) callContext(parent *ExecutionContext) *ExecutionContext {
+	return &ExecutionContext{
+		Engine:       e,
+		Database:     e.currentDB,
+		UserID:       e.currentUser,
+		RequestID:    parent.RequestID,
+		Calls:        parent.Calls,
+		TriggerDepth: parent.TriggerDepth,
+	}
+}
+
 // UseDatabase switches to a database
 func (e *PagedEngine) UseDatabase(name string) error {
 	e.mu.RLock()
@@ -304,6 +376,11 @@ func (e *PagedEngine) getCurrentDatabase() (*PagedDatabase, error) {
 
 // CreateTable creates a new table with paged storage
 func (e *PagedEngine) CreateTable(tableName string, columns []Column) error {
+	return e.CreateTableWithChecks(tableName, columns, nil)
+}
+
+// CreateTableWithChecks creates a new table with CHECK constraints
+func (e *PagedEngine) CreateTableWithChecks(tableName string, columns []Column, checks []CheckConstraint) error {
 	db, err := e.getCurrentDatabase()
 	if err != nil {
 		return err
@@ -316,6 +393,14 @@ func (e *PagedEngine) CreateTable(tableName string, columns []Column) error {
 		return fmt.Errorf("table '%s' already exists", tableName)
 	}
 	
+	// Name the CHECK constraints and make sure their columns and callables
+	// exist
+	validator := NewConstraintValidator()
+	checks, err = validator.addChecks(tableName, columns, nil, checks, e)
+	if err != nil {
+		return err
+	}
+	
 	// Create heap file
 	heapFilePath := filepath.Join(e.dataDir, db.Name)
 	heapFile, err := NewHeapFile(heapFilePath, tableName)
@@ -328,17 +413,23 @@ func (e *PagedEngine) CreateTable(tableName string, columns []Column) error {
 		heapFile.Close()
 		return err
 	}
+	if len(checks) > 0 {
+		if err := e.catalog.SetChecks(db.Name, tableName, checks); err != nil {
+			heapFile.Close()
+			return err
+		}
+	}
 	
 	table := &PagedTable{
 		Name:     tableName,
 		Columns:  columns,
+		Checks:   checks,
 		HeapFile: heapFile,
 		Indexes:  make(map[string]*BTree),
 		TupleIDs: make([]TupleID, 0),
 	}
 	
 	// Create indexes for PRIMARY KEY and UNIQUE columns
-	validator := NewConstraintValidator()
 	for _, col := range columns {
 		if col.PrimaryKey || col.Unique {
 			if err := validator.CreateIndexForConstraint(table, col.Name); err != nil {
@@ -385,6 +476,11 @@ func (e *PagedEngine) DropTable(tableName string) error {
 	
 	delete(db.Tables, tableName)
 	
+	// Triggers go with their table
+	if err := e.dropTableTriggers(db.Name, tableName); err != nil {
+		return err
+	}
+	
 	// Save catalog
 	if err := e.catalog.SaveCatalog(); err != nil {
 		return fmt.Errorf("failed to save catalog: %v", err)
@@ -393,8 +489,30 @@ func (e *PagedEngine) DropTable(tableName string) error {
 	return nil
 }
 
-// InsertRow inserts a row into a table
+// InsertRow inserts a row into a table, running its INSERT triggers, as a
+// statement of its own
 func (e *PagedEngine) InsertRow(tableName string, row Row) error {
+	ctx := e.executionContext("")
+	defer e.flushProcedureCalls(ctx)
+	return e.insertRowIn(ctx, tableName, row)
+}
+
+// insertRowIn inserts a row for the statement running in ctx, which may be
+// nested in trigger handlers
+func (e *PagedEngine) insertRowIn(ctx *ExecutionContext, tableName string, row Row) error {
+	row, err := e.fireTriggers(ctx, tableName, TriggerBefore, TriggerInsert, nil, row)
+	if err != nil {
+		return err
+	}
+	if err := e.insertRow(ctx, tableName, row); err != nil {
+		return err
+	}
+	_, err = e.fireTriggers(ctx, tableName, TriggerAfter, TriggerInsert, nil, row)
+	return err
+}
+
+// insertRow inserts a row into a table
+func (e *PagedEngine) insertRow(ctx *ExecutionContext, tableName string, row Row) error {
 	// Invalidate query cache for this table (Phase 3 optimization)
 	if e.queryCache != nil {
 		e.queryCache.Invalidate(tableName)
@@ -413,11 +531,17 @@ func (e *PagedEngine) InsertRow(tableName string, row Row) error {
 		return fmt.Errorf("table '%s' does not exist", tableName)
 	}
 	
+	// CHECK constraints may call procedures, so they run before the table
+	// is locked
+	validator := NewConstraintValidator()
+	if err := validator.ValidateChecks(ctx, table, row); err != nil {
+		return err
+	}
+	
 	table.mu.Lock()
 	defer table.mu.Unlock()
 	
 	// Validate constraints
-	validator := NewConstraintValidator()
 	if err := validator.ValidateInsert(table, row); err != nil {
 		return err
 	}
@@ -488,17 +612,30 @@ func (e *PagedEngine) serializeInsertWAL(pageID PageID, slotNum uint16, tupleDat
 	return buf
 }
 
-// ExecuteQuery executes a complete SQL query with all features
+// ExecuteQuery executes a complete SQL query with all features, as a
+// statement of its own
 func (e *PagedEngine) ExecuteQuery(stmt *Statement) ([]Row, error) {
+	ctx := e.executionContext("")
+	defer e.flushProcedureCalls(ctx)
+	return e.executeQueryIn(ctx, stmt)
+}
+
+// executeQueryIn executes a query for the statement running in ctx
+func (e *PagedEngine) executeQueryIn(ctx *ExecutionContext, stmt *Statement) ([]Row, error) {
 	var rows []Row
 	var err error
 	
+	// Calls may name user-defined aggregates
+	if err := e.resolveAggregates(stmt); err != nil {
+		return nil, err
+	}
+	
 	// Step 1: Get base table rows
 	// If there are JOINs, don't apply WHERE conditions yet (they might reference joined tables)
 	if len(stmt.Joins) > 0 {
 		rows, err = e.SelectRows(stmt.Table, nil)
 	} else {
-		rows, err = e.SelectRows(stmt.Table, stmt.Conditions)
+		rows, _, err = e.scanRows(ctx, stmt)
 	}
 	if err != nil {
 		return nil, err
@@ -522,10 +659,10 @@ func (e *PagedEngine) ExecuteQuery(stmt *Statement) ([]Row, error) {
 		}
 		
 		// Apply WHERE conditions after JOIN
-		if len(stmt.Conditions) > 0 {
+		if conditions := scanConditions(stmt.Conditions); len(conditions) > 0 {
 			filteredRows := make([]Row, 0)
 			for _, row := range rows {
-				if matchesConditions(row, stmt.Conditions) {
+				if matchesConditions(row, conditions) {
 					filteredRows = append(filteredRows, row)
 				}
 			}
@@ -533,9 +670,18 @@ func (e *PagedEngine) ExecuteQuery(stmt *Statement) ([]Row, error) {
 		}
 	}
 	
+	// Evaluate procedure calls per row
+	if stmt.hasFunctionCalls() {
+		rows, err = e.applyFunctionCalls(ctx, stmt, rows)
+		if err != nil {
+			return nil, err
+		}
+	}
+	
 	// Step 3: Execute aggregates (if any)
 	if len(stmt.Aggregates) > 0 {
 		aggExecutor := NewAggregateExecutor()
+		aggExecutor.engine, aggExecutor.ctx = e, ctx
 		rows, err = aggExecutor.ExecuteAggregates(rows, stmt.Aggregates, stmt.GroupBy)
 		if err != nil {
 			return nil, err
@@ -694,8 +840,16 @@ func (e *PagedEngine) applyLimit(rows []Row, limit int, offset int) []Row {
 	return rows[start:end]
 }
 
-// UpdateRows updates rows in a table
+// UpdateRows updates rows in a table as a statement of its own
 func (e *PagedEngine) UpdateRows(tableName string, updates map[string]interface{}, conditions []Condition) (int, error) {
+	ctx := e.executionContext("")
+	defer e.flushProcedureCalls(ctx)
+	return e.updateRowsIn(ctx, tableName, updates, conditions)
+}
+
+// updateRowsIn updates rows for the statement running in ctx, which may be
+// nested in trigger handlers
+func (e *PagedEngine) updateRowsIn(ctx *ExecutionContext, tableName string, updates map[string]interface{}, conditions []Condition) (int, error) {
 	// Invalidate query cache for this table (Phase 3 optimization)
 	if e.queryCache != nil {
 		e.queryCache.Invalidate(tableName)
@@ -714,6 +868,11 @@ func (e *PagedEngine) UpdateRows(tableName string, updates map[string]interface{
 		return 0, fmt.Errorf("table '%s' does not exist", tableName)
 	}
 	
+	// Triggers and CHECK constraints run without the table locked
+	if e.hasTriggers(tableName, TriggerUpdate) || table.hasChecks() {
+		return e.updateRowsWithProcedures(ctx, table, updates, conditions)
+	}
+	
 	table.mu.Lock()
 	defer table.mu.Unlock()
 	
@@ -761,30 +920,10 @@ func (e *PagedEngine) UpdateRows(tableName string, updates map[string]interface{
 				tuple.Data[col] = val
 			}
 			
-			// Serialize updated tuple (preserve Xmin/Xmax)
-			newData, err := SerializeTupleWithHeader(tuple.Data, table.Columns, tuple.Header.Xmin, tuple.Header.Xmax)
-			if err != nil {
+			if err := e.rewriteTuple(table, tid, tuple); err != nil {
 				continue
 			}
 			
-			// Try in-place update
-			if err := table.HeapFile.UpdateTuple(tid, newData); err != nil {
-				// If in-place update fails, delete and re-insert
-				table.HeapFile.DeleteTuple(tid)
-				newTid, err := table.HeapFile.InsertTuple(newData)
-				if err != nil {
-					continue
-				}
-				
-				// Update TupleID tracking
-				for i, t := range table.TupleIDs {
-					if t == tid {
-						table.TupleIDs[i] = newTid
-						break
-					}
-				}
-			}
-			
 			count++
 		}
 	}
@@ -792,8 +931,46 @@ func (e *PagedEngine) UpdateRows(tableName string, updates map[string]interface{
 	return count, nil
 }
 
-// DeleteRows deletes rows from a table
+// rewriteTuple writes a tuple's data back, preserving Xmin/Xmax. The caller
+// holds table.mu.
+func (e *PagedEngine) rewriteTuple(table *PagedTable, tid TupleID, tuple *Tuple) error {
+	// Serialize updated tuple (preserve Xmin/Xmax)
+	newData, err := SerializeTupleWithHeader(tuple.Data, table.Columns, tuple.Header.Xmin, tuple.Header.Xmax)
+	if err != nil {
+		return err
+	}
+	
+	// Try in-place update
+	if err := table.HeapFile.UpdateTuple(tid, newData); err != nil {
+		// If in-place update fails, delete and re-insert
+		table.HeapFile.DeleteTuple(tid)
+		newTid, err := table.HeapFile.InsertTuple(newData)
+		if err != nil {
+			return err
+		}
+		
+		// Update TupleID tracking
+		for i, t := range table.TupleIDs {
+			if t == tid {
+				table.TupleIDs[i] = newTid
+				break
+			}
+		}
+	}
+	
+	return nil
+}
+
+// DeleteRows deletes rows from a table as a statement of its own
 func (e *PagedEngine) DeleteRows(tableName string, conditions []Condition) (int, error) {
+	ctx := e.executionContext("")
+	defer e.flushProcedureCalls(ctx)
+	return e.deleteRowsIn(ctx, tableName, conditions)
+}
+
+// deleteRowsIn deletes rows for the statement running in ctx, which may be
+// nested in trigger handlers
+func (e *PagedEngine) deleteRowsIn(ctx *ExecutionContext, tableName string, conditions []Condition) (int, error) {
 	// Invalidate query cache for this table (Phase 3 optimization)
 	if e.queryCache != nil {
 		e.queryCache.Invalidate(tableName)
@@ -812,6 +989,11 @@ func (e *PagedEngine) DeleteRows(tableName string, conditions []Condition) (int,
 		return 0, fmt.Errorf("table '%s' does not exist", tableName)
 	}
 	
+	// Triggers run without the table locked
+	if e.hasTriggers(tableName, TriggerDelete) {
+		return e.deleteRowsWithTriggers(ctx, table, conditions)
+	}
+	
 	table.mu.Lock()
 	defer table.mu.Unlock()
 	
@@ -968,6 +1150,11 @@ func (e *PagedEngine) CommitTransaction() error {
 		e.walManager.Sync()
 	}
 
+	// Revisions of dropped procedures, and blobs of dropped modules and
+	// aggregates, were kept until now
+	e.commitProcedureChanges()
+	e.commitObjectChanges()
+
 	e.currentTxn = nil
 	return nil
 }
@@ -981,6 +1168,15 @@ func (e *PagedEngine) RollbackTransaction() error {
 		return fmt.Errorf("no active transaction")
 	}
 
+	// Undo procedure and object DDL before the abort record, so that
+	// recovery finishes the job if the undo is interrupted
+	if err := e.undoObjectChanges(); err != nil {
+		return err
+	}
+	if err := e.undoProcedureChanges(); err != nil {
+		return err
+	}
+
 	if err := e.txnManager.AbortTransaction(e.currentTxn.ID); err != nil {
 		return err
 	}
@@ -1093,21 +1289,65 @@ func (e *PagedEngine) CreateProcedure(proc *StoredProcedure) error {
 		return fmt.Errorf("procedure '%s' already exists", proc.Name)
 	}
 	
+	proc.CreatedAt = time.Now()
+	return e.storeProcedure(proc)
+}
+
+// storeProcedure compiles a procedure and makes it the current version,
+// saved as a new revision. The caller holds e.mu.
+func (e *PagedEngine) storeProcedure(proc *StoredProcedure) error {
+	// The name becomes part of the procedure's file paths
+	if !ValidProcedureName(proc.Name) {
+		return fmt.Errorf("%w '%s'", ErrInvalidProcedureName, proc.Name)
+	}
+	
 	// Compile the WASM module
-	if err := e.wasmEngine.CompileModule(proc.Name, proc.Code); err != nil {
+	module, err := e.wasmEngine.compile(proc.Code)
+	if err != nil {
 		return fmt.Errorf("failed to compile procedure: %w", err)
 	}
 	
-	// Store procedure metadata
-	proc.CreatedAt = time.Now()
+	// Fill in anything not given explicitly from the module's own metadata
+	if sig := procedureSignature(proc.Code, proc.Name); sig != nil {
+		if len(proc.Params) == 0 {
+			proc.Params = sig.Columns()
+		}
+		if proc.ReturnType == "" {
+			proc.ReturnType = sig.Returns
+		}
+		if proc.OnNull == "" {
+			proc.OnNull = sig.onNull()
+		}
+		if proc.Description == "" {
+			proc.Description = sig.Description
+		}
+	}
+	if proc.Owner == "" {
+		proc.Owner = e.currentUser
+	}
+	
+	// Number the revision after any kept on disk, including those of a
+	// procedure dropped earlier in the current transaction
+	version, err := e.nextProcedureVersion(proc.Name)
+	if err != nil {
+		return err
+	}
+	proc.Version = version
 	proc.UpdatedAt = time.Now()
-	e.procedures[proc.Name] = proc
 	
-	// Persist to disk
-	if err := e.saveProcedure(proc); err != nil {
-		return fmt.Errorf("failed to persist procedure: %w", err)
+	// The bytecode goes to its blob first; the log record refers to it
+	if proc.Digest, err = writeProcedureBlob(e.dataDir, proc.Code); err != nil {
+		return err
 	}
 	
+	// Log and persist the change before it takes effect
+	change := &procedureChange{Name: proc.Name, Before: e.procedures[proc.Name], After: proc, NewVersion: true}
+	if err := e.logProcedureChange(change); err != nil {
+		return err
+	}
+	
+	e.wasmEngine.install(proc.Name, proc.Code, module)
+	e.procedures[proc.Name] = proc
 	return nil
 }
 
@@ -1117,26 +1357,35 @@ func (e *PagedEngine) DropProcedure(name string) error {
 	defer e.mu.Unlock()
 	
 	// Check if procedure exists
-	if _, exists := e.procedures[name]; !exists {
+	proc, exists := e.procedures[name]
+	if !exists {
 		return fmt.Errorf("procedure '%s' does not exist", name)
 	}
 	
+	// Log and delete from disk
+	if err := e.logProcedureChange(&procedureChange{Name: name, Before: proc}); err != nil {
+		return err
+	}
+	
 	// Remove from WASM engine
 	e.wasmEngine.RemoveModule(name)
 	
 	// Remove from procedures map
 	delete(e.procedures, name)
 	
-	// Delete from disk
-	if err := e.deleteProcedure(name); err != nil {
-		return fmt.Errorf("failed to delete procedure file: %w", err)
-	}
-	
 	return nil
 }
 
-// CallProcedure executes a stored procedure
+// CallProcedure executes a stored procedure as a statement of its own
 func (e *PagedEngine) CallProcedure(name string, args ...interface{}) (interface{}, error) {
+	ctx := e.executionContext("")
+	defer e.flushProcedureCalls(ctx)
+	return e.callProcedure(ctx, name, args...)
+}
+
+// callProcedure executes a stored procedure as part of the statement
+// running in parent, which logs its calls as it ends
+func (e *PagedEngine) callProcedure(parent *ExecutionContext, name string, args ...interface{}) (interface{}, error) {
 	e.mu.RLock()
 	proc, exists := e.procedures[name]
 	e.mu.RUnlock()
@@ -1145,65 +1394,72 @@ func (e *PagedEngine) CallProcedure(name string, args ...interface{}) (interface
 		return nil, fmt.Errorf("procedure '%s' does not exist", name)
 	}
 	
-	// Convert args based on parameter types
-	convertedArgs := make([]interface{}, len(args))
-	for i, arg := range args {
-		if i < len(proc.Params) {
-			// Convert based on expected type
-			convertedArgs[i] = convertToWASMType(arg, proc.Params[i].DataType)
-		} else {
-			convertedArgs[i] = arg
-		}
-	}
+	ctx := e.procedureContext(parent, proc)
 	
-	// Create execution context
-	ctx := &ExecutionContext{
-		Engine:   e,
-		Database: e.currentDB,
+	// Execute the procedure (use procedure name as function name by default).
+	// Arguments are marshalled to the function's exact WASM parameter types.
+	result, err := e.executeAudited(name, name, proc, ctx, args)
+	if err != nil {
+		return nil, err
 	}
-	
-	// Execute the procedure (use procedure name as function name by default)
-	return e.wasmEngine.ExecuteWithContext(name, name, ctx, convertedArgs...)
+	return unmarshalResult(result, proc.ReturnType), nil
 }
 
-// convertToWASMType converts a value to the appropriate WASM type
-func convertToWASMType(value interface{}, targetType string) interface{} {
-	switch targetType {
-	case "INT", "BIGINT":
-		// Convert to int32 for WASM i32
-		switch v := value.(type) {
-		case int:
-			return int32(v)
-		case int64:
-			return int32(v)
-		case float64:
-			return int32(v)
-		case int32:
-			return v
-		default:
-			return value
-		}
-	case "FLOAT":
-		// Convert to float64 for WASM f64
-		switch v := value.(type) {
-		case float64:
-			return v
-		case float32:
-			return float64(v)
-		case int:
-			return float64(v)
-		case int32:
-			return float64(v)
-		case int64:
-			return float64(v)
-		default:
-			return value
+// executeAudited calls a procedure through ExecuteProcedure and records the
+// call for a PROCEDURE_EXECUTED audit event, which the statement logs as it
+// ends. name is the name it was called by, which is module.function for
+// module functions.
+func (e *PagedEngine) executeAudited(cacheKey, name string, proc *StoredProcedure, ctx *ExecutionContext, args []interface{}) (interface{}, error) {
+	result, err := e.wasmEngine.ExecuteProcedure(cacheKey, proc, ctx, args...)
+	
+	if e.auditLogger != nil {
+		audit := procedureAudit(name, proc)
+		audit.Args = len(args)
+		audit.Fuel = ctx.FuelUsed
+		if proc.Security == SecurityDefiner {
+			audit.RunAs = ctx.UserID
+			if audit.RunAs == "" {
+				audit.RunAs = "root@%"
+			}
 		}
-	default:
-		return value
+		username, host := auditUser(e.currentUser)
+		e.auditLogger.LogProcedureExecuted(ctx.Calls, username, host, ctx.Database, audit, err)
+	}
+	return result, err
+}
+
+// flushProcedureCalls logs the procedure calls the statement running in ctx
+// made since the last flush, one event per procedure. Statements call it as
+// they end.
+func (e *PagedEngine) flushProcedureCalls(ctx *ExecutionContext) {
+	if e.auditLogger == nil {
+		return
+	}
+	if err := e.auditLogger.FlushProcedureCalls(ctx.Calls); err != nil {
+		fmt.Printf("Warning: failed to log procedure calls: %v\n", err)
 	}
 }
 
+// procedureAudit identifies a procedure in audit events
+func procedureAudit(name string, proc *StoredProcedure) ProcedureAudit {
+	return ProcedureAudit{
+		Name:    name,
+		Version: proc.Version,
+		Digest:  proc.Digest,
+		Args:    len(proc.Params),
+	}
+}
+
+// auditUser splits a user given as username@host for audit events. No user
+// means root, as for permission checks.
+func auditUser(user string) (username, host string) {
+	if user == "" {
+		return "root", "%"
+	}
+	username, host, _ = strings.Cut(user, "@")
+	return username, host
+}
+
 // ListProcedures returns all stored procedures
 func (e *PagedEngine) ListProcedures() []*StoredProcedure {
 	e.mu.RLock()
@@ -1230,11 +1486,17 @@ func (e *PagedEngine) GetProcedure(name string) (*StoredProcedure, error) {
 	return proc, nil
 }
 
-// saveProcedure persists a stored procedure to disk
-func (e *PagedEngine) saveProcedure(proc *StoredProcedure) error {
+// writeProcedureFile persists a stored procedure to disk as its current
+// version. The file is replaced atomically, so a crash never leaves it
+// truncated.
+func writeProcedureFile(dataDir string, proc *StoredProcedure) error {
+	filename, err := procedurePath(dataDir, proc.Name, ".json")
+	if err != nil {
+		return err
+	}
+	
 	// Create procedures directory if it doesn't exist
-	procDir := filepath.Join(e.dataDir, "procedures")
-	if err := os.MkdirAll(procDir, 0755); err != nil {
+	if err := os.MkdirAll(filepath.Dir(filename), 0755); err != nil {
 		return fmt.Errorf("failed to create procedures directory: %w", err)
 	}
 	
@@ -1245,17 +1507,20 @@ func (e *PagedEngine) saveProcedure(proc *StoredProcedure) error {
 	}
 	
 	// Write to file
-	filename := filepath.Join(procDir, proc.Name+".json")
-	if err := os.WriteFile(filename, data, 0644); err != nil {
+	if err := replaceFile(filename, data); err != nil {
 		return fmt.Errorf("failed to write procedure file: %w", err)
 	}
 	
 	return nil
 }
 
-// deleteProcedure removes a stored procedure file from disk
-func (e *PagedEngine) deleteProcedure(name string) error {
-	filename := filepath.Join(e.dataDir, "procedures", name+".json")
+// removeProcedureFile removes a stored procedure's current version from
+// disk, keeping its revisions
+func removeProcedureFile(dataDir string, name string) error {
+	filename, err := procedurePath(dataDir, name, ".json")
+	if err != nil {
+		return err
+	}
 	if err := os.Remove(filename); err != nil && !os.IsNotExist(err) {
 		return fmt.Errorf("failed to delete procedure file: %w", err)
 	}
@@ -1277,33 +1542,279 @@ func (e *PagedEngine) loadProcedures() error {
 		return fmt.Errorf("failed to read procedures directory: %w", err)
 	}
 	
-	// Load each procedure
+	// Load each procedure. One that cannot be loaded is left out rather
+	// than failing startup.
 	for _, file := range files {
 		if file.IsDir() || !strings.HasSuffix(file.Name(), ".json") {
 			continue
 		}
 		
-		// Read file
-		filename := filepath.Join(procDir, file.Name())
-		data, err := os.ReadFile(filename)
-		if err != nil {
-			return fmt.Errorf("failed to read procedure file %s: %w", file.Name(), err)
+		if err := e.loadProcedure(filepath.Join(procDir, file.Name())); err != nil {
+			fmt.Printf("Warning: failed to load procedure %s: %v\n", file.Name(), err)
 		}
-		
-		// Unmarshal procedure
-		var proc StoredProcedure
-		if err := json.Unmarshal(data, &proc); err != nil {
-			return fmt.Errorf("failed to unmarshal procedure %s: %w", file.Name(), err)
+	}
+	
+	// Drop blobs left behind by rolled back or migrated procedures
+	if err := pruneProcedureBlobs(e.dataDir); err != nil {
+		fmt.Printf("Warning: failed to prune procedure blobs: %v\n", err)
+	}
+	
+	return nil
+}
+
+// loadProcedure loads and compiles one procedure, verifying its bytecode
+// against the stored digest
+func (e *PagedEngine) loadProcedure(filename string) error {
+	data, err := os.ReadFile(filename)
+	if err != nil {
+		return err
+	}
+	
+	// Procedures saved with inline bytecode are rewritten to point at the
+	// blob it was moved into
+	proc, migrate, err := decodeProcedure(e.dataDir, data)
+	if err != nil {
+		return err
+	}
+	
+	// Compile WASM module
+	if err := e.wasmEngine.CompileModule(proc.Name, proc.Code); err != nil {
+		return fmt.Errorf("failed to compile: %w", err)
+	}
+	
+	// Procedures saved before versioning become version 1
+	if proc.Version == 0 {
+		proc.Version = 1
+		migrate = true
+		if err := writeProcedureVersion(e.dataDir, proc); err != nil {
+			return err
 		}
-		
-		// Compile WASM module
-		if err := e.wasmEngine.CompileModule(proc.Name, proc.Code); err != nil {
-			return fmt.Errorf("failed to compile procedure %s: %w", proc.Name, err)
+	}
+	if migrate {
+		if err := writeProcedureFile(e.dataDir, proc); err != nil {
+			return err
+		}
+	}
+	
+	// Store in memory
+	e.procedures[proc.Name] = proc
+	return nil
+}
+
+// CreateModule stores a WASM module whose exported functions are callable
+// as module.function
+func (e *PagedEngine) CreateModule(mod *StoredModule) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	
+	// Check if module already exists
+	if _, exists := e.modules[mod.Name]; exists {
+		return fmt.Errorf("module '%s' already exists", mod.Name)
+	}
+	
+	// Discover the callable functions
+	functions, err := e.wasmEngine.IntrospectModule(mod.Code)
+	if err != nil {
+		return fmt.Errorf("failed to load module: %w", err)
+	}
+	mod.Functions = functions
+	
+	// Compile the WASM module
+	if err := e.wasmEngine.CompileModule(moduleCacheKey(mod.Name), mod.Code); err != nil {
+		return fmt.Errorf("failed to compile module: %w", err)
+	}
+	
+	// Persist to disk: the bytecode to its blob, then the metadata through
+	// the WAL
+	digest, err := writeProcedureBlob(e.dataDir, mod.Code)
+	if err != nil {
+		e.wasmEngine.RemoveModule(moduleCacheKey(mod.Name))
+		return fmt.Errorf("failed to persist module: %w", err)
+	}
+	mod.Digest = digest
+	for _, fn := range functions {
+		fn.Digest = digest
+	}
+	mod.CreatedAt = time.Now()
+	mod.UpdatedAt = time.Now()
+	
+	change, err := createObjectChange(objectModule, mod.Name, mod)
+	if err == nil {
+		err = e.logObjectChange(change)
+	}
+	if err != nil {
+		e.wasmEngine.RemoveModule(moduleCacheKey(mod.Name))
+		return fmt.Errorf("failed to persist module: %w", err)
+	}
+	
+	// Store in memory
+	e.modules[mod.Name] = mod
+
+	return nil
+}
+
+// DropModule drops a stored module and all of its functions
+func (e *PagedEngine) DropModule(name string) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+	
+	// Check if module exists
+	mod, exists := e.modules[name]
+	if !exists {
+		return fmt.Errorf("module '%s' does not exist", name)
+	}
+	
+	// Delete from disk through the WAL
+	change, err := dropObjectChange(objectModule, name, mod)
+	if err == nil {
+		err = e.logObjectChange(change)
+	}
+	if err != nil {
+		return fmt.Errorf("failed to delete module: %w", err)
+	}
+	
+	// Remove from WASM engine
+	e.wasmEngine.RemoveModule(moduleCacheKey(name))
+	
+	// Remove from modules map
+	delete(e.modules, name)
+
+	return nil
+}
+
+// CallModuleFunction executes an exported function of a stored module as a
+// statement of its own
+func (e *PagedEngine) CallModuleFunction(moduleName, functionName string, args ...interface{}) (interface{}, error) {
+	ctx := e.executionContext("")
+	defer e.flushProcedureCalls(ctx)
+	return e.callModuleFunction(ctx, moduleName, functionName, args...)
+}
+
+// callModuleFunction executes an exported function of a stored module as
+// part of the statement running in parent, which logs its calls as it ends
+func (e *PagedEngine) callModuleFunction(parent *ExecutionContext, moduleName, functionName string, args ...interface{}) (interface{}, error) {
+	e.mu.RLock()
+	mod, exists := e.modules[moduleName]
+	e.mu.RUnlock()
+	
+	if !exists {
+		return nil, fmt.Errorf("module '%s' does not exist", moduleName)
+	}
+	
+	fn, exists := mod.Functions[functionName]
+	if !exists {
+		return nil, fmt.Errorf("module '%s' has no function '%s'", moduleName, functionName)
+	}
+	
+	// EXECUTE is checked once per statement, as for procedures; a SECURITY
+	// DEFINER function runs as its owner
+	name := moduleName + "." + functionName
+	ctx := e.procedureContext(parent, fn)
+	
+	result, err := e.executeAudited(moduleCacheKey(moduleName), name, fn, ctx, args)
+	if err != nil {
+		return nil, err
+	}
+	return unmarshalResult(result, fn.ReturnType), nil
+}
+
+// ListModules returns all stored modules
+func (e *PagedEngine) ListModules() []*StoredModule {
+	e.mu.RLock()
+	defer e.mu.RUnlock()
+	
+	mods := make([]*StoredModule, 0, len(e.modules))
+	for _, mod := range e.modules {
+		mods = append(mods, mod)
+	}
+	
+	return mods
+}
+
+// GetStoredModule returns a stored module by name
+func (e *PagedEngine) GetStoredModule(name string) (*StoredModule, error) {
+	e.mu.RLock()
+	defer e.mu.RUnlock()
+	
+	mod, exists := e.modules[name]
+	if !exists {
+		return nil, fmt.Errorf("module '%s' does not exist", name)
+	}
+	
+	return mod, nil
+}
+
+// loadModules loads all stored modules from disk
+func (e *PagedEngine) loadModules() error {
+	modDir := filepath.Join(e.dataDir, "modules")
+	
+	// Check if modules directory exists
+	if _, err := os.Stat(modDir); os.IsNotExist(err) {
+		return nil // No modules to load
+	}
+	
+	// Read all module files
+	files, err := os.ReadDir(modDir)
+	if err != nil {
+		return fmt.Errorf("failed to read modules directory: %w", err)
+	}
+	
+	// Load each module, leaving out any that cannot be loaded
+	for _, file := range files {
+		if file.IsDir() || !strings.HasSuffix(file.Name(), ".json") {
+			continue
 		}
 		
-		// Store in memory
-		e.procedures[proc.Name] = &proc
+		if err := e.loadModule(filepath.Join(modDir, file.Name())); err != nil {
+			fmt.Printf("Warning: failed to load module %s: %v\n", file.Name(), err)
+		}
+	}
+	
+	return nil
+}
+
+// loadModule loads and compiles one module, verifying its bytecode against
+// the stored digest
+func (e *PagedEngine) loadModule(filename string) error {
+	data, err := os.ReadFile(filename)
+	if err != nil {
+		return err
+	}
+	
+	var mod StoredModule
+	if err := json.Unmarshal(data, &mod); err != nil {
+		return err
+	}
+	
+	// Modules saved with inline bytecode are rewritten to point at the blob
+	// it was moved into
+	code, migrate, err := loadBytecode(e.dataDir, "module", mod.Name, data, &mod.Digest)
+	if err != nil {
+		return err
+	}
+	mod.Code = code
+	
+	// Compile WASM module
+	if err := e.wasmEngine.CompileModule(moduleCacheKey(mod.Name), mod.Code); err != nil {
+		return fmt.Errorf("failed to compile: %w", err)
+	}
+	
+	// Calls to the functions are audited under the module's digest
+	for _, fn := range mod.Functions {
+		fn.Digest = mod.Digest
+	}
+	
+	if migrate {
+		data, err := json.Marshal(&mod)
+		if err != nil {
+			return err
+		}
+		if err := replaceFile(filename, data); err != nil {
+			return err
+		}
 	}
 	
+	// Store in memory
+	e.modules[mod.Name] = &mod
 	return nil
 }
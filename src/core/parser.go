@@ -2,7 +2,9 @@ package mindb
 
 import (
 	"fmt"
+	"math"
 	"regexp"
+	"strconv"
 	"strings"
 )
 
@@ -24,7 +26,14 @@ const (
 	RollbackTransaction
 	CreateProcedure
 	DropProcedure
+	AlterProcedure
 	CallProcedure
+	CreateModule
+	DropModule
+	CreateAggregate
+	DropAggregate
+	CreateTrigger
+	DropTrigger
 	DescribeTable
 	CreateUser
 	DropUser
@@ -52,7 +61,10 @@ type Statement struct {
 	Conditions  []Condition
 	Updates     map[string]interface{}
 	NewColumn   Column
+	Checks      []CheckConstraint // CHECK constraints of CREATE TABLE or ALTER TABLE ADD
+	Constraint  string            // Constraint named by ALTER TABLE DROP CONSTRAINT
 	OrderBy     string
+	OrderByCall *FunctionCall // ORDER BY procedure call; OrderBy holds its row key
 	OrderDesc   bool
 	GroupBy     string
 	Having      []Condition
@@ -63,13 +75,25 @@ type Statement struct {
 	Returning   []string
 	Joins       []JoinClause
 	Aggregates  []AggregateFunc
+	Functions   []FunctionCall // Procedure calls in the SELECT list
 	Subquery    *Statement
+	// Table-valued procedure in FROM; Table holds its call text
+	TableFunction *FunctionCall
 	// Stored procedure fields
 	ProcedureName string
 	ProcedureCode []byte
 	ProcedureLang string
 	ProcedureArgs []interface{}
+	OrReplace     bool // CREATE OR REPLACE PROCEDURE
+	Version       int  // Version for ALTER PROCEDURE ... ROLLBACK TO VERSION
 	ReturnType    string
+	OnNull        string // OnNullStrict or OnNullCalled, from CREATE PROCEDURE
+	Security      string // SecurityDefiner or SecurityInvoker, from CREATE PROCEDURE
+	ModuleName    string // Module for CREATE/DROP MODULE and CALL module.function
+	// Trigger fields; the handler is ProcedureName (in ModuleName, if set)
+	TriggerName   string
+	TriggerTiming string   // BEFORE or AFTER
+	TriggerEvents []string // INSERT, UPDATE and/or DELETE
 	// User management fields
 	Username    string
 	Password    string
@@ -103,6 +127,7 @@ type Condition struct {
 	Column   string
 	Operator string
 	Value    interface{}
+	Call     *FunctionCall // Procedure call compared instead of a column
 }
 
 // Parser parses SQL statements
@@ -195,10 +220,24 @@ func (p *Parser) Parse(sql string) (*Statement, error) {
 		return p.parseUpdate(sql)
 	case strings.HasPrefix(sqlUpper, "DELETE FROM"):
 		return p.parseDelete(sql)
-	case strings.HasPrefix(sqlUpper, "CREATE PROCEDURE"):
+	case strings.HasPrefix(sqlUpper, "CREATE PROCEDURE"), strings.HasPrefix(sqlUpper, "CREATE OR REPLACE PROCEDURE"):
 		return p.parseCreateProcedure(sql)
 	case strings.HasPrefix(sqlUpper, "DROP PROCEDURE"):
 		return p.parseDropProcedure(sql)
+	case strings.HasPrefix(sqlUpper, "ALTER PROCEDURE"):
+		return p.parseAlterProcedure(sql)
+	case strings.HasPrefix(sqlUpper, "CREATE MODULE"):
+		return p.parseCreateModule(sql)
+	case strings.HasPrefix(sqlUpper, "DROP MODULE"):
+		return p.parseDropModule(sql)
+	case strings.HasPrefix(sqlUpper, "CREATE AGGREGATE"):
+		return p.parseCreateAggregate(sql)
+	case strings.HasPrefix(sqlUpper, "DROP AGGREGATE"):
+		return p.parseDropAggregate(sql)
+	case strings.HasPrefix(sqlUpper, "CREATE TRIGGER"):
+		return p.parseCreateTrigger(sql)
+	case strings.HasPrefix(sqlUpper, "DROP TRIGGER"):
+		return p.parseDropTrigger(sql)
 	case strings.HasPrefix(sqlUpper, "CALL"):
 		return p.parseCallProcedure(sql)
 	case strings.HasPrefix(sqlUpper, "DESCRIBE"), strings.HasPrefix(sqlUpper, "DESC "):
@@ -282,7 +321,7 @@ func (p *Parser) parseCreateTable(sql string) (*Statement, error) {
 	columnsStr := matches[3]
 	ifNotExists := strings.Contains(strings.ToUpper(sql), "IF NOT EXISTS")
 
-	columns, err := p.parseColumnDefinitions(columnsStr)
+	columns, checks, err := p.parseTableElements(columnsStr)
 	if err != nil {
 		return nil, err
 	}
@@ -292,12 +331,123 @@ func (p *Parser) parseCreateTable(sql string) (*Statement, error) {
 		Schema:      schema,
 		Table:       tableName,
 		Columns:     columns,
+		Checks:      checks,
 		IfNotExists: ifNotExists,
 	}, nil
 }
 
+// parseTableElements parses the column definitions and CHECK constraints of
+// CREATE TABLE. A CHECK may follow a column definition or stand alone as a
+// table constraint, either optionally named with CONSTRAINT name.
+func (p *Parser) parseTableElements(elementsStr string) ([]Column, []CheckConstraint, error) {
+	var columns []Column
+	var checks []CheckConstraint
+
+	for _, def := range p.splitColumnDefinitions(elementsStr) {
+		def, check, err := p.extractCheck(def)
+		if err != nil {
+			return nil, nil, err
+		}
+
+		// Table constraint
+		if strings.TrimSpace(def) == "" && check != nil {
+			checks = append(checks, *check)
+			continue
+		}
+
+		cols, err := p.parseColumnDefinitions(def)
+		if err != nil {
+			return nil, nil, err
+		}
+		if check != nil {
+			check.Column = cols[0].Name
+			checks = append(checks, *check)
+		}
+		columns = append(columns, cols...)
+	}
+
+	return columns, checks, nil
+}
+
+// extractCheck removes a [CONSTRAINT name] CHECK (expr) clause from a
+// definition, returning the rest of the definition and the constraint, or
+// nil if there is none
+func (p *Parser) extractCheck(def string) (string, *CheckConstraint, error) {
+	re := regexp.MustCompile(`(?i)(?:\bCONSTRAINT\s+(\w+)\s+)?\bCHECK\s*\(`)
+	loc := re.FindStringSubmatchIndex(def)
+	if loc == nil {
+		return def, nil, nil
+	}
+
+	// Find the closing parenthesis, skipping quoted strings
+	open := loc[1] - 1
+	depth := 0
+	quoteChar := byte(0)
+	end := -1
+	for i := open; i < len(def) && end < 0; i++ {
+		ch := def[i]
+		switch {
+		case quoteChar != 0:
+			if ch == quoteChar {
+				quoteChar = 0
+			}
+		case ch == '\'' || ch == '"':
+			quoteChar = ch
+		case ch == '(':
+			depth++
+		case ch == ')':
+			depth--
+			if depth == 0 {
+				end = i
+			}
+		}
+	}
+	if end < 0 {
+		return "", nil, fmt.Errorf("unbalanced parentheses in CHECK constraint")
+	}
+
+	check := &CheckConstraint{Expr: strings.TrimSpace(def[open+1 : end])}
+	if loc[2] >= 0 {
+		check.Name = def[loc[2]:loc[3]]
+	}
+	if err := check.compile(); err != nil {
+		return "", nil, err
+	}
+
+	return def[:loc[0]] + def[end+1:], check, nil
+}
+
 // parseAlterTable parses ALTER TABLE statement
 func (p *Parser) parseAlterTable(sql string) (*Statement, error) {
+	// Constraints: ADD [CONSTRAINT name] CHECK (expr) and DROP CONSTRAINT name
+	constraintRe := regexp.MustCompile(`(?is)ALTER\s+TABLE\s+(?:IF\s+EXISTS\s+)?(?:(\w+)\.)?(\w+)\s+(ADD\s+(?:CONSTRAINT\s+\w+\s+)?CHECK\b.*|DROP\s+CONSTRAINT\s+(\w+))\s*;?\s*$`)
+	if matches := constraintRe.FindStringSubmatch(sql); matches != nil {
+		stmt := &Statement{
+			Type:       AlterTable,
+			Schema:     matches[1],
+			Table:      matches[2],
+			IfExists:   regexp.MustCompile(`(?i)^ALTER\s+TABLE\s+IF\s+EXISTS\b`).MatchString(strings.TrimSpace(sql)),
+			Constraint: matches[4],
+		}
+		if stmt.Constraint == "" {
+			rest, check, err := p.extractCheck(matches[3][3:])
+			if err != nil {
+				return nil, err
+			}
+			if check == nil || strings.Trim(rest, " \t\n;") != "" {
+				return nil, fmt.Errorf("invalid ALTER TABLE syntax")
+			}
+			stmt.Checks = []CheckConstraint{*check}
+		}
+		return stmt, nil
+	}
+
+	// A new column may carry a CHECK constraint
+	sql, check, err := p.extractCheck(sql)
+	if err != nil {
+		return nil, err
+	}
+
 	// Support schema-qualified names and IF EXISTS
 	re := regexp.MustCompile(`(?i)ALTER\s+TABLE\s+(?:IF\s+EXISTS\s+)?(?:(\w+)\.)?(\w+)\s+ADD\s+(?:COLUMN\s+)?(\w+)\s+(\w+(?:\(\d+\))?)`)
 	matches := re.FindStringSubmatch(sql)
@@ -309,7 +459,7 @@ func (p *Parser) parseAlterTable(sql string) (*Statement, error) {
 	tableName := matches[2]
 	ifExists := strings.Contains(strings.ToUpper(sql), "IF EXISTS")
 
-	return &Statement{
+	stmt := &Statement{
 		Type:     AlterTable,
 		Schema:   schema,
 		Table:    tableName,
@@ -318,7 +468,13 @@ func (p *Parser) parseAlterTable(sql string) (*Statement, error) {
 			Name:     matches[3],
 			DataType: matches[4],
 		},
-	}, nil
+	}
+	if check != nil {
+		check.Column = stmt.NewColumn.Name
+		stmt.Checks = []CheckConstraint{*check}
+	}
+
+	return stmt, nil
 }
 
 // parseDropTable parses DROP TABLE statement
@@ -360,6 +516,16 @@ func (p *Parser) parseSelect(sql string) (*Statement, error) {
 	stmt.Schema = fromMatches[1]
 	stmt.Table = fromMatches[2]
 
+	// A table-valued procedure call in FROM supplies the rows instead
+	tableFuncRe := regexp.MustCompile(`(?i)FROM\s+((?:\w+\.)?\w+\s*\((?:[^()']|'[^']*')*\))`)
+	if matches := tableFuncRe.FindStringSubmatch(sql); len(matches) == 2 {
+		if call := p.parseFunctionCall(matches[1]); call != nil {
+			stmt.TableFunction = call
+			stmt.Schema = ""
+			stmt.Table = call.String()
+		}
+	}
+
 	// Extract columns
 	selectRe := regexp.MustCompile(`(?i)SELECT\s+(.*?)\s+FROM`)
 	selectMatches := selectRe.FindStringSubmatch(sql)
@@ -371,15 +537,35 @@ func (p *Parser) parseSelect(sql string) (*Statement, error) {
 	
 	// Check for aggregate functions
 	if p.hasAggregateFunctions(columnsStr) {
-		aggregates, err := p.parseAggregateFunctions(columnsStr)
-		if err != nil {
-			return nil, err
+		for _, col := range p.splitColumnDefinitions(columnsStr) {
+			col = strings.TrimSpace(col)
+			// Calls other than the built-in aggregates are user-defined
+			// aggregates, resolved at execution
+			if call := p.parseSelectFunction(col); call != nil && !isBuiltinAggregate(call.Name) && call.Module == "" {
+				stmt.Aggregates = append(stmt.Aggregates, AggregateFunc{
+					Type:  UserFunc,
+					Name:  call.Name,
+					Args:  call.Args,
+					Alias: call.Alias,
+				})
+				continue
+			}
+			aggregates, err := p.parseAggregateFunctions(col)
+			if err != nil {
+				return nil, err
+			}
+			stmt.Aggregates = append(stmt.Aggregates, aggregates...)
 		}
-		stmt.Aggregates = aggregates
 	} else if columnsStr != "*" {
-		columnNames := strings.Split(columnsStr, ",")
-		for _, col := range columnNames {
-			stmt.Columns = append(stmt.Columns, Column{Name: strings.TrimSpace(col)})
+		for _, col := range p.splitColumnDefinitions(columnsStr) {
+			col = strings.TrimSpace(col)
+			// Procedure calls become computed columns named by their alias
+			if call := p.parseSelectFunction(col); call != nil {
+				stmt.Functions = append(stmt.Functions, *call)
+				stmt.Columns = append(stmt.Columns, Column{Name: call.Alias})
+				continue
+			}
+			stmt.Columns = append(stmt.Columns, Column{Name: col})
 		}
 	}
 
@@ -394,11 +580,15 @@ func (p *Parser) parseSelect(sql string) (*Statement, error) {
 		stmt.Conditions = conditions
 	}
 
-	// Extract ORDER BY clause
-	orderRe := regexp.MustCompile(`(?i)ORDER\s+BY\s+(\w+)(?:\s+(ASC|DESC))?`)
+	// Extract ORDER BY clause (a column or a procedure call)
+	orderRe := regexp.MustCompile(`(?i)ORDER\s+BY\s+((?:\w+\.)?\w+\s*\([^)]*\)|\w+)(?:\s+(ASC|DESC))?`)
 	orderMatches := orderRe.FindStringSubmatch(sql)
 	if len(orderMatches) >= 2 {
 		stmt.OrderBy = orderMatches[1]
+		if call := p.parseFunctionCall(orderMatches[1]); call != nil {
+			stmt.OrderByCall = call
+			stmt.OrderBy = call.String()
+		}
 		if len(orderMatches) >= 3 && strings.ToUpper(orderMatches[2]) == "DESC" {
 			stmt.OrderDesc = true
 		}
@@ -804,22 +994,113 @@ func (p *Parser) splitColumnDefinitions(columnsStr string) []string {
 func (p *Parser) parseConditions(condStr string) ([]Condition, error) {
 	var conditions []Condition
 
-	// Simple condition parsing (supports single condition for now)
-	re := regexp.MustCompile(`(\w+)\s*(=|!=|>|<|>=|<=)\s*(.+)`)
+	// Simple condition parsing (supports single condition for now). The left
+	// side is a column or a procedure call such as calculate_tax(amount, 2).
+	re := regexp.MustCompile(`((?:\w+\.)?\w+\s*\(.*\)|\w+)\s*(>=|<=|!=|=|>|<)\s*(.+)`)
 	matches := re.FindStringSubmatch(strings.TrimSpace(condStr))
 	if len(matches) < 4 {
 		return nil, fmt.Errorf("invalid WHERE condition")
 	}
 
-	conditions = append(conditions, Condition{
+	cond := Condition{
 		Column:   matches[1],
 		Operator: matches[2],
 		Value:    p.parseValue(strings.TrimSpace(matches[3])),
-	})
+	}
+	if call := p.parseFunctionCall(matches[1]); call != nil {
+		cond.Call = call
+		cond.Column = call.String()
+	}
+	conditions = append(conditions, cond)
 
 	return conditions, nil
 }
 
+// parseSelectFunction parses a SELECT-list procedure call with an optional
+// AS alias. The alias defaults to the call as written, so two calls of the
+// same function get distinct columns. Returns nil if expr is not a call.
+func (p *Parser) parseSelectFunction(expr string) *FunctionCall {
+	alias := ""
+	aliasRe := regexp.MustCompile(`(?is)^(.*\))\s+AS\s+(\w+)$`)
+	if matches := aliasRe.FindStringSubmatch(expr); len(matches) == 3 {
+		expr = matches[1]
+		alias = matches[2]
+	}
+
+	call := p.parseFunctionCall(expr)
+	if call == nil {
+		return nil
+	}
+	call.Alias = alias
+	if call.Alias == "" {
+		call.Alias = call.String()
+	}
+	return call
+}
+
+// parseFunctionCall parses name(args) or module.name(args). Arguments are
+// column names or literals. Returns nil if expr is not a call.
+func (p *Parser) parseFunctionCall(expr string) *FunctionCall {
+	callRe := regexp.MustCompile(`(?s)^(?:(\w+)\.)?(\w+)\s*\((.*)\)$`)
+	matches := callRe.FindStringSubmatch(strings.TrimSpace(expr))
+	if len(matches) < 4 {
+		return nil
+	}
+
+	call := &FunctionCall{Module: matches[1], Name: matches[2]}
+	if strings.TrimSpace(matches[3]) == "" {
+		return call
+	}
+
+	identRe := regexp.MustCompile(`^[A-Za-z_]\w*$`)
+	for _, arg := range p.splitArguments(matches[3]) {
+		arg = strings.TrimSpace(arg)
+		switch {
+		case strings.EqualFold(arg, "NULL"):
+			call.Args = append(call.Args, FunctionArg{Value: nil})
+		case strings.EqualFold(arg, "TRUE"), strings.EqualFold(arg, "FALSE"):
+			call.Args = append(call.Args, FunctionArg{Value: strings.EqualFold(arg, "TRUE")})
+		case identRe.MatchString(arg):
+			call.Args = append(call.Args, FunctionArg{Column: arg})
+		default:
+			call.Args = append(call.Args, FunctionArg{Value: p.parseValue(arg)})
+		}
+	}
+	return call
+}
+
+// splitArguments splits a comma-separated argument list, ignoring commas
+// inside quotes and parentheses
+func (p *Parser) splitArguments(argsStr string) []string {
+	var result []string
+	var current strings.Builder
+	parenDepth := 0
+	quoteChar := rune(0)
+
+	for _, ch := range argsStr {
+		switch {
+		case quoteChar != 0:
+			if ch == quoteChar {
+				quoteChar = 0
+			}
+		case ch == '\'' || ch == '"':
+			quoteChar = ch
+		case ch == '(':
+			parenDepth++
+		case ch == ')':
+			parenDepth--
+		case ch == ',' && parenDepth == 0:
+			result = append(result, current.String())
+			current.Reset()
+			continue
+		}
+		current.WriteRune(ch)
+	}
+	result = append(result, current.String())
+
+	return result
+}
+
 // parseValues parses a comma-separated list of values
 func (p *Parser) parseValues(valuesStr string) ([]interface{}, error) {
 	var values []interface{}
@@ -871,14 +1152,13 @@ func (p *Parser) parseValue(val string) interface{} {
 		return val[1 : len(val)-1]
 	}
 
-	// Try to parse as number
-	var num int
-	if _, err := fmt.Sscanf(val, "%d", &num); err == nil {
+	// Try to parse as number. The whole literal must parse, so 0.08 is a
+	// float rather than the integer prefix 0.
+	if num, err := strconv.Atoi(val); err == nil {
 		return num
 	}
 
-	var fnum float64
-	if _, err := fmt.Sscanf(val, "%f", &fnum); err == nil {
+	if fnum, err := strconv.ParseFloat(val, 64); err == nil && !math.IsNaN(fnum) && !math.IsInf(fnum, 0) {
 		return fnum
 	}
 
@@ -904,9 +1184,14 @@ func (p *Parser) parseSelectWithJoin(sql string) (*Statement, error) {
 	if len(selectMatches) >= 2 {
 		columnsStr := strings.TrimSpace(selectMatches[1])
 		if columnsStr != "*" {
-			columnNames := strings.Split(columnsStr, ",")
-			for _, col := range columnNames {
-				stmt.Columns = append(stmt.Columns, Column{Name: strings.TrimSpace(col)})
+			for _, col := range p.splitColumnDefinitions(columnsStr) {
+				col = strings.TrimSpace(col)
+				if call := p.parseSelectFunction(col); call != nil {
+					stmt.Functions = append(stmt.Functions, *call)
+					stmt.Columns = append(stmt.Columns, Column{Name: call.Alias})
+					continue
+				}
+				stmt.Columns = append(stmt.Columns, Column{Name: col})
 			}
 		}
 	}
@@ -958,17 +1243,21 @@ func (p *Parser) parseSelectWithJoin(sql string) (*Statement, error) {
 		stmt.Conditions = conditions
 	}
 
+	// Extract GROUP BY clause
+	groupRe := regexp.MustCompile(`(?i)GROUP\s+BY\s+(\w+)`)
+	if groupMatches := groupRe.FindStringSubmatch(sql); len(groupMatches) >= 2 {
+		stmt.GroupBy = groupMatches[1]
+	}
+
 	return stmt, nil
 }
 
 // hasAggregateFunctions checks if a column string contains aggregate functions
 func (p *Parser) hasAggregateFunctions(columnsStr string) bool {
-	upper := strings.ToUpper(columnsStr)
-	return strings.Contains(upper, "COUNT(") ||
-		strings.Contains(upper, "SUM(") ||
-		strings.Contains(upper, "AVG(") ||
-		strings.Contains(upper, "MIN(") ||
-		strings.Contains(upper, "MAX(")
+	// Match whole names only, so procedures such as checksum() are not
+	// mistaken for aggregates
+	aggRe := regexp.MustCompile(`(?i)\b(COUNT|SUM|AVG|MIN|MAX)\s*\(`)
+	return aggRe.MatchString(columnsStr)
 }
 
 // parseAggregateFunctions parses aggregate functions from column string
@@ -1014,24 +1303,42 @@ func (p *Parser) parseAggregateFunctions(columnsStr string) ([]AggregateFunc, er
 
 // parseCreateProcedure parses CREATE PROCEDURE statement
 func (p *Parser) parseCreateProcedure(sql string) (*Statement, error) {
-	// Syntax: CREATE PROCEDURE name(params) RETURNS type LANGUAGE lang AS 'base64_code'
-	re := regexp.MustCompile(`(?i)CREATE\s+PROCEDURE\s+(\w+)\s*\((.*?)\)\s+RETURNS\s+(\w+)\s+LANGUAGE\s+(\w+)\s+AS\s+'([^']+)'`)
+	// Syntax: CREATE [OR REPLACE] PROCEDURE name(params) RETURNS type
+	//   [STRICT | RETURNS NULL ON NULL INPUT | CALLED ON NULL INPUT]
+	//   [SECURITY DEFINER | SECURITY INVOKER] LANGUAGE lang AS 'base64_code'
+	re := regexp.MustCompile(`(?i)CREATE\s+(OR\s+REPLACE\s+)?PROCEDURE\s+(\w+)\s*\((.*?)\)\s+RETURNS\s+(\w+)\s+((?:(?:STRICT|RETURNS\s+NULL\s+ON\s+NULL\s+INPUT|CALLED\s+ON\s+NULL\s+INPUT|SECURITY\s+DEFINER|SECURITY\s+INVOKER)\s+)*)LANGUAGE\s+(\w+)\s+AS\s+'([^']+)'`)
 	matches := re.FindStringSubmatch(sql)
 	
-	if len(matches) < 6 {
+	if len(matches) < 8 {
 		return nil, fmt.Errorf("invalid CREATE PROCEDURE syntax")
 	}
 	
 	stmt := &Statement{
 		Type:          CreateProcedure,
-		ProcedureName: matches[1],
-		ReturnType:    matches[3],
-		ProcedureLang: matches[4],
+		OrReplace:     matches[1] != "",
+		ProcedureName: matches[2],
+		ReturnType:    matches[4],
+		ProcedureLang: matches[6],
+	}
+	
+	// RETURNS NULL ON NULL INPUT is the long form of STRICT
+	options := strings.ToUpper(strings.Join(strings.Fields(matches[5]), " "))
+	switch {
+	case strings.Contains(options, "CALLED ON NULL INPUT"):
+		stmt.OnNull = OnNullCalled
+	case strings.Contains(options, "STRICT"), strings.Contains(options, "RETURNS NULL ON NULL INPUT"):
+		stmt.OnNull = OnNullStrict
+	}
+	switch {
+	case strings.Contains(options, "SECURITY DEFINER"):
+		stmt.Security = SecurityDefiner
+	case strings.Contains(options, "SECURITY INVOKER"):
+		stmt.Security = SecurityInvoker
 	}
 	
 	// Parse parameters
-	if matches[2] != "" {
-		params, err := p.parseColumnDefinitions(matches[2])
+	if matches[3] != "" {
+		params, err := p.parseColumnDefinitions(matches[3])
 		if err != nil {
 			return nil, fmt.Errorf("failed to parse parameters: %w", err)
 		}
@@ -1039,11 +1346,31 @@ func (p *Parser) parseCreateProcedure(sql string) (*Statement, error) {
 	}
 	
 	// Decode base64 WASM code
-	stmt.ProcedureCode = []byte(matches[5]) // Store as-is, will decode in engine
+	stmt.ProcedureCode = []byte(matches[7]) // Store as-is, will decode in engine
 	
 	return stmt, nil
 }
 
+// parseAlterProcedure parses ALTER PROCEDURE name ROLLBACK TO VERSION n
+func (p *Parser) parseAlterProcedure(sql string) (*Statement, error) {
+	re := regexp.MustCompile(`(?i)^ALTER\s+PROCEDURE\s+(\w+)\s+ROLLBACK\s+TO\s+VERSION\s+(\d+)\s*;?$`)
+	matches := re.FindStringSubmatch(strings.TrimSpace(sql))
+	if matches == nil {
+		return nil, fmt.Errorf("invalid ALTER PROCEDURE syntax")
+	}
+
+	version, err := strconv.Atoi(matches[2])
+	if err != nil || version < 1 {
+		return nil, fmt.Errorf("invalid procedure version: %s", matches[2])
+	}
+
+	return &Statement{
+		Type:          AlterProcedure,
+		ProcedureName: matches[1],
+		Version:       version,
+	}, nil
+}
+
 // parseDropProcedure parses DROP PROCEDURE statement
 func (p *Parser) parseDropProcedure(sql string) (*Statement, error) {
 	re := regexp.MustCompile(`(?i)DROP\s+PROCEDURE\s+(?:IF\s+EXISTS\s+)?(\w+)`)
@@ -1069,10 +1396,11 @@ func (p *Parser) parseDropProcedure(sql string) (*Statement, error) {
 // parseCallProcedure parses CALL statement
 func (p *Parser) parseCallProcedure(sql string) (*Statement, error) {
 	// Syntax: CALL procedure_name(arg1, arg2, ...)
-	re := regexp.MustCompile(`(?i)CALL\s+(\w+)\s*\((.*?)\)`)
+	//         CALL module_name.function_name(arg1, arg2, ...)
+	re := regexp.MustCompile(`(?i)CALL\s+(\w+)(?:\.(\w+))?\s*\((.*?)\)`)
 	matches := re.FindStringSubmatch(sql)
 	
-	if len(matches) < 2 {
+	if len(matches) < 4 {
 		return nil, fmt.Errorf("invalid CALL syntax")
 	}
 	
@@ -1080,14 +1408,186 @@ func (p *Parser) parseCallProcedure(sql string) (*Statement, error) {
 		Type:          CallProcedure,
 		ProcedureName: matches[1],
 	}
+	if matches[2] != "" {
+		stmt.ModuleName = matches[1]
+		stmt.ProcedureName = matches[2]
+	}
 	
-	// Parse arguments
-	if len(matches) >= 3 && matches[2] != "" {
-		args, err := p.parseValues(matches[2])
-		if err != nil {
-			return nil, fmt.Errorf("failed to parse arguments: %w", err)
+	// Parse arguments; NULL is passed as nil rather than the string "NULL"
+	if strings.TrimSpace(matches[3]) != "" {
+		for _, arg := range p.splitArguments(matches[3]) {
+			arg = strings.TrimSpace(arg)
+			if strings.EqualFold(arg, "NULL") {
+				stmt.ProcedureArgs = append(stmt.ProcedureArgs, nil)
+			} else {
+				stmt.ProcedureArgs = append(stmt.ProcedureArgs, p.parseValue(arg))
+			}
+		}
+	}
+	
+	return stmt, nil
+}
+
+// parseCreateModule parses CREATE MODULE statement
+func (p *Parser) parseCreateModule(sql string) (*Statement, error) {
+	// Syntax: CREATE MODULE name [LANGUAGE lang] AS 'base64_code'
+	re := regexp.MustCompile(`(?i)CREATE\s+MODULE\s+(\w+)(?:\s+LANGUAGE\s+(\w+))?\s+AS\s+'([^']+)'`)
+	matches := re.FindStringSubmatch(sql)
+	
+	if len(matches) < 4 {
+		return nil, fmt.Errorf("invalid CREATE MODULE syntax")
+	}
+	
+	stmt := &Statement{
+		Type:          CreateModule,
+		ModuleName:    matches[1],
+		ProcedureLang: matches[2],
+		ProcedureCode: []byte(matches[3]), // Store as-is, will decode in engine
+	}
+	if stmt.ProcedureLang == "" {
+		stmt.ProcedureLang = "wasm"
+	}
+	
+	return stmt, nil
+}
+
+// parseDropModule parses DROP MODULE statement
+func (p *Parser) parseDropModule(sql string) (*Statement, error) {
+	re := regexp.MustCompile(`(?i)DROP\s+MODULE\s+(?:IF\s+EXISTS\s+)?(\w+)`)
+	matches := re.FindStringSubmatch(sql)
+	
+	if len(matches) < 2 {
+		return nil, fmt.Errorf("invalid DROP MODULE syntax")
+	}
+	
+	stmt := &Statement{
+		Type:       DropModule,
+		ModuleName: matches[1],
+	}
+	
+	// Check for IF EXISTS
+	if strings.Contains(strings.ToUpper(sql), "IF EXISTS") {
+		stmt.IfExists = true
+	}
+	
+	return stmt, nil
+}
+
+// parseCreateAggregate parses CREATE AGGREGATE statement
+func (p *Parser) parseCreateAggregate(sql string) (*Statement, error) {
+	// Syntax: CREATE AGGREGATE name[([arg] type, ...)] [RETURNS type] LANGUAGE lang AS 'base64_code'
+	re := regexp.MustCompile(`(?i)CREATE\s+AGGREGATE\s+(\w+)(?:\s*\((.*?)\))?(?:\s+RETURNS\s+(\w+))?\s+LANGUAGE\s+(\w+)\s+AS\s+'([^']+)'`)
+	matches := re.FindStringSubmatch(sql)
+	
+	if len(matches) < 6 {
+		return nil, fmt.Errorf("invalid CREATE AGGREGATE syntax")
+	}
+	
+	stmt := &Statement{
+		Type:          CreateAggregate,
+		ProcedureName: matches[1],
+		ReturnType:    matches[3],
+		ProcedureLang: matches[4],
+		ProcedureCode: []byte(matches[5]), // Store as-is, will decode in engine
+	}
+	
+	// Parse argument types; names are optional
+	if strings.TrimSpace(matches[2]) != "" {
+		for i, arg := range strings.Split(matches[2], ",") {
+			fields := strings.Fields(arg)
+			switch len(fields) {
+			case 1:
+				stmt.Columns = append(stmt.Columns, Column{Name: fmt.Sprintf("arg%d", i+1), DataType: strings.ToUpper(fields[0])})
+			case 2:
+				stmt.Columns = append(stmt.Columns, Column{Name: fields[0], DataType: strings.ToUpper(fields[1])})
+			default:
+				return nil, fmt.Errorf("invalid aggregate argument: %s", strings.TrimSpace(arg))
+			}
+		}
+	}
+	
+	return stmt, nil
+}
+
+// parseCreateTrigger parses CREATE TRIGGER statement
+func (p *Parser) parseCreateTrigger(sql string) (*Statement, error) {
+	// Syntax: CREATE TRIGGER name BEFORE|AFTER event [OR event ...] ON table
+	//         [FOR EACH ROW] EXECUTE PROCEDURE [module.]handler[()]
+	re := regexp.MustCompile(`(?i)CREATE\s+TRIGGER\s+(\w+)\s+(BEFORE|AFTER)\s+(\w+(?:\s+OR\s+\w+)*)\s+ON\s+(\w+)(?:\s+FOR\s+EACH\s+ROW)?\s+EXECUTE\s+(?:PROCEDURE|FUNCTION)\s+(?:(\w+)\.)?(\w+)(?:\s*\(\s*\))?\s*;?\s*$`)
+	matches := re.FindStringSubmatch(strings.TrimSpace(sql))
+	
+	if len(matches) < 7 {
+		return nil, fmt.Errorf("invalid CREATE TRIGGER syntax")
+	}
+	
+	stmt := &Statement{
+		Type:          CreateTrigger,
+		TriggerName:   matches[1],
+		TriggerTiming: strings.ToUpper(matches[2]),
+		Table:         matches[4],
+		ModuleName:    matches[5],
+		ProcedureName: matches[6],
+	}
+	
+	// Parse events, e.g. INSERT OR UPDATE
+	for _, event := range regexp.MustCompile(`(?i)\s+OR\s+`).Split(matches[3], -1) {
+		event = strings.ToUpper(event)
+		switch event {
+		case "INSERT", "UPDATE", "DELETE":
+		default:
+			return nil, fmt.Errorf("invalid trigger event: %s", event)
 		}
-		stmt.ProcedureArgs = args
+		for _, seen := range stmt.TriggerEvents {
+			if seen == event {
+				return nil, fmt.Errorf("duplicate trigger event: %s", event)
+			}
+		}
+		stmt.TriggerEvents = append(stmt.TriggerEvents, event)
+	}
+	
+	return stmt, nil
+}
+
+// parseDropTrigger parses DROP TRIGGER statement
+func (p *Parser) parseDropTrigger(sql string) (*Statement, error) {
+	re := regexp.MustCompile(`(?i)DROP\s+TRIGGER\s+(?:IF\s+EXISTS\s+)?(\w+)(?:\s+ON\s+(\w+))?`)
+	matches := re.FindStringSubmatch(sql)
+	
+	if len(matches) < 3 {
+		return nil, fmt.Errorf("invalid DROP TRIGGER syntax")
+	}
+	
+	stmt := &Statement{
+		Type:        DropTrigger,
+		TriggerName: matches[1],
+		Table:       matches[2],
+	}
+	
+	// Check for IF EXISTS
+	if strings.Contains(strings.ToUpper(sql), "IF EXISTS") {
+		stmt.IfExists = true
+	}
+	
+	return stmt, nil
+}
+
+// parseDropAggregate parses DROP AGGREGATE statement
+func (p *Parser) parseDropAggregate(sql string) (*Statement, error) {
+	re := regexp.MustCompile(`(?i)DROP\s+AGGREGATE\s+(?:IF\s+EXISTS\s+)?(\w+)`)
+	matches := re.FindStringSubmatch(sql)
+	
+	if len(matches) < 2 {
+		return nil, fmt.Errorf("invalid DROP AGGREGATE syntax")
+	}
+	
+	stmt := &Statement{
+		Type:          DropAggregate,
+		ProcedureName: matches[1],
+	}
+	
+	// Check for IF EXISTS
+	if strings.Contains(strings.ToUpper(sql), "IF EXISTS") {
+		stmt.IfExists = true
 	}
 	
 	return stmt, nil
@@ -1130,7 +1630,13 @@ func (p *Parser) parseDropUser(sql string) (*Statement, error) {
 
 // parseGrant parses GRANT statement
 // Syntax: GRANT privileges ON database.table TO 'username'@'host';
+//   or: GRANT EXECUTE ON PROCEDURE name TO 'username'@'host' | role;
 func (p *Parser) parseGrant(sql string) (*Statement, error) {
+	if stmt := parseProcedureGrant(sql, "GRANT", "TO"); stmt != nil {
+		stmt.Type = GrantPrivileges
+		return stmt, nil
+	}
+	
 	re := regexp.MustCompile(`(?i)GRANT\s+([\w\s,]+)\s+ON\s+([^.]+)\.([^\s]+)\s+TO\s+'([^']+)'@'([^']+)'`)
 	matches := re.FindStringSubmatch(sql)
 	
@@ -1162,7 +1668,13 @@ func (p *Parser) parseGrant(sql string) (*Statement, error) {
 
 // parseRevoke parses REVOKE statement
 // Syntax: REVOKE privileges ON database.table FROM 'username'@'host';
+//   or: REVOKE EXECUTE ON PROCEDURE name FROM 'username'@'host' | role;
 func (p *Parser) parseRevoke(sql string) (*Statement, error) {
+	if stmt := parseProcedureGrant(sql, "REVOKE", "FROM"); stmt != nil {
+		stmt.Type = RevokePrivileges
+		return stmt, nil
+	}
+	
 	re := regexp.MustCompile(`(?i)REVOKE\s+([\w\s,]+)\s+ON\s+([^.]+)\.([^\s]+)\s+FROM\s+'([^']+)'@'([^']+)'`)
 	matches := re.FindStringSubmatch(sql)
 	
@@ -1192,6 +1704,27 @@ func (p *Parser) parseRevoke(sql string) (*Statement, error) {
 	}, nil
 }
 
+// parseProcedureGrant parses the EXECUTE ON PROCEDURE form of GRANT (verb
+// "GRANT", preposition "TO") or REVOKE, returning nil for any other form.
+// The procedure may be a module, or a module function written module.function;
+// the grantee is a user, or a role given by name.
+func parseProcedureGrant(sql, verb, preposition string) *Statement {
+	re := regexp.MustCompile(`(?i)^` + verb + `\s+(?:EXECUTE|ALL(?:\s+PRIVILEGES)?)\s+ON\s+PROCEDURE\s+(\w+(?:\.\w+)?)\s+` +
+		preposition + `\s+(?:'([^']+)'@'([^']+)'|'([^']+)'|(\w+))\s*;?$`)
+	matches := re.FindStringSubmatch(strings.TrimSpace(sql))
+	if matches == nil {
+		return nil
+	}
+	
+	return &Statement{
+		ProcedureName: matches[1],
+		Privileges:    []string{string(PrivilegeExecute)},
+		Username:      matches[2],
+		Host:          matches[3],
+		RoleName:      matches[4] + matches[5],
+	}
+}
+
 // parseShowGrants parses SHOW GRANTS statement
 // Syntax: SHOW GRANTS FOR 'username'@'host';
 func (p *Parser) parseShowGrants(sql string) (*Statement, error) {
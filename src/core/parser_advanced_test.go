@@ -789,6 +789,36 @@ func TestParseStoredProcedures(t *testing.T) {
 			wantErr:  false,
 			wantType: CallProcedure,
 		},
+		{
+			name:     "CREATE MODULE",
+			sql:      "CREATE MODULE business_rules LANGUAGE rust AS 'AGFzbQEAAAA='",
+			wantErr:  false,
+			wantType: CreateModule,
+		},
+		{
+			name:     "DROP MODULE",
+			sql:      "DROP MODULE IF EXISTS business_rules",
+			wantErr:  false,
+			wantType: DropModule,
+		},
+		{
+			name:     "CREATE AGGREGATE",
+			sql:      "CREATE AGGREGATE weighted_avg(value FLOAT, weight FLOAT) RETURNS FLOAT LANGUAGE rust AS 'AGFzbQEAAAA='",
+			wantErr:  false,
+			wantType: CreateAggregate,
+		},
+		{
+			name:     "DROP AGGREGATE",
+			sql:      "DROP AGGREGATE IF EXISTS weighted_avg",
+			wantErr:  false,
+			wantType: DropAggregate,
+		},
+		{
+			name:     "CALL module function",
+			sql:      "CALL business_rules.calculate_tax(100.0, 2)",
+			wantErr:  false,
+			wantType: CallProcedure,
+		},
 	}
 
 	for _, tt := range tests {
@@ -808,6 +838,93 @@ func TestParseStoredProcedures(t *testing.T) {
 	}
 }
 
+// Test module statements keep the module and function names apart
+func TestParseModuleNames(t *testing.T) {
+	parser := NewParser()
+
+	stmt, err := parser.Parse("CREATE MODULE business_rules AS 'AGFzbQEAAAA='")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	if stmt.ModuleName != "business_rules" || stmt.ProcedureLang != "wasm" || string(stmt.ProcedureCode) != "AGFzbQEAAAA=" {
+		t.Errorf("Unexpected CREATE MODULE statement: %+v", stmt)
+	}
+
+	stmt, err = parser.Parse("CALL business_rules.calculate_tax(100.0, 2)")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	if stmt.ModuleName != "business_rules" || stmt.ProcedureName != "calculate_tax" || len(stmt.ProcedureArgs) != 2 {
+		t.Errorf("Unexpected CALL statement: %+v", stmt)
+	}
+
+	stmt, err = parser.Parse("CALL calculate_tax(100.0)")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	if stmt.ModuleName != "" || stmt.ProcedureName != "calculate_tax" {
+		t.Errorf("Unexpected CALL statement: %+v", stmt)
+	}
+}
+
+// Test numeric literals keep their full value and type
+func TestParseNumericArguments(t *testing.T) {
+	parser := NewParser()
+
+	stmt, err := parser.Parse("CALL calculate_tax(0.08, 100.0, 4111111111111111, -7, 1e3, 12abc)")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+
+	want := []interface{}{0.08, 100.0, 4111111111111111, -7, 1000.0, "12abc"}
+	if len(stmt.ProcedureArgs) != len(want) {
+		t.Fatalf("Expected %d args, got %d", len(want), len(stmt.ProcedureArgs))
+	}
+	for i, w := range want {
+		if stmt.ProcedureArgs[i] != w {
+			t.Errorf("Arg %d: expected %v (%T), got %v (%T)", i, w, w, stmt.ProcedureArgs[i], stmt.ProcedureArgs[i])
+		}
+	}
+}
+
+func TestParseSelectFunctionCalls(t *testing.T) {
+	parser := NewParser()
+
+	stmt, err := parser.Parse("SELECT id, rules.calculate_tax(amount, 0.08) AS tax, label(name, 'x, y', NULL) FROM orders WHERE checksum(name) >= 10 ORDER BY score(amount) DESC")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+
+	if len(stmt.Aggregates) != 0 {
+		t.Errorf("Expected no aggregates, got %+v", stmt.Aggregates)
+	}
+	if len(stmt.Columns) != 3 || stmt.Columns[1].Name != "tax" || stmt.Columns[2].Name != "label(name, 'x, y', NULL)" {
+		t.Errorf("Unexpected columns: %+v", stmt.Columns)
+	}
+	if len(stmt.Functions) != 2 {
+		t.Fatalf("Expected 2 function calls, got %d", len(stmt.Functions))
+	}
+
+	tax := stmt.Functions[0]
+	if tax.Module != "rules" || tax.Name != "calculate_tax" || len(tax.Args) != 2 ||
+		tax.Args[0].Column != "amount" || tax.Args[1].Value != 0.08 {
+		t.Errorf("Unexpected calculate_tax call: %+v", tax)
+	}
+	label := stmt.Functions[1]
+	if len(label.Args) != 3 || label.Args[1].Value != "x, y" || label.Args[2].Value != nil || label.Args[2].Column != "" {
+		t.Errorf("Unexpected label call: %+v", label)
+	}
+
+	if len(stmt.Conditions) != 1 || stmt.Conditions[0].Call == nil ||
+		stmt.Conditions[0].Call.Name != "checksum" || stmt.Conditions[0].Operator != ">=" || stmt.Conditions[0].Value != 10 {
+		t.Errorf("Unexpected WHERE condition: %+v", stmt.Conditions)
+	}
+
+	if stmt.OrderByCall == nil || stmt.OrderBy != "score(amount)" || !stmt.OrderDesc {
+		t.Errorf("Unexpected ORDER BY: %q %+v", stmt.OrderBy, stmt.OrderByCall)
+	}
+}
+
 // Test IF EXISTS / IF NOT EXISTS
 func TestParseIfExistsClause(t *testing.T) {
 	tests := []struct {
@@ -864,3 +981,297 @@ func TestParseIfExistsClause(t *testing.T) {
 		})
 	}
 }
+
+// Test user-defined aggregates in CREATE AGGREGATE and SELECT
+func TestParseAggregateStatements(t *testing.T) {
+	parser := NewParser()
+
+	stmt, err := parser.Parse("CREATE AGGREGATE weighted_avg(value FLOAT, INT) LANGUAGE rust AS 'AGFzbQEAAAA='")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	if stmt.ProcedureName != "weighted_avg" || stmt.ReturnType != "" || stmt.ProcedureLang != "rust" {
+		t.Errorf("Unexpected statement: %+v", stmt)
+	}
+	if len(stmt.Columns) != 2 || stmt.Columns[0].Name != "value" || stmt.Columns[0].DataType != "FLOAT" ||
+		stmt.Columns[1].Name != "arg2" || stmt.Columns[1].DataType != "INT" {
+		t.Errorf("Unexpected arguments: %+v", stmt.Columns)
+	}
+
+	stmt, err = parser.Parse("SELECT region, COUNT(*) AS n, weighted_avg(price, qty) AS avg_price FROM sales GROUP BY region")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	if len(stmt.Aggregates) != 2 {
+		t.Fatalf("Expected 2 aggregates, got %+v", stmt.Aggregates)
+	}
+	if stmt.Aggregates[0].Type != CountFunc || stmt.Aggregates[0].Alias != "n" {
+		t.Errorf("Unexpected COUNT aggregate: %+v", stmt.Aggregates[0])
+	}
+	user := stmt.Aggregates[1]
+	if user.Type != UserFunc || user.Name != "weighted_avg" || user.Alias != "avg_price" ||
+		len(user.Args) != 2 || user.Args[0].Column != "price" || user.Args[1].Column != "qty" {
+		t.Errorf("Unexpected user-defined aggregate: %+v", user)
+	}
+	if stmt.GroupBy != "region" {
+		t.Errorf("Expected GROUP BY region, got %q", stmt.GroupBy)
+	}
+}
+
+func TestParseTableFunction(t *testing.T) {
+	parser := NewParser()
+
+	stmt, err := parser.Parse("SELECT month, payment FROM finance.amortization(250000, 0.045, 'monthly (fixed)') WHERE month <= 12 ORDER BY month LIMIT 6")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	call := stmt.TableFunction
+	if call == nil {
+		t.Fatal("Expected a table function")
+	}
+	if call.Module != "finance" || call.Name != "amortization" || len(call.Args) != 3 ||
+		call.Args[0].Value != 250000 || call.Args[1].Value != 0.045 || call.Args[2].Value != "monthly (fixed)" {
+		t.Errorf("Unexpected call: %+v", call)
+	}
+	if stmt.Schema != "" || stmt.Table != call.String() {
+		t.Errorf("Unexpected table %q.%q", stmt.Schema, stmt.Table)
+	}
+	if len(stmt.Columns) != 2 || len(stmt.Conditions) != 1 || stmt.OrderBy != "month" || stmt.Limit != 6 {
+		t.Errorf("Unexpected clauses: %+v", stmt)
+	}
+
+	stmt, err = parser.Parse("SELECT * FROM orders WHERE total(amount) > 10")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	if stmt.TableFunction != nil || stmt.Table != "orders" {
+		t.Errorf("Expected a plain table, got %q %+v", stmt.Table, stmt.TableFunction)
+	}
+}
+
+func TestParseTriggerStatements(t *testing.T) {
+	parser := NewParser()
+
+	stmt, err := parser.Parse("CREATE TRIGGER check_card BEFORE INSERT OR update ON payments FOR EACH ROW EXECUTE PROCEDURE cards.validate_credit_card()")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	if stmt.Type != CreateTrigger || stmt.TriggerName != "check_card" || stmt.TriggerTiming != "BEFORE" || stmt.Table != "payments" {
+		t.Errorf("Unexpected statement: %+v", stmt)
+	}
+	if len(stmt.TriggerEvents) != 2 || stmt.TriggerEvents[0] != "INSERT" || stmt.TriggerEvents[1] != "UPDATE" {
+		t.Errorf("Expected INSERT and UPDATE, got %v", stmt.TriggerEvents)
+	}
+	if stmt.ModuleName != "cards" || stmt.ProcedureName != "validate_credit_card" {
+		t.Errorf("Expected cards.validate_credit_card, got %q.%q", stmt.ModuleName, stmt.ProcedureName)
+	}
+
+	stmt, err = parser.Parse("CREATE TRIGGER audit after DELETE ON payments EXECUTE PROCEDURE audit_payment;")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	if stmt.TriggerTiming != "AFTER" || len(stmt.TriggerEvents) != 1 || stmt.ModuleName != "" || stmt.ProcedureName != "audit_payment" {
+		t.Errorf("Unexpected statement: %+v", stmt)
+	}
+
+	for _, sql := range []string{
+		"CREATE TRIGGER t INSTEAD OF INSERT ON payments EXECUTE PROCEDURE p",
+		"CREATE TRIGGER t BEFORE TRUNCATE ON payments EXECUTE PROCEDURE p",
+		"CREATE TRIGGER t BEFORE INSERT OR INSERT ON payments EXECUTE PROCEDURE p",
+		"CREATE TRIGGER t BEFORE INSERT ON payments EXECUTE PROCEDURE p(1)",
+	} {
+		if _, err := parser.Parse(sql); err == nil {
+			t.Errorf("Expected %q to fail", sql)
+		}
+	}
+
+	stmt, err = parser.Parse("DROP TRIGGER IF EXISTS check_card ON payments")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	if stmt.Type != DropTrigger || stmt.TriggerName != "check_card" || stmt.Table != "payments" || !stmt.IfExists {
+		t.Errorf("Unexpected statement: %+v", stmt)
+	}
+}
+
+func TestParseCheckConstraints(t *testing.T) {
+	parser := NewParser()
+
+	stmt, err := parser.Parse("CREATE TABLE payments (id INT, card_number BIGINT CHECK (validate_credit_card(card_number) = 1), amount INT, CONSTRAINT positive_amount CHECK (amount > 0), CHECK (is_valid(id)))")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	if len(stmt.Columns) != 3 || stmt.Columns[1].Name != "card_number" || stmt.Columns[1].DataType != "BIGINT" {
+		t.Fatalf("Unexpected columns: %+v", stmt.Columns)
+	}
+	if len(stmt.Checks) != 3 {
+		t.Fatalf("Expected 3 checks, got %+v", stmt.Checks)
+	}
+
+	card := stmt.Checks[0]
+	if card.Column != "card_number" || card.Name != "" || card.Expr != "validate_credit_card(card_number) = 1" {
+		t.Errorf("Unexpected column check: %+v", card)
+	}
+	if card.Condition.Call == nil || card.Condition.Call.Name != "validate_credit_card" || card.Condition.Operator != "=" {
+		t.Errorf("Expected a call condition, got %+v", card.Condition)
+	}
+
+	amount := stmt.Checks[1]
+	if amount.Name != "positive_amount" || amount.Column != "" || amount.Condition.Column != "amount" || amount.Condition.Operator != ">" {
+		t.Errorf("Unexpected table check: %+v", amount)
+	}
+
+	// A bare call must return true
+	bare := stmt.Checks[2]
+	if bare.Condition.Call == nil || bare.Condition.Operator != "=" || bare.Condition.Value != true {
+		t.Errorf("Unexpected bare call check: %+v", bare.Condition)
+	}
+
+	stmt, err = parser.Parse("ALTER TABLE payments ADD CONSTRAINT valid_card CHECK (validate_credit_card(card_number) = 1)")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	if stmt.Type != AlterTable || stmt.Table != "payments" || len(stmt.Checks) != 1 || stmt.Checks[0].Name != "valid_card" || stmt.NewColumn.Name != "" {
+		t.Errorf("Unexpected statement: %+v", stmt)
+	}
+
+	stmt, err = parser.Parse("ALTER TABLE payments ADD COLUMN fee INT CHECK (fee >= 0)")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	if stmt.NewColumn.Name != "fee" || len(stmt.Checks) != 1 || stmt.Checks[0].Column != "fee" {
+		t.Errorf("Unexpected statement: %+v", stmt)
+	}
+
+	stmt, err = parser.Parse("ALTER TABLE payments DROP CONSTRAINT valid_card;")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	if stmt.Constraint != "valid_card" || len(stmt.Checks) != 0 {
+		t.Errorf("Unexpected statement: %+v", stmt)
+	}
+
+	for _, sql := range []string{
+		"CREATE TABLE t (a INT CHECK (a > 0)",
+		"CREATE TABLE t (a INT CHECK (nonsense))",
+		"ALTER TABLE t ADD CHECK (a > 0) extra",
+		"CREATE TABLE t (a INT, b INT, CHECK (a > 0 AND b > 0))",
+		"CREATE TABLE t (a INT, b INT, CHECK (f(a) > 0 AND g(b) > 0))",
+		"CREATE TABLE t (a INT CHECK (is_valid(a) OR a > 0))",
+	} {
+		if _, err := parser.Parse(sql); err == nil {
+			t.Errorf("Expected %q to fail", sql)
+		}
+	}
+}
+
+func TestParseProcedureVersioning(t *testing.T) {
+	parser := NewParser()
+
+	stmt, err := parser.Parse("CREATE OR REPLACE PROCEDURE discount(amount INT) RETURNS INT LANGUAGE wasm AS 'AGFzbQ=='")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	if stmt.Type != CreateProcedure || !stmt.OrReplace || stmt.ProcedureName != "discount" || stmt.ReturnType != "INT" || len(stmt.Columns) != 1 {
+		t.Errorf("Unexpected statement: %+v", stmt)
+	}
+
+	stmt, err = parser.Parse("CREATE PROCEDURE discount(amount INT) RETURNS INT LANGUAGE wasm AS 'AGFzbQ=='")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	if stmt.OrReplace {
+		t.Error("Expected OrReplace to be false")
+	}
+
+	stmt, err = parser.Parse("ALTER PROCEDURE discount ROLLBACK TO VERSION 3;")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	if stmt.Type != AlterProcedure || stmt.ProcedureName != "discount" || stmt.Version != 3 {
+		t.Errorf("Unexpected statement: %+v", stmt)
+	}
+
+	for _, sql := range []string{
+		"ALTER PROCEDURE discount ROLLBACK TO VERSION 0",
+		"ALTER PROCEDURE discount ROLLBACK",
+		"ALTER PROCEDURE discount RENAME TO rebate",
+	} {
+		if _, err := parser.Parse(sql); err == nil {
+			t.Errorf("Expected %q to fail", sql)
+		}
+	}
+}
+
+func TestParseProcedureOnNull(t *testing.T) {
+	parser := NewParser()
+
+	tests := []struct {
+		clause string
+		want   string
+	}{
+		{"", ""},
+		{"STRICT ", OnNullStrict},
+		{"RETURNS NULL ON NULL INPUT ", OnNullStrict},
+		{"called on null input ", OnNullCalled},
+	}
+	for _, tt := range tests {
+		stmt, err := parser.Parse("CREATE PROCEDURE discount(amount INT) RETURNS INT " + tt.clause + "LANGUAGE wasm AS 'AGFzbQ=='")
+		if err != nil {
+			t.Fatalf("Parse(%q) error = %v", tt.clause, err)
+		}
+		if stmt.OnNull != tt.want || stmt.ProcedureLang != "wasm" || string(stmt.ProcedureCode) != "AGFzbQ==" {
+			t.Errorf("Parse(%q): unexpected statement: %+v", tt.clause, stmt)
+		}
+	}
+
+	stmt, err := parser.Parse("CALL discount(NULL, 'NULL', 2)")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	if len(stmt.ProcedureArgs) != 3 || stmt.ProcedureArgs[0] != nil || stmt.ProcedureArgs[1] != "NULL" || stmt.ProcedureArgs[2] != 2 {
+		t.Errorf("Unexpected arguments: %#v", stmt.ProcedureArgs)
+	}
+}
+
+func TestParseProcedurePrivileges(t *testing.T) {
+	parser := NewParser()
+
+	stmt, err := parser.Parse("CREATE PROCEDURE payout(amount INT) RETURNS INT STRICT SECURITY DEFINER LANGUAGE wasm AS 'AGFzbQ=='")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	if stmt.Security != SecurityDefiner || stmt.OnNull != OnNullStrict {
+		t.Errorf("Unexpected statement: %+v", stmt)
+	}
+
+	tests := []struct {
+		sql       string
+		typ       StatementType
+		procedure string
+		username  string
+		role      string
+	}{
+		{"GRANT EXECUTE ON PROCEDURE calculate_commission TO sales", GrantPrivileges, "calculate_commission", "", "sales"},
+		{"GRANT EXECUTE ON PROCEDURE pricing.discount TO 'john'@'localhost'", GrantPrivileges, "pricing.discount", "john", ""},
+		{"revoke execute on procedure calculate_commission from 'sales';", RevokePrivileges, "calculate_commission", "", "sales"},
+	}
+	for _, tt := range tests {
+		stmt, err := parser.Parse(tt.sql)
+		if err != nil {
+			t.Fatalf("Parse(%q) error = %v", tt.sql, err)
+		}
+		if stmt.Type != tt.typ || stmt.ProcedureName != tt.procedure || stmt.Username != tt.username || stmt.RoleName != tt.role {
+			t.Errorf("Parse(%q): unexpected statement: %+v", tt.sql, stmt)
+		}
+	}
+
+	// Table grants are unchanged
+	stmt, err = parser.Parse("GRANT EXECUTE ON shop.* TO 'john'@'localhost'")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	if stmt.ProcedureName != "" || stmt.Table != "*" || stmt.Privileges[0] != "EXECUTE" {
+		t.Errorf("Unexpected statement: %+v", stmt)
+	}
+}
@@ -2,6 +2,7 @@ package mindb
 
 import (
 	"encoding/binary"
+	"encoding/json"
 	"fmt"
 	"sync"
 )
@@ -10,6 +11,7 @@ import (
 type RecoveryManager struct {
 	walManager *WALManager
 	heapFiles  map[string]*HeapFile // tableName -> HeapFile
+	dataDir    string               // Data directory holding procedures, if set
 	maxTxnID   uint32               // Maximum transaction ID seen during recovery
 	mu         sync.RWMutex
 }
@@ -29,6 +31,14 @@ func (rm *RecoveryManager) RegisterHeapFile(tableName string, hf *HeapFile) {
 	rm.heapFiles[tableName] = hf
 }
 
+// SetDataDir sets the data directory whose procedures, modules, aggregates
+// and triggers are recovered. Without it, DDL records are skipped.
+func (rm *RecoveryManager) SetDataDir(dataDir string) {
+	rm.mu.Lock()
+	defer rm.mu.Unlock()
+	rm.dataDir = dataDir
+}
+
 // Recover performs ARIES-style recovery
 func (rm *RecoveryManager) Recover() error {
 	fmt.Println("Starting ARIES recovery...")
@@ -117,7 +127,9 @@ func (rm *RecoveryManager) analysisPass() (LSN, map[uint32]bool, error) {
 		}
 
 		switch record.Header.RecordType {
-		case WALRecordInsert, WALRecordUpdate, WALRecordDelete:
+		case WALRecordInsert, WALRecordUpdate, WALRecordDelete,
+			WALRecordCreateProcedure, WALRecordDropProcedure,
+			WALRecordCreateObject, WALRecordDropObject:
 			activeTxns[record.Header.TxnID] = true
 		case WALRecordCommit, WALRecordAbort:
 			delete(activeTxns, record.Header.TxnID)
@@ -134,8 +146,32 @@ func (rm *RecoveryManager) redoPass(redoLSN LSN) error {
 		return err
 	}
 
+	// DDL is redone only for committed transactions: changes of
+	// transactions that rolled back were undone before their abort record,
+	// and those still active are undone by the undo pass
+	committed := make(map[uint32]bool)
+	for _, record := range records {
+		if record.Header.RecordType == WALRecordCommit {
+			committed[record.Header.TxnID] = true
+		}
+	}
+
 	for _, record := range records {
 		switch record.Header.RecordType {
+		case WALRecordCreateProcedure, WALRecordDropProcedure:
+			if !committed[record.Header.TxnID] {
+				continue
+			}
+			if err := rm.redoProcedure(record); err != nil {
+				fmt.Printf("Warning: failed to redo procedure change LSN %d: %v\n", record.Header.LSN, err)
+			}
+		case WALRecordCreateObject, WALRecordDropObject:
+			if !committed[record.Header.TxnID] {
+				continue
+			}
+			if err := rm.redoObject(record); err != nil {
+				fmt.Printf("Warning: failed to redo object change LSN %d: %v\n", record.Header.LSN, err)
+			}
 		case WALRecordInsert:
 			if err := rm.redoInsert(record); err != nil {
 				fmt.Printf("Warning: failed to redo insert LSN %d: %v\n", record.Header.LSN, err)
@@ -178,6 +214,7 @@ func (rm *RecoveryManager) undoPass(activeTxns map[uint32]bool) error {
 	// Undo each transaction
 	for txnID, txnRecs := range txnRecords {
 		fmt.Printf("Undoing transaction %d (%d records)\n", txnID, len(txnRecs))
+		ddl := false
 		for _, record := range txnRecs {
 			switch record.Header.RecordType {
 			case WALRecordInsert:
@@ -192,11 +229,31 @@ func (rm *RecoveryManager) undoPass(activeTxns map[uint32]bool) error {
 				if err := rm.undoDelete(record); err != nil {
 					fmt.Printf("Warning: failed to undo delete: %v\n", err)
 				}
+			case WALRecordCreateProcedure, WALRecordDropProcedure:
+				ddl = true
+				if err := rm.undoProcedure(record); err != nil {
+					fmt.Printf("Warning: failed to undo procedure change: %v\n", err)
+				}
+			case WALRecordCreateObject, WALRecordDropObject:
+				ddl = true
+				if err := rm.undoObject(record); err != nil {
+					fmt.Printf("Warning: failed to undo object change: %v\n", err)
+				}
 			}
 		}
+
+		// Mark a transaction that made DDL changes aborted, so a later
+		// recovery does not undo it again over changes committed since.
+		// Row changes are not undone, so other transactions need no record.
+		if !ddl {
+			continue
+		}
+		if _, err := rm.walManager.AppendRecord(txnID, WALRecordAbort, []byte{}); err != nil {
+			return fmt.Errorf("failed to write abort record: %w", err)
+		}
 	}
 
-	return nil
+	return rm.walManager.Sync()
 }
 
 // redoInsert replays an insert operation
@@ -252,6 +309,70 @@ func (rm *RecoveryManager) undoDelete(record *WALRecord) error {
 	return nil
 }
 
+// redoProcedure replays a committed procedure change
+func (rm *RecoveryManager) redoProcedure(record *WALRecord) error {
+	rm.mu.RLock()
+	defer rm.mu.RUnlock()
+
+	if rm.dataDir == "" {
+		return nil
+	}
+
+	change, err := parseProcedureChange(record.Data)
+	if err != nil {
+		return err
+	}
+	return redoProcedureChange(rm.dataDir, change, true)
+}
+
+// undoProcedure reverses a procedure change (restore the previous version)
+func (rm *RecoveryManager) undoProcedure(record *WALRecord) error {
+	rm.mu.RLock()
+	defer rm.mu.RUnlock()
+
+	if rm.dataDir == "" {
+		return nil
+	}
+
+	change, err := parseProcedureChange(record.Data)
+	if err != nil {
+		return err
+	}
+	return undoProcedureChange(rm.dataDir, change)
+}
+
+// redoObject replays a committed module, aggregate or trigger change
+func (rm *RecoveryManager) redoObject(record *WALRecord) error {
+	rm.mu.RLock()
+	defer rm.mu.RUnlock()
+
+	if rm.dataDir == "" {
+		return nil
+	}
+
+	change, err := parseObjectChange(record.Data)
+	if err != nil {
+		return err
+	}
+	return redoObjectChange(rm.dataDir, change)
+}
+
+// undoObject reverses a module, aggregate or trigger change
+func (rm *RecoveryManager) undoObject(record *WALRecord) error {
+	rm.mu.RLock()
+	defer rm.mu.RUnlock()
+
+	if rm.dataDir == "" {
+		return nil
+	}
+
+	change, err := parseObjectChange(record.Data)
+	if err != nil {
+		return err
+	}
+	return undoObjectChange(rm.dataDir, change)
+}
+
 // CheckpointData contains checkpoint information
 type CheckpointData struct {
 	RedoLSN    LSN
@@ -425,6 +546,22 @@ func parseDeleteData(data []byte) *DeleteData {
 	}
 }
 
+func parseProcedureChange(data []byte) (*procedureChange, error) {
+	var change procedureChange
+	if err := json.Unmarshal(data, &change); err != nil {
+		return nil, fmt.Errorf("invalid procedure change: %v", err)
+	}
+	return &change, nil
+}
+
+func parseObjectChange(data []byte) (*objectChange, error) {
+	var change objectChange
+	if err := json.Unmarshal(data, &change); err != nil {
+		return nil, fmt.Errorf("invalid object change: %v", err)
+	}
+	return &change, nil
+}
+
 // Data structures for parsed WAL records
 
 type InsertData struct {
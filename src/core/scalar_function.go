@@ -0,0 +1,191 @@
+package mindb
+
+import (
+	"fmt"
+)
+
+// FunctionCall is a call to a stored procedure (or module.function) inside a
+// SELECT list, WHERE condition or ORDER BY clause, evaluated once per row
+type FunctionCall struct {
+	Module string        // Module name for module.function calls
+	Name   string        // Procedure or function name
+	Args   []FunctionArg // Arguments in call order
+	Alias  string        // Output column name in a SELECT list
+}
+
+// FunctionArg is a function call argument: a column of the current row or a
+// literal value
+type FunctionArg struct {
+	Column string      // Column name, if the argument refers to one
+	Value  interface{} // Literal value otherwise
+}
+
+// String renders the call as written, e.g. calculate_tax(amount, 2). It is
+// also the row key for calls that have no alias.
+func (f *FunctionCall) String() string {
+	args := ""
+	for i, arg := range f.Args {
+		if i > 0 {
+			args += ", "
+		}
+		switch {
+		case arg.Column != "":
+			args += arg.Column
+		case arg.Value == nil:
+			args += "NULL"
+		default:
+			if s, ok := arg.Value.(string); ok {
+				args += "'" + s + "'"
+			} else {
+				args += fmt.Sprintf("%v", arg.Value)
+			}
+		}
+	}
+
+	return f.procedure() + "(" + args + ")"
+}
+
+// procedure returns the name of the called procedure, or module.function
+func (f *FunctionCall) procedure() string {
+	if f.Module != "" {
+		return f.Module + "." + f.Name
+	}
+	return f.Name
+}
+
+// callable returns the procedure a CALL or CREATE TRIGGER names, qualified
+// as module.function for a module function
+func (stmt *Statement) callable() string {
+	if stmt.ModuleName != "" {
+		return stmt.ModuleName + "." + stmt.ProcedureName
+	}
+	return stmt.ProcedureName
+}
+
+// hasFunctionCalls reports whether a SELECT calls any procedures
+func (stmt *Statement) hasFunctionCalls() bool {
+	if len(stmt.Functions) > 0 || stmt.OrderByCall != nil {
+		return true
+	}
+	for _, cond := range stmt.Conditions {
+		if cond.Call != nil {
+			return true
+		}
+	}
+	return false
+}
+
+// procedureCalls returns the procedure calls of a SELECT: those in its SELECT
+// list, WHERE conditions and ORDER BY clause, and its table-valued procedure
+func (stmt *Statement) procedureCalls() []*FunctionCall {
+	var calls []*FunctionCall
+	for i := range stmt.Functions {
+		calls = append(calls, &stmt.Functions[i])
+	}
+	for _, cond := range stmt.Conditions {
+		if cond.Call != nil {
+			calls = append(calls, cond.Call)
+		}
+	}
+	if stmt.OrderByCall != nil {
+		calls = append(calls, stmt.OrderByCall)
+	}
+	if stmt.TableFunction != nil {
+		calls = append(calls, stmt.TableFunction)
+	}
+	return calls
+}
+
+// scanConditions returns the conditions that can be checked during a table
+// scan, leaving out those that call procedures
+func scanConditions(conditions []Condition) []Condition {
+	var scan []Condition
+	for _, cond := range conditions {
+		if cond.Call == nil {
+			scan = append(scan, cond)
+		}
+	}
+	return scan
+}
+
+// callFunction evaluates a function call against one row for the statement
+// running in ctx
+func (e *PagedEngine) callFunction(ctx *ExecutionContext, call *FunctionCall, row Row) (interface{}, error) {
+	args := make([]interface{}, len(call.Args))
+	for i, arg := range call.Args {
+		if arg.Column == "" {
+			args[i] = arg.Value
+			continue
+		}
+		val, exists := row[arg.Column]
+		if !exists {
+			return nil, fmt.Errorf("column '%s' does not exist", arg.Column)
+		}
+		args[i] = val
+	}
+
+	var result interface{}
+	var err error
+	if call.Module != "" {
+		result, err = e.callModuleFunction(ctx, call.Module, call.Name, args...)
+	} else {
+		result, err = e.callProcedure(ctx, call.Name, args...)
+	}
+	if err != nil {
+		return nil, fmt.Errorf("%s: %w", call.Name, err)
+	}
+	return result, nil
+}
+
+// applyFunctionCalls evaluates the procedure calls of a SELECT for each row:
+// rows failing a WHERE condition that calls a procedure are dropped, and the
+// remaining rows gain a column per SELECT-list call (named by its alias) and
+// for the ORDER BY call, if any. Rows are copied rather than modified, since
+// they may be shared with the query cache.
+func (e *PagedEngine) applyFunctionCalls(ctx *ExecutionContext, stmt *Statement, rows []Row) ([]Row, error) {
+	result := make([]Row, 0, len(rows))
+
+	for _, row := range rows {
+		match := true
+		for _, cond := range stmt.Conditions {
+			if cond.Call == nil {
+				continue
+			}
+			val, err := e.callFunction(ctx, cond.Call, row)
+			if err != nil {
+				return nil, err
+			}
+			if !matchesConditions(Row{cond.Column: val}, []Condition{cond}) {
+				match = false
+				break
+			}
+		}
+		if !match {
+			continue
+		}
+
+		computed := make(Row, len(row)+len(stmt.Functions)+1)
+		for k, v := range row {
+			computed[k] = v
+		}
+		for i := range stmt.Functions {
+			call := &stmt.Functions[i]
+			val, err := e.callFunction(ctx, call, row)
+			if err != nil {
+				return nil, err
+			}
+			computed[call.Alias] = val
+		}
+		if stmt.OrderByCall != nil {
+			val, err := e.callFunction(ctx, stmt.OrderByCall, row)
+			if err != nil {
+				return nil, err
+			}
+			computed[stmt.OrderBy] = val
+		}
+
+		result = append(result, computed)
+	}
+
+	return result, nil
+}
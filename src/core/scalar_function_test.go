@@ -0,0 +1,170 @@
+package mindb
+
+import (
+	"strings"
+	"testing"
+)
+
+// newScalarTestAdapter returns an adapter with an orders table, the
+// business_rules module and an add procedure
+func newScalarTestAdapter(t *testing.T) *EngineAdapter {
+	t.Helper()
+
+	adapter, err := NewEngineAdapter(t.TempDir(), false)
+	if err != nil {
+		t.Fatalf("Failed to create adapter: %v", err)
+	}
+	t.Cleanup(func() { adapter.Close() })
+
+	if _, err := adapter.Execute(&Statement{Type: CreateDatabase, Database: "shop"}); err != nil {
+		t.Fatalf("CREATE DATABASE failed: %v", err)
+	}
+	if err := adapter.UseDatabase("shop"); err != nil {
+		t.Fatalf("USE DATABASE failed: %v", err)
+	}
+	if err := adapter.pagedEngine.CreateModule(&StoredModule{Name: "business_rules", Code: businessRulesWASM}); err != nil {
+		t.Fatalf("Failed to create module: %v", err)
+	}
+	proc := &StoredProcedure{
+		Name:       "add",
+		Language:   "wasm",
+		Code:       simpleAddWASM,
+		Params:     []Column{{Name: "a", DataType: "INT"}, {Name: "b", DataType: "INT"}},
+		ReturnType: "INT",
+	}
+	if err := adapter.pagedEngine.CreateProcedure(proc); err != nil {
+		t.Fatalf("Failed to create procedure: %v", err)
+	}
+
+	for _, sql := range []string{
+		"CREATE TABLE orders (id INT PRIMARY KEY, amount FLOAT, qty INT)",
+		"INSERT INTO orders (id, amount, qty) VALUES (1, 100, 1)",
+		"INSERT INTO orders (id, amount, qty) VALUES (2, 250, 4)",
+		"INSERT INTO orders (id, amount, qty) VALUES (3, 50, 2)",
+	} {
+		execSQL(t, adapter, sql)
+	}
+
+	return adapter
+}
+
+func execSQL(t *testing.T, adapter *EngineAdapter, sql string) string {
+	t.Helper()
+
+	stmt, err := NewParser().Parse(sql)
+	if err != nil {
+		t.Fatalf("Parse(%q) error = %v", sql, err)
+	}
+	result, err := adapter.Execute(stmt)
+	if err != nil {
+		t.Fatalf("Execute(%q) error = %v", sql, err)
+	}
+	return result
+}
+
+func TestEngineAdapter_SelectFunctionCall(t *testing.T) {
+	adapter := newScalarTestAdapter(t)
+
+	result := execSQL(t, adapter, "SELECT id, business_rules.calculate_tax(amount, 0.1) AS tax, add(qty, 10) FROM orders")
+	if !strings.Contains(result, "tax") || !strings.Contains(result, "add") {
+		t.Errorf("Expected tax and add columns, got:\n%s", result)
+	}
+	for _, want := range []string{"| 10 ", "| 25 ", "| 5 ", "| 11 ", "| 14 ", "| 12 "} {
+		if !strings.Contains(result, want) {
+			t.Errorf("Expected %q in result, got:\n%s", want, result)
+		}
+	}
+}
+
+func TestEngineAdapter_RepeatedFunctionCall(t *testing.T) {
+	adapter := newScalarTestAdapter(t)
+
+	// Each call gets its own column, named as written
+	result := execSQL(t, adapter, "SELECT id, add(qty, 10), add(qty, 20) FROM orders WHERE id = 1")
+	for _, want := range []string{"add(qty, 10)", "add(qty, 20)", "| 11 ", "| 21 "} {
+		if !strings.Contains(result, want) {
+			t.Errorf("Expected %q in result, got:\n%s", want, result)
+		}
+	}
+}
+
+func TestEngineAdapter_JoinFunctionCall(t *testing.T) {
+	adapter := newScalarTestAdapter(t)
+	execSQL(t, adapter, "CREATE TABLE shipments (order_id INT PRIMARY KEY, carrier VARCHAR(20))")
+	execSQL(t, adapter, "INSERT INTO shipments (order_id, carrier) VALUES (1, 'post')")
+	execSQL(t, adapter, "INSERT INTO shipments (order_id, carrier) VALUES (2, 'courier')")
+
+	result := execSQL(t, adapter, "SELECT id, carrier, add(qty, 100) AS total FROM orders INNER JOIN shipments ON orders.id = shipments.order_id WHERE add(qty, 0) > 1")
+	if !strings.Contains(result, "courier") || !strings.Contains(result, "| 104 ") {
+		t.Errorf("Expected order 2 with its computed total, got:\n%s", result)
+	}
+	if strings.Contains(result, "post") {
+		t.Errorf("Expected order 1 to be filtered out, got:\n%s", result)
+	}
+}
+
+func TestPagedEngine_ExecuteQueryFunctionCall(t *testing.T) {
+	adapter := newScalarTestAdapter(t)
+
+	stmt, err := NewParser().Parse("SELECT id FROM orders WHERE add(qty, 0) >= 2")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	rows, err := adapter.pagedEngine.ExecuteQuery(stmt)
+	if err != nil {
+		t.Fatalf("ExecuteQuery failed: %v", err)
+	}
+	if len(rows) != 2 {
+		t.Errorf("Expected orders 2 and 3, got %v", rows)
+	}
+}
+
+func TestEngineAdapter_WhereFunctionCall(t *testing.T) {
+	adapter := newScalarTestAdapter(t)
+
+	result := execSQL(t, adapter, "SELECT id FROM orders WHERE business_rules.calculate_tax(amount, 0.1) >= 10")
+	if !strings.Contains(result, "| 1 ") || !strings.Contains(result, "| 2 ") {
+		t.Errorf("Expected orders 1 and 2, got:\n%s", result)
+	}
+	if strings.Contains(result, "| 3 ") {
+		t.Errorf("Expected order 3 to be filtered out, got:\n%s", result)
+	}
+}
+
+func TestEngineAdapter_OrderByFunctionCall(t *testing.T) {
+	adapter := newScalarTestAdapter(t)
+
+	result := execSQL(t, adapter, "SELECT id FROM orders ORDER BY add(qty, 0) DESC")
+	first := strings.Index(result, "| 2 ")
+	second := strings.Index(result, "| 3 ")
+	third := strings.Index(result, "| 1 ")
+	if first < 0 || second < 0 || third < 0 || !(first < second && second < third) {
+		t.Errorf("Expected orders in qty order 2, 3, 1, got:\n%s", result)
+	}
+}
+
+func TestEngineAdapter_FunctionCallErrors(t *testing.T) {
+	adapter := newScalarTestAdapter(t)
+
+	tests := []struct {
+		name    string
+		sql     string
+		wantErr string
+	}{
+		{"unknown procedure", "SELECT missing(amount) FROM orders", "missing"},
+		{"unknown column", "SELECT add(nope, 1) FROM orders", "column 'nope' does not exist"},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			stmt, err := NewParser().Parse(tt.sql)
+			if err != nil {
+				t.Fatalf("Parse() error = %v", err)
+			}
+			_, err = adapter.Execute(stmt)
+			if err == nil || !strings.Contains(err.Error(), tt.wantErr) {
+				t.Errorf("Expected error containing %q, got: %v", tt.wantErr, err)
+			}
+		})
+	}
+}
@@ -7,6 +7,7 @@ import (
 	"fmt"
 	"os"
 	"path/filepath"
+	"strings"
 	"sync"
 	"time"
 )
@@ -15,13 +16,14 @@ import (
 type Privilege string
 
 const (
-	PrivilegeSelect Privilege = "SELECT"
-	PrivilegeInsert Privilege = "INSERT"
-	PrivilegeUpdate Privilege = "UPDATE"
-	PrivilegeDelete Privilege = "DELETE"
-	PrivilegeCreate Privilege = "CREATE"
-	PrivilegeDrop   Privilege = "DROP"
-	PrivilegeAll    Privilege = "ALL"
+	PrivilegeSelect  Privilege = "SELECT"
+	PrivilegeInsert  Privilege = "INSERT"
+	PrivilegeUpdate  Privilege = "UPDATE"
+	PrivilegeDelete  Privilege = "DELETE"
+	PrivilegeCreate  Privilege = "CREATE"
+	PrivilegeDrop    Privilege = "DROP"
+	PrivilegeExecute Privilege = "EXECUTE" // Call procedures and module functions
+	PrivilegeAll     Privilege = "ALL"
 )
 
 // User represents a database user
@@ -43,6 +45,7 @@ type Grant struct {
 	Host       string
 	Database   string // '*' for all databases
 	Table      string // '*' for all tables
+	Procedure  string // Procedure or module of an ON PROCEDURE grant, else empty
 	Privileges []Privilege
 	GrantedAt  time.Time
 }
@@ -78,12 +81,19 @@ type UserManager struct {
 
 // UserData represents serializable user data
 type UserData struct {
+	Version   int                  `json:"version"` // userDataVersion when saved; 0 before versioning
 	Users     map[string]*User     `json:"users"`
 	Grants    map[string][]Grant   `json:"grants"`
 	Roles     map[string]*Role     `json:"roles"`
 	UserRoles map[string][]string  `json:"user_roles"`
 }
 
+// userDataVersion is the revision of the users.json format. Revision 1
+// added the EXECUTE privilege. Calling a procedure used to take SELECT on
+// the database, so loading an older file adds EXECUTE to every grant of
+// SELECT on a whole database; callers keep the access they had.
+const userDataVersion = 1
+
 // NewUserManager creates a new user manager
 func NewUserManager() *UserManager {
 	um := &UserManager{
@@ -310,7 +320,7 @@ func (um *UserManager) HasPrivilege(username, host, database, table string, priv
 	if exists {
 		for _, grant := range grants {
 			// Check if grant applies to this database/table
-			if !matchesPattern(grant.Database, database) {
+			if grant.Procedure != "" || !matchesPattern(grant.Database, database) {
 				continue
 			}
 			if !matchesPattern(grant.Table, table) {
@@ -336,7 +346,7 @@ func (um *UserManager) HasPrivilege(username, host, database, table string, priv
 		
 		for _, grant := range role.Grants {
 			// Check if grant applies to this database/table
-			if !matchesPattern(grant.Database, database) {
+			if grant.Procedure != "" || !matchesPattern(grant.Database, database) {
 				continue
 			}
 			if !matchesPattern(grant.Table, table) {
@@ -355,6 +365,127 @@ func (um *UserManager) HasPrivilege(username, host, database, table string, priv
 	return false
 }
 
+// GrantExecute grants EXECUTE on a procedure to a user. A module name
+// covers every function of the module.
+func (um *UserManager) GrantExecute(username, host, procedure string) error {
+	um.mu.Lock()
+	defer um.mu.Unlock()
+	
+	key := userKey(username, host)
+	if _, exists := um.users[key]; !exists {
+		return fmt.Errorf("user '%s'@'%s' does not exist", username, host)
+	}
+	
+	if !hasExecuteGrant(um.grants[key], procedure) {
+		um.grants[key] = append(um.grants[key], executeGrant(username, host, procedure))
+	}
+	
+	return nil
+}
+
+// RevokeExecute revokes EXECUTE on a procedure from a user
+func (um *UserManager) RevokeExecute(username, host, procedure string) error {
+	um.mu.Lock()
+	defer um.mu.Unlock()
+	
+	key := userKey(username, host)
+	if _, exists := um.users[key]; !exists {
+		return fmt.Errorf("user '%s'@'%s' does not exist", username, host)
+	}
+	
+	um.grants[key] = withoutExecute(um.grants[key], procedure)
+	
+	return nil
+}
+
+// HasExecutePrivilege checks if a user may call a procedure, or module
+// function named module.function. EXECUTE (or ALL) on the procedure, its
+// module or every table of the database allows it; direct and role grants
+// both count.
+func (um *UserManager) HasExecutePrivilege(username, host, database, procedure string) bool {
+	um.mu.RLock()
+	defer um.mu.RUnlock()
+	
+	key := userKey(username, host)
+	grants, exists := um.grants[key]
+	if !exists {
+		// Try wildcard host
+		key = userKey(username, "%")
+		grants = um.grants[key]
+	}
+	
+	for _, grant := range grants {
+		if grant.allowsExecute(database, procedure) {
+			return true
+		}
+	}
+	
+	for _, roleName := range um.userRoles[key] {
+		role, roleExists := um.roles[roleName]
+		if !roleExists {
+			continue
+		}
+		for _, grant := range role.Grants {
+			if grant.allowsExecute(database, procedure) {
+				return true
+			}
+		}
+	}
+	
+	return false
+}
+
+// allowsExecute reports whether a grant allows calling procedure in database
+func (g Grant) allowsExecute(database, procedure string) bool {
+	if g.Procedure == "" {
+		if !matchesPattern(g.Database, database) || g.Table != "*" {
+			return false
+		}
+	} else if g.Procedure != procedure && !strings.HasPrefix(procedure, g.Procedure+".") {
+		return false
+	}
+	
+	for _, p := range g.Privileges {
+		if p == PrivilegeAll || p == PrivilegeExecute {
+			return true
+		}
+	}
+	return false
+}
+
+// executeGrant returns an EXECUTE grant on a procedure
+func executeGrant(username, host, procedure string) Grant {
+	return Grant{
+		Username:   username,
+		Host:       host,
+		Database:   "*",
+		Procedure:  procedure,
+		Privileges: []Privilege{PrivilegeExecute},
+		GrantedAt:  time.Now(),
+	}
+}
+
+// hasExecuteGrant reports whether grants include one on procedure
+func hasExecuteGrant(grants []Grant, procedure string) bool {
+	for _, grant := range grants {
+		if grant.Procedure == procedure {
+			return true
+		}
+	}
+	return false
+}
+
+// withoutExecute returns grants less those on procedure
+func withoutExecute(grants []Grant, procedure string) []Grant {
+	kept := []Grant{}
+	for _, grant := range grants {
+		if grant.Procedure != procedure {
+			kept = append(kept, grant)
+		}
+	}
+	return kept
+}
+
 // ListUsers returns all users
 func (um *UserManager) ListUsers() []*User {
 	um.mu.RLock()
@@ -436,6 +567,7 @@ func (um *UserManager) Save() error {
 	
 	// Prepare data for serialization
 	data := UserData{
+		Version:   userDataVersion,
 		Users:     um.users,
 		Grants:    um.grants,
 		Roles:     um.roles,
@@ -499,9 +631,37 @@ func (um *UserManager) Load() error {
 		um.userRoles = data.UserRoles
 	}
 	
+	// Grants saved before EXECUTE existed keep allowing calls
+	if data.Version < 1 {
+		for _, grants := range um.grants {
+			grantExecuteWithSelect(grants)
+		}
+		for _, role := range um.roles {
+			grantExecuteWithSelect(role.Grants)
+		}
+	}
+	
 	return nil
 }
 
+// grantExecuteWithSelect adds EXECUTE to grants of SELECT on every table of
+// a database, which is what calling its procedures took before EXECUTE
+func grantExecuteWithSelect(grants []Grant) {
+	for i, grant := range grants {
+		if grant.Procedure != "" || grant.Table != "*" {
+			continue
+		}
+		hasSelect, hasExecute := false, false
+		for _, p := range grant.Privileges {
+			hasSelect = hasSelect || p == PrivilegeSelect
+			hasExecute = hasExecute || p == PrivilegeExecute || p == PrivilegeAll
+		}
+		if hasSelect && !hasExecute {
+			grants[i].Privileges = append(grant.Privileges, PrivilegeExecute)
+		}
+	}
+}
+
 // UnlockAccount manually unlocks a user account
 func (um *UserManager) UnlockAccount(username, host string) error {
 	um.mu.Lock()
@@ -661,6 +821,40 @@ func (um *UserManager) GrantRolePrivileges(roleName, database, table string, pri
 	return nil
 }
 
+// GrantRoleExecute grants EXECUTE on a procedure to a role
+func (um *UserManager) GrantRoleExecute(roleName, procedure string) error {
+	um.mu.Lock()
+	defer um.mu.Unlock()
+	
+	role, exists := um.roles[roleName]
+	if !exists {
+		return fmt.Errorf("role '%s' does not exist", roleName)
+	}
+	
+	if !hasExecuteGrant(role.Grants, procedure) {
+		role.Grants = append(role.Grants, executeGrant("", "", procedure))
+		role.UpdatedAt = time.Now()
+	}
+	
+	return nil
+}
+
+// RevokeRoleExecute revokes EXECUTE on a procedure from a role
+func (um *UserManager) RevokeRoleExecute(roleName, procedure string) error {
+	um.mu.Lock()
+	defer um.mu.Unlock()
+	
+	role, exists := um.roles[roleName]
+	if !exists {
+		return fmt.Errorf("role '%s' does not exist", roleName)
+	}
+	
+	role.Grants = withoutExecute(role.Grants, procedure)
+	role.UpdatedAt = time.Now()
+	
+	return nil
+}
+
 // GrantRoleToUser assigns a role to a user
 func (um *UserManager) GrantRoleToUser(username, host, roleName string) error {
 	um.mu.Lock()
@@ -607,3 +607,99 @@ func TestPrivilegeConstants(t *testing.T) {
 		}
 	}
 }
+
+func TestUserManager_ExecutePrivilege(t *testing.T) {
+	um := NewUserManager()
+	um.CreateUser("clerk", "secret", "localhost")
+	
+	if um.HasExecutePrivilege("clerk", "localhost", "shop", "calculate_commission") {
+		t.Error("Expected no EXECUTE privilege before any grant")
+	}
+	
+	// A grant on a module covers its functions, but not other procedures
+	for i := 0; i < 2; i++ {
+		if err := um.GrantExecute("clerk", "localhost", "pricing"); err != nil {
+			t.Fatalf("GrantExecute failed: %v", err)
+		}
+	}
+	if grants := um.grants["clerk@localhost"]; len(grants) != 1 {
+		t.Errorf("Expected a repeated grant to be kept once, got %d grants", len(grants))
+	}
+	if !um.HasExecutePrivilege("clerk", "localhost", "shop", "pricing.discount") {
+		t.Error("Expected EXECUTE on a function of the granted module")
+	}
+	if um.HasExecutePrivilege("clerk", "localhost", "shop", "pricing_v2") {
+		t.Error("Expected no EXECUTE on a procedure sharing the module's prefix")
+	}
+	
+	// Procedure grants are not table grants
+	if um.HasPrivilege("clerk", "localhost", "shop", "", PrivilegeExecute) {
+		t.Error("Expected procedure grants to be ignored by HasPrivilege")
+	}
+	
+	if err := um.RevokeExecute("clerk", "localhost", "pricing"); err != nil {
+		t.Fatalf("RevokeExecute failed: %v", err)
+	}
+	if um.HasExecutePrivilege("clerk", "localhost", "shop", "pricing.discount") {
+		t.Error("Expected no EXECUTE after revoke")
+	}
+	
+	// ALL on a database covers its procedures; SELECT does not
+	um.GrantPrivileges("clerk", "localhost", "shop", "*", []Privilege{PrivilegeSelect})
+	if um.HasExecutePrivilege("clerk", "localhost", "shop", "calculate_commission") {
+		t.Error("Expected SELECT not to allow EXECUTE")
+	}
+	um.GrantRoleToUser("clerk", "localhost", "admin")
+	if !um.HasExecutePrivilege("clerk", "localhost", "shop", "calculate_commission") {
+		t.Error("Expected the admin role to allow EXECUTE")
+	}
+}
+
+func TestUserManager_LoadGrantsExecuteWithSelect(t *testing.T) {
+	tmpDir := t.TempDir()
+	
+	// Users saved before EXECUTE existed, when calls took SELECT
+	legacy := `{
+		"users": {"clerk@%": {"username": "clerk", "host": "%"}},
+		"grants": {"clerk@%": [
+			{"database": "shop", "table": "*", "privileges": ["SELECT"]},
+			{"database": "hr", "table": "staff", "privileges": ["SELECT"]}
+		]}
+	}`
+	if err := os.WriteFile(filepath.Join(tmpDir, "users.json"), []byte(legacy), 0600); err != nil {
+		t.Fatalf("Failed to write users: %v", err)
+	}
+	
+	um := NewUserManager()
+	um.SetDataDir(tmpDir)
+	if err := um.Load(); err != nil {
+		t.Fatalf("Failed to load: %v", err)
+	}
+	if !um.HasExecutePrivilege("clerk", "%", "shop", "calculate_commission") {
+		t.Error("Expected SELECT on a database saved before EXECUTE to allow calls")
+	}
+	if um.HasExecutePrivilege("clerk", "%", "hr", "calculate_commission") {
+		t.Error("Expected SELECT on a single table not to allow calls")
+	}
+	
+	// Once saved in the current format, SELECT granted later does not
+	if err := um.Save(); err != nil {
+		t.Fatalf("Failed to save: %v", err)
+	}
+	um.GrantPrivileges("clerk", "%", "hr", "*", []Privilege{PrivilegeSelect})
+	if err := um.Save(); err != nil {
+		t.Fatalf("Failed to save: %v", err)
+	}
+	
+	um = NewUserManager()
+	um.SetDataDir(tmpDir)
+	if err := um.Load(); err != nil {
+		t.Fatalf("Failed to load: %v", err)
+	}
+	if !um.HasExecutePrivilege("clerk", "%", "shop", "calculate_commission") {
+		t.Error("Expected the migrated grant to survive a save")
+	}
+	if um.HasExecutePrivilege("clerk", "%", "hr", "calculate_commission") {
+		t.Error("Expected a new SELECT grant not to allow calls")
+	}
+}
@@ -18,6 +18,15 @@ const (
 	WALRecordCheckpoint = 4
 	WALRecordCommit     = 5
 	WALRecordAbort      = 6
+
+	// Procedure DDL; the record data is a procedureChange as JSON
+	WALRecordCreateProcedure = 7 // Procedure created, replaced or rolled back to a version
+	WALRecordDropProcedure   = 8
+
+	// Module, aggregate and trigger DDL; the record data is an objectChange
+	// as JSON
+	WALRecordCreateObject = 9
+	WALRecordDropObject   = 10
 )
 
 // WAL constants
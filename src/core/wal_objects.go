@@ -0,0 +1,234 @@
+package mindb
+
+import (
+	"encoding/json"
+	"fmt"
+	"os"
+	"path/filepath"
+)
+
+// Kinds of stored object other than procedures, named by the directory
+// their metadata is kept in
+const (
+	objectModule    = "modules"
+	objectAggregate = "aggregates"
+	objectTrigger   = "triggers"
+)
+
+// objectChange is a change to a stored module, aggregate or trigger made by
+// CREATE or DROP. Like a procedureChange, it is logged to the WAL before the
+// object's directory is touched, and carries the object's metadata on both
+// sides of the change so that it can be redone or undone. Bytecode is
+// already in its blob.
+type objectChange struct {
+	Kind   string          `json:"kind"`
+	Name   string          `json:"name"`
+	Before json.RawMessage `json:"before,omitempty"` // Nil if the object did not exist
+	After  json.RawMessage `json:"after,omitempty"`  // Nil if the object was dropped
+
+	obj            interface{} // The object created or dropped, to undo the change in memory
+	user, database string      // Who made the change, and where, for its audit event
+}
+
+// createObjectChange returns the change that creates an object
+func createObjectChange(kind, name string, obj interface{}) (*objectChange, error) {
+	data, err := json.Marshal(obj)
+	if err != nil {
+		return nil, fmt.Errorf("failed to marshal %s: %w", name, err)
+	}
+	return &objectChange{Kind: kind, Name: name, After: data, obj: obj}, nil
+}
+
+// dropObjectChange returns the change that drops an object
+func dropObjectChange(kind, name string, obj interface{}) (*objectChange, error) {
+	data, err := json.Marshal(obj)
+	if err != nil {
+		return nil, fmt.Errorf("failed to marshal %s: %w", name, err)
+	}
+	return &objectChange{Kind: kind, Name: name, Before: data, obj: obj}, nil
+}
+
+// recordType returns the WAL record type for the change
+func (c *objectChange) recordType() uint8 {
+	if c.After == nil {
+		return WALRecordDropObject
+	}
+	return WALRecordCreateObject
+}
+
+// filename returns the path of the object's metadata
+func (c *objectChange) filename(dataDir string) string {
+	return filepath.Join(dataDir, c.Kind, c.Name+".json")
+}
+
+// logObjectChange writes an object change to the WAL, then to the object's
+// directory. Outside a transaction the change commits at once; inside one it
+// is kept until COMMIT or ROLLBACK, as procedure DDL is. The caller holds
+// e.mu and applies the change in memory afterwards.
+func (e *PagedEngine) logObjectChange(change *objectChange) error {
+	change.user, change.database = e.currentUser, e.currentDB
+	txn := e.currentTxn
+	if e.walManager != nil {
+		if txn == nil {
+			var err error
+			if txn, err = e.txnManager.BeginTransaction(); err != nil {
+				return err
+			}
+		}
+
+		data, err := json.Marshal(change)
+		if err != nil {
+			return fmt.Errorf("failed to marshal %s change: %w", change.Name, err)
+		}
+		if _, err := e.walManager.AppendRecord(txn.ID, change.recordType(), data); err != nil {
+			return fmt.Errorf("failed to write WAL: %v", err)
+		}
+		if err := e.walManager.Sync(); err != nil {
+			return fmt.Errorf("failed to sync WAL: %v", err)
+		}
+	}
+
+	// Without a commit record the change is undone by recovery, so a
+	// failed write needs no cleanup here
+	if err := redoObjectChange(e.dataDir, change); err != nil {
+		if txn != nil && txn != e.currentTxn {
+			e.txnManager.AbortTransaction(txn.ID)
+		}
+		return err
+	}
+
+	if e.currentTxn != nil {
+		e.txnObjects = append(e.txnObjects, change)
+		return nil
+	}
+
+	if txn != nil {
+		if err := e.txnManager.CommitTransaction(txn.ID); err != nil {
+			return err
+		}
+		if _, err := e.walManager.AppendRecord(txn.ID, WALRecordCommit, []byte{}); err != nil {
+			return fmt.Errorf("failed to write commit record: %v", err)
+		}
+		if err := e.walManager.Sync(); err != nil {
+			return fmt.Errorf("failed to sync WAL: %v", err)
+		}
+	}
+
+	// Blobs only the dropped object used can go once the drop commits
+	if change.After == nil && change.Kind != objectTrigger {
+		if err := pruneProcedureBlobs(e.dataDir); err != nil {
+			fmt.Printf("Warning: failed to prune procedure blobs: %v\n", err)
+		}
+	}
+
+	e.auditObjectChange(change)
+	return nil
+}
+
+// commitObjectChanges finishes the object changes of a committed
+// transaction by pruning the blobs of modules and aggregates it dropped, and
+// audits them. The caller holds e.mu.
+func (e *PagedEngine) commitObjectChanges() {
+	dropped := false
+	for _, change := range e.txnObjects {
+		e.auditObjectChange(change)
+		if change.After == nil && change.Kind != objectTrigger {
+			dropped = true
+		}
+	}
+	if dropped {
+		if err := pruneProcedureBlobs(e.dataDir); err != nil {
+			fmt.Printf("Warning: failed to prune procedure blobs: %v\n", err)
+		}
+	}
+	e.txnObjects = nil
+}
+
+// auditObjectChange logs a committed module change as a MODULE_CREATED or
+// MODULE_DROPPED event. Aggregate and trigger DDL is not audited.
+func (e *PagedEngine) auditObjectChange(change *objectChange) {
+	mod, ok := change.obj.(*StoredModule)
+	if e.auditLogger == nil || !ok {
+		return
+	}
+	username, host := auditUser(change.user)
+	if change.After != nil {
+		e.auditLogger.LogModuleCreated(username, host, change.database, ProcedureAudit{Name: mod.Name, Digest: mod.Digest})
+	} else {
+		e.auditLogger.LogModuleDropped(username, host, change.database, ProcedureAudit{Name: mod.Name, Digest: mod.Digest})
+	}
+}
+
+// undoObjectChanges reverts the object changes of a transaction being rolled
+// back, newest first. The caller holds e.mu.
+func (e *PagedEngine) undoObjectChanges() error {
+	for i := len(e.txnObjects) - 1; i >= 0; i-- {
+		change := e.txnObjects[i]
+		if err := undoObjectChange(e.dataDir, change); err != nil {
+			return err
+		}
+
+		created := change.Before == nil
+		switch obj := change.obj.(type) {
+		case *StoredModule:
+			if created {
+				e.wasmEngine.RemoveModule(moduleCacheKey(obj.Name))
+				delete(e.modules, obj.Name)
+			} else {
+				if err := e.wasmEngine.CompileModule(moduleCacheKey(obj.Name), obj.Code); err != nil {
+					return fmt.Errorf("failed to restore module %s: %w", obj.Name, err)
+				}
+				e.modules[obj.Name] = obj
+			}
+		case *StoredAggregate:
+			if created {
+				e.wasmEngine.RemoveModule(aggregateCacheKey(obj.Name))
+				delete(e.aggregates, obj.Name)
+			} else {
+				if err := e.wasmEngine.CompileModule(aggregateCacheKey(obj.Name), obj.Code); err != nil {
+					return fmt.Errorf("failed to restore aggregate %s: %w", obj.Name, err)
+				}
+				e.aggregates[obj.Name] = obj
+			}
+		case *StoredTrigger:
+			if created {
+				delete(e.triggers, obj.key())
+			} else {
+				e.triggers[obj.key()] = obj
+			}
+		}
+		e.txnObjects = e.txnObjects[:i]
+	}
+
+	return nil
+}
+
+// redoObjectChange writes an object change to the object's directory
+func redoObjectChange(dataDir string, change *objectChange) error {
+	return writeObjectFile(change.filename(dataDir), change.After)
+}
+
+// undoObjectChange restores the object's directory to its state before a
+// change
+func undoObjectChange(dataDir string, change *objectChange) error {
+	return writeObjectFile(change.filename(dataDir), change.Before)
+}
+
+// writeObjectFile replaces an object's metadata, or removes it if data is
+// nil
+func writeObjectFile(filename string, data json.RawMessage) error {
+	if data == nil {
+		if err := os.Remove(filename); err != nil && !os.IsNotExist(err) {
+			return fmt.Errorf("failed to delete %s: %w", filepath.Base(filename), err)
+		}
+		return nil
+	}
+
+	if err := os.MkdirAll(filepath.Dir(filename), 0755); err != nil {
+		return fmt.Errorf("failed to create %s directory: %w", filepath.Base(filepath.Dir(filename)), err)
+	}
+	if err := replaceFile(filename, data); err != nil {
+		return fmt.Errorf("failed to write %s: %w", filepath.Base(filename), err)
+	}
+	return nil
+}
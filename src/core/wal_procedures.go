@@ -0,0 +1,223 @@
+package mindb
+
+import (
+	"encoding/json"
+	"fmt"
+	"os"
+	"path/filepath"
+	"strconv"
+)
+
+// procedureChange is a change to a stored procedure made by CREATE [OR
+// REPLACE], ALTER ... ROLLBACK TO VERSION or DROP PROCEDURE. It is logged to
+// the WAL before the procedures directory is touched, and carries the
+// procedure on both sides of the change so that it can be redone or undone.
+type procedureChange struct {
+	Name       string           `json:"name"`
+	Before     *StoredProcedure `json:"before,omitempty"`      // Nil if the procedure did not exist
+	After      *StoredProcedure `json:"after,omitempty"`       // Nil if the procedure was dropped
+	NewVersion bool             `json:"new_version,omitempty"` // After is a new revision
+
+	user, database string // Who made the change, and where, for its audit event
+}
+
+// recordType returns the WAL record type for the change
+func (c *procedureChange) recordType() uint8 {
+	if c.After == nil {
+		return WALRecordDropProcedure
+	}
+	return WALRecordCreateProcedure
+}
+
+// logProcedureChange writes a procedure change to the WAL, then to the
+// procedures directory. Outside a transaction the change commits at once;
+// inside one it is kept until COMMIT or ROLLBACK. The caller holds e.mu and
+// applies the change in memory afterwards.
+func (e *PagedEngine) logProcedureChange(change *procedureChange) error {
+	change.user, change.database = e.currentUser, e.currentDB
+	txn := e.currentTxn
+	if e.walManager != nil {
+		if txn == nil {
+			var err error
+			if txn, err = e.txnManager.BeginTransaction(); err != nil {
+				return err
+			}
+		}
+
+		data, err := json.Marshal(change)
+		if err != nil {
+			return fmt.Errorf("failed to marshal procedure change: %w", err)
+		}
+		if _, err := e.walManager.AppendRecord(txn.ID, change.recordType(), data); err != nil {
+			return fmt.Errorf("failed to write WAL: %v", err)
+		}
+		if err := e.walManager.Sync(); err != nil {
+			return fmt.Errorf("failed to sync WAL: %v", err)
+		}
+	}
+
+	// Without a commit record the change is undone by recovery, so a
+	// failed write needs no cleanup here
+	if err := redoProcedureChange(e.dataDir, change, e.currentTxn == nil); err != nil {
+		if txn != nil && txn != e.currentTxn {
+			e.txnManager.AbortTransaction(txn.ID)
+		}
+		return fmt.Errorf("failed to persist procedure: %w", err)
+	}
+
+	if e.currentTxn != nil {
+		e.txnProcedures = append(e.txnProcedures, change)
+		return nil
+	}
+
+	if txn != nil {
+		if err := e.txnManager.CommitTransaction(txn.ID); err != nil {
+			return err
+		}
+		if _, err := e.walManager.AppendRecord(txn.ID, WALRecordCommit, []byte{}); err != nil {
+			return fmt.Errorf("failed to write commit record: %v", err)
+		}
+		if err := e.walManager.Sync(); err != nil {
+			return fmt.Errorf("failed to sync WAL: %v", err)
+		}
+	}
+
+	// Blobs only the dropped procedure used can go once the drop commits
+	if change.After == nil {
+		if err := pruneProcedureBlobs(e.dataDir); err != nil {
+			fmt.Printf("Warning: failed to prune procedure blobs: %v\n", err)
+		}
+	}
+
+	e.auditProcedureChange(change)
+	return nil
+}
+
+// commitProcedureChanges finishes the procedure changes of a committed
+// transaction by removing the revisions and blobs of procedures it dropped,
+// and audits them; those of a transaction rolled back are never audited.
+// The caller holds e.mu.
+func (e *PagedEngine) commitProcedureChanges() {
+	dropped := false
+	for _, change := range e.txnProcedures {
+		e.auditProcedureChange(change)
+		if _, exists := e.procedures[change.Name]; change.After == nil && !exists {
+			if err := removeProcedureVersions(e.dataDir, change.Name); err != nil {
+				fmt.Printf("Warning: failed to delete versions of procedure %s: %v\n", change.Name, err)
+			}
+			dropped = true
+		}
+	}
+	if dropped {
+		if err := pruneProcedureBlobs(e.dataDir); err != nil {
+			fmt.Printf("Warning: failed to prune procedure blobs: %v\n", err)
+		}
+	}
+	e.txnProcedures = nil
+}
+
+// auditProcedureChange logs a committed procedure change as a
+// PROCEDURE_CREATED event, naming the version it installed, or a
+// PROCEDURE_DROPPED one
+func (e *PagedEngine) auditProcedureChange(change *procedureChange) {
+	if e.auditLogger == nil {
+		return
+	}
+	username, host := auditUser(change.user)
+	if change.After != nil {
+		e.auditLogger.LogProcedureCreated(username, host, change.database, procedureAudit(change.Name, change.After))
+	} else {
+		e.auditLogger.LogProcedureDropped(username, host, change.database, procedureAudit(change.Name, change.Before))
+	}
+}
+
+// undoProcedureChanges reverts the procedure changes of a transaction being
+// rolled back, newest first. The caller holds e.mu.
+func (e *PagedEngine) undoProcedureChanges() error {
+	for i := len(e.txnProcedures) - 1; i >= 0; i-- {
+		change := e.txnProcedures[i]
+		if err := undoProcedureChange(e.dataDir, change); err != nil {
+			return err
+		}
+
+		if change.Before == nil {
+			e.wasmEngine.RemoveModule(change.Name)
+			delete(e.procedures, change.Name)
+		} else {
+			if err := e.wasmEngine.CompileModule(change.Name, change.Before.Code); err != nil {
+				return fmt.Errorf("failed to restore procedure %s: %w", change.Name, err)
+			}
+			e.procedures[change.Name] = change.Before
+		}
+		e.txnProcedures = e.txnProcedures[:i]
+	}
+
+	return nil
+}
+
+// redoProcedureChange writes a procedure change to the procedures
+// directory. The bytecode is already in its blob. A dropped procedure's
+// revisions are only removed once the drop has committed, so that it can
+// still be undone until then; its blobs are left for pruneProcedureBlobs,
+// since during recovery a later record may refer to them again.
+func redoProcedureChange(dataDir string, change *procedureChange, committed bool) error {
+	if change.After == nil {
+		if err := removeProcedureFile(dataDir, change.Name); err != nil {
+			return err
+		}
+		if committed {
+			return removeProcedureVersions(dataDir, change.Name)
+		}
+		return nil
+	}
+
+	if change.NewVersion {
+		if err := writeProcedureVersion(dataDir, change.After); err != nil {
+			return err
+		}
+	}
+	return writeProcedureFile(dataDir, change.After)
+}
+
+// removeProcedureVersions deletes the revisions of a dropped procedure
+func removeProcedureVersions(dataDir, name string) error {
+	versionDir, err := procedureVersionDir(dataDir, name)
+	if err != nil {
+		return err
+	}
+	return os.RemoveAll(versionDir)
+}
+
+// undoProcedureChange restores the procedures directory to its state
+// before a change
+func undoProcedureChange(dataDir string, change *procedureChange) error {
+	if change.After != nil && change.NewVersion {
+		versionDir, err := procedureVersionDir(dataDir, change.Name)
+		if err != nil {
+			return err
+		}
+		filename := filepath.Join(versionDir, strconv.Itoa(change.After.Version)+".json")
+		if err := os.Remove(filename); err != nil && !os.IsNotExist(err) {
+			return fmt.Errorf("failed to delete procedure version file: %w", err)
+		}
+	}
+
+	if change.Before == nil {
+		return removeProcedureFile(dataDir, change.Name)
+	}
+	return writeProcedureFile(dataDir, change.Before)
+}
+
+// replaceFile writes a file through a temporary file and a rename, so a
+// crash leaves either the old contents or the new, never a truncated file
+func replaceFile(filename string, data []byte) error {
+	tempPath := filename + ".tmp"
+	if err := os.WriteFile(tempPath, data, 0644); err != nil {
+		return err
+	}
+	if err := os.Rename(tempPath, filename); err != nil {
+		os.Remove(tempPath) // Clean up temp file
+		return err
+	}
+	return nil
+}
@@ -0,0 +1,276 @@
+package mindb
+
+import (
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestProcedureDDLRollback(t *testing.T) {
+	dataDir := t.TempDir()
+
+	engine, err := NewPagedEngine(dataDir)
+	if err != nil {
+		t.Fatalf("Failed to create engine: %v", err)
+	}
+	defer engine.Close()
+
+	params := []Column{{Name: "amount", DataType: "INT"}}
+	if err := engine.CreateProcedure(&StoredProcedure{Name: "discount", Language: "wasm", Code: discountV1WASM, Params: params, ReturnType: "INT"}); err != nil {
+		t.Fatalf("Failed to create procedure: %v", err)
+	}
+
+	// A replace rolled back leaves version 1 and no second revision
+	engine.BeginTransaction()
+	if err := engine.ReplaceProcedure(&StoredProcedure{Name: "discount", Language: "wasm", Code: discountV2WASM, Params: params, ReturnType: "INT"}); err != nil {
+		t.Fatalf("Failed to replace procedure: %v", err)
+	}
+	if callDiscount(t, engine) != 80 {
+		t.Error("Expected version 2 inside the transaction")
+	}
+	if err := engine.RollbackTransaction(); err != nil {
+		t.Fatalf("Failed to roll back: %v", err)
+	}
+	if callDiscount(t, engine) != 90 {
+		t.Error("Expected version 1 after rollback")
+	}
+	versions, err := engine.ProcedureVersions("discount")
+	if err != nil || len(versions) != 1 {
+		t.Errorf("Expected 1 version after rollback, got %d (%v)", len(versions), err)
+	}
+
+	// A drop rolled back restores the procedure
+	engine.BeginTransaction()
+	if err := engine.DropProcedure("discount"); err != nil {
+		t.Fatalf("Failed to drop procedure: %v", err)
+	}
+	if err := engine.RollbackTransaction(); err != nil {
+		t.Fatalf("Failed to roll back: %v", err)
+	}
+	if callDiscount(t, engine) != 90 {
+		t.Error("Expected procedure to be restored after rollback")
+	}
+
+	// A committed drop removes the revisions too
+	engine.BeginTransaction()
+	if err := engine.DropProcedure("discount"); err != nil {
+		t.Fatalf("Failed to drop procedure: %v", err)
+	}
+	if _, err := os.Stat(filepath.Join(dataDir, "procedures", "discount")); err != nil {
+		t.Error("Expected revisions to be kept until commit")
+	}
+	if err := engine.CommitTransaction(); err != nil {
+		t.Fatalf("Failed to commit: %v", err)
+	}
+	if _, err := os.Stat(filepath.Join(dataDir, "procedures", "discount")); !os.IsNotExist(err) {
+		t.Error("Expected revisions to be removed after commit")
+	}
+}
+
+func TestObjectDDLRollback(t *testing.T) {
+	dataDir := t.TempDir()
+
+	engine, err := NewPagedEngine(dataDir)
+	if err != nil {
+		t.Fatalf("Failed to create engine: %v", err)
+	}
+	defer engine.Close()
+
+	// A create rolled back leaves neither the module nor its metadata
+	modPath := filepath.Join(dataDir, "modules", "ledger.json")
+	engine.BeginTransaction()
+	if err := engine.CreateModule(&StoredModule{Name: "ledger", Language: "wasm", Code: ledgerWASM}); err != nil {
+		t.Fatalf("Failed to create module: %v", err)
+	}
+	if err := engine.RollbackTransaction(); err != nil {
+		t.Fatalf("Failed to roll back: %v", err)
+	}
+	if _, err := engine.GetStoredModule("ledger"); err == nil {
+		t.Error("Expected module to be gone after rollback")
+	}
+	if _, err := engine.CallModuleFunction("ledger", "add_cents", 1, 2); err == nil {
+		t.Error("Expected module function to be gone after rollback")
+	}
+	if _, err := os.Stat(modPath); !os.IsNotExist(err) {
+		t.Error("Expected module metadata to be removed after rollback")
+	}
+
+	// A committed create stays
+	engine.BeginTransaction()
+	if err := engine.CreateModule(&StoredModule{Name: "ledger", Language: "wasm", Code: ledgerWASM}); err != nil {
+		t.Fatalf("Failed to create module: %v", err)
+	}
+	if err := engine.CommitTransaction(); err != nil {
+		t.Fatalf("Failed to commit: %v", err)
+	}
+	if _, err := os.Stat(modPath); err != nil {
+		t.Error("Expected module metadata after commit")
+	}
+
+	// A drop rolled back restores the module
+	engine.BeginTransaction()
+	if err := engine.DropModule("ledger"); err != nil {
+		t.Fatalf("Failed to drop module: %v", err)
+	}
+	if err := engine.RollbackTransaction(); err != nil {
+		t.Fatalf("Failed to roll back: %v", err)
+	}
+	result, err := engine.CallModuleFunction("ledger", "add_cents", 1, 2)
+	if err != nil || result != int64(3) {
+		t.Errorf("Expected module to be restored after rollback, got %v (%v)", result, err)
+	}
+	if _, err := os.Stat(modPath); err != nil {
+		t.Error("Expected module metadata to be restored after rollback")
+	}
+}
+
+func TestProcedureDDLRecovery(t *testing.T) {
+	dataDir := t.TempDir()
+
+	engine, err := NewPagedEngineWithWAL(dataDir, true)
+	if err != nil {
+		t.Fatalf("Failed to create engine: %v", err)
+	}
+
+	params := []Column{{Name: "amount", DataType: "INT"}}
+	if err := engine.CreateProcedure(&StoredProcedure{Name: "discount", Language: "wasm", Code: discountV1WASM, Params: params, ReturnType: "INT"}); err != nil {
+		t.Fatalf("Failed to create procedure: %v", err)
+	}
+	if err := engine.ReplaceProcedure(&StoredProcedure{Name: "discount", Language: "wasm", Code: discountV2WASM, Params: params, ReturnType: "INT"}); err != nil {
+		t.Fatalf("Failed to replace procedure: %v", err)
+	}
+
+	records, _ := engine.walManager.ReadRecords(0)
+	logged := 0
+	for _, record := range records {
+		if record.Header.RecordType == WALRecordCreateProcedure {
+			logged++
+		}
+	}
+	if logged != 2 {
+		t.Errorf("Expected 2 procedure records in WAL, got %d", logged)
+	}
+	engine.Close()
+
+	// Simulate a crash part way through writing the procedure file
+	procPath := filepath.Join(dataDir, "procedures", "discount.json")
+	if err := os.WriteFile(procPath, []byte(`{"name":"disc`), 0644); err != nil {
+		t.Fatalf("Failed to truncate procedure file: %v", err)
+	}
+
+	engine, err = NewPagedEngineWithWAL(dataDir, true)
+	if err != nil {
+		t.Fatalf("Failed to reopen engine: %v", err)
+	}
+	if callDiscount(t, engine) != 80 {
+		t.Error("Expected recovery to restore version 2")
+	}
+
+	// A replace left uncommitted at a crash is undone
+	engine.BeginTransaction()
+	if err := engine.ReplaceProcedure(&StoredProcedure{Name: "discount", Language: "wasm", Code: discountV1WASM, Params: params, ReturnType: "INT"}); err != nil {
+		t.Fatalf("Failed to replace procedure: %v", err)
+	}
+	engine.Close()
+
+	engine, err = NewPagedEngineWithWAL(dataDir, true)
+	if err != nil {
+		t.Fatalf("Failed to reopen engine: %v", err)
+	}
+	if callDiscount(t, engine) != 80 {
+		t.Error("Expected the uncommitted replace to be undone")
+	}
+	versions, err := engine.ProcedureVersions("discount")
+	if err != nil || len(versions) != 2 {
+		t.Errorf("Expected 2 versions after recovery, got %d (%v)", len(versions), err)
+	}
+
+	// The undone transaction is not undone again over later changes
+	if err := engine.RollbackProcedure("discount", 1); err != nil {
+		t.Fatalf("Failed to roll back procedure: %v", err)
+	}
+	engine.Close()
+
+	engine, err = NewPagedEngineWithWAL(dataDir, true)
+	if err != nil {
+		t.Fatalf("Failed to reopen engine: %v", err)
+	}
+	defer engine.Close()
+	if callDiscount(t, engine) != 90 {
+		t.Error("Expected version 1 to survive a second recovery")
+	}
+}
+
+func TestObjectDDLRecovery(t *testing.T) {
+	dataDir := t.TempDir()
+
+	engine, err := NewPagedEngineWithWAL(dataDir, true)
+	if err != nil {
+		t.Fatalf("Failed to create engine: %v", err)
+	}
+	if err := engine.CreateModule(&StoredModule{Name: "ledger", Language: "wasm", Code: ledgerWASM}); err != nil {
+		t.Fatalf("Failed to create module: %v", err)
+	}
+
+	records, _ := engine.walManager.ReadRecords(0)
+	logged := 0
+	for _, record := range records {
+		if record.Header.RecordType == WALRecordCreateObject {
+			logged++
+		}
+	}
+	if logged != 1 {
+		t.Errorf("Expected 1 object record in WAL, got %d", logged)
+	}
+
+	// The bytecode is stored as a blob, apart from the metadata
+	modPath := filepath.Join(dataDir, "modules", "ledger.json")
+	data, err := os.ReadFile(modPath)
+	if err != nil {
+		t.Fatalf("Failed to read module metadata: %v", err)
+	}
+	if strings.Contains(string(data), `"Code"`) || !strings.Contains(string(data), procedureDigest(ledgerWASM)) {
+		t.Errorf("Expected module metadata with digest and no inline code, got %s", data)
+	}
+	engine.Close()
+
+	// Simulate a crash part way through writing the module file, next to a
+	// file that cannot be loaded at all
+	if err := os.WriteFile(modPath, []byte(`{"Name":"led`), 0644); err != nil {
+		t.Fatalf("Failed to truncate module file: %v", err)
+	}
+	if err := os.WriteFile(filepath.Join(dataDir, "modules", "broken.json"), []byte(`{"Name":"broken"}`), 0644); err != nil {
+		t.Fatalf("Failed to write broken module file: %v", err)
+	}
+
+	engine, err = NewPagedEngineWithWAL(dataDir, true)
+	if err != nil {
+		t.Fatalf("Expected startup to survive a broken module: %v", err)
+	}
+	result, err := engine.CallModuleFunction("ledger", "add_cents", 1, 2)
+	if err != nil || result != int64(3) {
+		t.Errorf("Expected recovery to restore the module, got %v (%v)", result, err)
+	}
+	if _, err := engine.GetStoredModule("broken"); err == nil {
+		t.Error("Expected broken module to be left out")
+	}
+
+	// Dropping the module removes its blob and survives a restart
+	if err := engine.DropModule("ledger"); err != nil {
+		t.Fatalf("Failed to drop module: %v", err)
+	}
+	if _, err := os.Stat(filepath.Join(procedureBlobDir(dataDir), procedureDigest(ledgerWASM)+".wasm")); !os.IsNotExist(err) {
+		t.Error("Expected blob of dropped module to be removed")
+	}
+	engine.Close()
+
+	engine, err = NewPagedEngineWithWAL(dataDir, true)
+	if err != nil {
+		t.Fatalf("Failed to reopen engine: %v", err)
+	}
+	defer engine.Close()
+	if _, err := engine.GetStoredModule("ledger"); err == nil {
+		t.Error("Expected module to stay dropped")
+	}
+}
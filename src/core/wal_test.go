@@ -232,6 +232,9 @@ func TestRecoveryManager(t *testing.T) {
 	wm.AppendRecord(2, WALRecordInsert, []byte("insert2"))
 	// Transaction 2 not committed
 
+	wm.AppendRecord(3, WALRecordCreateProcedure, []byte("{}"))
+	// Transaction 3 changed a procedure and was not committed
+
 	wm.Sync()
 
 	// Run recovery
@@ -240,7 +243,20 @@ func TestRecoveryManager(t *testing.T) {
 		t.Fatalf("Recovery failed: %v", err)
 	}
 
-	// Recovery should complete without error
+	// Only the transaction that changed a procedure is marked aborted
+	records, err := wm.ReadRecords(0)
+	if err != nil {
+		t.Fatalf("Failed to read records: %v", err)
+	}
+	aborted := make(map[uint32]bool)
+	for _, record := range records {
+		if record.Header.RecordType == WALRecordAbort {
+			aborted[record.Header.TxnID] = true
+		}
+	}
+	if aborted[2] || !aborted[3] {
+		t.Errorf("Expected an abort record for transaction 3 only, got %v", aborted)
+	}
 }
 
 func TestCheckpoint(t *testing.T) {
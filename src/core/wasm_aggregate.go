@@ -0,0 +1,440 @@
+package mindb
+
+import (
+	"encoding/json"
+	"fmt"
+	"os"
+	"path/filepath"
+	"strings"
+	"time"
+
+	"github.com/bytecodealliance/wasmtime-go/v25"
+)
+
+// StoredAggregate is a user-defined aggregate function backed by a WASM
+// module. For an aggregate called name the module exports:
+//
+//	name_init() -> state                 allocate an empty state in guest memory
+//	name_step(state, args...)            fold one row into the state
+//	name_merge(state, other)             fold other into state, freeing other
+//	name_finalize(state) -> result       compute the result, freeing the state
+//
+// States are opaque i32 handles (guest pointers). TEXT and BLOB arguments
+// and results use the same ABI as procedures.
+type StoredAggregate struct {
+	Name        string
+	Language    string
+	Code        []byte   `json:"-"` // WASM bytecode, stored as a blob named by Digest
+	Digest      string   // SHA-256 of Code, hex encoded
+	Params      []Column // Argument definitions, not counting the state
+	ReturnType  string
+	Description string
+	CreatedAt   time.Time
+	UpdatedAt   time.Time
+}
+
+// aggregateCacheKey is the WASMEngine cache key for a stored aggregate, kept
+// apart from procedure and module names
+func aggregateCacheKey(name string) string {
+	return "aggregate:" + name
+}
+
+// aggregateFunctionNames returns the init, step, merge and finalize export
+// names of an aggregate
+func aggregateFunctionNames(name string) []string {
+	return []string{name + "_init", name + "_step", name + "_merge", name + "_finalize"}
+}
+
+// IntrospectAggregate checks that a module exports the entry points of the
+// named aggregate and derives its signature, preferring the one recorded by
+// the SDK's #[aggregate] attribute
+func (w *WASMEngine) IntrospectAggregate(code []byte, name string) (*StoredAggregate, error) {
+	module, err := wasmtime.NewModule(w.engine, code)
+	if err != nil {
+		return nil, fmt.Errorf("failed to compile WASM module: %w", err)
+	}
+
+	funcTypes := make(map[string]*wasmtime.FuncType)
+	for _, exp := range module.Exports() {
+		if funcType := exp.Type().FuncType(); funcType != nil {
+			funcTypes[exp.Name()] = funcType
+		}
+	}
+
+	names := aggregateFunctionNames(name)
+	var missing []string
+	for _, fn := range names {
+		if funcTypes[fn] == nil {
+			missing = append(missing, fn)
+		}
+	}
+	if len(missing) > 0 {
+		return nil, fmt.Errorf("module must export %s; missing %s", strings.Join(names, ", "), strings.Join(missing, ", "))
+	}
+
+	// Every entry point but init takes the state handle first
+	initType, stepType, mergeType, finalizeType := funcTypes[names[0]], funcTypes[names[1]], funcTypes[names[2]], funcTypes[names[3]]
+	if len(initType.Params()) != 0 || len(initType.Results()) != 1 || initType.Results()[0].Kind() != wasmtime.KindI32 {
+		return nil, fmt.Errorf("%s must take no arguments and return an i32 state", names[0])
+	}
+	for _, fn := range names[1:] {
+		params := funcTypes[fn].Params()
+		if len(params) == 0 || params[0].Kind() != wasmtime.KindI32 {
+			return nil, fmt.Errorf("%s must take the i32 state as its first argument", fn)
+		}
+	}
+	if len(mergeType.Params()) != 2 || mergeType.Params()[1].Kind() != wasmtime.KindI32 {
+		return nil, fmt.Errorf("%s must take two i32 states", names[2])
+	}
+	if len(finalizeType.Params()) != 1 {
+		return nil, fmt.Errorf("%s must take only the state", names[3])
+	}
+
+	agg := &StoredAggregate{Name: name}
+	if sig := procedureSignature(code, name); sig != nil && sig.Kind == "aggregate" {
+		agg.Params = sig.Columns()
+		agg.ReturnType = sig.Returns
+		agg.Description = sig.Description
+		return agg, nil
+	}
+
+	stepParams := stepType.Params()[1:]
+	agg.Params = make([]Column, len(stepParams))
+	for i, pt := range stepParams {
+		agg.Params[i] = Column{Name: fmt.Sprintf("arg%d", i+1), DataType: wasmTypeToSQL(pt.Kind())}
+	}
+	if results := finalizeType.Results(); len(results) > 0 {
+		agg.ReturnType = wasmTypeToSQL(results[0].Kind())
+	}
+
+	return agg, nil
+}
+
+// CreateAggregate stores a user-defined aggregate. Parameters, return type
+// and description not given are taken from the module.
+func (e *PagedEngine) CreateAggregate(agg *StoredAggregate) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+
+	// Check if aggregate already exists
+	if _, exists := e.aggregates[agg.Name]; exists {
+		return fmt.Errorf("aggregate '%s' already exists", agg.Name)
+	}
+	if isBuiltinAggregate(agg.Name) {
+		return fmt.Errorf("aggregate '%s' is built in", agg.Name)
+	}
+
+	sig, err := e.wasmEngine.IntrospectAggregate(agg.Code, agg.Name)
+	if err != nil {
+		return fmt.Errorf("failed to load aggregate: %w", err)
+	}
+	if len(agg.Params) == 0 {
+		agg.Params = sig.Params
+	}
+	if agg.ReturnType == "" {
+		agg.ReturnType = sig.ReturnType
+	}
+	if agg.Description == "" {
+		agg.Description = sig.Description
+	}
+	if agg.ReturnType == "" {
+		return fmt.Errorf("cannot determine the return type of aggregate '%s'; specify RETURNS", agg.Name)
+	}
+
+	// Compile the WASM module
+	if err := e.wasmEngine.CompileModule(aggregateCacheKey(agg.Name), agg.Code); err != nil {
+		return fmt.Errorf("failed to compile aggregate: %w", err)
+	}
+
+	// Persist to disk: the bytecode to its blob, then the metadata through
+	// the WAL
+	digest, err := writeProcedureBlob(e.dataDir, agg.Code)
+	if err != nil {
+		e.wasmEngine.RemoveModule(aggregateCacheKey(agg.Name))
+		return fmt.Errorf("failed to persist aggregate: %w", err)
+	}
+	agg.Digest = digest
+	agg.CreatedAt = time.Now()
+	agg.UpdatedAt = time.Now()
+
+	change, err := createObjectChange(objectAggregate, agg.Name, agg)
+	if err == nil {
+		err = e.logObjectChange(change)
+	}
+	if err != nil {
+		e.wasmEngine.RemoveModule(aggregateCacheKey(agg.Name))
+		return fmt.Errorf("failed to persist aggregate: %w", err)
+	}
+
+	// Store in memory
+	e.aggregates[agg.Name] = agg
+	return nil
+}
+
+// DropAggregate drops a user-defined aggregate
+func (e *PagedEngine) DropAggregate(name string) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+
+	// Check if aggregate exists
+	agg, exists := e.aggregates[name]
+	if !exists {
+		return fmt.Errorf("aggregate '%s' does not exist", name)
+	}
+
+	// Delete from disk through the WAL
+	change, err := dropObjectChange(objectAggregate, name, agg)
+	if err == nil {
+		err = e.logObjectChange(change)
+	}
+	if err != nil {
+		return fmt.Errorf("failed to delete aggregate: %w", err)
+	}
+
+	// Remove from WASM engine
+	e.wasmEngine.RemoveModule(aggregateCacheKey(name))
+
+	// Remove from aggregates map
+	delete(e.aggregates, name)
+
+	return nil
+}
+
+// GetAggregate returns a user-defined aggregate by name
+func (e *PagedEngine) GetAggregate(name string) (*StoredAggregate, error) {
+	e.mu.RLock()
+	defer e.mu.RUnlock()
+
+	agg, exists := e.aggregates[name]
+	if !exists {
+		return nil, fmt.Errorf("aggregate '%s' does not exist", name)
+	}
+
+	return agg, nil
+}
+
+// ListAggregates returns all user-defined aggregates
+func (e *PagedEngine) ListAggregates() []*StoredAggregate {
+	e.mu.RLock()
+	defer e.mu.RUnlock()
+
+	aggs := make([]*StoredAggregate, 0, len(e.aggregates))
+	for _, agg := range e.aggregates {
+		aggs = append(aggs, agg)
+	}
+
+	return aggs
+}
+
+// isAggregate reports whether name is a user-defined aggregate
+func (e *PagedEngine) isAggregate(name string) bool {
+	e.mu.RLock()
+	defer e.mu.RUnlock()
+
+	_, exists := e.aggregates[name]
+	return exists
+}
+
+// resolveAggregates turns calls in the SELECT list of a query without
+// built-in aggregates into user-defined aggregates, where they name one.
+// Such calls would otherwise be taken for per-row procedure calls.
+func (e *PagedEngine) resolveAggregates(stmt *Statement) error {
+	if len(stmt.Aggregates) > 0 || len(stmt.Functions) == 0 {
+		return nil
+	}
+
+	var aggregates []AggregateFunc
+	var scalar *FunctionCall
+	for i := range stmt.Functions {
+		call := &stmt.Functions[i]
+		if call.Module != "" || !e.isAggregate(call.Name) {
+			scalar = call
+			continue
+		}
+		aggregates = append(aggregates, AggregateFunc{
+			Type:  UserFunc,
+			Name:  call.Name,
+			Args:  call.Args,
+			Alias: call.Alias,
+		})
+	}
+	if len(aggregates) == 0 {
+		return nil
+	}
+	if scalar != nil {
+		return fmt.Errorf("cannot use %s alongside aggregate '%s'", scalar.String(), aggregates[0].Name)
+	}
+
+	stmt.Aggregates = aggregates
+	stmt.Functions = nil
+	return nil
+}
+
+// runAggregate feeds inputs, one argument list per row, through a
+// user-defined aggregate and returns its result. All calls share one
+// instance, since the states live in its memory. parent is the context of
+// the statement running the aggregate.
+func (e *PagedEngine) runAggregate(parent *ExecutionContext, name string, inputs [][]interface{}) (interface{}, error) {
+	agg, err := e.GetAggregate(name)
+	if err != nil {
+		return nil, err
+	}
+
+	ctx := e.callContext(parent)
+	session, err := e.wasmEngine.openSession(aggregateCacheKey(name), ctx)
+	if err != nil {
+		return nil, fmt.Errorf("%s: %w", name, err)
+	}
+
+	result, err := foldAggregate(session, agg, inputs)
+	e.auditAggregate(agg, ctx, session.fuel, err)
+	if err != nil {
+		// States left behind would leak guest memory
+		session.discard()
+		return nil, fmt.Errorf("%s: %w", name, err)
+	}
+	session.close()
+
+	return unmarshalResult(result, agg.ReturnType), nil
+}
+
+// auditAggregate records one run of an aggregate over a group for a
+// PROCEDURE_EXECUTED audit event, like a procedure call
+func (e *PagedEngine) auditAggregate(agg *StoredAggregate, ctx *ExecutionContext, fuel uint64, err error) {
+	if e.auditLogger == nil {
+		return
+	}
+	audit := ProcedureAudit{Name: agg.Name, Digest: agg.Digest, Args: len(agg.Params), Fuel: fuel}
+	username, host := auditUser(e.currentUser)
+	e.auditLogger.LogProcedureExecuted(ctx.Calls, username, host, ctx.Database, audit, err)
+}
+
+// aggregateBatchRows is how many rows are stepped into one partial state
+var aggregateBatchRows = 1024
+
+// foldAggregate runs init, step and finalize for one group of rows. The
+// rows are stepped in batches, each into a state of its own that merge then
+// folds into the first, so that the partial states a parallel scan would
+// produce are combined the same way.
+func foldAggregate(session *wasmSession, agg *StoredAggregate, inputs [][]interface{}) (interface{}, error) {
+	names := aggregateFunctionNames(agg.Name)
+
+	batch := inputs
+	if len(batch) > aggregateBatchRows {
+		batch = batch[:aggregateBatchRows]
+	}
+	state, err := stepAggregate(session, agg, batch)
+	if err != nil {
+		return nil, err
+	}
+
+	for start := len(batch); start < len(inputs); start += aggregateBatchRows {
+		end := start + aggregateBatchRows
+		if end > len(inputs) {
+			end = len(inputs)
+		}
+		partial, err := stepAggregate(session, agg, inputs[start:end])
+		if err != nil {
+			return nil, err
+		}
+		if _, err := session.call(names[2], nil, []interface{}{state, partial}); err != nil {
+			return nil, err
+		}
+	}
+
+	return session.call(names[3], nil, []interface{}{state})
+}
+
+// stepAggregate allocates a state and steps every row into it
+func stepAggregate(session *wasmSession, agg *StoredAggregate, inputs [][]interface{}) (int32, error) {
+	names := aggregateFunctionNames(agg.Name)
+	stepParams := append([]Column{{Name: "state", DataType: "INT"}}, agg.Params...)
+
+	result, err := session.call(names[0], nil, nil)
+	if err != nil {
+		return 0, err
+	}
+	state, ok := result.(int32)
+	if !ok {
+		return 0, fmt.Errorf("%s returned %T, expected an i32 state", names[0], result)
+	}
+
+	for _, args := range inputs {
+		stepArgs := append([]interface{}{state}, args...)
+		if _, err := session.call(names[1], stepParams, stepArgs); err != nil {
+			return 0, err
+		}
+	}
+
+	return state, nil
+}
+
+// loadAggregates loads all user-defined aggregates from disk
+func (e *PagedEngine) loadAggregates() error {
+	aggDir := filepath.Join(e.dataDir, "aggregates")
+
+	// Check if aggregates directory exists
+	if _, err := os.Stat(aggDir); os.IsNotExist(err) {
+		return nil // No aggregates to load
+	}
+
+	// Read all aggregate files
+	files, err := os.ReadDir(aggDir)
+	if err != nil {
+		return fmt.Errorf("failed to read aggregates directory: %w", err)
+	}
+
+	// Load each aggregate, leaving out any that cannot be loaded
+	for _, file := range files {
+		if file.IsDir() || !strings.HasSuffix(file.Name(), ".json") {
+			continue
+		}
+
+		if err := e.loadAggregate(filepath.Join(aggDir, file.Name())); err != nil {
+			fmt.Printf("Warning: failed to load aggregate %s: %v\n", file.Name(), err)
+		}
+	}
+
+	return nil
+}
+
+// loadAggregate loads and compiles one aggregate, verifying its bytecode
+// against the stored digest
+func (e *PagedEngine) loadAggregate(filename string) error {
+	data, err := os.ReadFile(filename)
+	if err != nil {
+		return err
+	}
+
+	var agg StoredAggregate
+	if err := json.Unmarshal(data, &agg); err != nil {
+		return err
+	}
+
+	// Aggregates saved with inline bytecode are rewritten to point at the
+	// blob it was moved into
+	code, migrate, err := loadBytecode(e.dataDir, "aggregate", agg.Name, data, &agg.Digest)
+	if err != nil {
+		return err
+	}
+	agg.Code = code
+
+	// Compile WASM module
+	if err := e.wasmEngine.CompileModule(aggregateCacheKey(agg.Name), agg.Code); err != nil {
+		return fmt.Errorf("failed to compile: %w", err)
+	}
+
+	if migrate {
+		data, err := json.Marshal(&agg)
+		if err != nil {
+			return err
+		}
+		if err := replaceFile(filename, data); err != nil {
+			return err
+		}
+	}
+
+	// Store in memory
+	e.aggregates[agg.Name] = &agg
+	return nil
+}
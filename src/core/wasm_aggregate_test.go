@@ -0,0 +1,223 @@
+package mindb
+
+import (
+	"encoding/base64"
+	"strings"
+	"testing"
+)
+
+// WASM module implementing a weighted average aggregate, with the state
+// (sum of value*weight, sum of weight) in two f64s bump-allocated from a
+// heap pointer at offset 0. Equivalent to:
+//
+//	/// Average of value weighted by weight
+//	#[aggregate]
+//	impl WeightedAvg {
+//	    fn step(&mut self, value: f64, weight: f64) { self.sum += value * weight; self.weight += weight }
+//	    fn merge(&mut self, other: Self) { self.sum += other.sum; self.weight += other.weight }
+//	    fn finalize(self) -> f64 { self.sum / self.weight }
+//	}
+var weightedAvgWASM = []byte{
+	0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x15, 0x04, 0x60,
+	0x00, 0x01, 0x7f, 0x60, 0x03, 0x7f, 0x7c, 0x7c, 0x00, 0x60, 0x02, 0x7f,
+	0x7f, 0x00, 0x60, 0x01, 0x7f, 0x01, 0x7c, 0x03, 0x05, 0x04, 0x00, 0x01,
+	0x02, 0x03, 0x05, 0x03, 0x01, 0x00, 0x01, 0x07, 0x5f, 0x05, 0x06, 0x6d,
+	0x65, 0x6d, 0x6f, 0x72, 0x79, 0x02, 0x00, 0x11, 0x77, 0x65, 0x69, 0x67,
+	0x68, 0x74, 0x65, 0x64, 0x5f, 0x61, 0x76, 0x67, 0x5f, 0x69, 0x6e, 0x69,
+	0x74, 0x00, 0x00, 0x11, 0x77, 0x65, 0x69, 0x67, 0x68, 0x74, 0x65, 0x64,
+	0x5f, 0x61, 0x76, 0x67, 0x5f, 0x73, 0x74, 0x65, 0x70, 0x00, 0x01, 0x12,
+	0x77, 0x65, 0x69, 0x67, 0x68, 0x74, 0x65, 0x64, 0x5f, 0x61, 0x76, 0x67,
+	0x5f, 0x6d, 0x65, 0x72, 0x67, 0x65, 0x00, 0x02, 0x15, 0x77, 0x65, 0x69,
+	0x67, 0x68, 0x74, 0x65, 0x64, 0x5f, 0x61, 0x76, 0x67, 0x5f, 0x66, 0x69,
+	0x6e, 0x61, 0x6c, 0x69, 0x7a, 0x65, 0x00, 0x03, 0x0a, 0x84, 0x01, 0x04,
+	0x31, 0x01, 0x01, 0x7f, 0x41, 0x00, 0x41, 0x00, 0x28, 0x02, 0x00, 0x22,
+	0x00, 0x41, 0x10, 0x6a, 0x36, 0x02, 0x00, 0x20, 0x00, 0x44, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x39, 0x03, 0x00, 0x20, 0x00, 0x44,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x39, 0x03, 0x08, 0x20,
+	0x00, 0x0b, 0x1f, 0x00, 0x20, 0x00, 0x20, 0x00, 0x2b, 0x03, 0x00, 0x20,
+	0x01, 0x20, 0x02, 0xa2, 0xa0, 0x39, 0x03, 0x00, 0x20, 0x00, 0x20, 0x00,
+	0x2b, 0x03, 0x08, 0x20, 0x02, 0xa0, 0x39, 0x03, 0x08, 0x0b, 0x22, 0x00,
+	0x20, 0x00, 0x20, 0x00, 0x2b, 0x03, 0x00, 0x20, 0x01, 0x2b, 0x03, 0x00,
+	0xa0, 0x39, 0x03, 0x00, 0x20, 0x00, 0x20, 0x00, 0x2b, 0x03, 0x08, 0x20,
+	0x01, 0x2b, 0x03, 0x08, 0xa0, 0x39, 0x03, 0x08, 0x0b, 0x0d, 0x00, 0x20,
+	0x00, 0x2b, 0x03, 0x00, 0x20, 0x00, 0x2b, 0x03, 0x08, 0xa3, 0x0b, 0x0b,
+	0x0a, 0x01, 0x00, 0x41, 0x00, 0x0b, 0x04, 0x00, 0x04, 0x00, 0x00, 0x00,
+	0xce, 0x01, 0x10, 0x6d, 0x69, 0x6e, 0x64, 0x62, 0x2e, 0x70, 0x72, 0x6f,
+	0x63, 0x65, 0x64, 0x75, 0x72, 0x65, 0x73, 0x7b, 0x22, 0x6e, 0x61, 0x6d,
+	0x65, 0x22, 0x3a, 0x22, 0x77, 0x65, 0x69, 0x67, 0x68, 0x74, 0x65, 0x64,
+	0x5f, 0x61, 0x76, 0x67, 0x22, 0x2c, 0x22, 0x6b, 0x69, 0x6e, 0x64, 0x22,
+	0x3a, 0x22, 0x61, 0x67, 0x67, 0x72, 0x65, 0x67, 0x61, 0x74, 0x65, 0x22,
+	0x2c, 0x22, 0x70, 0x61, 0x72, 0x61, 0x6d, 0x73, 0x22, 0x3a, 0x5b, 0x7b,
+	0x22, 0x6e, 0x61, 0x6d, 0x65, 0x22, 0x3a, 0x22, 0x76, 0x61, 0x6c, 0x75,
+	0x65, 0x22, 0x2c, 0x22, 0x74, 0x79, 0x70, 0x65, 0x22, 0x3a, 0x22, 0x46,
+	0x4c, 0x4f, 0x41, 0x54, 0x22, 0x7d, 0x2c, 0x7b, 0x22, 0x6e, 0x61, 0x6d,
+	0x65, 0x22, 0x3a, 0x22, 0x77, 0x65, 0x69, 0x67, 0x68, 0x74, 0x22, 0x2c,
+	0x22, 0x74, 0x79, 0x70, 0x65, 0x22, 0x3a, 0x22, 0x46, 0x4c, 0x4f, 0x41,
+	0x54, 0x22, 0x7d, 0x5d, 0x2c, 0x22, 0x72, 0x65, 0x74, 0x75, 0x72, 0x6e,
+	0x73, 0x22, 0x3a, 0x22, 0x46, 0x4c, 0x4f, 0x41, 0x54, 0x22, 0x2c, 0x22,
+	0x64, 0x65, 0x73, 0x63, 0x72, 0x69, 0x70, 0x74, 0x69, 0x6f, 0x6e, 0x22,
+	0x3a, 0x22, 0x41, 0x76, 0x65, 0x72, 0x61, 0x67, 0x65, 0x20, 0x6f, 0x66,
+	0x20, 0x76, 0x61, 0x6c, 0x75, 0x65, 0x20, 0x77, 0x65, 0x69, 0x67, 0x68,
+	0x74, 0x65, 0x64, 0x20, 0x62, 0x79, 0x20, 0x77, 0x65, 0x69, 0x67, 0x68,
+	0x74, 0x22, 0x7d, 0x0a,
+}
+
+// newAggregateTestAdapter returns an adapter with a sales table and the
+// weighted_avg aggregate, created through SQL
+func newAggregateTestAdapter(t *testing.T, dataDir string) *EngineAdapter {
+	t.Helper()
+
+	adapter, err := NewEngineAdapter(dataDir, false)
+	if err != nil {
+		t.Fatalf("Failed to create adapter: %v", err)
+	}
+
+	if _, err := adapter.Execute(&Statement{Type: CreateDatabase, Database: "shop"}); err != nil {
+		t.Fatalf("CREATE DATABASE failed: %v", err)
+	}
+	if err := adapter.UseDatabase("shop"); err != nil {
+		t.Fatalf("USE DATABASE failed: %v", err)
+	}
+
+	code := base64.StdEncoding.EncodeToString(weightedAvgWASM)
+	for _, sql := range []string{
+		"CREATE AGGREGATE weighted_avg LANGUAGE rust AS '" + code + "'",
+		"CREATE TABLE sales (id INT PRIMARY KEY, region TEXT, price FLOAT, qty FLOAT)",
+		"INSERT INTO sales (id, region, price, qty) VALUES (1, 'east', 10, 1)",
+		"INSERT INTO sales (id, region, price, qty) VALUES (2, 'east', 20, 3)",
+		"INSERT INTO sales (id, region, price, qty) VALUES (3, 'east', 40, 1)",
+		"INSERT INTO sales (id, region, price, qty) VALUES (4, 'west', 4, 5)",
+	} {
+		execSQL(t, adapter, sql)
+	}
+
+	return adapter
+}
+
+func TestWASMEngine_IntrospectAggregate(t *testing.T) {
+	engine, err := NewWASMEngine(DefaultWASMConfig())
+	if err != nil {
+		t.Fatalf("Failed to create WASM engine: %v", err)
+	}
+	defer engine.Close()
+
+	agg, err := engine.IntrospectAggregate(weightedAvgWASM, "weighted_avg")
+	if err != nil {
+		t.Fatalf("IntrospectAggregate() error = %v", err)
+	}
+	if len(agg.Params) != 2 || agg.Params[0].Name != "value" || agg.Params[1].DataType != "FLOAT" {
+		t.Errorf("Unexpected params: %+v", agg.Params)
+	}
+	if agg.ReturnType != "FLOAT" || agg.Description != "Average of value weighted by weight" {
+		t.Errorf("Unexpected signature: %+v", agg)
+	}
+
+	_, err = engine.IntrospectAggregate(simpleAddWASM, "add")
+	if err == nil || !strings.Contains(err.Error(), "missing add_init, add_step, add_merge, add_finalize") {
+		t.Errorf("Expected missing exports error, got: %v", err)
+	}
+
+	// The entry points are not listed as module functions
+	if funcs, err := engine.IntrospectModule(weightedAvgWASM); err != nil || len(funcs) != 0 {
+		t.Errorf("Expected no module functions, got %+v, %v", funcs, err)
+	}
+}
+
+func TestEngineAdapter_SelectUserAggregate(t *testing.T) {
+	adapter := newAggregateTestAdapter(t, t.TempDir())
+	defer adapter.Close()
+
+	result := execSQL(t, adapter, "SELECT region, weighted_avg(price, qty) AS avg_price FROM sales GROUP BY region")
+	if !strings.Contains(result, "avg_price") || !strings.Contains(result, "| 22 ") || !strings.Contains(result, "| 4 ") {
+		t.Errorf("Expected east 22 and west 4, got:\n%s", result)
+	}
+
+	result = execSQL(t, adapter, "SELECT weighted_avg(price, qty) FROM sales WHERE region = 'east'")
+	if !strings.Contains(result, "| 22 ") {
+		t.Errorf("Expected 22, got:\n%s", result)
+	}
+
+	result = execSQL(t, adapter, "SELECT COUNT(*) AS n, weighted_avg(price, qty) AS avg_price FROM sales")
+	if !strings.Contains(result, "| 4 ") || !strings.Contains(result, "| 13 ") {
+		t.Errorf("Expected 4 rows averaging 13, got:\n%s", result)
+	}
+
+	// Aggregates apply to joined rows too
+	execSQL(t, adapter, "CREATE TABLE regions (id INT PRIMARY KEY, name TEXT, zone TEXT)")
+	execSQL(t, adapter, "INSERT INTO regions (id, name, zone) VALUES (1, 'east', 'atlantic')")
+	execSQL(t, adapter, "INSERT INTO regions (id, name, zone) VALUES (2, 'west', 'pacific')")
+	result = execSQL(t, adapter, "SELECT zone, weighted_avg(price, qty) AS avg_price FROM sales JOIN regions ON sales.region = regions.name GROUP BY zone")
+	if !strings.Contains(result, "atlantic") || !strings.Contains(result, "| 22 ") || !strings.Contains(result, "| 4 ") {
+		t.Errorf("Expected atlantic 22 and pacific 4, got:\n%s", result)
+	}
+}
+
+func TestEngineAdapter_UserAggregateMerge(t *testing.T) {
+	adapter := newAggregateTestAdapter(t, t.TempDir())
+	defer adapter.Close()
+
+	// The three east rows are stepped into two partial states, which only
+	// average to 22 once merged
+	defer func(rows int) { aggregateBatchRows = rows }(aggregateBatchRows)
+	aggregateBatchRows = 2
+
+	result := execSQL(t, adapter, "SELECT region, weighted_avg(price, qty) AS avg_price FROM sales GROUP BY region")
+	if !strings.Contains(result, "| 22 ") || !strings.Contains(result, "| 4 ") {
+		t.Errorf("Expected east 22 and west 4, got:\n%s", result)
+	}
+}
+
+func TestEngineAdapter_UserAggregateErrors(t *testing.T) {
+	adapter := newAggregateTestAdapter(t, t.TempDir())
+	defer adapter.Close()
+
+	tests := []struct {
+		name    string
+		sql     string
+		wantErr string
+	}{
+		{"duplicate", "CREATE AGGREGATE weighted_avg LANGUAGE rust AS '" + base64.StdEncoding.EncodeToString(weightedAvgWASM) + "'", "already exists"},
+		{"missing exports", "CREATE AGGREGATE add LANGUAGE rust AS '" + base64.StdEncoding.EncodeToString(simpleAddWASM) + "'", "missing"},
+		{"unknown column", "SELECT weighted_avg(price, nope) FROM sales", "column 'nope' does not exist"},
+		{"mixed with procedure", "SELECT weighted_avg(price, qty), total(price) FROM sales", "alongside aggregate 'weighted_avg'"},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			stmt, err := NewParser().Parse(tt.sql)
+			if err != nil {
+				t.Fatalf("Parse() error = %v", err)
+			}
+			_, err = adapter.Execute(stmt)
+			if err == nil || !strings.Contains(err.Error(), tt.wantErr) {
+				t.Errorf("Expected error containing %q, got: %v", tt.wantErr, err)
+			}
+		})
+	}
+}
+
+func TestEngineAdapter_UserAggregatePersistence(t *testing.T) {
+	dataDir := t.TempDir()
+	adapter := newAggregateTestAdapter(t, dataDir)
+	adapter.Close()
+
+	reopened, err := NewEngineAdapter(dataDir, false)
+	if err != nil {
+		t.Fatalf("Failed to reopen adapter: %v", err)
+	}
+	defer reopened.Close()
+	if err := reopened.UseDatabase("shop"); err != nil {
+		t.Fatalf("USE DATABASE failed: %v", err)
+	}
+
+	result := execSQL(t, reopened, "SELECT weighted_avg(price, qty) FROM sales WHERE region = 'west'")
+	if !strings.Contains(result, "| 4 ") {
+		t.Errorf("Expected 4 after reload, got:\n%s", result)
+	}
+
+	execSQL(t, reopened, "DROP AGGREGATE weighted_avg")
+	if _, err := reopened.pagedEngine.GetAggregate("weighted_avg"); err == nil {
+		t.Error("Expected aggregate to be dropped")
+	}
+	execSQL(t, reopened, "DROP AGGREGATE IF EXISTS weighted_avg")
+}
@@ -0,0 +1,170 @@
+package mindb
+
+import (
+	"bytes"
+	"crypto/sha256"
+	"encoding/hex"
+	"fmt"
+	"os"
+	"path/filepath"
+	"runtime/debug"
+	"strings"
+	"sync/atomic"
+
+	"github.com/bytecodealliance/wasmtime-go/v25"
+)
+
+// Compiled modules are cached on disk as serialized Wasmtime artifacts, so a
+// restart or a repeated CREATE skips compilation. An artifact's file name is
+// the SHA-256 of the WASM bytecode followed by a digest of the settings that
+// shape the compiled code: the Wasmtime version, fuel metering and epoch
+// interruption. A change to any gives every module a new name. Stale
+// artifacts, of another configuration or of bytecode no longer stored, are
+// removed when the engine starts.
+
+// wasmtimeModule is the Go module providing Wasmtime
+const wasmtimeModule = "github.com/bytecodealliance/wasmtime-go/v25"
+
+// artifactSuffix is the file extension of cached artifacts
+const artifactSuffix = ".cwasm"
+
+// epochInterruption is whether compiled code checks the epoch deadline,
+// which enforces MaxExecutionTime. It is always on, but shapes the code.
+const epochInterruption = true
+
+// wasmtimeVersion returns the version of the Wasmtime bindings this binary
+// was built with
+func wasmtimeVersion() string {
+	if info, ok := debug.ReadBuildInfo(); ok {
+		for _, dep := range info.Deps {
+			if dep.Path == wasmtimeModule {
+				if dep.Replace != nil {
+					dep = dep.Replace
+				}
+				return dep.Path + "@" + dep.Version
+			}
+		}
+	}
+	return wasmtimeModule
+}
+
+// artifactConfigKey digests the Wasmtime version and the configuration
+// affecting code generation. Limits enforced at run time, such as FuelLimit
+// and MaxExecutionTime, are left out, so changing them keeps the cache.
+func artifactConfigKey(config *WASMConfig) string {
+	key := fmt.Sprintf("%s|fuel=%t|epoch=%t", wasmtimeVersion(), config.EnableFuelMetering, epochInterruption)
+	sum := sha256.Sum256([]byte(key))
+	return hex.EncodeToString(sum[:8])
+}
+
+// artifactPath returns where the compiled artifact of code is cached
+func (w *WASMEngine) artifactPath(code []byte) string {
+	return filepath.Join(w.config.CacheDir, procedureDigest(code)+"-"+w.configKey+artifactSuffix)
+}
+
+// loadArtifact returns the cached compiled module for code, or nil if there
+// is none usable. An artifact is prefixed with its own SHA-256 so that a
+// damaged file is recompiled rather than deserialized.
+func (w *WASMEngine) loadArtifact(code []byte) *wasmtime.Module {
+	data, err := os.ReadFile(w.artifactPath(code))
+	if err != nil || len(data) < sha256.Size {
+		return nil
+	}
+
+	sum, artifact := data[:sha256.Size], data[sha256.Size:]
+	if actual := sha256.Sum256(artifact); !bytes.Equal(sum, actual[:]) {
+		return nil
+	}
+
+	module, err := wasmtime.NewModuleDeserialize(w.engine, artifact)
+	if err != nil {
+		return nil
+	}
+	return module
+}
+
+// storeArtifact caches a compiled module. Failing to do so only costs a
+// compilation later, so errors are reported and otherwise ignored.
+func (w *WASMEngine) storeArtifact(code []byte, module *wasmtime.Module) {
+	artifact, err := module.Serialize()
+	if err != nil {
+		fmt.Printf("Warning: failed to serialize WASM module: %v\n", err)
+		return
+	}
+
+	if err := os.MkdirAll(w.config.CacheDir, 0755); err != nil {
+		fmt.Printf("Warning: failed to create module cache directory: %v\n", err)
+		return
+	}
+
+	sum := sha256.Sum256(artifact)
+	if err := replaceFile(w.artifactPath(code), append(sum[:], artifact...)); err != nil {
+		fmt.Printf("Warning: failed to cache compiled module: %v\n", err)
+	}
+}
+
+// compile compiles WASM bytecode, going through the artifact cache if one
+// is configured
+func (w *WASMEngine) compile(code []byte) (*wasmtime.Module, error) {
+	if w.config.CacheDir != "" {
+		if module := w.loadArtifact(code); module != nil {
+			atomic.AddUint64(&w.cacheHits, 1)
+			return module, nil
+		}
+		atomic.AddUint64(&w.cacheMisses, 1)
+	}
+
+	module, err := wasmtime.NewModule(w.engine, code)
+	if err != nil {
+		return nil, fmt.Errorf("%w: %w", ErrModuleCompile, err)
+	}
+
+	if w.config.CacheDir != "" {
+		w.storeArtifact(code, module)
+	}
+	return module, nil
+}
+
+// pruneArtifacts removes cached artifacts built by another Wasmtime version
+// or configuration
+func (w *WASMEngine) pruneArtifacts() {
+	current := "-" + w.configKey + artifactSuffix
+	w.removeArtifacts(func(name string) bool {
+		return strings.HasSuffix(name, current)
+	})
+}
+
+// PruneArtifacts removes cached artifacts that no cached module was compiled
+// from: those of bytecode since replaced or dropped, as well as those of
+// another configuration. Call it once every stored module is compiled.
+func (w *WASMEngine) PruneArtifacts() {
+	if w.config.CacheDir == "" {
+		return
+	}
+
+	w.mu.RLock()
+	used := make(map[string]bool, len(w.digests))
+	for _, digest := range w.digests {
+		used[digest+"-"+w.configKey+artifactSuffix] = true
+	}
+	w.mu.RUnlock()
+
+	w.removeArtifacts(func(name string) bool {
+		return used[name]
+	})
+}
+
+// removeArtifacts removes the files of the cache directory not kept
+func (w *WASMEngine) removeArtifacts(keep func(name string) bool) {
+	files, err := os.ReadDir(w.config.CacheDir)
+	if err != nil {
+		return
+	}
+
+	for _, file := range files {
+		if file.IsDir() || keep(file.Name()) {
+			continue
+		}
+		os.Remove(filepath.Join(w.config.CacheDir, file.Name()))
+	}
+}
@@ -0,0 +1,137 @@
+package mindb
+
+import (
+	"os"
+	"path/filepath"
+	"testing"
+)
+
+func TestWASMEngine_ArtifactCache(t *testing.T) {
+	config := DefaultWASMConfig()
+	config.CacheDir = t.TempDir()
+
+	engine, err := NewWASMEngine(config)
+	if err != nil {
+		t.Fatalf("Failed to create WASM engine: %v", err)
+	}
+	if err := engine.CompileModule("add", simpleAddWASM); err != nil {
+		t.Fatalf("Failed to compile module: %v", err)
+	}
+	if stats := engine.GetStats(); stats["cache_misses"] != uint64(1) || stats["cache_hits"] != uint64(0) {
+		t.Errorf("Expected a cache miss on first compile, got %v", stats)
+	}
+	if _, err := os.Stat(engine.artifactPath(simpleAddWASM)); err != nil {
+		t.Fatalf("Expected compiled artifact to be cached: %v", err)
+	}
+	engine.Close()
+
+	// A new engine with the same config loads the artifact
+	engine, err = NewWASMEngine(config)
+	if err != nil {
+		t.Fatalf("Failed to create WASM engine: %v", err)
+	}
+	if err := engine.CompileModule("add", simpleAddWASM); err != nil {
+		t.Fatalf("Failed to load cached module: %v", err)
+	}
+	if stats := engine.GetStats(); stats["cache_hits"] != uint64(1) {
+		t.Errorf("Expected a cache hit after restart, got %v", stats)
+	}
+	add := &StoredProcedure{Name: "add", Params: []Column{{Name: "a", DataType: "INT"}, {Name: "b", DataType: "INT"}}, ReturnType: "INT"}
+	result, err := engine.ExecuteProcedure("add", add, nil, 2, 3)
+	if err != nil || result.(int32) != 5 {
+		t.Errorf("Expected cached module to run, got %v (%v)", result, err)
+	}
+
+	// A damaged artifact is recompiled rather than loaded
+	artifact := engine.artifactPath(simpleAddWASM)
+	data, _ := os.ReadFile(artifact)
+	data[len(data)-1] ^= 0xff
+	os.WriteFile(artifact, data, 0644)
+	if err := engine.CompileModule("add", simpleAddWASM); err != nil {
+		t.Fatalf("Failed to recompile module: %v", err)
+	}
+	if stats := engine.GetStats(); stats["cache_misses"] != uint64(1) {
+		t.Errorf("Expected a damaged artifact to miss, got %v", stats)
+	}
+	engine.Close()
+
+	// A limit enforced at run time does not affect the artifact...
+	config.FuelLimit++
+	engine, err = NewWASMEngine(config)
+	if err != nil {
+		t.Fatalf("Failed to create WASM engine: %v", err)
+	}
+	if engine.artifactPath(simpleAddWASM) != artifact {
+		t.Error("Expected the artifact name to survive a FuelLimit change")
+	}
+	engine.Close()
+
+	// ...but a setting that shapes the compiled code invalidates and
+	// removes it
+	config.EnableFuelMetering = !config.EnableFuelMetering
+	engine, err = NewWASMEngine(config)
+	if err != nil {
+		t.Fatalf("Failed to create WASM engine: %v", err)
+	}
+	defer engine.Close()
+	if _, err := os.Stat(artifact); !os.IsNotExist(err) {
+		t.Error("Expected artifact of the old config to be removed")
+	}
+	if engine.artifactPath(simpleAddWASM) == artifact {
+		t.Error("Expected a new artifact name for the new config")
+	}
+	files, _ := os.ReadDir(config.CacheDir)
+	if len(files) != 0 {
+		t.Errorf("Expected an empty cache, got %d files", len(files))
+	}
+}
+
+func TestPagedEngine_ModuleCache(t *testing.T) {
+	dataDir := t.TempDir()
+
+	engine, err := NewPagedEngine(dataDir)
+	if err != nil {
+		t.Fatalf("Failed to create engine: %v", err)
+	}
+	params := []Column{{Name: "amount", DataType: "INT"}}
+	if err := engine.CreateProcedure(&StoredProcedure{Name: "discount", Language: "wasm", Code: discountV1WASM, Params: params, ReturnType: "INT"}); err != nil {
+		t.Fatalf("Failed to create procedure: %v", err)
+	}
+	engine.Close()
+
+	cached, _ := filepath.Glob(filepath.Join(dataDir, "module_cache", "*"+artifactSuffix))
+	if len(cached) != 1 {
+		t.Fatalf("Expected 1 cached artifact, got %d", len(cached))
+	}
+
+	// Startup loads the procedure from its artifact
+	engine, err = NewPagedEngine(dataDir)
+	if err != nil {
+		t.Fatalf("Failed to reopen engine: %v", err)
+	}
+
+	if stats := engine.wasmEngine.GetStats(); stats["cache_hits"] != uint64(1) || stats["cache_misses"] != uint64(0) {
+		t.Errorf("Expected startup to hit the cache, got %v", stats)
+	}
+	if callDiscount(t, engine) != 90 {
+		t.Error("Expected cached procedure to run")
+	}
+
+	// The artifact of replaced bytecode is removed at the next startup
+	if err := engine.ReplaceProcedure(&StoredProcedure{Name: "discount", Language: "wasm", Code: discountV2WASM, Params: params, ReturnType: "INT"}); err != nil {
+		t.Fatalf("Failed to replace procedure: %v", err)
+	}
+	engine.Close()
+
+	engine, err = NewPagedEngine(dataDir)
+	if err != nil {
+		t.Fatalf("Failed to reopen engine: %v", err)
+	}
+	defer engine.Close()
+
+	current := engine.wasmEngine.artifactPath(discountV2WASM)
+	cached, _ = filepath.Glob(filepath.Join(dataDir, "module_cache", "*"+artifactSuffix))
+	if len(cached) != 1 || cached[0] != current {
+		t.Errorf("Expected only the artifact of the current bytecode, got %v", cached)
+	}
+}
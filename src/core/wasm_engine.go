@@ -5,48 +5,100 @@ import (
 	"fmt"
 	"strings"
 	"sync"
+	"sync/atomic"
 	"time"
 
 	"github.com/bytecodealliance/wasmtime-go/v25"
+	"github.com/rs/zerolog"
 )
 
 // WASMEngine manages WASM stored procedures
 type WASMEngine struct {
 	engine  *wasmtime.Engine
 	modules map[string]*wasmtime.Module // Cached compiled modules
+	digests map[string]string           // Bytecode digest of each cached module
+	pools   map[string]*instancePool    // Reusable instances per module
 	mu      sync.RWMutex
 	config  *WASMConfig
+	stop    chan struct{} // Stops the epoch ticker
+	closing sync.Once
+
+	logger    zerolog.Logger         // Destination of guest log lines
+	logLimits map[string]*logLimiter // Guest log rate limits per procedure
+	logMu     sync.Mutex
+
+	configKey   string // Names artifacts built by this Wasmtime version and config
+	cacheHits   uint64 // Modules loaded from the artifact cache
+	cacheMisses uint64 // Modules compiled with the artifact cache enabled
 }
 
 // WASMConfig configures the WASM engine
 type WASMConfig struct {
-	MaxMemoryBytes    uint64        // Maximum memory per instance (default: 100MB)
+	MaxMemoryBytes    uint64        // Maximum linear memory per instance (default: 100MB)
+	MaxTableElements  uint64        // Maximum elements per table (default: 10000)
 	MaxExecutionTime  time.Duration // Maximum execution time (default: 5s)
-	MaxInstances      int           // Maximum concurrent instances (default: 100)
+	MaxInstances      int           // Maximum instances per module, idle or in use (default: 100)
+	MaxEmittedRows    int           // Maximum rows a table-valued procedure may emit per call (default: 100000)
 	EnableFuelMetering bool         // Enable fuel-based execution limits
 	FuelLimit         uint64        // Fuel limit per execution
+	CacheDir          string        // Directory for precompiled module artifacts (default: none, no caching)
+	LogRateLimit      int           // Guest log lines per second per procedure, 0 for no limit (default: 100)
 }
 
 // StoredProcedure represents a WASM stored procedure
 type StoredProcedure struct {
 	Name        string
 	Language    string    // "wasm", "rust", "go", etc.
-	Code        []byte    // WASM bytecode
+	Code        []byte    `json:"-"` // WASM bytecode, stored as a blob named by Digest
+	Digest      string    // SHA-256 of Code (of the module, for module functions), hex encoded
 	Params      []Column  // Parameter definitions
 	ReturnType  string    // Return type (e.g., "INT", "TEXT", "FLOAT")
+	OnNull      string    // OnNullStrict, OnNullCalled, or empty to reject NULL arguments
+	Security    string    // SecurityDefiner or SecurityInvoker; empty means SecurityInvoker
+	Owner       string    // User that created the procedure, as username@host
 	Description string
+	Version     int // Revision number, starting at 1
 	CreatedAt   time.Time
 	UpdatedAt   time.Time
 }
 
+// How a procedure handles NULL arguments, as in CREATE PROCEDURE ... STRICT
+// or CALLED ON NULL INPUT
+const (
+	OnNullStrict = "STRICT" // A NULL argument makes the result NULL without calling the procedure
+	OnNullCalled = "CALLED" // NULL arguments are passed as zero values, flagged for mindb_arg_is_null
+)
+
+// StoredModule is a WASM module whose exported functions are each callable
+// from SQL as module.function
+type StoredModule struct {
+	Name        string
+	Language    string
+	Code        []byte                      `json:"-"` // WASM bytecode, stored as a blob named by Digest
+	Digest      string                      // SHA-256 of Code, hex encoded
+	Functions   map[string]*StoredProcedure // Exported functions by name (Code unset)
+	Description string
+	CreatedAt   time.Time
+	UpdatedAt   time.Time
+}
+
+// moduleCacheKey is the WASMEngine cache key for a stored module, kept apart
+// from procedure names
+func moduleCacheKey(name string) string {
+	return "module:" + name
+}
+
 // DefaultWASMConfig returns default WASM configuration
 func DefaultWASMConfig() *WASMConfig {
 	return &WASMConfig{
 		MaxMemoryBytes:     100 * 1024 * 1024, // 100MB
+		MaxTableElements:   10000,
 		MaxExecutionTime:   5 * time.Second,
 		MaxInstances:       100,
+		MaxEmittedRows:     100000,
 		EnableFuelMetering: true,
 		FuelLimit:          10000000, // 10 million - increased for complex operations
+		LogRateLimit:       100,
 	}
 }
 
@@ -64,32 +116,55 @@ func NewWASMEngine(config *WASMConfig) (*WASMEngine, error) {
 		engineConfig.SetConsumeFuel(true)
 	}
 
+	// Epoch interruption stops calls that run past MaxExecutionTime
+	engineConfig.SetEpochInterruption(epochInterruption)
+
 	engine := wasmtime.NewEngineWithConfig(engineConfig)
 
-	return &WASMEngine{
-		engine:  engine,
-		modules: make(map[string]*wasmtime.Module),
-		config:  config,
-	}, nil
+	w := &WASMEngine{
+		engine:    engine,
+		modules:   make(map[string]*wasmtime.Module),
+		digests:   make(map[string]string),
+		pools:     make(map[string]*instancePool),
+		config:    config,
+		stop:      make(chan struct{}),
+		configKey: artifactConfigKey(config),
+		logger:    zerolog.Nop(),
+		logLimits: make(map[string]*logLimiter),
+	}
+	if config.CacheDir != "" {
+		w.pruneArtifacts()
+	}
+	go w.tickEpochs()
+
+	return w, nil
 }
 
-// CompileModule compiles WASM bytecode into a module
+// CompileModule compiles WASM bytecode into a module. A module already
+// cached under the name keeps serving calls until the new one has compiled,
+// then is replaced at once.
 func (w *WASMEngine) CompileModule(name string, code []byte) error {
-	w.mu.Lock()
-	defer w.mu.Unlock()
-
-	// Compile the WASM module
-	module, err := wasmtime.NewModule(w.engine, code)
+	module, err := w.compile(code)
 	if err != nil {
-		return fmt.Errorf("failed to compile WASM module: %w", err)
+		return err
 	}
 
-	// Cache the compiled module
-	w.modules[name] = module
-
+	w.install(name, code, module)
 	return nil
 }
 
+// install caches module, compiled from code, under name. Instances of a
+// previous version are dropped with its pool; calls already using them
+// finish on that version.
+func (w *WASMEngine) install(name string, code []byte, module *wasmtime.Module) {
+	w.mu.Lock()
+	defer w.mu.Unlock()
+
+	w.modules[name] = module
+	w.digests[name] = procedureDigest(code)
+	w.pools[name] = newInstancePool(module, w.config.MaxInstances)
+}
+
 // IntrospectFunction inspects a WASM function's signature
 func (w *WASMEngine) IntrospectFunction(code []byte, functionName string) (params []Column, returnType string, err error) {
 	// Compile the module temporarily
@@ -113,6 +188,58 @@ func (w *WASMEngine) IntrospectFunction(code []byte, functionName string) (param
 		return nil, "", fmt.Errorf("function '%s' not found in module", functionName)
 	}
 
+	params, returnType = functionSignature(code, functionName, funcType)
+	return params, returnType, nil
+}
+
+// IntrospectModule inspects every exported function of a WASM module,
+// returning one procedure definition per function keyed by export name
+func (w *WASMEngine) IntrospectModule(code []byte) (map[string]*StoredProcedure, error) {
+	module, err := wasmtime.NewModule(w.engine, code)
+	if err != nil {
+		return nil, fmt.Errorf("failed to compile WASM module: %w", err)
+	}
+
+	functions := make(map[string]*StoredProcedure)
+	aggregates := aggregateExports(code)
+	digest := procedureDigest(code)
+	for _, exp := range module.Exports() {
+		// mindb_alloc and friends belong to the calling convention, and
+		// aggregate entry points only work together
+		funcType := exp.Type().FuncType()
+		if funcType == nil || strings.HasPrefix(exp.Name(), "mindb_") || aggregates[exp.Name()] {
+			continue
+		}
+
+		params, returnType := functionSignature(code, exp.Name(), funcType)
+		proc := &StoredProcedure{
+			Name:       exp.Name(),
+			Params:     params,
+			ReturnType: returnType,
+			Digest:     digest,
+		}
+		if sig := procedureSignature(code, exp.Name()); sig != nil {
+			proc.OnNull = sig.onNull()
+			proc.Description = sig.Description
+		}
+		functions[exp.Name()] = proc
+	}
+
+	if len(functions) == 0 {
+		return nil, fmt.Errorf("module exports no functions")
+	}
+
+	return functions, nil
+}
+
+// functionSignature derives parameter definitions and the return type of an
+// exported function
+func functionSignature(code []byte, functionName string, funcType *wasmtime.FuncType) (params []Column, returnType string) {
+	// Prefer the signature recorded by the SDK's #[procedure] attribute
+	if sig := procedureSignature(code, functionName); sig != nil {
+		return sig.Columns(), sig.Returns
+	}
+
 	// Extract parameter types
 	paramTypes := funcType.Params()
 	params = make([]Column, len(paramTypes))
@@ -139,7 +266,7 @@ func (w *WASMEngine) IntrospectFunction(code []byte, functionName string) (param
 		returnType = "VOID"
 	}
 
-	return params, returnType, nil
+	return params, returnType
 }
 
 // wasmTypeToSQL converts WASM value type to SQL type
@@ -150,7 +277,7 @@ func wasmTypeToSQL(kind wasmtime.ValKind) string {
 	case wasmtime.KindI64:
 		return "BIGINT"
 	case wasmtime.KindF32:
-		return "FLOAT"
+		return "REAL"
 	case wasmtime.KindF64:
 		return "FLOAT"
 	default:
@@ -231,155 +358,68 @@ func (w *WASMEngine) RemoveModule(name string) {
 	defer w.mu.Unlock()
 
 	delete(w.modules, name)
+	delete(w.digests, name)
+	delete(w.pools, name)
 }
 
 // Execute executes a stored procedure
 func (w *WASMEngine) Execute(procName string, functionName string, args ...interface{}) (interface{}, error) {
-	// Get compiled module
-	module, exists := w.GetModule(procName)
-	if !exists {
-		return nil, fmt.Errorf("stored procedure '%s' not found", procName)
-	}
-
-	// Create a new store for this execution
-	store := wasmtime.NewStore(w.engine)
-
-	// Set fuel limit if enabled
-	if w.config.EnableFuelMetering {
-		store.SetFuel(w.config.FuelLimit)
-	}
-
-	// Create linker for host functions
-	linker := wasmtime.NewLinker(w.engine)
-
-	// TODO: Add host functions here (database access, etc.)
-
-	// Instantiate the module
-	instance, err := linker.Instantiate(store, module)
-	if err != nil {
-		return nil, fmt.Errorf("failed to instantiate module: %w", err)
-	}
-
-	// Get the exported function
-	fn := instance.GetFunc(store, functionName)
-	if fn == nil {
-		return nil, fmt.Errorf("function '%s' not found in module", functionName)
-	}
-
-	// Convert Go args to WASM values
-	wasmArgs := make([]interface{}, len(args))
-	copy(wasmArgs, args)
-
-	// Execute with timeout
-	resultChan := make(chan interface{}, 1)
-	errorChan := make(chan error, 1)
-
-	go func() {
-		result, err := fn.Call(store, wasmArgs...)
-		if err != nil {
-			errorChan <- err
-			return
-		}
-		resultChan <- result
-	}()
-
-	// Wait for result or timeout
-	select {
-	case result := <-resultChan:
-		return result, nil
-	case err := <-errorChan:
-		return nil, fmt.Errorf("execution error: %w", err)
-	case <-time.After(w.config.MaxExecutionTime):
-		return nil, fmt.Errorf("execution timeout after %v", w.config.MaxExecutionTime)
-	}
+	// Database access fails without a context
+	return w.execute(procName, functionName, nil, nil, args)
 }
 
 // ExecuteWithContext executes a stored procedure with additional context
 func (w *WASMEngine) ExecuteWithContext(procName string, functionName string, ctx *ExecutionContext, args ...interface{}) (interface{}, error) {
-	// Get compiled module
-	module, exists := w.GetModule(procName)
-	if !exists {
-		return nil, fmt.Errorf("stored procedure '%s' not found", procName)
-	}
-
-	// Create a new store for this execution
-	store := wasmtime.NewStore(w.engine)
-
-	// Set fuel limit if enabled
-	if w.config.EnableFuelMetering {
-		store.SetFuel(w.config.FuelLimit)
-	}
-
-	// Create linker with host functions
-	linker := wasmtime.NewLinker(w.engine)
+	// Host functions get database access through ctx
+	return w.execute(procName, functionName, nil, ctx, args)
+}
 
-	// Add host functions for database access
-	if ctx != nil && ctx.Engine != nil {
-		w.addHostFunctions(linker, store, ctx)
+// ExecuteProcedure calls proc.Name in the module cached under moduleName,
+// passing arguments according to the declared parameter types. Unlike
+// ExecuteWithContext, this lets TEXT and BLOB parameters go through guest
+// memory.
+func (w *WASMEngine) ExecuteProcedure(moduleName string, proc *StoredProcedure, ctx *ExecutionContext, args ...interface{}) (interface{}, error) {
+	if proc.OnNull == OnNullStrict && hasNullArg(args) {
+		return nil, nil
 	}
 
-	// Instantiate the module
-	instance, err := linker.Instantiate(store, module)
+	session, err := w.openSession(moduleName, ctx)
 	if err != nil {
-		return nil, fmt.Errorf("failed to instantiate module: %w", err)
+		return nil, err
 	}
+	defer session.close()
 
-	// Get the exported function
-	fn := instance.GetFunc(store, functionName)
-	if fn == nil {
-		return nil, fmt.Errorf("function '%s' not found in module", functionName)
+	session.calledOnNull = proc.OnNull == OnNullCalled
+	result, err := session.call(proc.Name, proc.Params, args)
+	if ctx != nil {
+		ctx.FuelUsed = session.fuel
 	}
+	return result, err
+}
 
-	// Convert Go args to WASM values
-	wasmArgs := make([]interface{}, len(args))
-	copy(wasmArgs, args)
-
-	// Execute with timeout
-	resultChan := make(chan interface{}, 1)
-	errorChan := make(chan error, 1)
-
-	go func() {
-		result, err := fn.Call(store, wasmArgs...)
-		if err != nil {
-			errorChan <- err
-			return
-		}
-		resultChan <- result
-	}()
-
-	// Wait for result or timeout
-	select {
-	case result := <-resultChan:
-		return result, nil
-	case err := <-errorChan:
-		return nil, fmt.Errorf("execution error: %w", err)
-	case <-time.After(w.config.MaxExecutionTime):
-		return nil, fmt.Errorf("execution timeout after %v", w.config.MaxExecutionTime)
+// execute calls one function of a compiled module on a pooled instance.
+// params, when known, are the declared parameter types.
+func (w *WASMEngine) execute(procName string, functionName string, params []Column, ctx *ExecutionContext, args []interface{}) (interface{}, error) {
+	session, err := w.openSession(procName, ctx)
+	if err != nil {
+		return nil, err
 	}
+	defer session.close()
+
+	return session.call(functionName, params, args)
 }
 
 // ExecutionContext provides context for WASM execution
 type ExecutionContext struct {
-	Engine      *PagedEngine
-	Database    string
-	Transaction *Transaction
-	UserID      string
-}
-
-// addHostFunctions adds host functions that WASM can call
-func (w *WASMEngine) addHostFunctions(linker *wasmtime.Linker, store wasmtime.Storelike, ctx *ExecutionContext) {
-	// Example: Add a simple logging function
-	// linker.FuncWrap("env", "log", func(caller *wasmtime.Caller, ptr int32, len int32) {
-	//     // Read string from WASM memory and log it
-	//     // Implementation depends on memory access patterns
-	// })
-
-	// TODO: Add more host functions:
-	// - mindb_query: Execute SQL query
-	// - mindb_get_row: Get single row
-	// - mindb_insert: Insert row
-	// - mindb_update: Update rows
-	// - mindb_delete: Delete rows
+	Engine       *PagedEngine
+	Database     string
+	Transaction  *Transaction
+	UserID       string
+	RequestID    string          // ID of the HTTP request that led to the call, if any
+	Trigger      *TriggerEvent   // Set when called as a trigger handler
+	TriggerDepth int             // Trigger handlers the call is nested in, its own included
+	FuelUsed     uint64          // Set by ExecuteProcedure to the fuel the call used
+	Calls        *ProcedureCalls // Successful calls of the statement, audited as it ends
 }
 
 // LoadProcedureFromBase64 loads a procedure from base64-encoded WASM
@@ -397,12 +437,23 @@ func (w *WASMEngine) GetStats() map[string]interface{} {
 	w.mu.RLock()
 	defer w.mu.RUnlock()
 
+	// Instances are counted per module; each module has up to MaxInstances
+	instances := 0
+	for _, pool := range w.pools {
+		instances += len(pool.slots)
+	}
+
 	return map[string]interface{}{
 		"compiled_modules":   len(w.modules),
+		"instances":          instances,
+		"max_instances":      w.config.MaxInstances,
 		"max_memory_bytes":   w.config.MaxMemoryBytes,
+		"max_table_elements": w.config.MaxTableElements,
 		"max_execution_time": w.config.MaxExecutionTime.String(),
 		"fuel_enabled":       w.config.EnableFuelMetering,
 		"fuel_limit":         w.config.FuelLimit,
+		"cache_hits":         atomic.LoadUint64(&w.cacheHits),
+		"cache_misses":       atomic.LoadUint64(&w.cacheMisses),
 	}
 }
 
@@ -411,8 +462,11 @@ func (w *WASMEngine) Close() error {
 	w.mu.Lock()
 	defer w.mu.Unlock()
 
-	// Clear all modules
+	// Clear all modules and their instances
 	w.modules = make(map[string]*wasmtime.Module)
+	w.pools = make(map[string]*instancePool)
+
+	w.closing.Do(func() { close(w.stop) })
 
 	return nil
 }
@@ -0,0 +1,110 @@
+package mindb
+
+import (
+	"errors"
+	"fmt"
+	"time"
+
+	"github.com/bytecodealliance/wasmtime-go/v25"
+)
+
+// SQLSTATE-style codes of procedure errors. Guests raise their own codes
+// through mindb_raise; the host assigns the others when it stops a call.
+const (
+	SQLStateRaiseException  = "P0001" // Raised through mindb_error, or mindb_raise without a code
+	SQLStateExternalRoutine = "38000" // The guest trapped
+	SQLStateProgramLimit    = "54000" // The call used up its fuel
+	SQLStateQueryCanceled   = "57014" // The call exceeded MaxExecutionTime
+)
+
+// ErrInvalidProcedureName is wrapped by the error of storing a procedure
+// under a name that is not an identifier
+var ErrInvalidProcedureName = errors.New("invalid procedure name")
+
+// ErrModuleCompile is wrapped by the error of storing WASM bytecode that
+// Wasmtime rejects
+var ErrModuleCompile = errors.New("failed to compile WASM module")
+
+// ProcedureError is the error a procedure call fails with once the guest
+// is running: either an error the guest raised, or a trap, timeout or fuel
+// exhaustion that ended the call. Code tells them apart.
+type ProcedureError struct {
+	Code    string // Five digits or uppercase letters, as in SQLSTATE
+	Message string
+	Detail  string // Optional; not part of Error()
+	Raised  bool   // The guest raised the error, rather than the host stopping the call
+	err     error  // The trap that ended the call, if any
+}
+
+func (e *ProcedureError) Error() string {
+	if e.Raised {
+		return "procedure error: " + e.Message
+	}
+	return e.Message
+}
+
+func (e *ProcedureError) Unwrap() error {
+	return e.err
+}
+
+// callError converts the error a guest call ended with into a
+// ProcedureError, preferring the error the guest raised before trapping
+func (w *WASMEngine) callError(err error, state *callState) *ProcedureError {
+	var trap *wasmtime.Trap
+	if errors.As(err, &trap) {
+		if code := trap.Code(); code != nil {
+			switch *code {
+			case wasmtime.Interrupt:
+				return timeoutError(w.config.MaxExecutionTime, err)
+			case wasmtime.OutOfFuel:
+				return &ProcedureError{
+					Code:    SQLStateProgramLimit,
+					Message: fmt.Sprintf("execution error: %v", err),
+					err:     err,
+				}
+			}
+		}
+	}
+	if state.errMessage != "" {
+		code := state.errCode
+		if code == "" {
+			code = SQLStateRaiseException
+		}
+		return &ProcedureError{
+			Code:    code,
+			Message: state.errMessage,
+			Detail:  state.errDetail,
+			Raised:  true,
+			err:     err,
+		}
+	}
+	return &ProcedureError{
+		Code:    SQLStateExternalRoutine,
+		Message: fmt.Sprintf("execution error: %v", err),
+		err:     err,
+	}
+}
+
+// timeoutError is the error of a call that ran longer than limit
+func timeoutError(limit time.Duration, err error) *ProcedureError {
+	return &ProcedureError{
+		Code:    SQLStateQueryCanceled,
+		Message: fmt.Sprintf("execution timeout after %v", limit),
+		err:     err,
+	}
+}
+
+// validSQLState reports whether code is five digits or uppercase ASCII
+// letters outside class 00, which means success
+func validSQLState(code string) bool {
+	if len(code) != 5 || code[:2] == "00" {
+		return false
+	}
+	for i := 0; i < len(code); i++ {
+		c := code[i]
+		if !(c >= '0' && c <= '9') && !(c >= 'A' && c <= 'Z') {
+			return false
+		}
+	}
+	return true
+}
@@ -0,0 +1,550 @@
+package mindb
+
+import (
+	"bytes"
+	"encoding/json"
+	"fmt"
+	"sort"
+
+	"github.com/bytecodealliance/wasmtime-go/v25"
+)
+
+// callState carries per-call data shared between host functions and the caller
+type callState struct {
+	errMessage string            // Message recorded by mindb_error or mindb_raise before the guest traps
+	errCode    string            // SQLSTATE-style code recorded by mindb_raise
+	errDetail  string            // Detail recorded by mindb_raise
+	procedure  string            // Name of the function being called, for guest log lines
+	ctx        *ExecutionContext // Database context; nil when called through Execute
+	result     []byte            // Pending result for mindb_result_read
+	output     interface{}       // TEXT (string) or BLOB ([]byte) result set by mindb_return_*
+	rows       []Row             // Rows emitted by mindb_emit_row
+	columns    []string          // Columns of the emitted rows, in order of first appearance
+	nulls      []bool            // Arguments passed as NULL, for mindb_arg_is_null
+	returnNull bool              // Result set to NULL by mindb_return_null
+}
+
+// addGuestImports registers the host functions every procedure may import.
+// These do not need database access, so they are available from Execute too.
+func (w *WASMEngine) addGuestImports(linker *wasmtime.Linker, state *callState) error {
+	// mindb_error(ptr, len): record an error message; the guest traps right after
+	err := linker.FuncWrap("env", "mindb_error", func(caller *wasmtime.Caller, ptr int32, length int32) {
+		msg, err := readGuestMemory(caller, ptr, length)
+		if err != nil {
+			state.errMessage = err.Error()
+			return
+		}
+		state.errMessage = string(msg)
+	})
+	if err != nil {
+		return fmt.Errorf("failed to define mindb_error: %w", err)
+	}
+
+	// mindb_raise(code_ptr, code_len, msg_ptr, msg_len, detail_ptr, detail_len):
+	// record a typed error; the guest traps right after. An empty code means
+	// P0001; an invalid one traps here, reported as a trap.
+	err = linker.FuncWrap("env", "mindb_raise", func(caller *wasmtime.Caller, codePtr, codeLen, msgPtr, msgLen, detailPtr, detailLen int32) *wasmtime.Trap {
+		code, err := readGuestMemory(caller, codePtr, codeLen)
+		if err != nil {
+			return wasmtime.NewTrap(err.Error())
+		}
+		if len(code) > 0 && !validSQLState(string(code)) {
+			return wasmtime.NewTrap(fmt.Sprintf("mindb_raise: invalid SQLSTATE code %q", code))
+		}
+		msg, err := readGuestMemory(caller, msgPtr, msgLen)
+		if err != nil {
+			return wasmtime.NewTrap(err.Error())
+		}
+		detail, err := readGuestMemory(caller, detailPtr, detailLen)
+		if err != nil {
+			return wasmtime.NewTrap(err.Error())
+		}
+		state.errCode = string(code)
+		state.errMessage = string(msg)
+		state.errDetail = string(detail)
+		return nil
+	})
+	if err != nil {
+		return fmt.Errorf("failed to define mindb_raise: %w", err)
+	}
+
+	// mindb_log(level, ptr, len): write a line to the server log. Levels are
+	// numbered as in the Rust log crate, 1 (error) to 5 (trace).
+	err = linker.FuncWrap("env", "mindb_log", func(caller *wasmtime.Caller, level, ptr, length int32) *wasmtime.Trap {
+		zlevel, err := guestLogLevel(level)
+		if err != nil {
+			return wasmtime.NewTrap(err.Error())
+		}
+		msg, err := readGuestMemory(caller, ptr, length)
+		if err != nil {
+			return wasmtime.NewTrap(err.Error())
+		}
+		w.guestLog(state, zlevel, string(msg))
+		return nil
+	})
+	if err != nil {
+		return fmt.Errorf("failed to define mindb_log: %w", err)
+	}
+
+	// mindb_result_read(ptr, len) -> total: copy up to len bytes of the pending
+	// result into guest memory and return its full length. len 0 just queries
+	// the length.
+	err = linker.FuncWrap("env", "mindb_result_read", func(caller *wasmtime.Caller, ptr int32, length int32) int32 {
+		n := int32(len(state.result))
+		if length > n {
+			length = n
+		}
+		if length > 0 {
+			if err := writeGuestMemory(caller, ptr, state.result[:length]); err != nil {
+				return -1
+			}
+		}
+		return n
+	})
+	if err != nil {
+		return fmt.Errorf("failed to define mindb_result_read: %w", err)
+	}
+
+	// mindb_return_text(ptr, len) / mindb_return_blob(ptr, len): set the
+	// call's result. Procedures returning TEXT or BLOB have no WASM result;
+	// the bytes are copied out here so the guest can free its buffer.
+	err = linker.FuncWrap("env", "mindb_return_text", func(caller *wasmtime.Caller, ptr int32, length int32) *wasmtime.Trap {
+		buf, err := readGuestMemory(caller, ptr, length)
+		if err != nil {
+			return wasmtime.NewTrap(err.Error())
+		}
+		state.output = string(buf)
+		return nil
+	})
+	if err != nil {
+		return fmt.Errorf("failed to define mindb_return_text: %w", err)
+	}
+
+	err = linker.FuncWrap("env", "mindb_return_blob", func(caller *wasmtime.Caller, ptr int32, length int32) *wasmtime.Trap {
+		buf, err := readGuestMemory(caller, ptr, length)
+		if err != nil {
+			return wasmtime.NewTrap(err.Error())
+		}
+		state.output = buf
+		return nil
+	})
+	if err != nil {
+		return fmt.Errorf("failed to define mindb_return_blob: %w", err)
+	}
+
+	// mindb_arg_is_null(index) -> 0 | 1: whether the argument at index,
+	// counting from 0, is NULL. Only procedures called on NULL input receive
+	// NULL arguments; they arrive as zero values.
+	err = linker.FuncWrap("env", "mindb_arg_is_null", func(index int32) int32 {
+		if index >= 0 && int(index) < len(state.nulls) && state.nulls[index] {
+			return 1
+		}
+		return 0
+	})
+	if err != nil {
+		return fmt.Errorf("failed to define mindb_arg_is_null: %w", err)
+	}
+
+	// mindb_return_null(): make the call's result NULL, whatever the guest
+	// returns
+	err = linker.FuncWrap("env", "mindb_return_null", func() {
+		state.returnNull = true
+	})
+	if err != nil {
+		return fmt.Errorf("failed to define mindb_return_null: %w", err)
+	}
+
+	// mindb_emit_row(ptr, len) -> status: append a row, given as a JSON
+	// object, to the call's result set. Procedures returning TABLE emit their
+	// rows this way. Returns -1 with the message as the pending result if the
+	// row is invalid or MaxEmittedRows is exceeded.
+	err = linker.FuncWrap("env", "mindb_emit_row", func(caller *wasmtime.Caller, ptr int32, length int32) int32 {
+		state.result = nil
+		if err := state.emitRow(caller, ptr, length, w.config.MaxEmittedRows); err != nil {
+			state.result = []byte(err.Error())
+			return -1
+		}
+		return 0
+	})
+	if err != nil {
+		return fmt.Errorf("failed to define mindb_emit_row: %w", err)
+	}
+
+	return nil
+}
+
+// hostCall wraps a database host function body. On success the returned
+// payload (if any) becomes the pending result and n is handed to the guest;
+// on failure the error message becomes the pending result and the guest gets -1.
+func (s *callState) hostCall(fn func(engine *PagedEngine) (payload []byte, n int32, err error)) int32 {
+	s.result = nil
+	if s.ctx == nil || s.ctx.Engine == nil {
+		s.result = []byte("database access is not available in this context")
+		return -1
+	}
+
+	payload, n, err := fn(s.ctx.Engine)
+	if err != nil {
+		s.result = []byte(err.Error())
+		return -1
+	}
+	s.result = payload
+	return n
+}
+
+// triggerCall wraps a trigger host function body like hostCall, failing
+// when the procedure was not called as a trigger handler
+func (s *callState) triggerCall(fn func(ev *TriggerEvent) (payload []byte, n int32, err error)) int32 {
+	s.result = nil
+	if s.ctx == nil || s.ctx.Trigger == nil {
+		s.result = []byte("trigger data is only available to trigger handlers")
+		return -1
+	}
+
+	payload, n, err := fn(s.ctx.Trigger)
+	if err != nil {
+		s.result = []byte(err.Error())
+		return -1
+	}
+	s.result = payload
+	return n
+}
+
+// addHostFunctions registers the database access imports: mindb_query,
+// mindb_get_row, mindb_insert, mindb_update and mindb_delete. They are always
+// linked so any module can be instantiated; without a database context they
+// fail at call time instead. Strings (SQL, table names) are UTF-8; rows and filters are JSON objects.
+// A filter matches rows whose columns equal every given value; an empty
+// filter matches all rows. All functions return -1 on error with the message
+// left as the pending result. They need the same privileges as the statements
+// they stand for, checked for the context's user: the caller, or the owner
+// of a SECURITY DEFINER procedure.
+func (w *WASMEngine) addHostFunctions(linker *wasmtime.Linker, state *callState) error {
+	// mindb_query(sql_ptr, sql_len) -> len: run a SELECT; result is a JSON array of rows
+	err := linker.FuncWrap("env", "mindb_query", func(caller *wasmtime.Caller, sqlPtr, sqlLen int32) int32 {
+		return state.hostCall(func(engine *PagedEngine) ([]byte, int32, error) {
+			sql, err := readGuestMemory(caller, sqlPtr, sqlLen)
+			if err != nil {
+				return nil, 0, err
+			}
+			stmt, err := NewParser().Parse(string(sql))
+			if err != nil {
+				return nil, 0, err
+			}
+			if stmt.Type != Select {
+				return nil, 0, fmt.Errorf("mindb_query only supports SELECT statements")
+			}
+			if err := state.ctx.authorizeQuery(stmt); err != nil {
+				return nil, 0, err
+			}
+			rows, err := engine.executeQueryIn(state.ctx, stmt)
+			if err != nil {
+				return nil, 0, err
+			}
+			if rows == nil {
+				rows = []Row{}
+			}
+			return encodeHostResult(rows)
+		})
+	})
+	if err != nil {
+		return fmt.Errorf("failed to define mindb_query: %w", err)
+	}
+
+	// mindb_get_row(table_ptr, table_len, filter_ptr, filter_len) -> len: the
+	// first matching row as a JSON object, or 0 if no row matches
+	err = linker.FuncWrap("env", "mindb_get_row", func(caller *wasmtime.Caller, tablePtr, tableLen, filterPtr, filterLen int32) int32 {
+		return state.hostCall(func(engine *PagedEngine) ([]byte, int32, error) {
+			table, conditions, err := readTableAndFilter(caller, tablePtr, tableLen, filterPtr, filterLen)
+			if err != nil {
+				return nil, 0, err
+			}
+			if err := state.ctx.authorize(table, PrivilegeSelect); err != nil {
+				return nil, 0, err
+			}
+			rows, err := engine.SelectRows(table, conditions)
+			if err != nil {
+				return nil, 0, err
+			}
+			if len(rows) == 0 {
+				return nil, 0, nil
+			}
+			return encodeHostResult(rows[0])
+		})
+	})
+	if err != nil {
+		return fmt.Errorf("failed to define mindb_get_row: %w", err)
+	}
+
+	// mindb_insert(table_ptr, table_len, row_ptr, row_len) -> rows inserted
+	err = linker.FuncWrap("env", "mindb_insert", func(caller *wasmtime.Caller, tablePtr, tableLen, rowPtr, rowLen int32) int32 {
+		return state.hostCall(func(engine *PagedEngine) ([]byte, int32, error) {
+			table, err := readTableName(caller, tablePtr, tableLen)
+			if err != nil {
+				return nil, 0, err
+			}
+			if err := state.ctx.authorize(table, PrivilegeInsert); err != nil {
+				return nil, 0, err
+			}
+			row, err := readGuestObject(caller, rowPtr, rowLen)
+			if err != nil {
+				return nil, 0, err
+			}
+			if err := engine.insertRowIn(state.ctx, table, Row(row)); err != nil {
+				return nil, 0, err
+			}
+			return nil, 1, nil
+		})
+	})
+	if err != nil {
+		return fmt.Errorf("failed to define mindb_insert: %w", err)
+	}
+
+	// mindb_update(table_ptr, table_len, set_ptr, set_len, filter_ptr, filter_len) -> rows updated
+	err = linker.FuncWrap("env", "mindb_update", func(caller *wasmtime.Caller, tablePtr, tableLen, setPtr, setLen, filterPtr, filterLen int32) int32 {
+		return state.hostCall(func(engine *PagedEngine) ([]byte, int32, error) {
+			table, conditions, err := readTableAndFilter(caller, tablePtr, tableLen, filterPtr, filterLen)
+			if err != nil {
+				return nil, 0, err
+			}
+			if err := state.ctx.authorize(table, PrivilegeUpdate); err != nil {
+				return nil, 0, err
+			}
+			updates, err := readGuestObject(caller, setPtr, setLen)
+			if err != nil {
+				return nil, 0, err
+			}
+			if len(updates) == 0 {
+				return nil, 0, fmt.Errorf("mindb_update requires at least one column to set")
+			}
+			count, err := engine.updateRowsIn(state.ctx, table, updates, conditions)
+			if err != nil {
+				return nil, 0, err
+			}
+			return nil, int32(count), nil
+		})
+	})
+	if err != nil {
+		return fmt.Errorf("failed to define mindb_update: %w", err)
+	}
+
+	// mindb_delete(table_ptr, table_len, filter_ptr, filter_len) -> rows deleted
+	err = linker.FuncWrap("env", "mindb_delete", func(caller *wasmtime.Caller, tablePtr, tableLen, filterPtr, filterLen int32) int32 {
+		return state.hostCall(func(engine *PagedEngine) ([]byte, int32, error) {
+			table, conditions, err := readTableAndFilter(caller, tablePtr, tableLen, filterPtr, filterLen)
+			if err != nil {
+				return nil, 0, err
+			}
+			if err := state.ctx.authorize(table, PrivilegeDelete); err != nil {
+				return nil, 0, err
+			}
+			count, err := engine.deleteRowsIn(state.ctx, table, conditions)
+			if err != nil {
+				return nil, 0, err
+			}
+			return nil, int32(count), nil
+		})
+	})
+	if err != nil {
+		return fmt.Errorf("failed to define mindb_delete: %w", err)
+	}
+
+	return nil
+}
+
+// addTriggerFunctions registers the imports trigger handlers use to see the
+// row change they were called for: mindb_trigger_event, mindb_trigger_old,
+// mindb_trigger_new and mindb_trigger_set_new. Outside a trigger they fail
+// at call time. All functions return -1 on error with the message left as
+// the pending result.
+func (w *WASMEngine) addTriggerFunctions(linker *wasmtime.Linker, state *callState) error {
+	// mindb_trigger_event() -> len: a JSON object with the trigger, table,
+	// timing (BEFORE or AFTER) and event (INSERT, UPDATE or DELETE)
+	err := linker.FuncWrap("env", "mindb_trigger_event", func() int32 {
+		return state.triggerCall(func(ev *TriggerEvent) ([]byte, int32, error) {
+			return encodeHostResult(map[string]string{
+				"trigger": ev.Trigger,
+				"table":   ev.Table,
+				"timing":  ev.Timing,
+				"event":   ev.Event,
+			})
+		})
+	})
+	if err != nil {
+		return fmt.Errorf("failed to define mindb_trigger_event: %w", err)
+	}
+
+	// mindb_trigger_old() -> len: the OLD row as a JSON object, or 0 for INSERT
+	err = linker.FuncWrap("env", "mindb_trigger_old", func() int32 {
+		return state.triggerCall(func(ev *TriggerEvent) ([]byte, int32, error) {
+			if ev.Old == nil {
+				return nil, 0, nil
+			}
+			return encodeHostResult(ev.Old)
+		})
+	})
+	if err != nil {
+		return fmt.Errorf("failed to define mindb_trigger_old: %w", err)
+	}
+
+	// mindb_trigger_new() -> len: the NEW row as a JSON object, or 0 for DELETE
+	err = linker.FuncWrap("env", "mindb_trigger_new", func() int32 {
+		return state.triggerCall(func(ev *TriggerEvent) ([]byte, int32, error) {
+			if ev.New == nil {
+				return nil, 0, nil
+			}
+			return encodeHostResult(ev.New)
+		})
+	})
+	if err != nil {
+		return fmt.Errorf("failed to define mindb_trigger_new: %w", err)
+	}
+
+	// mindb_trigger_set_new(ptr, len) -> status: set columns of the NEW row
+	// from a JSON object. Only BEFORE INSERT and BEFORE UPDATE handlers may
+	// change the row, and only in the table's columns; columns not given
+	// keep their values.
+	err = linker.FuncWrap("env", "mindb_trigger_set_new", func(caller *wasmtime.Caller, ptr int32, length int32) int32 {
+		return state.triggerCall(func(ev *TriggerEvent) ([]byte, int32, error) {
+			if ev.Timing != TriggerBefore || ev.New == nil {
+				return nil, 0, fmt.Errorf("only BEFORE INSERT or UPDATE triggers can change the new row")
+			}
+			values, err := readGuestObject(caller, ptr, length)
+			if err != nil {
+				return nil, 0, err
+			}
+			for col := range values {
+				if !ev.hasColumn(col) {
+					return nil, 0, fmt.Errorf("column '%s' does not exist in table '%s'", col, ev.Table)
+				}
+			}
+			for col, val := range values {
+				ev.New[col] = val
+			}
+			return nil, 0, nil
+		})
+	})
+	if err != nil {
+		return fmt.Errorf("failed to define mindb_trigger_set_new: %w", err)
+	}
+
+	return nil
+}
+
+// encodeHostResult JSON-encodes a result and returns it with its length
+func encodeHostResult(v interface{}) ([]byte, int32, error) {
+	data, err := json.Marshal(v)
+	if err != nil {
+		return nil, 0, fmt.Errorf("failed to encode result: %w", err)
+	}
+	return data, int32(len(data)), nil
+}
+
+// readTableAndFilter reads a table name and an equality filter from guest memory
+func readTableAndFilter(caller *wasmtime.Caller, tablePtr, tableLen, filterPtr, filterLen int32) (string, []Condition, error) {
+	table, err := readTableName(caller, tablePtr, tableLen)
+	if err != nil {
+		return "", nil, err
+	}
+	filter, err := readGuestObject(caller, filterPtr, filterLen)
+	if err != nil {
+		return "", nil, err
+	}
+
+	// Sort for a stable condition order (the query cache keys on it)
+	columns := make([]string, 0, len(filter))
+	for col := range filter {
+		columns = append(columns, col)
+	}
+	sort.Strings(columns)
+
+	conditions := make([]Condition, len(columns))
+	for i, col := range columns {
+		conditions[i] = Condition{Column: col, Operator: "=", Value: filter[col]}
+	}
+	return table, conditions, nil
+}
+
+// readTableName reads a table name from guest memory
+func readTableName(caller *wasmtime.Caller, ptr int32, length int32) (string, error) {
+	data, err := readGuestMemory(caller, ptr, length)
+	if err != nil {
+		return "", err
+	}
+	if len(data) == 0 {
+		return "", fmt.Errorf("table name must not be empty")
+	}
+	return string(data), nil
+}
+
+// readGuestObject reads a JSON object from guest memory. Integers stay int64
+// so they are not rounded through float64; a zero length means no columns.
+func readGuestObject(caller *wasmtime.Caller, ptr int32, length int32) (map[string]interface{}, error) {
+	if length == 0 {
+		return map[string]interface{}{}, nil
+	}
+	data, err := readGuestMemory(caller, ptr, length)
+	if err != nil {
+		return nil, err
+	}
+	return decodeGuestObject(data)
+}
+
+// decodeGuestObject decodes a JSON object of scalar column values
+func decodeGuestObject(data []byte) (map[string]interface{}, error) {
+	decoder := json.NewDecoder(bytes.NewReader(data))
+	decoder.UseNumber()
+	var obj map[string]interface{}
+	if err := decoder.Decode(&obj); err != nil {
+		return nil, fmt.Errorf("invalid JSON object from procedure: %w", err)
+	}
+
+	for key, value := range obj {
+		switch v := value.(type) {
+		case json.Number:
+			if i, err := v.Int64(); err == nil {
+				obj[key] = i
+			} else if f, err := v.Float64(); err == nil {
+				obj[key] = f
+			} else {
+				return nil, fmt.Errorf("invalid number for column '%s': %s", key, v)
+			}
+		case map[string]interface{}, []interface{}:
+			return nil, fmt.Errorf("column '%s' must be a scalar value", key)
+		}
+	}
+	return obj, nil
+}
+
+// readGuestMemory copies length bytes at ptr out of the guest's exported memory
+func readGuestMemory(caller *wasmtime.Caller, ptr int32, length int32) ([]byte, error) {
+	export := caller.GetExport("memory")
+	if export == nil || export.Memory() == nil {
+		return nil, fmt.Errorf("module does not export memory")
+	}
+
+	data := export.Memory().UnsafeData(caller)
+	if ptr < 0 || length < 0 || int64(ptr)+int64(length) > int64(len(data)) {
+		return nil, fmt.Errorf("guest memory access out of bounds (ptr=%d, len=%d)", ptr, length)
+	}
+
+	buf := make([]byte, length)
+	copy(buf, data[ptr:ptr+length])
+	return buf, nil
+}
+
+// writeGuestMemory copies buf into the guest's exported memory at ptr
+func writeGuestMemory(caller *wasmtime.Caller, ptr int32, buf []byte) error {
+	export := caller.GetExport("memory")
+	if export == nil || export.Memory() == nil {
+		return fmt.Errorf("module does not export memory")
+	}
+
+	data := export.Memory().UnsafeData(caller)
+	if ptr < 0 || int64(ptr)+int64(len(buf)) > int64(len(data)) {
+		return fmt.Errorf("guest memory access out of bounds (ptr=%d, len=%d)", ptr, len(buf))
+	}
+
+	copy(data[ptr:], buf)
+	return nil
+}
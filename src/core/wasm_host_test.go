@@ -0,0 +1,172 @@
+package mindb
+
+import (
+	"strings"
+	"testing"
+)
+
+// WASM module exercising the database host functions. Each export makes one
+// call with fixed arguments from its data segment:
+//
+//	insert_customer:  mindb_insert("customers", {"id":7,"tier":3})
+//	find_customer:    mindb_get_row("customers", {"id":7})
+//	read_customer:    mindb_get_row, then mindb_result_read into offset 128
+//	                  and return the byte at 134 (the id digit)
+//	promote_customer: mindb_update("customers", {"tier":4}, {"id":7})
+//	remove_customer:  mindb_delete("customers", {"id":7})
+//	query_error:      mindb_query("DELETE FROM customers")
+var databaseAccessWASM = []byte{
+	0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x1d, 0x04, 0x60,
+	0x02, 0x7f, 0x7f, 0x01, 0x7f, 0x60, 0x04, 0x7f, 0x7f, 0x7f, 0x7f, 0x01,
+	0x7f, 0x60, 0x06, 0x7f, 0x7f, 0x7f, 0x7f, 0x7f, 0x7f, 0x01, 0x7f, 0x60,
+	0x00, 0x01, 0x7f, 0x02, 0x78, 0x06, 0x03, 0x65, 0x6e, 0x76, 0x11, 0x6d,
+	0x69, 0x6e, 0x64, 0x62, 0x5f, 0x72, 0x65, 0x73, 0x75, 0x6c, 0x74, 0x5f,
+	0x72, 0x65, 0x61, 0x64, 0x00, 0x00, 0x03, 0x65, 0x6e, 0x76, 0x0b, 0x6d,
+	0x69, 0x6e, 0x64, 0x62, 0x5f, 0x71, 0x75, 0x65, 0x72, 0x79, 0x00, 0x00,
+	0x03, 0x65, 0x6e, 0x76, 0x0d, 0x6d, 0x69, 0x6e, 0x64, 0x62, 0x5f, 0x67,
+	0x65, 0x74, 0x5f, 0x72, 0x6f, 0x77, 0x00, 0x01, 0x03, 0x65, 0x6e, 0x76,
+	0x0c, 0x6d, 0x69, 0x6e, 0x64, 0x62, 0x5f, 0x69, 0x6e, 0x73, 0x65, 0x72,
+	0x74, 0x00, 0x01, 0x03, 0x65, 0x6e, 0x76, 0x0c, 0x6d, 0x69, 0x6e, 0x64,
+	0x62, 0x5f, 0x75, 0x70, 0x64, 0x61, 0x74, 0x65, 0x00, 0x02, 0x03, 0x65,
+	0x6e, 0x76, 0x0c, 0x6d, 0x69, 0x6e, 0x64, 0x62, 0x5f, 0x64, 0x65, 0x6c,
+	0x65, 0x74, 0x65, 0x00, 0x01, 0x03, 0x07, 0x06, 0x03, 0x03, 0x03, 0x03,
+	0x03, 0x03, 0x05, 0x03, 0x01, 0x00, 0x01, 0x07, 0x6f, 0x07, 0x06, 0x6d,
+	0x65, 0x6d, 0x6f, 0x72, 0x79, 0x02, 0x00, 0x0f, 0x69, 0x6e, 0x73, 0x65,
+	0x72, 0x74, 0x5f, 0x63, 0x75, 0x73, 0x74, 0x6f, 0x6d, 0x65, 0x72, 0x00,
+	0x06, 0x0d, 0x66, 0x69, 0x6e, 0x64, 0x5f, 0x63, 0x75, 0x73, 0x74, 0x6f,
+	0x6d, 0x65, 0x72, 0x00, 0x07, 0x0d, 0x72, 0x65, 0x61, 0x64, 0x5f, 0x63,
+	0x75, 0x73, 0x74, 0x6f, 0x6d, 0x65, 0x72, 0x00, 0x08, 0x10, 0x70, 0x72,
+	0x6f, 0x6d, 0x6f, 0x74, 0x65, 0x5f, 0x63, 0x75, 0x73, 0x74, 0x6f, 0x6d,
+	0x65, 0x72, 0x00, 0x09, 0x0f, 0x72, 0x65, 0x6d, 0x6f, 0x76, 0x65, 0x5f,
+	0x63, 0x75, 0x73, 0x74, 0x6f, 0x6d, 0x65, 0x72, 0x00, 0x0a, 0x0b, 0x71,
+	0x75, 0x65, 0x72, 0x79, 0x5f, 0x65, 0x72, 0x72, 0x6f, 0x72, 0x00, 0x0b,
+	0x0a, 0x61, 0x06, 0x0c, 0x00, 0x41, 0x00, 0x41, 0x09, 0x41, 0x10, 0x41,
+	0x11, 0x10, 0x03, 0x0b, 0x0c, 0x00, 0x41, 0x00, 0x41, 0x09, 0x41, 0x30,
+	0x41, 0x08, 0x10, 0x02, 0x0b, 0x1c, 0x00, 0x41, 0x00, 0x41, 0x09, 0x41,
+	0x30, 0x41, 0x08, 0x10, 0x02, 0x1a, 0x41, 0x80, 0x01, 0x41, 0xc0, 0x00,
+	0x10, 0x00, 0x1a, 0x41, 0x00, 0x2d, 0x00, 0x86, 0x01, 0x0b, 0x11, 0x00,
+	0x41, 0x00, 0x41, 0x09, 0x41, 0xc0, 0x00, 0x41, 0x0a, 0x41, 0x30, 0x41,
+	0x08, 0x10, 0x04, 0x0b, 0x0c, 0x00, 0x41, 0x00, 0x41, 0x09, 0x41, 0x30,
+	0x41, 0x08, 0x10, 0x05, 0x0b, 0x09, 0x00, 0x41, 0xd0, 0x00, 0x41, 0x15,
+	0x10, 0x01, 0x0b, 0x0b, 0x5d, 0x05, 0x00, 0x41, 0x00, 0x0b, 0x09, 0x63,
+	0x75, 0x73, 0x74, 0x6f, 0x6d, 0x65, 0x72, 0x73, 0x00, 0x41, 0x10, 0x0b,
+	0x11, 0x7b, 0x22, 0x69, 0x64, 0x22, 0x3a, 0x37, 0x2c, 0x22, 0x74, 0x69,
+	0x65, 0x72, 0x22, 0x3a, 0x33, 0x7d, 0x00, 0x41, 0x30, 0x0b, 0x08, 0x7b,
+	0x22, 0x69, 0x64, 0x22, 0x3a, 0x37, 0x7d, 0x00, 0x41, 0xc0, 0x00, 0x0b,
+	0x0a, 0x7b, 0x22, 0x74, 0x69, 0x65, 0x72, 0x22, 0x3a, 0x34, 0x7d, 0x00,
+	0x41, 0xd0, 0x00, 0x0b, 0x15, 0x44, 0x45, 0x4c, 0x45, 0x54, 0x45, 0x20,
+	0x46, 0x52, 0x4f, 0x4d, 0x20, 0x63, 0x75, 0x73, 0x74, 0x6f, 0x6d, 0x65,
+	0x72, 0x73,
+}
+
+func newHostTestEngine(t *testing.T) (*PagedEngine, *WASMEngine) {
+	t.Helper()
+
+	engine, err := NewPagedEngine(t.TempDir())
+	if err != nil {
+		t.Fatalf("Failed to create engine: %v", err)
+	}
+	t.Cleanup(func() { engine.Close() })
+
+	if err := engine.CreateDatabase("testdb"); err != nil {
+		t.Fatalf("Failed to create database: %v", err)
+	}
+	columns := []Column{
+		{Name: "id", DataType: "INT", PrimaryKey: true},
+		{Name: "tier", DataType: "INT"},
+	}
+	if err := engine.CreateTable("customers", columns); err != nil {
+		t.Fatalf("Failed to create table: %v", err)
+	}
+
+	wasmEngine := engine.GetWASMEngine()
+	if err := wasmEngine.CompileModule("customers_db", databaseAccessWASM); err != nil {
+		t.Fatalf("Failed to compile module: %v", err)
+	}
+	return engine, wasmEngine
+}
+
+func callHostTest(t *testing.T, wasmEngine *WASMEngine, ctx *ExecutionContext, function string) int32 {
+	t.Helper()
+
+	result, err := wasmEngine.ExecuteWithContext("customers_db", function, ctx)
+	if err != nil {
+		t.Fatalf("%s failed: %v", function, err)
+	}
+	return result.(int32)
+}
+
+func TestWASMHost_DatabaseAccess(t *testing.T) {
+	engine, wasmEngine := newHostTestEngine(t)
+	ctx := &ExecutionContext{Engine: engine, Database: "testdb"}
+
+	if n := callHostTest(t, wasmEngine, ctx, "insert_customer"); n != 1 {
+		t.Errorf("Expected 1 row inserted, got %d", n)
+	}
+
+	rows, err := engine.SelectRows("customers", nil)
+	if err != nil {
+		t.Fatalf("Failed to select rows: %v", err)
+	}
+	if len(rows) != 1 || CompareValues(rows[0]["tier"], 3) != 0 {
+		t.Fatalf("Expected inserted customer with tier 3, got %v", rows)
+	}
+
+	// {"id":7,"tier":3}
+	if n := callHostTest(t, wasmEngine, ctx, "find_customer"); n != 17 {
+		t.Errorf("Expected 17-byte row, got %d", n)
+	}
+	if b := callHostTest(t, wasmEngine, ctx, "read_customer"); b != '7' {
+		t.Errorf("Expected result copied into guest memory, got byte %q", rune(b))
+	}
+
+	if n := callHostTest(t, wasmEngine, ctx, "promote_customer"); n != 1 {
+		t.Errorf("Expected 1 row updated, got %d", n)
+	}
+	rows, _ = engine.SelectRows("customers", []Condition{{Column: "id", Operator: "=", Value: 7}})
+	if len(rows) != 1 || CompareValues(rows[0]["tier"], 4) != 0 {
+		t.Errorf("Expected tier 4 after update, got %v", rows)
+	}
+
+	if n := callHostTest(t, wasmEngine, ctx, "remove_customer"); n != 1 {
+		t.Errorf("Expected 1 row deleted, got %d", n)
+	}
+	if n := callHostTest(t, wasmEngine, ctx, "find_customer"); n != 0 {
+		t.Errorf("Expected no row after delete, got %d", n)
+	}
+}
+
+func TestWASMHost_DatabaseErrors(t *testing.T) {
+	engine, wasmEngine := newHostTestEngine(t)
+	ctx := &ExecutionContext{Engine: engine, Database: "testdb"}
+
+	// Only SELECT is allowed through mindb_query
+	if n := callHostTest(t, wasmEngine, ctx, "query_error"); n != -1 {
+		t.Errorf("Expected -1 for non-SELECT query, got %d", n)
+	}
+
+	// Duplicate primary key surfaces as an error, not a trap
+	callHostTest(t, wasmEngine, ctx, "insert_customer")
+	if n := callHostTest(t, wasmEngine, ctx, "insert_customer"); n != -1 {
+		t.Errorf("Expected -1 for duplicate insert, got %d", n)
+	}
+
+	// Without a database context the imports link but fail when called
+	result, err := wasmEngine.Execute("customers_db", "insert_customer")
+	if err != nil {
+		t.Fatalf("Execute failed: %v", err)
+	}
+	if result.(int32) != -1 {
+		t.Errorf("Expected -1 without database context, got %v", result)
+	}
+}
+
+func TestCallState_HostCall(t *testing.T) {
+	state := &callState{}
+	if n := state.hostCall(nil); n != -1 {
+		t.Errorf("Expected -1 without context, got %d", n)
+	}
+	if !strings.Contains(string(state.result), "not available") {
+		t.Errorf("Expected error message as pending result, got %q", state.result)
+	}
+}
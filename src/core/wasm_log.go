@@ -0,0 +1,118 @@
+package mindb
+
+import (
+	"fmt"
+	"sync"
+	"time"
+
+	"github.com/rs/zerolog"
+)
+
+// Guest log levels passed to mindb_log, numbered as in the Rust log crate
+const (
+	guestLogError = 1
+	guestLogWarn  = 2
+	guestLogInfo  = 3
+	guestLogDebug = 4
+	guestLogTrace = 5
+)
+
+// guestLogLevel converts a mindb_log level to a zerolog level
+func guestLogLevel(level int32) (zerolog.Level, error) {
+	switch level {
+	case guestLogError:
+		return zerolog.ErrorLevel, nil
+	case guestLogWarn:
+		return zerolog.WarnLevel, nil
+	case guestLogInfo:
+		return zerolog.InfoLevel, nil
+	case guestLogDebug:
+		return zerolog.DebugLevel, nil
+	case guestLogTrace:
+		return zerolog.TraceLevel, nil
+	}
+	return zerolog.NoLevel, fmt.Errorf("mindb_log: invalid level %d", level)
+}
+
+// SetLogger sets the logger guest log lines are written to. Without one they
+// are discarded.
+func (w *WASMEngine) SetLogger(logger zerolog.Logger) {
+	w.logMu.Lock()
+	defer w.logMu.Unlock()
+	w.logger = logger
+}
+
+// guestLog writes a line a procedure logged through mindb_log, tagged with
+// the procedure and, when called with a context, the user and request. Lines
+// over the procedure's LogRateLimit are dropped; the next line written
+// reports how many.
+func (w *WASMEngine) guestLog(state *callState, level zerolog.Level, msg string) {
+	w.logMu.Lock()
+	logger := w.logger
+	limiter, exists := w.logLimits[state.procedure]
+	if !exists {
+		limiter = &logLimiter{}
+		w.logLimits[state.procedure] = limiter
+	}
+	w.logMu.Unlock()
+
+	// Lines the logger would discard do not count against the limit
+	if logger.GetLevel() > level || zerolog.GlobalLevel() > level {
+		return
+	}
+	allowed, dropped := limiter.allow(w.config.LogRateLimit, time.Now())
+	if !allowed {
+		return
+	}
+
+	event := logger.WithLevel(level).Str("procedure", state.procedure)
+	if ctx := state.ctx; ctx != nil {
+		if ctx.UserID != "" {
+			event = event.Str("user", ctx.UserID)
+		}
+		if ctx.RequestID != "" {
+			event = event.Str("request_id", ctx.RequestID)
+		}
+	}
+	if dropped > 0 {
+		event = event.Int("dropped", dropped)
+	}
+	event.Msg(msg)
+}
+
+// logLimiter is a token bucket holding up to one second of a procedure's
+// log lines
+type logLimiter struct {
+	mu      sync.Mutex
+	tokens  float64
+	last    time.Time
+	dropped int // Lines dropped since the last one written
+}
+
+// allow reports whether a line may be written at now under a limit of rate
+// lines per second (0 means no limit), and how many were dropped before it
+func (l *logLimiter) allow(rate int, now time.Time) (bool, int) {
+	l.mu.Lock()
+	defer l.mu.Unlock()
+
+	if rate > 0 {
+		if l.last.IsZero() {
+			l.tokens = float64(rate)
+		} else {
+			l.tokens += now.Sub(l.last).Seconds() * float64(rate)
+			if l.tokens > float64(rate) {
+				l.tokens = float64(rate)
+			}
+		}
+		l.last = now
+		if l.tokens < 1 {
+			l.dropped++
+			return false, 0
+		}
+		l.tokens--
+	}
+
+	dropped := l.dropped
+	l.dropped = 0
+	return true, dropped
+}
@@ -0,0 +1,139 @@
+package mindb
+
+import (
+	"bytes"
+	"encoding/json"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/rs/zerolog"
+)
+
+// WASM module that writes debug lines through the mindb_log host import.
+// Equivalent Rust (using the mindb-procedure SDK):
+//
+//	#[procedure]
+//	fn log_lines(n: i32) {
+//	    for _ in 0..n { debug!("pricing tier 2"); }
+//	}
+//
+// bad_level() logs at the undefined level 9.
+var guestLogWASM = []byte{
+	0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x0e, 0x03, 0x60,
+	0x03, 0x7f, 0x7f, 0x7f, 0x00, 0x60, 0x01, 0x7f, 0x00, 0x60, 0x00, 0x00,
+	0x02, 0x11, 0x01, 0x03, 0x65, 0x6e, 0x76, 0x09, 0x6d, 0x69, 0x6e, 0x64,
+	0x62, 0x5f, 0x6c, 0x6f, 0x67, 0x00, 0x00, 0x03, 0x03, 0x02, 0x01, 0x02,
+	0x05, 0x03, 0x01, 0x00, 0x01, 0x07, 0x22, 0x03, 0x06, 0x6d, 0x65, 0x6d,
+	0x6f, 0x72, 0x79, 0x02, 0x00, 0x09, 0x6c, 0x6f, 0x67, 0x5f, 0x6c, 0x69,
+	0x6e, 0x65, 0x73, 0x00, 0x01, 0x09, 0x62, 0x61, 0x64, 0x5f, 0x6c, 0x65,
+	0x76, 0x65, 0x6c, 0x00, 0x02, 0x0a, 0x2b, 0x02, 0x1e, 0x00, 0x02, 0x40,
+	0x03, 0x40, 0x20, 0x00, 0x45, 0x0d, 0x01, 0x41, 0x04, 0x41, 0x00, 0x41,
+	0x0e, 0x10, 0x00, 0x20, 0x00, 0x41, 0x01, 0x6b, 0x21, 0x00, 0x0c, 0x00,
+	0x0b, 0x0b, 0x0b, 0x0a, 0x00, 0x41, 0x09, 0x41, 0x00, 0x41, 0x00, 0x10,
+	0x00, 0x0b, 0x0b, 0x14, 0x01, 0x00, 0x41, 0x00, 0x0b, 0x0e, 0x70, 0x72,
+	0x69, 0x63, 0x69, 0x6e, 0x67, 0x20, 0x74, 0x69, 0x65, 0x72, 0x20, 0x32,
+
+}
+
+// logLines decodes the JSON lines a zerolog logger wrote
+func logLines(t *testing.T, buf *bytes.Buffer) []map[string]interface{} {
+	t.Helper()
+	var lines []map[string]interface{}
+	for _, line := range strings.Split(strings.TrimSpace(buf.String()), "\n") {
+		if line == "" {
+			continue
+		}
+		var fields map[string]interface{}
+		if err := json.Unmarshal([]byte(line), &fields); err != nil {
+			t.Fatalf("Invalid log line %q: %v", line, err)
+		}
+		lines = append(lines, fields)
+	}
+	return lines
+}
+
+func TestWASMEngine_GuestLog(t *testing.T) {
+	config := DefaultWASMConfig()
+	config.LogRateLimit = 2
+	engine, err := NewWASMEngine(config)
+	if err != nil {
+		t.Fatalf("Failed to create WASM engine: %v", err)
+	}
+	defer engine.Close()
+
+	var buf bytes.Buffer
+	engine.SetLogger(zerolog.New(&buf).Level(zerolog.DebugLevel))
+	if err := engine.CompileModule("pricing", guestLogWASM); err != nil {
+		t.Fatalf("Failed to compile module: %v", err)
+	}
+
+	ctx := &ExecutionContext{UserID: "alice@%", RequestID: "req-1"}
+	if _, err := engine.ExecuteWithContext("pricing", "log_lines", ctx, int32(5)); err != nil {
+		t.Fatalf("Failed to execute function: %v", err)
+	}
+
+	// Only the first 2 lines fit the rate limit
+	lines := logLines(t, &buf)
+	if len(lines) != 2 {
+		t.Fatalf("Expected 2 lines, got %d: %s", len(lines), buf.String())
+	}
+	want := map[string]interface{}{
+		"level":      "debug",
+		"message":    "pricing tier 2",
+		"procedure":  "log_lines",
+		"user":       "alice@%",
+		"request_id": "req-1",
+	}
+	for key, value := range want {
+		if lines[0][key] != value {
+			t.Errorf("Expected %s=%v, got %v", key, value, lines[0][key])
+		}
+	}
+
+	// Lines the logger discards are not counted or limited
+	buf.Reset()
+	engine.SetLogger(zerolog.New(&buf).Level(zerolog.InfoLevel))
+	if _, err := engine.Execute("pricing", "log_lines", int32(3)); err != nil {
+		t.Fatalf("Failed to execute function: %v", err)
+	}
+	if buf.Len() != 0 {
+		t.Errorf("Expected no lines above debug level, got: %s", buf.String())
+	}
+
+	// An undefined level traps
+	_, err = engine.Execute("pricing", "bad_level")
+	if err == nil || !strings.Contains(err.Error(), "invalid level 9") {
+		t.Errorf("Expected an invalid level error, got: %v", err)
+	}
+}
+
+func TestLogLimiter(t *testing.T) {
+	var limiter logLimiter
+	now := time.Now()
+
+	for i := 0; i < 3; i++ {
+		if ok, _ := limiter.allow(3, now); !ok {
+			t.Fatalf("Expected line %d to be allowed", i)
+		}
+	}
+	for i := 0; i < 2; i++ {
+		if ok, _ := limiter.allow(3, now); ok {
+			t.Fatalf("Expected line %d over the limit to be dropped", i)
+		}
+	}
+
+	// A third of a second refills one line, which reports the drops
+	ok, dropped := limiter.allow(3, now.Add(time.Second/3+time.Millisecond))
+	if !ok || dropped != 2 {
+		t.Errorf("Expected an allowed line after 2 drops, got %v, %d", ok, dropped)
+	}
+
+	// No limit
+	var unlimited logLimiter
+	for i := 0; i < 1000; i++ {
+		if ok, _ := unlimited.allow(0, now); !ok {
+			t.Fatal("Expected every line to be allowed without a limit")
+		}
+	}
+}
@@ -0,0 +1,317 @@
+package mindb
+
+import (
+	"encoding/json"
+	"fmt"
+	"math"
+	"strings"
+
+	"github.com/bytecodealliance/wasmtime-go/v25"
+)
+
+// maxExactFloatInt is the largest integer magnitude a float64 holds exactly
+const maxExactFloatInt = 1 << 53
+
+// marshalArgs converts call arguments to the exact Go types Wasmtime expects
+// for the function's parameters (int32, int64, float32, float64). Values that
+// do not fit are rejected rather than truncated. Parameters declared TEXT or
+// BLOB in params are copied into guest memory through buffers and passed as
+// a packed i64; params may be nil when no declared signature is known.
+// Arguments flagged in nulls are passed as zero values.
+func marshalArgs(funcType *wasmtime.FuncType, params []Column, args []interface{}, nulls []bool, buffers *guestBuffers) ([]interface{}, error) {
+	kinds := funcType.Params()
+	if len(args) != len(kinds) {
+		return nil, fmt.Errorf("expected %d argument(s), got %d", len(kinds), len(args))
+	}
+
+	wasmArgs := make([]interface{}, len(args))
+	for i, arg := range args {
+		var value interface{}
+		var err error
+		if i < len(nulls) && nulls[i] {
+			value, err = zeroArg(kinds[i].Kind())
+		} else if i < len(params) && isBytesType(params[i].DataType) {
+			value, err = marshalBytes(arg, kinds[i].Kind(), buffers)
+		} else {
+			value, err = marshalArg(arg, kinds[i].Kind())
+		}
+		if err != nil {
+			return nil, fmt.Errorf("argument %d: %w", i+1, err)
+		}
+		wasmArgs[i] = value
+	}
+	return wasmArgs, nil
+}
+
+// hasNullArg reports whether any argument is NULL
+func hasNullArg(args []interface{}) bool {
+	for _, arg := range args {
+		if arg == nil {
+			return true
+		}
+	}
+	return false
+}
+
+// nullArgs flags the NULL arguments, or returns nil if there are none
+func nullArgs(args []interface{}) []bool {
+	if !hasNullArg(args) {
+		return nil
+	}
+	nulls := make([]bool, len(args))
+	for i, arg := range args {
+		nulls[i] = arg == nil
+	}
+	return nulls
+}
+
+// zeroArg is the value a NULL argument is passed as. For TEXT and BLOB
+// parameters this is the packed empty value.
+func zeroArg(kind wasmtime.ValKind) (interface{}, error) {
+	switch kind {
+	case wasmtime.KindI32:
+		return int32(0), nil
+	case wasmtime.KindI64:
+		return int64(0), nil
+	case wasmtime.KindF32:
+		return float32(0), nil
+	case wasmtime.KindF64:
+		return float64(0), nil
+	default:
+		return nil, fmt.Errorf("unsupported WASM parameter type %v", kind)
+	}
+}
+
+// isBytesType reports whether a SQL type is passed through guest memory
+func isBytesType(sqlType string) bool {
+	switch strings.ToUpper(sqlType) {
+	case "TEXT", "VARCHAR", "CHAR", "STRING", "BLOB", "BYTEA":
+		return true
+	}
+	return false
+}
+
+// marshalBytes copies a TEXT or BLOB argument into guest memory and returns
+// the packed (ptr << 32) | len the guest receives
+func marshalBytes(value interface{}, kind wasmtime.ValKind, buffers *guestBuffers) (interface{}, error) {
+	if kind != wasmtime.KindI64 {
+		return nil, fmt.Errorf("TEXT and BLOB parameters must be i64 in WASM, got %v", kind)
+	}
+	switch v := value.(type) {
+	case string:
+		return buffers.copyIn([]byte(v))
+	case []byte:
+		return buffers.copyIn(v)
+	case nil:
+		return nil, fmt.Errorf("NULL is not a valid TEXT or BLOB argument")
+	default:
+		return nil, fmt.Errorf("cannot convert %T to TEXT or BLOB", value)
+	}
+}
+
+// guestBuffers copies TEXT and BLOB arguments into guest memory. Buffers come
+// from the module's mindb_alloc export and are handed back through
+// mindb_dealloc once the call returns.
+type guestBuffers struct {
+	store    *wasmtime.Store
+	instance *wasmtime.Instance
+	buffers  [][2]int32 // (ptr, len) of each allocation
+}
+
+// copyIn copies data into a fresh guest allocation and returns it packed as
+// (ptr << 32) | len. Empty values are passed as 0 without allocating.
+func (b *guestBuffers) copyIn(data []byte) (int64, error) {
+	if len(data) == 0 {
+		return 0, nil
+	}
+	if len(data) > math.MaxInt32 {
+		return 0, fmt.Errorf("value of %d bytes is too large to pass to WASM", len(data))
+	}
+
+	alloc := b.instance.GetFunc(b.store, "mindb_alloc")
+	if alloc == nil || b.instance.GetFunc(b.store, "mindb_dealloc") == nil {
+		return 0, fmt.Errorf("module must export mindb_alloc and mindb_dealloc to take TEXT or BLOB arguments")
+	}
+	result, err := alloc.Call(b.store, int32(len(data)))
+	if err != nil {
+		return 0, fmt.Errorf("mindb_alloc failed: %w", err)
+	}
+	ptr, ok := result.(int32)
+	if !ok {
+		return 0, fmt.Errorf("mindb_alloc must return an i32 pointer")
+	}
+
+	export := b.instance.GetExport(b.store, "memory")
+	if export == nil || export.Memory() == nil {
+		return 0, fmt.Errorf("module does not export memory")
+	}
+	mem := export.Memory().UnsafeData(b.store)
+	offset := int64(uint32(ptr))
+	if offset == 0 || offset+int64(len(data)) > int64(len(mem)) {
+		return 0, fmt.Errorf("mindb_alloc returned an invalid pointer (ptr=%d, len=%d)", offset, len(data))
+	}
+
+	copy(mem[offset:], data)
+	b.buffers = append(b.buffers, [2]int32{ptr, int32(len(data))})
+	return offset<<32 | int64(len(data)), nil
+}
+
+// free returns every allocation made by copyIn to the guest
+func (b *guestBuffers) free() error {
+	if len(b.buffers) == 0 {
+		return nil
+	}
+	dealloc := b.instance.GetFunc(b.store, "mindb_dealloc")
+	if dealloc == nil {
+		return fmt.Errorf("module does not export mindb_dealloc")
+	}
+	for _, buf := range b.buffers {
+		if _, err := dealloc.Call(b.store, buf[0], buf[1]); err != nil {
+			return fmt.Errorf("mindb_dealloc failed: %w", err)
+		}
+	}
+	b.buffers = nil
+	return nil
+}
+
+// marshalArg converts a single value to the Go type for a WASM value kind
+func marshalArg(value interface{}, kind wasmtime.ValKind) (interface{}, error) {
+	switch kind {
+	case wasmtime.KindI32:
+		i, err := toInt64(value)
+		if err != nil {
+			return nil, err
+		}
+		if i < math.MinInt32 || i > math.MaxInt32 {
+			return nil, fmt.Errorf("value %d overflows INT (i32)", i)
+		}
+		return int32(i), nil
+	case wasmtime.KindI64:
+		return toInt64(value)
+	case wasmtime.KindF32:
+		f, err := toFloat64(value)
+		if err != nil {
+			return nil, err
+		}
+		if !math.IsInf(f, 0) && math.Abs(f) > math.MaxFloat32 {
+			return nil, fmt.Errorf("value %g overflows REAL (f32)", f)
+		}
+		return float32(f), nil
+	case wasmtime.KindF64:
+		return toFloat64(value)
+	default:
+		return nil, fmt.Errorf("unsupported WASM parameter type %v", kind)
+	}
+}
+
+// toInt64 converts an integral value to int64. Floats must be whole numbers
+// small enough to have been represented exactly.
+func toInt64(value interface{}) (int64, error) {
+	switch v := value.(type) {
+	case int:
+		return int64(v), nil
+	case int8:
+		return int64(v), nil
+	case int16:
+		return int64(v), nil
+	case int32:
+		return int64(v), nil
+	case int64:
+		return v, nil
+	case uint8:
+		return int64(v), nil
+	case uint16:
+		return int64(v), nil
+	case uint32:
+		return int64(v), nil
+	case uint:
+		if uint64(v) > math.MaxInt64 {
+			return 0, fmt.Errorf("value %d overflows BIGINT (i64)", v)
+		}
+		return int64(v), nil
+	case uint64:
+		if v > math.MaxInt64 {
+			return 0, fmt.Errorf("value %d overflows BIGINT (i64)", v)
+		}
+		return int64(v), nil
+	case bool:
+		if v {
+			return 1, nil
+		}
+		return 0, nil
+	case float32:
+		return floatToInt64(float64(v))
+	case float64:
+		return floatToInt64(v)
+	case json.Number:
+		if i, err := v.Int64(); err == nil {
+			return i, nil
+		}
+		f, err := v.Float64()
+		if err != nil {
+			return 0, fmt.Errorf("value %s overflows BIGINT (i64)", v)
+		}
+		return floatToInt64(f)
+	case nil:
+		return 0, fmt.Errorf("NULL is not a valid integer argument")
+	default:
+		return 0, fmt.Errorf("cannot convert %T to an integer", value)
+	}
+}
+
+// floatToInt64 converts a whole-number float to int64
+func floatToInt64(f float64) (int64, error) {
+	if math.IsNaN(f) || math.IsInf(f, 0) || f != math.Trunc(f) {
+		return 0, fmt.Errorf("value %g is not an integer", f)
+	}
+	if math.Abs(f) > maxExactFloatInt {
+		return 0, fmt.Errorf("value %g is too large to be an exact integer", f)
+	}
+	return int64(f), nil
+}
+
+// toFloat64 converts a numeric value to float64
+func toFloat64(value interface{}) (float64, error) {
+	switch v := value.(type) {
+	case float64:
+		return v, nil
+	case float32:
+		return float64(v), nil
+	case json.Number:
+		f, err := v.Float64()
+		if err != nil {
+			return 0, fmt.Errorf("invalid number %s", v)
+		}
+		return f, nil
+	case nil:
+		return 0, fmt.Errorf("NULL is not a valid numeric argument")
+	case string:
+		return 0, fmt.Errorf("cannot convert %T to a number", value)
+	default:
+		i, err := toInt64(value)
+		if err != nil {
+			return 0, fmt.Errorf("cannot convert %T to a number", value)
+		}
+		return float64(i), nil
+	}
+}
+
+// unmarshalResult converts a WASM result to its declared SQL type. WASM has
+// no boolean, so BOOLEAN results arrive as an i32 of 0 or 1. TABLE
+// procedures that emitted no rows return an empty result set.
+func unmarshalResult(result interface{}, sqlType string) interface{} {
+	if isTableType(sqlType) {
+		if _, ok := result.(*ProcedureRows); !ok {
+			return &ProcedureRows{}
+		}
+		return result
+	}
+
+	switch sqlType {
+	case "BOOLEAN", "BOOL":
+		if i, ok := result.(int32); ok {
+			return i != 0
+		}
+	}
+	return result
+}
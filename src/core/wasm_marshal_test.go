@@ -0,0 +1,333 @@
+package mindb
+
+import (
+	"encoding/json"
+	"math"
+	"strings"
+	"testing"
+)
+
+// WASM module with 64-bit and 32-bit float parameters, equivalent to:
+//
+//	#[no_mangle] pub extern "C" fn add_cents(a: i64, b: i64) -> i64 { a + b }
+//	#[no_mangle] pub extern "C" fn scale(x: f32, factor: f32) -> f32 { x * factor }
+//
+//	/// Whether an amount is above zero.
+//	#[procedure]
+//	fn is_positive(amount: i64) -> bool { amount > 0 }
+var ledgerWASM = []byte{
+	0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x12, 0x03, 0x60,
+	0x02, 0x7e, 0x7e, 0x01, 0x7e, 0x60, 0x02, 0x7d, 0x7d, 0x01, 0x7d, 0x60,
+	0x01, 0x7e, 0x01, 0x7f, 0x03, 0x04, 0x03, 0x00, 0x01, 0x02, 0x07, 0x23,
+	0x03, 0x09, 0x61, 0x64, 0x64, 0x5f, 0x63, 0x65, 0x6e, 0x74, 0x73, 0x00,
+	0x00, 0x05, 0x73, 0x63, 0x61, 0x6c, 0x65, 0x00, 0x01, 0x0b, 0x69, 0x73,
+	0x5f, 0x70, 0x6f, 0x73, 0x69, 0x74, 0x69, 0x76, 0x65, 0x00, 0x02, 0x0a,
+	0x19, 0x03, 0x07, 0x00, 0x20, 0x00, 0x20, 0x01, 0x7c, 0x0b, 0x07, 0x00,
+	0x20, 0x00, 0x20, 0x01, 0x94, 0x0b, 0x07, 0x00, 0x20, 0x00, 0x42, 0x00,
+	0x55, 0x0b, 0x00, 0x9a, 0x01, 0x10, 0x6d, 0x69, 0x6e, 0x64, 0x62, 0x2e,
+	0x70, 0x72, 0x6f, 0x63, 0x65, 0x64, 0x75, 0x72, 0x65, 0x73, 0x7b, 0x22,
+	0x6e, 0x61, 0x6d, 0x65, 0x22, 0x3a, 0x22, 0x69, 0x73, 0x5f, 0x70, 0x6f,
+	0x73, 0x69, 0x74, 0x69, 0x76, 0x65, 0x22, 0x2c, 0x22, 0x70, 0x61, 0x72,
+	0x61, 0x6d, 0x73, 0x22, 0x3a, 0x5b, 0x7b, 0x22, 0x6e, 0x61, 0x6d, 0x65,
+	0x22, 0x3a, 0x22, 0x61, 0x6d, 0x6f, 0x75, 0x6e, 0x74, 0x22, 0x2c, 0x22,
+	0x74, 0x79, 0x70, 0x65, 0x22, 0x3a, 0x22, 0x42, 0x49, 0x47, 0x49, 0x4e,
+	0x54, 0x22, 0x7d, 0x5d, 0x2c, 0x22, 0x72, 0x65, 0x74, 0x75, 0x72, 0x6e,
+	0x73, 0x22, 0x3a, 0x22, 0x42, 0x4f, 0x4f, 0x4c, 0x45, 0x41, 0x4e, 0x22,
+	0x2c, 0x22, 0x64, 0x65, 0x73, 0x63, 0x72, 0x69, 0x70, 0x74, 0x69, 0x6f,
+	0x6e, 0x22, 0x3a, 0x22, 0x57, 0x68, 0x65, 0x74, 0x68, 0x65, 0x72, 0x20,
+	0x61, 0x6e, 0x20, 0x61, 0x6d, 0x6f, 0x75, 0x6e, 0x74, 0x20, 0x69, 0x73,
+	0x20, 0x61, 0x62, 0x6f, 0x76, 0x65, 0x20, 0x7a, 0x65, 0x72, 0x6f, 0x2e,
+	0x22, 0x7d, 0x0a,
+}
+
+func TestWASMEngine_IntrospectExactTypes(t *testing.T) {
+	engine, err := NewWASMEngine(DefaultWASMConfig())
+	if err != nil {
+		t.Fatalf("Failed to create WASM engine: %v", err)
+	}
+	defer engine.Close()
+
+	functions, err := engine.IntrospectModule(ledgerWASM)
+	if err != nil {
+		t.Fatalf("Failed to introspect module: %v", err)
+	}
+
+	add := functions["add_cents"]
+	if add == nil || len(add.Params) != 2 || add.Params[0].DataType != "BIGINT" || add.ReturnType != "BIGINT" {
+		t.Errorf("Unexpected add_cents signature: %+v", add)
+	}
+	scale := functions["scale"]
+	if scale == nil || len(scale.Params) != 2 || scale.Params[0].DataType != "REAL" || scale.ReturnType != "REAL" {
+		t.Errorf("Unexpected scale signature: %+v", scale)
+	}
+	if positive := functions["is_positive"]; positive == nil || positive.ReturnType != "BOOLEAN" {
+		t.Errorf("Unexpected is_positive signature: %+v", positive)
+	}
+}
+
+func TestPagedEngine_CallExactTypes(t *testing.T) {
+	engine, err := NewPagedEngine(t.TempDir())
+	if err != nil {
+		t.Fatalf("Failed to create engine: %v", err)
+	}
+	defer engine.Close()
+
+	if err := engine.CreateModule(&StoredModule{Name: "ledger", Language: "wasm", Code: ledgerWASM}); err != nil {
+		t.Fatalf("Failed to create module: %v", err)
+	}
+
+	tests := []struct {
+		name     string
+		function string
+		args     []interface{}
+		want     interface{}
+	}{
+		{"i64 beyond 2^53", "add_cents", []interface{}{int64(9007199254740993), 1}, int64(9007199254740994)},
+		{"i64 card number", "add_cents", []interface{}{4111111111111111, 0}, int64(4111111111111111)},
+		{"i64 from JSON", "add_cents", []interface{}{json.Number("9223372036854775806"), json.Number("1")}, int64(math.MaxInt64)},
+		{"i64 from whole float", "add_cents", []interface{}{100.0, -1}, int64(99)},
+		{"f32", "scale", []interface{}{1.5, 2}, float32(3)},
+		{"boolean result", "is_positive", []interface{}{-5}, false},
+		{"boolean result true", "is_positive", []interface{}{int64(3)}, true},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			result, err := engine.CallModuleFunction("ledger", tt.function, tt.args...)
+			if err != nil {
+				t.Fatalf("Call failed: %v", err)
+			}
+			if result != tt.want {
+				t.Errorf("Expected %v (%T), got %v (%T)", tt.want, tt.want, result, result)
+			}
+		})
+	}
+}
+
+func TestPagedEngine_CallOverflow(t *testing.T) {
+	engine, err := NewPagedEngine(t.TempDir())
+	if err != nil {
+		t.Fatalf("Failed to create engine: %v", err)
+	}
+	defer engine.Close()
+
+	if err := engine.CreateModule(&StoredModule{Name: "ledger", Code: ledgerWASM}); err != nil {
+		t.Fatalf("Failed to create module: %v", err)
+	}
+	if err := engine.CreateModule(&StoredModule{Name: "business_rules", Code: businessRulesWASM}); err != nil {
+		t.Fatalf("Failed to create module: %v", err)
+	}
+
+	tests := []struct {
+		name     string
+		module   string
+		function string
+		args     []interface{}
+		wantErr  string
+	}{
+		{"i32 overflow", "business_rules", "calculate_late_fee", []interface{}{int64(1) << 31, 0}, "argument 1: value 2147483648 overflows INT"},
+		{"i32 underflow", "business_rules", "calculate_late_fee", []interface{}{0, int64(math.MinInt32) - 1}, "argument 2: value -2147483649 overflows INT"},
+		{"i32 fraction", "business_rules", "calculate_late_fee", []interface{}{2.5, 0}, "value 2.5 is not an integer"},
+		{"i64 overflow", "ledger", "add_cents", []interface{}{uint64(math.MaxUint64), 0}, "overflows BIGINT"},
+		{"i64 JSON overflow", "ledger", "add_cents", []interface{}{json.Number("9223372036854775808"), 0}, "too large to be an exact integer"},
+		{"i64 inexact float", "ledger", "add_cents", []interface{}{1e17, 0}, "too large to be an exact integer"},
+		{"f32 overflow", "ledger", "scale", []interface{}{1e39, 1}, "overflows REAL"},
+		{"string", "ledger", "add_cents", []interface{}{"12", 0}, "cannot convert string"},
+		{"NULL", "ledger", "is_positive", []interface{}{nil}, "NULL"},
+		{"argument count", "ledger", "add_cents", []interface{}{1}, "expected 2 argument(s), got 1"},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			_, err := engine.CallModuleFunction(tt.module, tt.function, tt.args...)
+			if err == nil {
+				t.Fatalf("Expected error containing %q", tt.wantErr)
+			}
+			if !strings.Contains(err.Error(), tt.wantErr) {
+				t.Errorf("Expected error containing %q, got: %v", tt.wantErr, err)
+			}
+		})
+	}
+}
+
+func TestEngineAdapter_CallBigintLiteral(t *testing.T) {
+	adapter, err := NewEngineAdapter(t.TempDir(), false)
+	if err != nil {
+		t.Fatalf("Failed to create adapter: %v", err)
+	}
+	defer adapter.Close()
+
+	if err := adapter.pagedEngine.CreateModule(&StoredModule{Name: "ledger", Code: ledgerWASM}); err != nil {
+		t.Fatalf("Failed to create module: %v", err)
+	}
+
+	result, err := adapter.CallProcedureViaAdapter("ledger.add_cents", 9007199254740993, 2)
+	if err != nil {
+		t.Fatalf("Failed to call procedure: %v", err)
+	}
+	if result != int64(9007199254740995) {
+		t.Errorf("Expected 9007199254740995, got %v", result)
+	}
+}
+
+// WASM module taking and returning TEXT and BLOB through the guest memory
+// ABI, with a bump allocator standing in for the SDK's mindb_alloc:
+//
+//	#[procedure] fn echo(s: &str) -> String
+//	#[procedure] fn echo_blob(data: &[u8]) -> Vec<u8>
+//	#[procedure] fn text_length(s: &str, extra: i32) -> i32 { s.len() as i32 + extra }
+//	#[no_mangle] extern "C" fn freed() -> i32 // mindb_dealloc calls so far
+var textWASM = []byte{
+	0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x19, 0x05, 0x60,
+	0x02, 0x7f, 0x7f, 0x00, 0x60, 0x01, 0x7f, 0x01, 0x7f, 0x60, 0x01, 0x7e,
+	0x00, 0x60, 0x02, 0x7e, 0x7f, 0x01, 0x7f, 0x60, 0x00, 0x01, 0x7f, 0x02,
+	0x31, 0x02, 0x03, 0x65, 0x6e, 0x76, 0x11, 0x6d, 0x69, 0x6e, 0x64, 0x62,
+	0x5f, 0x72, 0x65, 0x74, 0x75, 0x72, 0x6e, 0x5f, 0x74, 0x65, 0x78, 0x74,
+	0x00, 0x00, 0x03, 0x65, 0x6e, 0x76, 0x11, 0x6d, 0x69, 0x6e, 0x64, 0x62,
+	0x5f, 0x72, 0x65, 0x74, 0x75, 0x72, 0x6e, 0x5f, 0x62, 0x6c, 0x6f, 0x62,
+	0x00, 0x00, 0x03, 0x07, 0x06, 0x01, 0x00, 0x02, 0x02, 0x03, 0x04, 0x05,
+	0x03, 0x01, 0x00, 0x01, 0x06, 0x0c, 0x02, 0x7f, 0x01, 0x41, 0x80, 0x08,
+	0x0b, 0x7f, 0x01, 0x41, 0x00, 0x0b, 0x07, 0x51, 0x07, 0x06, 0x6d, 0x65,
+	0x6d, 0x6f, 0x72, 0x79, 0x02, 0x00, 0x0b, 0x6d, 0x69, 0x6e, 0x64, 0x62,
+	0x5f, 0x61, 0x6c, 0x6c, 0x6f, 0x63, 0x00, 0x02, 0x0d, 0x6d, 0x69, 0x6e,
+	0x64, 0x62, 0x5f, 0x64, 0x65, 0x61, 0x6c, 0x6c, 0x6f, 0x63, 0x00, 0x03,
+	0x04, 0x65, 0x63, 0x68, 0x6f, 0x00, 0x04, 0x09, 0x65, 0x63, 0x68, 0x6f,
+	0x5f, 0x62, 0x6c, 0x6f, 0x62, 0x00, 0x05, 0x0b, 0x74, 0x65, 0x78, 0x74,
+	0x5f, 0x6c, 0x65, 0x6e, 0x67, 0x74, 0x68, 0x00, 0x06, 0x05, 0x66, 0x72,
+	0x65, 0x65, 0x64, 0x00, 0x07, 0x0a, 0x41, 0x06, 0x0b, 0x00, 0x23, 0x00,
+	0x23, 0x00, 0x20, 0x00, 0x6a, 0x24, 0x00, 0x0b, 0x09, 0x00, 0x23, 0x01,
+	0x41, 0x01, 0x6a, 0x24, 0x01, 0x0b, 0x0d, 0x00, 0x20, 0x00, 0x42, 0x20,
+	0x88, 0xa7, 0x20, 0x00, 0xa7, 0x10, 0x00, 0x0b, 0x0d, 0x00, 0x20, 0x00,
+	0x42, 0x20, 0x88, 0xa7, 0x20, 0x00, 0xa7, 0x10, 0x01, 0x0b, 0x08, 0x00,
+	0x20, 0x00, 0xa7, 0x20, 0x01, 0x6a, 0x0b, 0x04, 0x00, 0x23, 0x01, 0x0b,
+	0x00, 0x92, 0x02, 0x10, 0x6d, 0x69, 0x6e, 0x64, 0x62, 0x2e, 0x70, 0x72,
+	0x6f, 0x63, 0x65, 0x64, 0x75, 0x72, 0x65, 0x73, 0x7b, 0x22, 0x6e, 0x61,
+	0x6d, 0x65, 0x22, 0x3a, 0x22, 0x65, 0x63, 0x68, 0x6f, 0x22, 0x2c, 0x22,
+	0x70, 0x61, 0x72, 0x61, 0x6d, 0x73, 0x22, 0x3a, 0x5b, 0x7b, 0x22, 0x6e,
+	0x61, 0x6d, 0x65, 0x22, 0x3a, 0x22, 0x73, 0x22, 0x2c, 0x22, 0x74, 0x79,
+	0x70, 0x65, 0x22, 0x3a, 0x22, 0x54, 0x45, 0x58, 0x54, 0x22, 0x7d, 0x5d,
+	0x2c, 0x22, 0x72, 0x65, 0x74, 0x75, 0x72, 0x6e, 0x73, 0x22, 0x3a, 0x22,
+	0x54, 0x45, 0x58, 0x54, 0x22, 0x7d, 0x0a, 0x7b, 0x22, 0x6e, 0x61, 0x6d,
+	0x65, 0x22, 0x3a, 0x22, 0x65, 0x63, 0x68, 0x6f, 0x5f, 0x62, 0x6c, 0x6f,
+	0x62, 0x22, 0x2c, 0x22, 0x70, 0x61, 0x72, 0x61, 0x6d, 0x73, 0x22, 0x3a,
+	0x5b, 0x7b, 0x22, 0x6e, 0x61, 0x6d, 0x65, 0x22, 0x3a, 0x22, 0x64, 0x61,
+	0x74, 0x61, 0x22, 0x2c, 0x22, 0x74, 0x79, 0x70, 0x65, 0x22, 0x3a, 0x22,
+	0x42, 0x4c, 0x4f, 0x42, 0x22, 0x7d, 0x5d, 0x2c, 0x22, 0x72, 0x65, 0x74,
+	0x75, 0x72, 0x6e, 0x73, 0x22, 0x3a, 0x22, 0x42, 0x4c, 0x4f, 0x42, 0x22,
+	0x7d, 0x0a, 0x7b, 0x22, 0x6e, 0x61, 0x6d, 0x65, 0x22, 0x3a, 0x22, 0x74,
+	0x65, 0x78, 0x74, 0x5f, 0x6c, 0x65, 0x6e, 0x67, 0x74, 0x68, 0x22, 0x2c,
+	0x22, 0x70, 0x61, 0x72, 0x61, 0x6d, 0x73, 0x22, 0x3a, 0x5b, 0x7b, 0x22,
+	0x6e, 0x61, 0x6d, 0x65, 0x22, 0x3a, 0x22, 0x73, 0x22, 0x2c, 0x22, 0x74,
+	0x79, 0x70, 0x65, 0x22, 0x3a, 0x22, 0x54, 0x45, 0x58, 0x54, 0x22, 0x7d,
+	0x2c, 0x7b, 0x22, 0x6e, 0x61, 0x6d, 0x65, 0x22, 0x3a, 0x22, 0x65, 0x78,
+	0x74, 0x72, 0x61, 0x22, 0x2c, 0x22, 0x74, 0x79, 0x70, 0x65, 0x22, 0x3a,
+	0x22, 0x49, 0x4e, 0x54, 0x22, 0x7d, 0x5d, 0x2c, 0x22, 0x72, 0x65, 0x74,
+	0x75, 0x72, 0x6e, 0x73, 0x22, 0x3a, 0x22, 0x49, 0x4e, 0x54, 0x22, 0x7d,
+	0x0a,
+}
+
+func TestPagedEngine_CallTextAndBlob(t *testing.T) {
+	engine, err := NewPagedEngine(t.TempDir())
+	if err != nil {
+		t.Fatalf("Failed to create engine: %v", err)
+	}
+	defer engine.Close()
+
+	mod := &StoredModule{Name: "text", Code: textWASM}
+	if err := engine.CreateModule(mod); err != nil {
+		t.Fatalf("Failed to create module: %v", err)
+	}
+
+	// The allocator exports are part of the ABI, not callable functions
+	if _, exists := mod.Functions["mindb_alloc"]; exists || len(mod.Functions) != 4 {
+		t.Errorf("Unexpected functions: %v", mod.Functions)
+	}
+	if echo := mod.Functions["echo"]; echo == nil || echo.Params[0].DataType != "TEXT" || echo.ReturnType != "TEXT" {
+		t.Errorf("Unexpected echo signature: %+v", echo)
+	}
+
+	result, err := engine.CallModuleFunction("text", "echo", "héllo, wörld")
+	if err != nil {
+		t.Fatalf("Failed to call echo: %v", err)
+	}
+	if result != "héllo, wörld" {
+		t.Errorf("Expected echoed text, got %v (%T)", result, result)
+	}
+
+	result, err = engine.CallModuleFunction("text", "echo", "")
+	if err != nil {
+		t.Fatalf("Failed to call echo with empty text: %v", err)
+	}
+	if result != "" {
+		t.Errorf("Expected empty text, got %v (%T)", result, result)
+	}
+
+	result, err = engine.CallModuleFunction("text", "echo_blob", []byte{0x00, 0x01, 0xff})
+	if err != nil {
+		t.Fatalf("Failed to call echo_blob: %v", err)
+	}
+	if blob, ok := result.([]byte); !ok || string(blob) != "\x00\x01\xff" {
+		t.Errorf("Expected echoed blob, got %v (%T)", result, result)
+	}
+
+	result, err = engine.CallModuleFunction("text", "text_length", "héllo", 2)
+	if err != nil {
+		t.Fatalf("Failed to call text_length: %v", err)
+	}
+	if result != int32(8) {
+		t.Errorf("Expected 8, got %v", result)
+	}
+
+	if _, err := engine.CallModuleFunction("text", "echo", 42); err == nil || !strings.Contains(err.Error(), "cannot convert int to TEXT") {
+		t.Errorf("Expected conversion error, got: %v", err)
+	}
+}
+
+func TestPagedEngine_TextRequiresAllocator(t *testing.T) {
+	engine, err := NewPagedEngine(t.TempDir())
+	if err != nil {
+		t.Fatalf("Failed to create engine: %v", err)
+	}
+	defer engine.Close()
+
+	// add_cents takes an i64 but the module has no mindb_alloc export
+	proc := &StoredProcedure{
+		Name:       "add_cents",
+		Code:       ledgerWASM,
+		Params:     []Column{{Name: "note", DataType: "TEXT"}, {Name: "b", DataType: "BIGINT"}},
+		ReturnType: "BIGINT",
+	}
+	if err := engine.CreateProcedure(proc); err != nil {
+		t.Fatalf("Failed to create procedure: %v", err)
+	}
+
+	_, err = engine.CallProcedure("add_cents", "memo", 1)
+	if err == nil || !strings.Contains(err.Error(), "mindb_alloc") {
+		t.Errorf("Expected missing allocator error, got: %v", err)
+	}
+}
+
+func TestEngineAdapter_CallText(t *testing.T) {
+	adapter, err := NewEngineAdapter(t.TempDir(), false)
+	if err != nil {
+		t.Fatalf("Failed to create adapter: %v", err)
+	}
+	defer adapter.Close()
+
+	if err := adapter.pagedEngine.CreateModule(&StoredModule{Name: "text", Code: textWASM}); err != nil {
+		t.Fatalf("Failed to create module: %v", err)
+	}
+
+	parser := NewParser()
+	stmt, err := parser.Parse("CALL text.echo('hi there')")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	result, err := adapter.Execute(stmt)
+	if err != nil {
+		t.Fatalf("Failed to execute CALL: %v", err)
+	}
+	if !strings.Contains(result, "hi there") {
+		t.Errorf("Expected echoed text in result, got: %s", result)
+	}
+}
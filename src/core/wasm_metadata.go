@@ -0,0 +1,166 @@
+package mindb
+
+import (
+	"bytes"
+	"encoding/json"
+	"fmt"
+	"strings"
+)
+
+// ProcedureMetadataSection is the custom section written by the Rust SDK's
+// #[procedure] attribute. It holds one JSON object per line, one per exported
+// procedure.
+const ProcedureMetadataSection = "mindb.procedures"
+
+// ProcedureSignature describes a procedure as recorded in its WASM module
+type ProcedureSignature struct {
+	Name        string           `json:"name"`
+	Kind        string           `json:"kind,omitempty"` // "aggregate" for #[aggregate]; empty for procedures
+	Params      []ProcedureParam `json:"params"`
+	Returns     string           `json:"returns"`
+	OnNull      string           `json:"on_null,omitempty"` // "strict" or "called"; empty rejects NULL arguments
+	Description string           `json:"description,omitempty"`
+}
+
+// ProcedureParam is a named, typed procedure parameter
+type ProcedureParam struct {
+	Name string `json:"name"`
+	Type string `json:"type"`
+}
+
+// Columns converts the recorded parameters to column definitions
+func (s *ProcedureSignature) Columns() []Column {
+	columns := make([]Column, len(s.Params))
+	for i, p := range s.Params {
+		columns[i] = Column{Name: p.Name, DataType: p.Type}
+	}
+	return columns
+}
+
+// onNull converts the recorded NULL handling to a StoredProcedure OnNull
+func (s *ProcedureSignature) onNull() string {
+	switch strings.ToLower(s.OnNull) {
+	case "strict":
+		return OnNullStrict
+	case "called":
+		return OnNullCalled
+	}
+	return ""
+}
+
+// ParseProcedureMetadata reads procedure signatures from a WASM module's
+// mindb.procedures custom section, keyed by function name. Modules without
+// the section return an empty map.
+func ParseProcedureMetadata(code []byte) (map[string]*ProcedureSignature, error) {
+	payload, err := wasmCustomSection(code, ProcedureMetadataSection)
+	if err != nil {
+		return nil, err
+	}
+
+	signatures := make(map[string]*ProcedureSignature)
+	for _, line := range bytes.Split(payload, []byte("\n")) {
+		line = bytes.TrimSpace(line)
+		if len(line) == 0 {
+			continue
+		}
+
+		var sig ProcedureSignature
+		if err := json.Unmarshal(line, &sig); err != nil {
+			return nil, fmt.Errorf("invalid procedure metadata: %w", err)
+		}
+		if sig.Name == "" {
+			return nil, fmt.Errorf("invalid procedure metadata: missing name")
+		}
+		signatures[sig.Name] = &sig
+	}
+
+	return signatures, nil
+}
+
+// aggregateExports lists the exports that make up the recorded aggregates,
+// which are not callable as procedures on their own
+func aggregateExports(code []byte) map[string]bool {
+	exports := make(map[string]bool)
+	signatures, err := ParseProcedureMetadata(code)
+	if err != nil {
+		return exports
+	}
+	for _, sig := range signatures {
+		if sig.Kind == "aggregate" {
+			for _, fn := range aggregateFunctionNames(sig.Name) {
+				exports[fn] = true
+			}
+		}
+	}
+	return exports
+}
+
+// procedureSignature returns the recorded signature for functionName, or nil
+// if the module carries none
+func procedureSignature(code []byte, functionName string) *ProcedureSignature {
+	signatures, err := ParseProcedureMetadata(code)
+	if err != nil {
+		return nil
+	}
+	return signatures[functionName]
+}
+
+// wasmCustomSection returns the concatenated payload of every custom section
+// with the given name
+func wasmCustomSection(code []byte, name string) ([]byte, error) {
+	if len(code) < 8 || !bytes.Equal(code[:4], []byte("\x00asm")) {
+		return nil, fmt.Errorf("not a WASM module")
+	}
+
+	var payload []byte
+	pos := 8
+	for pos < len(code) {
+		id := code[pos]
+		pos++
+
+		size, n, err := readULEB128(code[pos:])
+		if err != nil {
+			return nil, err
+		}
+		pos += n
+		if uint64(len(code)-pos) < size {
+			return nil, fmt.Errorf("truncated WASM section")
+		}
+		section := code[pos : pos+int(size)]
+		pos += int(size)
+
+		// Custom sections have id 0 and start with their name
+		if id != 0 {
+			continue
+		}
+		nameLen, n, err := readULEB128(section)
+		if err != nil {
+			return nil, err
+		}
+		if uint64(len(section)-n) < nameLen {
+			return nil, fmt.Errorf("truncated WASM custom section name")
+		}
+		if string(section[n:n+int(nameLen)]) == name {
+			payload = append(payload, section[n+int(nameLen):]...)
+		}
+	}
+
+	return payload, nil
+}
+
+// readULEB128 decodes an unsigned LEB128 value, returning it and its length
+func readULEB128(data []byte) (uint64, int, error) {
+	var result uint64
+	var shift uint
+	for i, b := range data {
+		if shift >= 64 {
+			break
+		}
+		result |= uint64(b&0x7f) << shift
+		if b&0x80 == 0 {
+			return result, i + 1, nil
+		}
+		shift += 7
+	}
+	return 0, 0, fmt.Errorf("malformed LEB128 value in WASM module")
+}
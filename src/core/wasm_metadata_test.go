@@ -0,0 +1,137 @@
+package mindb
+
+import (
+	"testing"
+)
+
+// WASM module carrying a mindb.procedures custom section, as produced by:
+//
+//	/// Sales tax for a state.
+//	#[procedure]
+//	fn calculate_tax(amount: f64, state_code: i32) -> f64 { amount }
+var procedureMetadataWASM = []byte{
+	0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x07, 0x01, 0x60,
+	0x02, 0x7c, 0x7f, 0x01, 0x7c, 0x03, 0x02, 0x01, 0x00, 0x07, 0x11, 0x01,
+	0x0d, 0x63, 0x61, 0x6c, 0x63, 0x75, 0x6c, 0x61, 0x74, 0x65, 0x5f, 0x74,
+	0x61, 0x78, 0x00, 0x00, 0x0a, 0x06, 0x01, 0x04, 0x00, 0x20, 0x00, 0x0b,
+	0x00, 0xb2, 0x01, 0x10, 0x6d, 0x69, 0x6e, 0x64, 0x62, 0x2e, 0x70, 0x72,
+	0x6f, 0x63, 0x65, 0x64, 0x75, 0x72, 0x65, 0x73, 0x7b, 0x22, 0x6e, 0x61,
+	0x6d, 0x65, 0x22, 0x3a, 0x22, 0x63, 0x61, 0x6c, 0x63, 0x75, 0x6c, 0x61,
+	0x74, 0x65, 0x5f, 0x74, 0x61, 0x78, 0x22, 0x2c, 0x22, 0x70, 0x61, 0x72,
+	0x61, 0x6d, 0x73, 0x22, 0x3a, 0x5b, 0x7b, 0x22, 0x6e, 0x61, 0x6d, 0x65,
+	0x22, 0x3a, 0x22, 0x61, 0x6d, 0x6f, 0x75, 0x6e, 0x74, 0x22, 0x2c, 0x22,
+	0x74, 0x79, 0x70, 0x65, 0x22, 0x3a, 0x22, 0x46, 0x4c, 0x4f, 0x41, 0x54,
+	0x22, 0x7d, 0x2c, 0x7b, 0x22, 0x6e, 0x61, 0x6d, 0x65, 0x22, 0x3a, 0x22,
+	0x73, 0x74, 0x61, 0x74, 0x65, 0x5f, 0x63, 0x6f, 0x64, 0x65, 0x22, 0x2c,
+	0x22, 0x74, 0x79, 0x70, 0x65, 0x22, 0x3a, 0x22, 0x49, 0x4e, 0x54, 0x22,
+	0x7d, 0x5d, 0x2c, 0x22, 0x72, 0x65, 0x74, 0x75, 0x72, 0x6e, 0x73, 0x22,
+	0x3a, 0x22, 0x46, 0x4c, 0x4f, 0x41, 0x54, 0x22, 0x2c, 0x22, 0x64, 0x65,
+	0x73, 0x63, 0x72, 0x69, 0x70, 0x74, 0x69, 0x6f, 0x6e, 0x22, 0x3a, 0x22,
+	0x53, 0x61, 0x6c, 0x65, 0x73, 0x20, 0x74, 0x61, 0x78, 0x20, 0x66, 0x6f,
+	0x72, 0x20, 0x61, 0x20, 0x73, 0x74, 0x61, 0x74, 0x65, 0x2e, 0x22, 0x7d,
+	0x0a,
+}
+
+func TestParseProcedureMetadata(t *testing.T) {
+	signatures, err := ParseProcedureMetadata(procedureMetadataWASM)
+	if err != nil {
+		t.Fatalf("Failed to parse metadata: %v", err)
+	}
+
+	sig, ok := signatures["calculate_tax"]
+	if !ok {
+		t.Fatalf("Expected signature for calculate_tax, got %v", signatures)
+	}
+	if len(sig.Params) != 2 {
+		t.Fatalf("Expected 2 parameters, got %d", len(sig.Params))
+	}
+	if sig.Params[0] != (ProcedureParam{Name: "amount", Type: "FLOAT"}) {
+		t.Errorf("Unexpected first parameter: %+v", sig.Params[0])
+	}
+	if sig.Params[1] != (ProcedureParam{Name: "state_code", Type: "INT"}) {
+		t.Errorf("Unexpected second parameter: %+v", sig.Params[1])
+	}
+	if sig.Returns != "FLOAT" {
+		t.Errorf("Expected return type FLOAT, got %s", sig.Returns)
+	}
+	if sig.Description != "Sales tax for a state." {
+		t.Errorf("Unexpected description: %q", sig.Description)
+	}
+}
+
+func TestParseProcedureMetadata_NoSection(t *testing.T) {
+	signatures, err := ParseProcedureMetadata(simpleAddWASM)
+	if err != nil {
+		t.Fatalf("Failed to parse metadata: %v", err)
+	}
+	if len(signatures) != 0 {
+		t.Errorf("Expected no signatures, got %v", signatures)
+	}
+}
+
+func TestParseProcedureMetadata_Invalid(t *testing.T) {
+	if _, err := ParseProcedureMetadata([]byte("not wasm")); err == nil {
+		t.Error("Expected error for non-WASM input")
+	}
+
+	// Truncate inside the custom section
+	truncated := procedureMetadataWASM[:len(procedureMetadataWASM)-20]
+	if _, err := ParseProcedureMetadata(truncated); err == nil {
+		t.Error("Expected error for truncated module")
+	}
+}
+
+func TestWASMEngine_IntrospectFunction_Metadata(t *testing.T) {
+	engine, err := NewWASMEngine(DefaultWASMConfig())
+	if err != nil {
+		t.Fatalf("Failed to create WASM engine: %v", err)
+	}
+	defer engine.Close()
+
+	// Recorded names win over the guesses from generateParamNames
+	params, returnType, err := engine.IntrospectFunction(procedureMetadataWASM, "calculate_tax")
+	if err != nil {
+		t.Fatalf("Failed to introspect function: %v", err)
+	}
+	if len(params) != 2 || params[0].Name != "amount" || params[1].Name != "state_code" {
+		t.Errorf("Expected parameters amount, state_code; got %v", params)
+	}
+	if params[1].DataType != "INT" {
+		t.Errorf("Expected state_code to be INT, got %s", params[1].DataType)
+	}
+	if returnType != "FLOAT" {
+		t.Errorf("Expected return type FLOAT, got %s", returnType)
+	}
+}
+
+func TestPagedEngine_CreateProcedure_Metadata(t *testing.T) {
+	engine, err := NewPagedEngine(t.TempDir())
+	if err != nil {
+		t.Fatalf("Failed to create engine: %v", err)
+	}
+	defer engine.Close()
+
+	// No parameter list: CREATE PROCEDURE takes it from the module
+	proc := &StoredProcedure{
+		Name:     "calculate_tax",
+		Language: "rust",
+		Code:     procedureMetadataWASM,
+	}
+	if err := engine.CreateProcedure(proc); err != nil {
+		t.Fatalf("Failed to create procedure: %v", err)
+	}
+
+	retrieved, err := engine.GetProcedure("calculate_tax")
+	if err != nil {
+		t.Fatalf("Failed to get procedure: %v", err)
+	}
+	if len(retrieved.Params) != 2 || retrieved.Params[0].Name != "amount" {
+		t.Errorf("Expected parameters from metadata, got %v", retrieved.Params)
+	}
+	if retrieved.ReturnType != "FLOAT" {
+		t.Errorf("Expected return type FLOAT, got %s", retrieved.ReturnType)
+	}
+	if retrieved.Description != "Sales tax for a state." {
+		t.Errorf("Expected description from metadata, got %q", retrieved.Description)
+	}
+}
@@ -0,0 +1,140 @@
+package mindb
+
+import (
+	"encoding/base64"
+	"strings"
+	"testing"
+)
+
+// WASM module with procedures called on NULL input and one that is STRICT,
+// equivalent to:
+//
+//	#[procedure]
+//	fn coalesce_rate(rate: Option<f64>) -> f64 { rate.unwrap_or(0.05) }
+//
+//	#[procedure]
+//	fn safe_div(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+//	    match (a, b) {
+//	        (Some(a), Some(b)) if b != 0.0 => Some(a / b),
+//	        _ => None,
+//	    }
+//	}
+//
+//	#[procedure]
+//	fn half(x: f64) -> f64 { x * 0.5 }
+var nullAwareWASM = []byte{
+	0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x14, 0x04, 0x60,
+	0x01, 0x7f, 0x01, 0x7f, 0x60, 0x00, 0x00, 0x60, 0x01, 0x7c, 0x01, 0x7c,
+	0x60, 0x02, 0x7c, 0x7c, 0x01, 0x7c, 0x02, 0x31, 0x02, 0x03, 0x65, 0x6e,
+	0x76, 0x11, 0x6d, 0x69, 0x6e, 0x64, 0x62, 0x5f, 0x61, 0x72, 0x67, 0x5f,
+	0x69, 0x73, 0x5f, 0x6e, 0x75, 0x6c, 0x6c, 0x00, 0x00, 0x03, 0x65, 0x6e,
+	0x76, 0x11, 0x6d, 0x69, 0x6e, 0x64, 0x62, 0x5f, 0x72, 0x65, 0x74, 0x75,
+	0x72, 0x6e, 0x5f, 0x6e, 0x75, 0x6c, 0x6c, 0x00, 0x01, 0x03, 0x04, 0x03,
+	0x02, 0x03, 0x02, 0x07, 0x23, 0x03, 0x0d, 0x63, 0x6f, 0x61, 0x6c, 0x65,
+	0x73, 0x63, 0x65, 0x5f, 0x72, 0x61, 0x74, 0x65, 0x00, 0x02, 0x08, 0x73,
+	0x61, 0x66, 0x65, 0x5f, 0x64, 0x69, 0x76, 0x00, 0x03, 0x04, 0x68, 0x61,
+	0x6c, 0x66, 0x00, 0x04, 0x0a, 0x53, 0x03, 0x15, 0x00, 0x41, 0x00, 0x10,
+	0x00, 0x04, 0x7c, 0x44, 0x9a, 0x99, 0x99, 0x99, 0x99, 0x99, 0xa9, 0x3f,
+	0x05, 0x20, 0x00, 0x0b, 0x0b, 0x2c, 0x00, 0x41, 0x00, 0x10, 0x00, 0x41,
+	0x01, 0x10, 0x00, 0x72, 0x20, 0x01, 0x44, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x61, 0x72, 0x04, 0x7c, 0x10, 0x01, 0x44, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x20, 0x00, 0x20, 0x01, 0xa3,
+	0x0b, 0x0b, 0x0e, 0x00, 0x20, 0x00, 0x44, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0xe0, 0x3f, 0xa2, 0x0b, 0x00, 0xd1, 0x02, 0x10, 0x6d, 0x69, 0x6e,
+	0x64, 0x62, 0x2e, 0x70, 0x72, 0x6f, 0x63, 0x65, 0x64, 0x75, 0x72, 0x65,
+	0x73, 0x7b, 0x22, 0x6e, 0x61, 0x6d, 0x65, 0x22, 0x3a, 0x22, 0x63, 0x6f,
+	0x61, 0x6c, 0x65, 0x73, 0x63, 0x65, 0x5f, 0x72, 0x61, 0x74, 0x65, 0x22,
+	0x2c, 0x22, 0x70, 0x61, 0x72, 0x61, 0x6d, 0x73, 0x22, 0x3a, 0x5b, 0x7b,
+	0x22, 0x6e, 0x61, 0x6d, 0x65, 0x22, 0x3a, 0x22, 0x72, 0x61, 0x74, 0x65,
+	0x22, 0x2c, 0x22, 0x74, 0x79, 0x70, 0x65, 0x22, 0x3a, 0x22, 0x46, 0x4c,
+	0x4f, 0x41, 0x54, 0x22, 0x7d, 0x5d, 0x2c, 0x22, 0x72, 0x65, 0x74, 0x75,
+	0x72, 0x6e, 0x73, 0x22, 0x3a, 0x22, 0x46, 0x4c, 0x4f, 0x41, 0x54, 0x22,
+	0x2c, 0x22, 0x6f, 0x6e, 0x5f, 0x6e, 0x75, 0x6c, 0x6c, 0x22, 0x3a, 0x22,
+	0x63, 0x61, 0x6c, 0x6c, 0x65, 0x64, 0x22, 0x7d, 0x0a, 0x7b, 0x22, 0x6e,
+	0x61, 0x6d, 0x65, 0x22, 0x3a, 0x22, 0x73, 0x61, 0x66, 0x65, 0x5f, 0x64,
+	0x69, 0x76, 0x22, 0x2c, 0x22, 0x70, 0x61, 0x72, 0x61, 0x6d, 0x73, 0x22,
+	0x3a, 0x5b, 0x7b, 0x22, 0x6e, 0x61, 0x6d, 0x65, 0x22, 0x3a, 0x22, 0x61,
+	0x22, 0x2c, 0x22, 0x74, 0x79, 0x70, 0x65, 0x22, 0x3a, 0x22, 0x46, 0x4c,
+	0x4f, 0x41, 0x54, 0x22, 0x7d, 0x2c, 0x7b, 0x22, 0x6e, 0x61, 0x6d, 0x65,
+	0x22, 0x3a, 0x22, 0x62, 0x22, 0x2c, 0x22, 0x74, 0x79, 0x70, 0x65, 0x22,
+	0x3a, 0x22, 0x46, 0x4c, 0x4f, 0x41, 0x54, 0x22, 0x7d, 0x5d, 0x2c, 0x22,
+	0x72, 0x65, 0x74, 0x75, 0x72, 0x6e, 0x73, 0x22, 0x3a, 0x22, 0x46, 0x4c,
+	0x4f, 0x41, 0x54, 0x22, 0x2c, 0x22, 0x6f, 0x6e, 0x5f, 0x6e, 0x75, 0x6c,
+	0x6c, 0x22, 0x3a, 0x22, 0x63, 0x61, 0x6c, 0x6c, 0x65, 0x64, 0x22, 0x7d,
+	0x0a, 0x7b, 0x22, 0x6e, 0x61, 0x6d, 0x65, 0x22, 0x3a, 0x22, 0x68, 0x61,
+	0x6c, 0x66, 0x22, 0x2c, 0x22, 0x70, 0x61, 0x72, 0x61, 0x6d, 0x73, 0x22,
+	0x3a, 0x5b, 0x7b, 0x22, 0x6e, 0x61, 0x6d, 0x65, 0x22, 0x3a, 0x22, 0x78,
+	0x22, 0x2c, 0x22, 0x74, 0x79, 0x70, 0x65, 0x22, 0x3a, 0x22, 0x46, 0x4c,
+	0x4f, 0x41, 0x54, 0x22, 0x7d, 0x5d, 0x2c, 0x22, 0x72, 0x65, 0x74, 0x75,
+	0x72, 0x6e, 0x73, 0x22, 0x3a, 0x22, 0x46, 0x4c, 0x4f, 0x41, 0x54, 0x22,
+	0x2c, 0x22, 0x6f, 0x6e, 0x5f, 0x6e, 0x75, 0x6c, 0x6c, 0x22, 0x3a, 0x22,
+	0x73, 0x74, 0x72, 0x69, 0x63, 0x74, 0x22, 0x7d, 0x0a,
+}
+
+func TestPagedEngine_CallOnNull(t *testing.T) {
+	engine, err := NewPagedEngine(t.TempDir())
+	if err != nil {
+		t.Fatalf("Failed to create engine: %v", err)
+	}
+	defer engine.Close()
+
+	for _, name := range []string{"coalesce_rate", "safe_div", "half"} {
+		if err := engine.CreateProcedure(&StoredProcedure{Name: name, Language: "wasm", Code: nullAwareWASM}); err != nil {
+			t.Fatalf("Failed to create %s: %v", name, err)
+		}
+	}
+	if proc := engine.procedures["safe_div"]; proc.OnNull != OnNullCalled {
+		t.Errorf("Expected safe_div to be called on NULL input, got %q", proc.OnNull)
+	}
+	if proc := engine.procedures["half"]; proc.OnNull != OnNullStrict {
+		t.Errorf("Expected half to be STRICT, got %q", proc.OnNull)
+	}
+
+	tests := []struct {
+		name      string
+		procedure string
+		args      []interface{}
+		want      interface{}
+	}{
+		{"NULL-aware default", "coalesce_rate", []interface{}{nil}, 0.05},
+		{"NULL-aware value", "coalesce_rate", []interface{}{0.1}, 0.1},
+		{"NULL-aware returns NULL", "safe_div", []interface{}{3.0, nil}, nil},
+		{"NULL-aware returns NULL for value", "safe_div", []interface{}{3.0, 0}, nil},
+		{"NULL-aware returns value", "safe_div", []interface{}{3.0, 2}, 1.5},
+		{"STRICT skips NULL", "half", []interface{}{nil}, nil},
+		{"STRICT value", "half", []interface{}{3}, 1.5},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			result, err := engine.CallProcedure(tt.procedure, tt.args...)
+			if err != nil {
+				t.Fatalf("Call failed: %v", err)
+			}
+			if result != tt.want {
+				t.Errorf("Expected %v (%T), got %v (%T)", tt.want, tt.want, result, result)
+			}
+		})
+	}
+}
+
+func TestEngineAdapter_CallOnNull(t *testing.T) {
+	adapter, err := NewEngineAdapter(t.TempDir(), false)
+	if err != nil {
+		t.Fatalf("Failed to create adapter: %v", err)
+	}
+	defer adapter.Close()
+
+	code := base64.StdEncoding.EncodeToString(nullAwareWASM)
+	execSQL(t, adapter, "CREATE PROCEDURE coalesce_rate(rate FLOAT) RETURNS FLOAT LANGUAGE wasm AS '"+code+"'")
+	execSQL(t, adapter, "CREATE PROCEDURE half(x FLOAT) RETURNS FLOAT CALLED ON NULL INPUT LANGUAGE wasm AS '"+code+"'")
+
+	if result := execSQL(t, adapter, "CALL coalesce_rate(NULL)"); !strings.Contains(result, "0.05") {
+		t.Errorf("Expected the default rate, got:\n%s", result)
+	}
+
+	// The declaration overrides the module's own
+	if proc := adapter.pagedEngine.procedures["half"]; proc.OnNull != OnNullCalled {
+		t.Errorf("Expected half to be called on NULL input, got %q", proc.OnNull)
+	}
+}
@@ -0,0 +1,320 @@
+package mindb
+
+import (
+	"fmt"
+	"math"
+	"time"
+
+	"github.com/bytecodealliance/wasmtime-go/v25"
+)
+
+// pooledInstance is an instantiated module with its own store. Host
+// functions are linked against state, which is reset before every call, so
+// one instance can serve the calls of a session in turn.
+type pooledInstance struct {
+	store    *wasmtime.Store
+	instance *wasmtime.Instance
+	state    *callState
+}
+
+// instancePool keeps fresh instances of one compiled module ready for
+// calls. At most max instances exist at a time, idle or in use; further
+// calls wait for one to be released.
+type instancePool struct {
+	module *wasmtime.Module
+	idle   chan *pooledInstance // Instances ready for a call
+	slots  chan struct{}        // One token per live instance
+}
+
+// newInstancePool creates an empty pool for module
+func newInstancePool(module *wasmtime.Module, max int) *instancePool {
+	if max < 1 {
+		max = 1
+	}
+	return &instancePool{
+		module: module,
+		idle:   make(chan *pooledInstance, max),
+		slots:  make(chan struct{}, max),
+	}
+}
+
+// acquire returns an idle instance, instantiating a new one while the pool
+// is below its limit. It waits up to timeout for an instance to be released.
+func (w *WASMEngine) acquire(pool *instancePool, timeout time.Duration) (*pooledInstance, error) {
+	select {
+	case inst := <-pool.idle:
+		return inst, nil
+	default:
+	}
+
+	timer := time.NewTimer(timeout)
+	defer timer.Stop()
+
+	select {
+	case inst := <-pool.idle:
+		return inst, nil
+	case pool.slots <- struct{}{}:
+		inst, err := w.instantiate(pool.module)
+		if err != nil {
+			<-pool.slots
+			return nil, err
+		}
+		return inst, nil
+	case <-timer.C:
+		return nil, fmt.Errorf("no WASM instance available after %v (MaxInstances: %d)", timeout, cap(pool.slots))
+	}
+}
+
+// release replaces an instance that has served a session with a fresh
+// one. Its linear memory and globals may still hold what the session's
+// calls left there, such as the rows and arguments of another user's call,
+// so an instance never serves a second session.
+func (w *WASMEngine) release(pool *instancePool) {
+	inst, err := w.instantiate(pool.module)
+	if err != nil {
+		pool.discard()
+		return
+	}
+	pool.idle <- inst
+}
+
+// discard drops an instance whose state can no longer be trusted, such as
+// after a trap or timeout, freeing its slot for a fresh one
+func (pool *instancePool) discard() {
+	<-pool.slots
+}
+
+// wasmSession is a series of calls on one pooled instance. Callers that keep
+// state in guest memory between calls, such as aggregates, make all of their
+// calls through one session.
+type wasmSession struct {
+	w    *WASMEngine
+	pool *instancePool
+	inst *pooledInstance
+	ctx  *ExecutionContext
+	done bool // Instance released, discarded or left to a timed-out call
+
+	calledOnNull bool   // Pass NULL arguments to the guest instead of rejecting them
+	fuel         uint64 // Fuel used by the session's completed calls
+}
+
+// openSession takes an instance of the module cached under name
+func (w *WASMEngine) openSession(name string, ctx *ExecutionContext) (*wasmSession, error) {
+	w.mu.RLock()
+	pool, exists := w.pools[name]
+	w.mu.RUnlock()
+	if !exists {
+		return nil, fmt.Errorf("stored procedure '%s' not found", name)
+	}
+
+	inst, err := w.acquire(pool, w.config.MaxExecutionTime)
+	if err != nil {
+		return nil, err
+	}
+
+	return &wasmSession{w: w, pool: pool, inst: inst, ctx: ctx}, nil
+}
+
+// call calls an exported function. params, when known, are the declared
+// parameter types. The instance is discarded if the call traps or times out,
+// ending the session.
+func (s *wasmSession) call(functionName string, params []Column, args []interface{}) (interface{}, error) {
+	if s.done {
+		return nil, fmt.Errorf("function '%s': instance is no longer available", functionName)
+	}
+
+	w := s.w
+	if err := w.reset(s.inst, s.ctx); err != nil {
+		s.discard()
+		return nil, err
+	}
+	store, state := s.inst.store, s.inst.state
+	state.procedure = functionName
+
+	// Get the exported function
+	fn := s.inst.instance.GetFunc(store, functionName)
+	if fn == nil {
+		return nil, fmt.Errorf("function '%s' not found in module", functionName)
+	}
+
+	// Convert Go args to the exact WASM parameter types, copying TEXT and
+	// BLOB values into guest memory. NULL arguments of NULL-aware procedures
+	// are passed as zero values; the guest asks mindb_arg_is_null.
+	if s.calledOnNull {
+		state.nulls = nullArgs(args)
+	}
+	buffers := &guestBuffers{store: store, instance: s.inst.instance}
+	wasmArgs, err := marshalArgs(fn.Type(store), params, args, state.nulls, buffers)
+	if err != nil {
+		if freeErr := buffers.free(); freeErr != nil {
+			s.discard()
+		}
+		return nil, fmt.Errorf("function '%s': %w", functionName, err)
+	}
+
+	// Execute with timeout. fuel is only read once the call has finished.
+	resultChan := make(chan interface{}, 1)
+	errorChan := make(chan error, 1)
+	var fuel uint64
+
+	go func() {
+		result, err := fn.Call(store, wasmArgs...)
+		if err != nil {
+			err = w.callError(err, state)
+		} else {
+			err = buffers.free()
+		}
+		fuel = w.fuelUsed(store)
+		if err != nil {
+			errorChan <- err
+			return
+		}
+		// TEXT and BLOB results are handed over through mindb_return_*,
+		// table rows through mindb_emit_row
+		if state.returnNull {
+			result = nil
+		} else if state.output != nil {
+			result = state.output
+		} else if state.rows != nil {
+			result = &ProcedureRows{Columns: state.columns, Rows: state.rows}
+		}
+		resultChan <- result
+	}()
+
+	// Wait for result or timeout. After a trap the instance's memory may be
+	// inconsistent, so it is not reused.
+	select {
+	case result := <-resultChan:
+		s.fuel += fuel
+		return result, nil
+	case err := <-errorChan:
+		s.fuel += fuel
+		s.discard()
+		return nil, err
+	case <-time.After(w.config.MaxExecutionTime):
+		// The call stops at its epoch deadline; its instance is dropped then
+		s.done = true
+		go func() {
+			select {
+			case <-resultChan:
+			case <-errorChan:
+			}
+			s.pool.discard()
+		}()
+		return nil, timeoutError(w.config.MaxExecutionTime, nil)
+	}
+}
+
+// close releases the instance, replacing it in the pool with a fresh one
+func (s *wasmSession) close() {
+	if !s.done {
+		s.done = true
+		s.w.release(s.pool)
+	}
+}
+
+// discard drops the instance instead of returning it to the pool
+func (s *wasmSession) discard() {
+	if !s.done {
+		s.done = true
+		s.pool.discard()
+	}
+}
+
+// instantiate creates a store, links the host functions and instantiates
+// module
+func (w *WASMEngine) instantiate(module *wasmtime.Module) (*pooledInstance, error) {
+	inst := &pooledInstance{
+		store: wasmtime.NewStore(w.engine),
+		state: &callState{},
+	}
+	inst.store.Limiter(
+		limit(w.config.MaxMemoryBytes),
+		limit(w.config.MaxTableElements),
+		-1, -1, -1, // Default instance, table and memory counts
+	)
+
+	linker := wasmtime.NewLinker(w.engine)
+	if err := w.addGuestImports(linker, inst.state); err != nil {
+		return nil, err
+	}
+	if err := w.addHostFunctions(linker, inst.state); err != nil {
+		return nil, err
+	}
+	if err := w.addTriggerFunctions(linker, inst.state); err != nil {
+		return nil, err
+	}
+
+	// Start functions run within the limits of the first call
+	if err := w.resetLimits(inst.store); err != nil {
+		return nil, err
+	}
+
+	instance, err := linker.Instantiate(inst.store, module)
+	if err != nil {
+		return nil, fmt.Errorf("failed to instantiate module: %w", err)
+	}
+	inst.instance = instance
+
+	return inst, nil
+}
+
+// reset prepares an instance for its next call
+func (w *WASMEngine) reset(inst *pooledInstance, ctx *ExecutionContext) error {
+	*inst.state = callState{ctx: ctx}
+	return w.resetLimits(inst.store)
+}
+
+// resetLimits refills the store's fuel and sets its epoch deadline to
+// MaxExecutionTime from now
+func (w *WASMEngine) resetLimits(store *wasmtime.Store) error {
+	store.SetEpochDeadline(uint64(w.config.MaxExecutionTime/epochTick) + 1)
+
+	if !w.config.EnableFuelMetering {
+		return nil
+	}
+	if err := store.SetFuel(w.config.FuelLimit); err != nil {
+		return fmt.Errorf("failed to set fuel: %w", err)
+	}
+	return nil
+}
+
+// fuelUsed returns the fuel a store has used since resetLimits, or 0
+// without fuel metering
+func (w *WASMEngine) fuelUsed(store *wasmtime.Store) uint64 {
+	if !w.config.EnableFuelMetering {
+		return 0
+	}
+	remaining, err := store.GetFuel()
+	if err != nil || remaining > w.config.FuelLimit {
+		return 0
+	}
+	return w.config.FuelLimit - remaining
+}
+
+// epochTick is how often the engine epoch advances. Calls are interrupted
+// within one tick of their deadline.
+const epochTick = 10 * time.Millisecond
+
+// tickEpochs advances the engine epoch until the engine is closed
+func (w *WASMEngine) tickEpochs() {
+	ticker := time.NewTicker(epochTick)
+	defer ticker.Stop()
+
+	for {
+		select {
+		case <-ticker.C:
+			w.engine.IncrementEpoch()
+		case <-w.stop:
+			return
+		}
+	}
+}
+
+// limit converts a configured maximum to a store limit; 0 means no limit
+func limit(max uint64) int64 {
+	if max == 0 || max > math.MaxInt64 {
+		return -1
+	}
+	return int64(max)
+}
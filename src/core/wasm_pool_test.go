@@ -0,0 +1,263 @@
+package mindb
+
+import (
+	"errors"
+	"strings"
+	"sync"
+	"testing"
+	"time"
+)
+
+// WASM module with one page of memory that misbehaves on request:
+//
+//	grow(pages i32) -> i32 { memory.grow(pages) }
+//	spin()                 { loop {} }
+var runawayWASM = []byte{
+	0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x09, 0x02, 0x60,
+	0x01, 0x7f, 0x01, 0x7f, 0x60, 0x00, 0x00, 0x03, 0x03, 0x02, 0x00, 0x01,
+	0x05, 0x03, 0x01, 0x00, 0x01, 0x07, 0x18, 0x03, 0x06, 0x6d, 0x65, 0x6d,
+	0x6f, 0x72, 0x79, 0x02, 0x00, 0x04, 0x67, 0x72, 0x6f, 0x77, 0x00, 0x00,
+	0x04, 0x73, 0x70, 0x69, 0x6e, 0x00, 0x01, 0x0a, 0x10, 0x02, 0x06, 0x00,
+	0x20, 0x00, 0x40, 0x00, 0x0b, 0x07, 0x00, 0x03, 0x40, 0x0c, 0x00, 0x0b,
+	0x0b,
+}
+
+func TestWASMEngine_FreshInstancePerCall(t *testing.T) {
+	engine, err := NewWASMEngine(DefaultWASMConfig())
+	if err != nil {
+		t.Fatalf("Failed to create WASM engine: %v", err)
+	}
+	defer engine.Close()
+
+	if err := engine.CompileModule("text", textWASM); err != nil {
+		t.Fatalf("Failed to compile module: %v", err)
+	}
+
+	echo := &StoredProcedure{Name: "echo", Params: []Column{{Name: "s", DataType: "TEXT"}}, ReturnType: "TEXT"}
+	for _, s := range []string{"one", "two", "three"} {
+		result, err := engine.ExecuteProcedure("text", echo, nil, s)
+		if err != nil {
+			t.Fatalf("Failed to call echo: %v", err)
+		}
+		if result != s {
+			t.Errorf("Expected %q, got %v", s, result)
+		}
+	}
+
+	// Nothing a call leaves in guest memory, such as the dealloc counter
+	// each echo bumped, reaches the next call
+	freed, err := engine.Execute("text", "freed")
+	if err != nil {
+		t.Fatalf("Failed to call freed: %v", err)
+	}
+	if freed != int32(0) {
+		t.Errorf("Expected a fresh instance to have freed nothing, got %v", freed)
+	}
+
+	// The instance is replaced in its slot rather than added to the pool
+	if n := engine.GetStats()["instances"]; n != 1 {
+		t.Errorf("Expected 1 instance, got %v", n)
+	}
+}
+
+func TestWASMEngine_DiscardsInstanceAfterTrap(t *testing.T) {
+	engine, err := NewWASMEngine(DefaultWASMConfig())
+	if err != nil {
+		t.Fatalf("Failed to create WASM engine: %v", err)
+	}
+	defer engine.Close()
+
+	if err := engine.CompileModule("check_tier", guestErrorWASM); err != nil {
+		t.Fatalf("Failed to compile module: %v", err)
+	}
+
+	if _, err := engine.Execute("check_tier", "check_tier", int32(3)); err != nil {
+		t.Fatalf("Failed to execute function: %v", err)
+	}
+	if n := engine.GetStats()["instances"]; n != 1 {
+		t.Errorf("Expected 1 instance after a successful call, got %v", n)
+	}
+
+	_, err = engine.Execute("check_tier", "check_tier", int32(0))
+	if err == nil || !strings.Contains(err.Error(), "bad tier") {
+		t.Fatalf("Expected guest error, got: %v", err)
+	}
+	if n := engine.GetStats()["instances"]; n != 0 {
+		t.Errorf("Expected the trapped instance to be discarded, got %v instances", n)
+	}
+
+	// The error message does not leak into the next call
+	result, err := engine.Execute("check_tier", "check_tier", int32(2))
+	if err != nil || result != int32(2) {
+		t.Errorf("Expected 2 from a fresh instance, got %v, %v", result, err)
+	}
+}
+
+func TestWASMEngine_MaxInstances(t *testing.T) {
+	config := DefaultWASMConfig()
+	config.MaxInstances = 2
+	engine, err := NewWASMEngine(config)
+	if err != nil {
+		t.Fatalf("Failed to create WASM engine: %v", err)
+	}
+	defer engine.Close()
+
+	if err := engine.CompileModule("test_add", simpleAddWASM); err != nil {
+		t.Fatalf("Failed to compile module: %v", err)
+	}
+
+	var wg sync.WaitGroup
+	errs := make(chan error, 80)
+	for i := 0; i < 8; i++ {
+		wg.Add(1)
+		go func(i int) {
+			defer wg.Done()
+			for j := 0; j < 10; j++ {
+				result, err := engine.Execute("test_add", "add", int32(i), int32(j))
+				if err != nil {
+					errs <- err
+					continue
+				}
+				if result != int32(i+j) {
+					t.Errorf("Expected %d, got %v", i+j, result)
+				}
+			}
+		}(i)
+	}
+	wg.Wait()
+	close(errs)
+
+	for err := range errs {
+		t.Errorf("Call failed: %v", err)
+	}
+	if n := engine.GetStats()["instances"].(int); n < 1 || n > 2 {
+		t.Errorf("Expected 1-2 instances, got %d", n)
+	}
+}
+
+func TestWASMEngine_RecompileDropsInstances(t *testing.T) {
+	engine, err := NewWASMEngine(DefaultWASMConfig())
+	if err != nil {
+		t.Fatalf("Failed to create WASM engine: %v", err)
+	}
+	defer engine.Close()
+
+	if err := engine.CompileModule("proc", simpleAddWASM); err != nil {
+		t.Fatalf("Failed to compile module: %v", err)
+	}
+	if _, err := engine.Execute("proc", "add", int32(1), int32(2)); err != nil {
+		t.Fatalf("Failed to execute: %v", err)
+	}
+
+	// Replacing the code must not reuse instances of the old module
+	if err := engine.CompileModule("proc", guestErrorWASM); err != nil {
+		t.Fatalf("Failed to compile module: %v", err)
+	}
+	if _, err := engine.Execute("proc", "add", int32(1), int32(2)); err == nil {
+		t.Error("Expected add to be missing from the new module")
+	}
+	result, err := engine.Execute("proc", "check_tier", int32(4))
+	if err != nil || result != int32(4) {
+		t.Errorf("Expected 4 from the new module, got %v, %v", result, err)
+	}
+}
+
+func TestWASMEngine_MemoryLimit(t *testing.T) {
+	config := DefaultWASMConfig()
+	config.MaxMemoryBytes = 2 * 64 * 1024 // Two pages
+	engine, err := NewWASMEngine(config)
+	if err != nil {
+		t.Fatalf("Failed to create WASM engine: %v", err)
+	}
+	defer engine.Close()
+
+	if err := engine.CompileModule("runaway", runawayWASM); err != nil {
+		t.Fatalf("Failed to compile module: %v", err)
+	}
+
+	// Growing to the limit succeeds and returns the old size in pages
+	result, err := engine.Execute("runaway", "grow", int32(1))
+	if err != nil || result != int32(1) {
+		t.Fatalf("Expected grow(1) to return 1, got %v, %v", result, err)
+	}
+
+	// Growing past it fails inside the guest
+	result, err = engine.Execute("runaway", "grow", int32(1))
+	if err != nil || result != int32(-1) {
+		t.Errorf("Expected grow past the limit to return -1, got %v, %v", result, err)
+	}
+
+	// A module whose initial memory is over the limit cannot be instantiated
+	smallConfig := DefaultWASMConfig()
+	smallConfig.MaxMemoryBytes = 32 * 1024
+	small, err := NewWASMEngine(smallConfig)
+	if err != nil {
+		t.Fatalf("Failed to create WASM engine: %v", err)
+	}
+	defer small.Close()
+	if err := small.CompileModule("runaway", runawayWASM); err != nil {
+		t.Fatalf("Failed to compile module: %v", err)
+	}
+	if _, err := small.Execute("runaway", "grow", int32(0)); err == nil || !strings.Contains(err.Error(), "instantiate") {
+		t.Errorf("Expected instantiation to fail, got: %v", err)
+	}
+}
+
+func TestWASMEngine_InterruptsRunawayLoop(t *testing.T) {
+	config := DefaultWASMConfig()
+	config.EnableFuelMetering = false // Only the epoch deadline can stop it
+	config.MaxExecutionTime = 100 * time.Millisecond
+	engine, err := NewWASMEngine(config)
+	if err != nil {
+		t.Fatalf("Failed to create WASM engine: %v", err)
+	}
+	defer engine.Close()
+
+	if err := engine.CompileModule("runaway", runawayWASM); err != nil {
+		t.Fatalf("Failed to compile module: %v", err)
+	}
+
+	_, err = engine.Execute("runaway", "spin")
+	if err == nil || !strings.Contains(err.Error(), "timeout") {
+		t.Fatalf("Expected timeout, got: %v", err)
+	}
+	var procErr *ProcedureError
+	if !errors.As(err, &procErr) || procErr.Code != SQLStateQueryCanceled {
+		t.Errorf("Expected a %s procedure error, got: %#v", SQLStateQueryCanceled, procErr)
+	}
+
+	// The loop really stops: its instance is discarded shortly after
+	deadline := time.Now().Add(2 * time.Second)
+	for engine.GetStats()["instances"] != 0 {
+		if time.Now().After(deadline) {
+			t.Fatal("Runaway call is still holding its instance")
+		}
+		time.Sleep(10 * time.Millisecond)
+	}
+
+	// Later calls get a fresh deadline
+	result, err := engine.Execute("runaway", "grow", int32(0))
+	if err != nil || result != int32(1) {
+		t.Errorf("Expected grow(0) to return 1, got %v, %v", result, err)
+	}
+}
+
+func TestWASMEngine_OutOfFuel(t *testing.T) {
+	config := DefaultWASMConfig()
+	config.FuelLimit = 100_000
+	engine, err := NewWASMEngine(config)
+	if err != nil {
+		t.Fatalf("Failed to create WASM engine: %v", err)
+	}
+	defer engine.Close()
+
+	if err := engine.CompileModule("runaway", runawayWASM); err != nil {
+		t.Fatalf("Failed to compile module: %v", err)
+	}
+
+	_, err = engine.Execute("runaway", "spin")
+	var procErr *ProcedureError
+	if !errors.As(err, &procErr) || procErr.Code != SQLStateProgramLimit {
+		t.Fatalf("Expected a %s procedure error, got: %v", SQLStateProgramLimit, err)
+	}
+}
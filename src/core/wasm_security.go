@@ -0,0 +1,123 @@
+package mindb
+
+import (
+	"fmt"
+	"strings"
+)
+
+// Whose privileges apply to the database access of a procedure, as in
+// CREATE PROCEDURE ... SECURITY DEFINER
+const (
+	SecurityInvoker = "INVOKER" // The calling user's; the default
+	SecurityDefiner = "DEFINER" // Those of the procedure's owner, the user who created it
+)
+
+// procedureContext returns the context a stored procedure called by the
+// statement running in parent runs in. A SECURITY DEFINER procedure runs as
+// its owner rather than the current user.
+func (e *PagedEngine) procedureContext(parent *ExecutionContext, proc *StoredProcedure) *ExecutionContext {
+	ctx := e.callContext(parent)
+	if proc.Security == SecurityDefiner {
+		ctx.UserID = proc.Owner
+	}
+	return ctx
+}
+
+// ownsProcedure reports whether the current user owns a procedure. root,
+// which is also the user when there is none, owns every procedure.
+func (e *PagedEngine) ownsProcedure(proc *StoredProcedure) bool {
+	username, _, _ := strings.Cut(e.currentUser, "@")
+	return username == "" || username == "root" || proc.Owner == e.currentUser
+}
+
+// hasCallable reports whether EXECUTE can be granted on name: a stored
+// procedure, an aggregate, a module, or a module function named
+// module.function
+func (e *PagedEngine) hasCallable(name string) bool {
+	e.mu.RLock()
+	defer e.mu.RUnlock()
+
+	if module, function, qualified := strings.Cut(name, "."); qualified {
+		_, err := e.lookupHandler(module, function)
+		return err == nil
+	}
+	_, isProcedure := e.procedures[name]
+	_, isAggregate := e.aggregates[name]
+	_, isModule := e.modules[name]
+	return isProcedure || isAggregate || isModule
+}
+
+// checkedUser splits UserID into a username and host. ok is false when
+// there is nothing to check: calls without a user, and root's.
+func (ctx *ExecutionContext) checkedUser() (username, host string, ok bool) {
+	if ctx.UserID == "" || ctx.Engine == nil {
+		return "", "", false
+	}
+	username, host, found := strings.Cut(ctx.UserID, "@")
+	if !found {
+		host = "%"
+	}
+	return username, host, username != "root"
+}
+
+// authorize checks that the context's user holds privilege on a table of
+// the current database
+func (ctx *ExecutionContext) authorize(table string, privilege Privilege) error {
+	username, host, ok := ctx.checkedUser()
+	if !ok {
+		return nil
+	}
+	if !ctx.Engine.userManager.HasPrivilege(username, host, ctx.Database, table, privilege) {
+		return fmt.Errorf("access denied for user '%s'@'%s' (missing %s privilege on %s.%s)",
+			username, host, privilege, ctx.Database, table)
+	}
+	return nil
+}
+
+// authorizeExecute checks that the context's user may call a procedure, or
+// a module function named module.function
+func (ctx *ExecutionContext) authorizeExecute(procedure string) error {
+	username, host, ok := ctx.checkedUser()
+	if !ok {
+		return nil
+	}
+	if !ctx.Engine.userManager.HasExecutePrivilege(username, host, ctx.Database, procedure) {
+		return fmt.Errorf("access denied for user '%s'@'%s' (missing EXECUTE privilege on procedure %s)",
+			username, host, procedure)
+	}
+	return nil
+}
+
+// authorizeCalls checks that the context's user may call every procedure,
+// module function and aggregate a SELECT calls. This is the one EXECUTE
+// check for them: calls made row by row, and those of CHECK constraints and
+// triggers, are not checked again.
+func (ctx *ExecutionContext) authorizeCalls(stmt *Statement) error {
+	for _, call := range stmt.procedureCalls() {
+		if err := ctx.authorizeExecute(call.procedure()); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// authorizeQuery checks that the context's user may run a SELECT: SELECT on
+// each table it reads, and EXECUTE on each procedure it calls
+func (ctx *ExecutionContext) authorizeQuery(stmt *Statement) error {
+	if stmt.TableFunction == nil {
+		if err := ctx.authorize(stmt.Table, PrivilegeSelect); err != nil {
+			return err
+		}
+	}
+	for _, join := range stmt.Joins {
+		if err := ctx.authorize(join.Table, PrivilegeSelect); err != nil {
+			return err
+		}
+	}
+	if stmt.Subquery != nil {
+		if err := ctx.authorizeQuery(stmt.Subquery); err != nil {
+			return err
+		}
+	}
+	return ctx.authorizeCalls(stmt)
+}
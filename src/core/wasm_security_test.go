@@ -0,0 +1,194 @@
+package mindb
+
+import (
+	"encoding/base64"
+	"strings"
+	"testing"
+)
+
+func TestWASMHost_Authorization(t *testing.T) {
+	engine, wasmEngine := newHostTestEngine(t)
+	um := engine.userManager
+	um.CreateUser("clerk", "secret", "%")
+	um.GrantPrivileges("clerk", "%", "testdb", "customers", []Privilege{PrivilegeSelect})
+	ctx := &ExecutionContext{Engine: engine, Database: "testdb", UserID: "clerk@%"}
+
+	// SELECT alone does not allow writes
+	if n := callHostTest(t, wasmEngine, ctx, "insert_customer"); n != -1 {
+		t.Errorf("Expected -1 without INSERT privilege, got %d", n)
+	}
+
+	um.GrantPrivileges("clerk", "%", "testdb", "customers", []Privilege{PrivilegeInsert})
+	if n := callHostTest(t, wasmEngine, ctx, "insert_customer"); n != 1 {
+		t.Errorf("Expected 1 row inserted with INSERT privilege, got %d", n)
+	}
+	if n := callHostTest(t, wasmEngine, ctx, "find_customer"); n != 17 {
+		t.Errorf("Expected 17-byte row with SELECT privilege, got %d", n)
+	}
+	if n := callHostTest(t, wasmEngine, ctx, "promote_customer"); n != -1 {
+		t.Errorf("Expected -1 without UPDATE privilege, got %d", n)
+	}
+	if n := callHostTest(t, wasmEngine, ctx, "remove_customer"); n != -1 {
+		t.Errorf("Expected -1 without DELETE privilege, got %d", n)
+	}
+
+	// root is not checked
+	ctx.UserID = "root@%"
+	if n := callHostTest(t, wasmEngine, ctx, "remove_customer"); n != 1 {
+		t.Errorf("Expected 1 row deleted as root, got %d", n)
+	}
+}
+
+func TestPagedEngine_SecurityDefiner(t *testing.T) {
+	engine, _ := newHostTestEngine(t)
+	um := engine.userManager
+	um.CreateUser("owner", "secret", "%")
+	um.GrantPrivileges("owner", "%", "testdb", "*", []Privilege{PrivilegeInsert, PrivilegeDelete})
+	um.CreateUser("clerk", "secret", "%")
+
+	engine.currentUser = "owner@%"
+	for _, proc := range []*StoredProcedure{
+		{Name: "insert_customer", Language: "wasm", Code: databaseAccessWASM, ReturnType: "INT", Security: SecurityDefiner},
+		{Name: "remove_customer", Language: "wasm", Code: databaseAccessWASM, ReturnType: "INT"},
+	} {
+		if err := engine.CreateProcedure(proc); err != nil {
+			t.Fatalf("Failed to create procedure %s: %v", proc.Name, err)
+		}
+		if proc.Owner != "owner@%" {
+			t.Errorf("Expected %s to be owned by its creator, got %q", proc.Name, proc.Owner)
+		}
+	}
+
+	// The definer's privileges apply to a SECURITY DEFINER procedure...
+	engine.currentUser = "clerk@%"
+	result, err := engine.CallProcedure("insert_customer")
+	if err != nil {
+		t.Fatalf("CallProcedure failed: %v", err)
+	}
+	if CompareValues(result, 1) != 0 {
+		t.Errorf("Expected the row inserted with the owner's privileges, got %v", result)
+	}
+
+	// ...and the caller's to any other
+	result, err = engine.CallProcedure("remove_customer")
+	if err != nil {
+		t.Fatalf("CallProcedure failed: %v", err)
+	}
+	if CompareValues(result, -1) != 0 {
+		t.Errorf("Expected the delete denied with the caller's privileges, got %v", result)
+	}
+}
+
+func TestEngineAdapter_ExecutePrivilege(t *testing.T) {
+	adapter, err := NewEngineAdapter(t.TempDir(), false)
+	if err != nil {
+		t.Fatalf("Failed to create adapter: %v", err)
+	}
+	defer adapter.Close()
+
+	code := base64.StdEncoding.EncodeToString(databaseAccessWASM)
+	execSQL(t, adapter, "CREATE DATABASE shop")
+	if err := adapter.UseDatabase("shop"); err != nil {
+		t.Fatalf("USE DATABASE failed: %v", err)
+	}
+	execSQL(t, adapter, "CREATE TABLE customers (id INT PRIMARY KEY, tier INT)")
+	execSQL(t, adapter, "CREATE TABLE orders (id INT PRIMARY KEY)")
+	execSQL(t, adapter, "CREATE PROCEDURE insert_customer() RETURNS INT SECURITY DEFINER LANGUAGE wasm AS '"+code+"'")
+	execSQL(t, adapter, "CREATE USER 'clerk'@'%' IDENTIFIED BY 'secret'")
+	execSQL(t, adapter, "GRANT SELECT ON shop.* TO 'clerk'@'%'")
+
+	um := adapter.pagedEngine.userManager
+	if err := um.CreateRole("cashier", "Runs checkout procedures"); err != nil {
+		t.Fatalf("Failed to create role: %v", err)
+	}
+	if err := um.GrantRoleToUser("clerk", "%", "cashier"); err != nil {
+		t.Fatalf("Failed to grant role: %v", err)
+	}
+
+	// SELECT no longer allows calling procedures
+	adapter.SetCurrentUser("clerk", "%")
+	if err := execSQLError(t, adapter, "CALL insert_customer()"); !strings.Contains(err.Error(), "missing EXECUTE privilege on procedure insert_customer") {
+		t.Errorf("Unexpected error: %v", err)
+	}
+	if _, err := adapter.CallProcedureViaAdapter("insert_customer"); err == nil {
+		t.Error("Expected CallProcedureViaAdapter to be denied")
+	}
+
+	// Nor can a trigger bind the DEFINER procedure to run on clerk's behalf
+	um.GrantPrivileges("clerk", "%", "shop", "*", []Privilege{PrivilegeCreate})
+	createTrigger := "CREATE TRIGGER log_order AFTER DELETE ON orders EXECUTE PROCEDURE insert_customer"
+	if err := execSQLError(t, adapter, createTrigger); !strings.Contains(err.Error(), "missing EXECUTE privilege on procedure insert_customer") {
+		t.Errorf("Unexpected error: %v", err)
+	}
+
+	adapter.SetCurrentUser("root", "%")
+	if err := execSQLError(t, adapter, "GRANT EXECUTE ON PROCEDURE no_such_procedure TO cashier"); !strings.Contains(err.Error(), "does not exist") {
+		t.Errorf("Unexpected error: %v", err)
+	}
+	execSQL(t, adapter, "GRANT EXECUTE ON PROCEDURE insert_customer TO cashier")
+
+	// The procedure inserts with root's privileges, which clerk lacks
+	adapter.SetCurrentUser("clerk", "%")
+	if result := execSQL(t, adapter, "CALL insert_customer()"); !strings.Contains(result, "| 1 ") {
+		t.Errorf("Expected 1 row inserted, got:\n%s", result)
+	}
+	execSQL(t, adapter, createTrigger)
+
+	adapter.SetCurrentUser("root", "%")
+	execSQL(t, adapter, "REVOKE EXECUTE ON PROCEDURE insert_customer FROM 'cashier'")
+	adapter.SetCurrentUser("clerk", "%")
+	if _, err := adapter.CallProcedureViaAdapter("insert_customer"); err == nil {
+		t.Error("Expected CallProcedureViaAdapter to be denied after REVOKE")
+	}
+}
+
+func TestEngineAdapter_CallableExecutePrivilege(t *testing.T) {
+	adapter := newScalarTestAdapter(t)
+	execSQL(t, adapter, "CREATE USER 'clerk'@'%' IDENTIFIED BY 'secret'")
+	execSQL(t, adapter, "GRANT SELECT ON shop.* TO 'clerk'@'%'")
+
+	// Module functions and procedures follow the same rule: EXECUTE on
+	// each one the statement calls, checked before it runs
+	adapter.SetCurrentUser("clerk", "%")
+	for _, sql := range []string{
+		"SELECT id, business_rules.calculate_tax(amount, 0.1) FROM orders",
+		"SELECT id, add(qty, 10) FROM orders",
+	} {
+		if err := execSQLError(t, adapter, sql); !strings.Contains(err.Error(), "missing EXECUTE privilege") {
+			t.Errorf("Expected %q to be denied, got %v", sql, err)
+		}
+	}
+
+	adapter.SetCurrentUser("root", "%")
+	execSQL(t, adapter, "GRANT EXECUTE ON PROCEDURE business_rules TO 'clerk'@'%'")
+	execSQL(t, adapter, "GRANT EXECUTE ON PROCEDURE add TO 'clerk'@'%'")
+	adapter.SetCurrentUser("clerk", "%")
+	result := execSQL(t, adapter, "SELECT id, business_rules.calculate_tax(amount, 0.1) AS tax, add(qty, 10) FROM orders")
+	if !strings.Contains(result, "| 25 ") || !strings.Contains(result, "| 14 ") {
+		t.Errorf("Expected the calls to be allowed with EXECUTE, got:\n%s", result)
+	}
+	if _, err := adapter.CallProcedureViaAdapter("business_rules.calculate_late_fee", 10, 5); err != nil {
+		t.Errorf("Expected the call to be allowed with EXECUTE on the module: %v", err)
+	}
+}
+
+func TestEngineAdapter_AggregateExecutePrivilege(t *testing.T) {
+	adapter := newAggregateTestAdapter(t, t.TempDir())
+	defer adapter.Close()
+	execSQL(t, adapter, "CREATE USER 'clerk'@'%' IDENTIFIED BY 'secret'")
+	execSQL(t, adapter, "GRANT SELECT ON shop.* TO 'clerk'@'%'")
+
+	query := "SELECT region, weighted_avg(price, qty) AS avg_price FROM sales GROUP BY region"
+	adapter.SetCurrentUser("clerk", "%")
+	if err := execSQLError(t, adapter, query); !strings.Contains(err.Error(), "missing EXECUTE privilege on procedure weighted_avg") {
+		t.Errorf("Unexpected error: %v", err)
+	}
+
+	// EXECUTE can be granted on an aggregate like on a procedure
+	adapter.SetCurrentUser("root", "%")
+	execSQL(t, adapter, "GRANT EXECUTE ON PROCEDURE weighted_avg TO 'clerk'@'%'")
+	adapter.SetCurrentUser("clerk", "%")
+	if result := execSQL(t, adapter, query); !strings.Contains(result, "east") || !strings.Contains(result, "| 4 ") {
+		t.Errorf("Expected per-region averages, got:\n%s", result)
+	}
+}
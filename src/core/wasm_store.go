@@ -0,0 +1,165 @@
+package mindb
+
+import (
+	"crypto/sha256"
+	"encoding/hex"
+	"encoding/json"
+	"fmt"
+	"os"
+	"path/filepath"
+	"strings"
+)
+
+// Procedure, module and aggregate bytecode is kept as raw .wasm blobs named
+// by their SHA-256 digest, apart from the JSON metadata in procedures/,
+// modules/ and aggregates/. Versions that share a
+// build share its blob, and the digest in the metadata lets a load detect a
+// blob that was damaged on disk.
+
+// procedureBlobDir returns the directory holding procedure bytecode
+func procedureBlobDir(dataDir string) string {
+	return filepath.Join(dataDir, "procedure_blobs")
+}
+
+// procedureDigest returns the hex encoded SHA-256 of WASM bytecode
+func procedureDigest(code []byte) string {
+	sum := sha256.Sum256(code)
+	return hex.EncodeToString(sum[:])
+}
+
+// writeProcedureBlob stores WASM bytecode under its digest and returns the
+// digest. A blob already stored intact is left as it is.
+func writeProcedureBlob(dataDir string, code []byte) (string, error) {
+	digest := procedureDigest(code)
+	if _, err := readProcedureBlob(dataDir, digest); err == nil {
+		return digest, nil
+	}
+
+	if err := os.MkdirAll(procedureBlobDir(dataDir), 0755); err != nil {
+		return "", fmt.Errorf("failed to create procedure blob directory: %w", err)
+	}
+
+	filename := filepath.Join(procedureBlobDir(dataDir), digest+".wasm")
+	if err := replaceFile(filename, code); err != nil {
+		return "", fmt.Errorf("failed to write procedure blob: %w", err)
+	}
+
+	return digest, nil
+}
+
+// readProcedureBlob reads the WASM bytecode stored under a digest and checks
+// that it still matches the digest
+func readProcedureBlob(dataDir string, digest string) ([]byte, error) {
+	code, err := os.ReadFile(filepath.Join(procedureBlobDir(dataDir), digest+".wasm"))
+	if err != nil {
+		return nil, fmt.Errorf("failed to read procedure blob %s: %w", digest, err)
+	}
+
+	if procedureDigest(code) != digest {
+		return nil, fmt.Errorf("procedure blob %s is corrupted: digest mismatch", digest)
+	}
+
+	return code, nil
+}
+
+// decodeProcedure unmarshals a procedure's metadata and loads its bytecode.
+// Procedures saved before the blob store carry their bytecode inline; it is
+// moved into a blob and Digest set, and migrated reports that the metadata
+// should be saved again.
+func decodeProcedure(dataDir string, data []byte) (proc *StoredProcedure, migrated bool, err error) {
+	proc = &StoredProcedure{}
+	if err := json.Unmarshal(data, proc); err != nil {
+		return nil, false, err
+	}
+
+	proc.Code, migrated, err = loadBytecode(dataDir, "procedure", proc.Name, data, &proc.Digest)
+	if err != nil {
+		return nil, false, err
+	}
+	return proc, migrated, nil
+}
+
+// loadBytecode loads the bytecode of a procedure, module or aggregate whose
+// metadata is data. If the metadata predates the blob store, the inline
+// bytecode is moved into a blob, *digest set, and migrated is true.
+func loadBytecode(dataDir, kind, name string, data []byte, digest *string) (code []byte, migrated bool, err error) {
+	if *digest != "" {
+		code, err := readProcedureBlob(dataDir, *digest)
+		if err != nil {
+			return nil, false, err
+		}
+		return code, false, nil
+	}
+
+	var inline struct {
+		Code []byte
+	}
+	if err := json.Unmarshal(data, &inline); err != nil {
+		return nil, false, err
+	}
+	if len(inline.Code) == 0 {
+		return nil, false, fmt.Errorf("%s '%s' has no bytecode", kind, name)
+	}
+
+	if *digest, err = writeProcedureBlob(dataDir, inline.Code); err != nil {
+		return nil, false, err
+	}
+	return inline.Code, true, nil
+}
+
+// pruneProcedureBlobs removes blobs no procedure, revision, module or
+// aggregate refers to. If any metadata cannot be read, nothing is removed.
+func pruneProcedureBlobs(dataDir string) error {
+	blobs, err := os.ReadDir(procedureBlobDir(dataDir))
+	if os.IsNotExist(err) {
+		return nil
+	}
+	if err != nil {
+		return err
+	}
+
+	procDir := filepath.Join(dataDir, "procedures")
+	files, err := filepath.Glob(filepath.Join(procDir, "*.json"))
+	if err != nil {
+		return err
+	}
+	revisions, err := filepath.Glob(filepath.Join(procDir, "*", "*.json"))
+	if err != nil {
+		return err
+	}
+	files = append(files, revisions...)
+	for _, kind := range []string{objectModule, objectAggregate} {
+		objects, err := filepath.Glob(filepath.Join(dataDir, kind, "*.json"))
+		if err != nil {
+			return err
+		}
+		files = append(files, objects...)
+	}
+
+	used := make(map[string]bool)
+	for _, filename := range files {
+		data, err := os.ReadFile(filename)
+		if err != nil {
+			return err
+		}
+		var meta struct {
+			Digest string
+		}
+		if err := json.Unmarshal(data, &meta); err != nil {
+			return fmt.Errorf("failed to unmarshal %s: %w", filename, err)
+		}
+		used[meta.Digest] = true
+	}
+
+	for _, blob := range blobs {
+		digest := strings.TrimSuffix(blob.Name(), ".wasm")
+		if blob.IsDir() || used[digest] {
+			continue
+		}
+		if err := os.Remove(filepath.Join(procedureBlobDir(dataDir), blob.Name())); err != nil && !os.IsNotExist(err) {
+			return err
+		}
+	}
+
+	return nil
+}
@@ -0,0 +1,107 @@
+package mindb
+
+import (
+	"bytes"
+	"encoding/json"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestProcedureBlobStore(t *testing.T) {
+	dataDir := t.TempDir()
+
+	engine, err := NewPagedEngine(dataDir)
+	if err != nil {
+		t.Fatalf("Failed to create engine: %v", err)
+	}
+
+	params := []Column{{Name: "amount", DataType: "INT"}}
+	if err := engine.CreateProcedure(&StoredProcedure{Name: "discount", Language: "wasm", Code: discountV1WASM, Params: params, ReturnType: "INT"}); err != nil {
+		t.Fatalf("Failed to create procedure: %v", err)
+	}
+	if err := engine.CreateProcedure(&StoredProcedure{Name: "markdown", Language: "wasm", Code: discountV2WASM, Params: params, ReturnType: "INT"}); err != nil {
+		t.Fatalf("Failed to create procedure: %v", err)
+	}
+
+	// The bytecode is stored raw, apart from the metadata
+	digest := procedureDigest(discountV1WASM)
+	blob, err := os.ReadFile(filepath.Join(procedureBlobDir(dataDir), digest+".wasm"))
+	if err != nil || !bytes.Equal(blob, discountV1WASM) {
+		t.Fatalf("Expected raw bytecode in blob %s: %v", digest, err)
+	}
+	data, err := os.ReadFile(filepath.Join(dataDir, "procedures", "discount.json"))
+	if err != nil {
+		t.Fatalf("Failed to read procedure metadata: %v", err)
+	}
+	var meta map[string]interface{}
+	json.Unmarshal(data, &meta)
+	if _, inline := meta["Code"]; inline || meta["Digest"] != digest {
+		t.Errorf("Expected metadata with digest and no inline code, got %s", data)
+	}
+	engine.Close()
+
+	// A corrupted blob leaves out its procedure, not the others
+	blobPath := filepath.Join(procedureBlobDir(dataDir), digest+".wasm")
+	if err := os.WriteFile(blobPath, append(blob, 0), 0644); err != nil {
+		t.Fatalf("Failed to corrupt blob: %v", err)
+	}
+
+	engine, err = NewPagedEngine(dataDir)
+	if err != nil {
+		t.Fatalf("Expected startup to survive a corrupted procedure: %v", err)
+	}
+	defer engine.Close()
+
+	if _, err := engine.GetProcedure("discount"); err == nil {
+		t.Error("Expected corrupted procedure to be left out")
+	}
+	result, err := engine.CallProcedure("markdown", 100)
+	if err != nil || result.(int32) != 80 {
+		t.Errorf("Expected intact procedure to load, got %v (%v)", result, err)
+	}
+
+	// Dropping a procedure removes the blob only it used
+	markdownBlob := filepath.Join(procedureBlobDir(dataDir), procedureDigest(discountV2WASM)+".wasm")
+	if err := engine.DropProcedure("markdown"); err != nil {
+		t.Fatalf("Failed to drop procedure: %v", err)
+	}
+	if _, err := os.Stat(markdownBlob); !os.IsNotExist(err) {
+		t.Error("Expected blob of dropped procedure to be removed")
+	}
+}
+
+func TestProcedureBlobStoreMigration(t *testing.T) {
+	dataDir := t.TempDir()
+
+	// A procedure saved with its bytecode inline, before the blob store
+	procDir := filepath.Join(dataDir, "procedures")
+	os.MkdirAll(procDir, 0755)
+	legacy, _ := json.Marshal(map[string]interface{}{
+		"Name":       "discount",
+		"Language":   "wasm",
+		"Code":       discountV1WASM,
+		"Params":     []Column{{Name: "amount", DataType: "INT"}},
+		"ReturnType": "INT",
+		"Version":    1,
+	})
+	if err := os.WriteFile(filepath.Join(procDir, "discount.json"), legacy, 0644); err != nil {
+		t.Fatalf("Failed to write legacy procedure: %v", err)
+	}
+
+	engine, err := NewPagedEngine(dataDir)
+	if err != nil {
+		t.Fatalf("Failed to create engine: %v", err)
+	}
+	defer engine.Close()
+
+	if callDiscount(t, engine) != 90 {
+		t.Error("Expected legacy procedure to load")
+	}
+
+	data, _ := os.ReadFile(filepath.Join(procDir, "discount.json"))
+	if strings.Contains(string(data), `"Code"`) || !strings.Contains(string(data), procedureDigest(discountV1WASM)) {
+		t.Errorf("Expected legacy procedure to be migrated to a blob, got %s", data)
+	}
+}
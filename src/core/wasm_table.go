@@ -0,0 +1,141 @@
+package mindb
+
+import (
+	"bytes"
+	"encoding/json"
+	"fmt"
+	"strings"
+
+	"github.com/bytecodealliance/wasmtime-go/v25"
+)
+
+// ProcedureRows is the result of a table-valued procedure: the rows it
+// emitted through mindb_emit_row, with columns in order of first appearance.
+// Procedures declared RETURNS TABLE return an empty result if they emit
+// nothing.
+type ProcedureRows struct {
+	Columns []string
+	Rows    []Row
+}
+
+// isTableType reports whether a procedure return type is TABLE
+func isTableType(returnType string) bool {
+	return strings.EqualFold(returnType, "TABLE")
+}
+
+// emitRow appends a row, given as a JSON object in guest memory, to the
+// call's result set
+func (s *callState) emitRow(caller *wasmtime.Caller, ptr int32, length int32, maxRows int) error {
+	if maxRows > 0 && len(s.rows) >= maxRows {
+		return fmt.Errorf("procedure emitted more than %d rows", maxRows)
+	}
+
+	data, err := readGuestMemory(caller, ptr, length)
+	if err != nil {
+		return err
+	}
+	keys, err := objectKeys(data)
+	if err != nil {
+		return err
+	}
+	row, err := decodeGuestObject(data)
+	if err != nil {
+		return err
+	}
+
+	for _, key := range keys {
+		known := false
+		for _, col := range s.columns {
+			if col == key {
+				known = true
+				break
+			}
+		}
+		if !known {
+			s.columns = append(s.columns, key)
+		}
+	}
+	s.rows = append(s.rows, Row(row))
+	return nil
+}
+
+// objectKeys returns the keys of a JSON object in the order they appear
+func objectKeys(data []byte) ([]string, error) {
+	decoder := json.NewDecoder(bytes.NewReader(data))
+	if token, err := decoder.Token(); err != nil {
+		return nil, fmt.Errorf("invalid JSON object from procedure: %w", err)
+	} else if token != json.Delim('{') {
+		return nil, fmt.Errorf("invalid JSON object from procedure: unexpected %v", token)
+	}
+
+	var keys []string
+	for decoder.More() {
+		token, err := decoder.Token()
+		if err != nil {
+			return nil, fmt.Errorf("invalid JSON object from procedure: %w", err)
+		}
+		key, ok := token.(string)
+		if !ok {
+			return nil, fmt.Errorf("invalid JSON object from procedure: unexpected %v", token)
+		}
+		var value json.RawMessage
+		if err := decoder.Decode(&value); err != nil {
+			return nil, fmt.Errorf("invalid JSON object from procedure: %w", err)
+		}
+		keys = append(keys, key)
+	}
+	return keys, nil
+}
+
+// callTableFunction calls a table-valued procedure named in a FROM clause.
+// Its arguments must be constants.
+func (e *PagedEngine) callTableFunction(ctx *ExecutionContext, call *FunctionCall) (*ProcedureRows, error) {
+	args := make([]interface{}, len(call.Args))
+	for i, arg := range call.Args {
+		if arg.Column != "" {
+			return nil, fmt.Errorf("%s: arguments must be constants, got column '%s'", call.Name, arg.Column)
+		}
+		args[i] = arg.Value
+	}
+
+	var result interface{}
+	var err error
+	if call.Module != "" {
+		result, err = e.callModuleFunction(ctx, call.Module, call.Name, args...)
+	} else {
+		result, err = e.callProcedure(ctx, call.Name, args...)
+	}
+	if err != nil {
+		return nil, fmt.Errorf("%s: %w", call.Name, err)
+	}
+
+	rows, ok := result.(*ProcedureRows)
+	if !ok {
+		return nil, fmt.Errorf("procedure '%s' does not return a table", call.Name)
+	}
+	return rows, nil
+}
+
+// scanRows reads the rows a SELECT starts from: those of stmt.Table matching
+// the conditions that can be checked during a scan or, for a table-valued
+// procedure in FROM, the matching rows it emits. columns is the procedure's
+// column order, or nil for tables.
+func (e *PagedEngine) scanRows(ctx *ExecutionContext, stmt *Statement) (rows []Row, columns []string, err error) {
+	conditions := scanConditions(stmt.Conditions)
+	if stmt.TableFunction == nil {
+		rows, err = e.SelectRows(stmt.Table, conditions)
+		return rows, nil, err
+	}
+
+	result, err := e.callTableFunction(ctx, stmt.TableFunction)
+	if err != nil {
+		return nil, nil, err
+	}
+	rows = make([]Row, 0, len(result.Rows))
+	for _, row := range result.Rows {
+		if matchesConditions(row, conditions) {
+			rows = append(rows, row)
+		}
+	}
+	return rows, result.Columns, nil
+}
@@ -0,0 +1,224 @@
+package mindb
+
+import (
+	"strings"
+	"testing"
+)
+
+// WASM module with table-valued procedures that emit rows through
+// mindb_emit_row, raising the host's message through mindb_error when a row
+// is rejected. Equivalent to:
+//
+//	/// Shipping rates by zone
+//	#[procedure]
+//	fn rates() -> Result<Table> {
+//	    let mut table = Table::new();
+//	    table.emit(&[("zone", "A".into()), ("weight", 1.into()), ("rate", 4.5.into())])?;
+//	    table.emit(&[("zone", "B".into()), ("weight", 5.into()), ("rate", 12.25.into())])?;
+//	    table.emit(&[("zone", "C".into()), ("weight", 10.into()), ("rate", 20.into()), ("express", true.into())])?;
+//	    Ok(table)
+//	}
+//
+// plus repeat(n) emitting zone A n times, nested() emitting a row with a
+// nested object, empty() emitting nothing and answer() returning 42.
+var tableRowsWASM = []byte{
+	0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x17, 0x05, 0x60,
+	0x02, 0x7f, 0x7f, 0x01, 0x7f, 0x60, 0x02, 0x7f, 0x7f, 0x00, 0x60, 0x01,
+	0x7f, 0x00, 0x60, 0x00, 0x00, 0x60, 0x00, 0x01, 0x7f, 0x02, 0x40, 0x03,
+	0x03, 0x65, 0x6e, 0x76, 0x0e, 0x6d, 0x69, 0x6e, 0x64, 0x62, 0x5f, 0x65,
+	0x6d, 0x69, 0x74, 0x5f, 0x72, 0x6f, 0x77, 0x00, 0x00, 0x03, 0x65, 0x6e,
+	0x76, 0x11, 0x6d, 0x69, 0x6e, 0x64, 0x62, 0x5f, 0x72, 0x65, 0x73, 0x75,
+	0x6c, 0x74, 0x5f, 0x72, 0x65, 0x61, 0x64, 0x00, 0x00, 0x03, 0x65, 0x6e,
+	0x76, 0x0b, 0x6d, 0x69, 0x6e, 0x64, 0x62, 0x5f, 0x65, 0x72, 0x72, 0x6f,
+	0x72, 0x00, 0x01, 0x03, 0x07, 0x06, 0x02, 0x03, 0x02, 0x03, 0x03, 0x04,
+	0x05, 0x03, 0x01, 0x00, 0x01, 0x07, 0x35, 0x06, 0x06, 0x6d, 0x65, 0x6d,
+	0x6f, 0x72, 0x79, 0x02, 0x00, 0x05, 0x72, 0x61, 0x74, 0x65, 0x73, 0x00,
+	0x04, 0x06, 0x72, 0x65, 0x70, 0x65, 0x61, 0x74, 0x00, 0x05, 0x06, 0x6e,
+	0x65, 0x73, 0x74, 0x65, 0x64, 0x00, 0x06, 0x05, 0x65, 0x6d, 0x70, 0x74,
+	0x79, 0x00, 0x07, 0x06, 0x61, 0x6e, 0x73, 0x77, 0x65, 0x72, 0x00, 0x08,
+	0x0a, 0x6e, 0x06, 0x18, 0x00, 0x20, 0x00, 0x41, 0x00, 0x48, 0x04, 0x40,
+	0x41, 0x80, 0x04, 0x41, 0x80, 0x04, 0x41, 0x80, 0x02, 0x10, 0x01, 0x10,
+	0x02, 0x00, 0x0b, 0x0b, 0x1d, 0x00, 0x41, 0xc0, 0x00, 0x41, 0x22, 0x10,
+	0x00, 0x10, 0x03, 0x41, 0xe2, 0x00, 0x41, 0x24, 0x10, 0x00, 0x10, 0x03,
+	0x41, 0x86, 0x01, 0x41, 0x31, 0x10, 0x00, 0x10, 0x03, 0x0b, 0x21, 0x00,
+	0x02, 0x40, 0x03, 0x40, 0x20, 0x00, 0x41, 0x00, 0x4c, 0x0d, 0x01, 0x41,
+	0xc0, 0x00, 0x41, 0x22, 0x10, 0x00, 0x10, 0x03, 0x20, 0x00, 0x41, 0x01,
+	0x6b, 0x21, 0x00, 0x0c, 0x00, 0x0b, 0x0b, 0x0b, 0x0b, 0x00, 0x41, 0xb7,
+	0x01, 0x41, 0x10, 0x10, 0x00, 0x10, 0x03, 0x0b, 0x02, 0x00, 0x0b, 0x04,
+	0x00, 0x41, 0x2a, 0x0b, 0x0b, 0xa0, 0x01, 0x04, 0x00, 0x41, 0xc0, 0x00,
+	0x0b, 0x22, 0x7b, 0x22, 0x7a, 0x6f, 0x6e, 0x65, 0x22, 0x3a, 0x22, 0x41,
+	0x22, 0x2c, 0x22, 0x77, 0x65, 0x69, 0x67, 0x68, 0x74, 0x22, 0x3a, 0x31,
+	0x2c, 0x22, 0x72, 0x61, 0x74, 0x65, 0x22, 0x3a, 0x34, 0x2e, 0x35, 0x7d,
+	0x00, 0x41, 0xe2, 0x00, 0x0b, 0x24, 0x7b, 0x22, 0x7a, 0x6f, 0x6e, 0x65,
+	0x22, 0x3a, 0x22, 0x42, 0x22, 0x2c, 0x22, 0x77, 0x65, 0x69, 0x67, 0x68,
+	0x74, 0x22, 0x3a, 0x35, 0x2c, 0x22, 0x72, 0x61, 0x74, 0x65, 0x22, 0x3a,
+	0x31, 0x32, 0x2e, 0x32, 0x35, 0x7d, 0x00, 0x41, 0x86, 0x01, 0x0b, 0x31,
+	0x7b, 0x22, 0x7a, 0x6f, 0x6e, 0x65, 0x22, 0x3a, 0x22, 0x43, 0x22, 0x2c,
+	0x22, 0x77, 0x65, 0x69, 0x67, 0x68, 0x74, 0x22, 0x3a, 0x31, 0x30, 0x2c,
+	0x22, 0x72, 0x61, 0x74, 0x65, 0x22, 0x3a, 0x32, 0x30, 0x2c, 0x22, 0x65,
+	0x78, 0x70, 0x72, 0x65, 0x73, 0x73, 0x22, 0x3a, 0x74, 0x72, 0x75, 0x65,
+	0x7d, 0x00, 0x41, 0xb7, 0x01, 0x0b, 0x10, 0x7b, 0x22, 0x7a, 0x6f, 0x6e,
+	0x65, 0x22, 0x3a, 0x7b, 0x22, 0x78, 0x22, 0x3a, 0x31, 0x7d, 0x7d, 0x00,
+	0x67, 0x10, 0x6d, 0x69, 0x6e, 0x64, 0x62, 0x2e, 0x70, 0x72, 0x6f, 0x63,
+	0x65, 0x64, 0x75, 0x72, 0x65, 0x73, 0x7b, 0x22, 0x6e, 0x61, 0x6d, 0x65,
+	0x22, 0x3a, 0x22, 0x72, 0x61, 0x74, 0x65, 0x73, 0x22, 0x2c, 0x22, 0x70,
+	0x61, 0x72, 0x61, 0x6d, 0x73, 0x22, 0x3a, 0x5b, 0x5d, 0x2c, 0x22, 0x72,
+	0x65, 0x74, 0x75, 0x72, 0x6e, 0x73, 0x22, 0x3a, 0x22, 0x54, 0x41, 0x42,
+	0x4c, 0x45, 0x22, 0x2c, 0x22, 0x64, 0x65, 0x73, 0x63, 0x72, 0x69, 0x70,
+	0x74, 0x69, 0x6f, 0x6e, 0x22, 0x3a, 0x22, 0x53, 0x68, 0x69, 0x70, 0x70,
+	0x69, 0x6e, 0x67, 0x20, 0x72, 0x61, 0x74, 0x65, 0x73, 0x20, 0x62, 0x79,
+	0x20, 0x7a, 0x6f, 0x6e, 0x65, 0x22, 0x7d, 0x0a,
+}
+
+// newTableTestAdapter returns an adapter with the table procedures and the
+// shipping module
+func newTableTestAdapter(t *testing.T) *EngineAdapter {
+	t.Helper()
+
+	adapter, err := NewEngineAdapter(t.TempDir(), false)
+	if err != nil {
+		t.Fatalf("Failed to create adapter: %v", err)
+	}
+	t.Cleanup(func() { adapter.Close() })
+
+	if _, err := adapter.Execute(&Statement{Type: CreateDatabase, Database: "shop"}); err != nil {
+		t.Fatalf("CREATE DATABASE failed: %v", err)
+	}
+	if err := adapter.UseDatabase("shop"); err != nil {
+		t.Fatalf("USE DATABASE failed: %v", err)
+	}
+	if err := adapter.pagedEngine.CreateModule(&StoredModule{Name: "shipping", Code: tableRowsWASM}); err != nil {
+		t.Fatalf("Failed to create module: %v", err)
+	}
+
+	for _, proc := range []*StoredProcedure{
+		{Name: "rates"}, // TABLE from the module's metadata
+		{Name: "repeat", Params: []Column{{Name: "n", DataType: "INT"}}, ReturnType: "TABLE"},
+		{Name: "nested", ReturnType: "TABLE"},
+		{Name: "empty", ReturnType: "TABLE"},
+		{Name: "answer", ReturnType: "INT"},
+	} {
+		proc.Language = "wasm"
+		proc.Code = tableRowsWASM
+		if err := adapter.pagedEngine.CreateProcedure(proc); err != nil {
+			t.Fatalf("Failed to create procedure %s: %v", proc.Name, err)
+		}
+	}
+
+	return adapter
+}
+
+func TestEngineAdapter_SelectFromTableFunction(t *testing.T) {
+	adapter := newTableTestAdapter(t)
+
+	result := execSQL(t, adapter, "SELECT * FROM rates()")
+	if !strings.Contains(result, "3 row(s) returned") {
+		t.Errorf("Expected 3 rows, got:\n%s", result)
+	}
+	// Columns appear in the order they were first emitted
+	header := strings.Split(result, "\n")[1]
+	zone, weight, rate, express := strings.Index(header, "zone"), strings.Index(header, "weight"),
+		strings.Index(header, "rate"), strings.Index(header, "express")
+	if zone < 0 || !(zone < weight && weight < rate && rate < express) {
+		t.Errorf("Expected columns zone, weight, rate, express, got: %s", header)
+	}
+	for _, want := range []string{"| 4.5 ", "| 12.25 ", "| true "} {
+		if !strings.Contains(result, want) {
+			t.Errorf("Expected %q in result, got:\n%s", want, result)
+		}
+	}
+
+	result = execSQL(t, adapter, "SELECT zone, rate FROM rates() WHERE weight >= 5 ORDER BY rate DESC LIMIT 1")
+	if !strings.Contains(result, "| C ") || strings.Contains(result, "| B ") || strings.Contains(result, "weight") {
+		t.Errorf("Expected only zone C with zone and rate columns, got:\n%s", result)
+	}
+
+	result = execSQL(t, adapter, "SELECT zone FROM shipping.rates() WHERE zone = 'B'")
+	if !strings.Contains(result, "| B ") || !strings.Contains(result, "1 row(s) returned") {
+		t.Errorf("Expected zone B from the module function, got:\n%s", result)
+	}
+
+	result = execSQL(t, adapter, "SELECT SUM(weight) AS total FROM rates()")
+	if !strings.Contains(result, "| 16 ") {
+		t.Errorf("Expected total weight 16, got:\n%s", result)
+	}
+}
+
+func TestEngineAdapter_TableFunctionArguments(t *testing.T) {
+	adapter := newTableTestAdapter(t)
+
+	result := execSQL(t, adapter, "SELECT * FROM repeat(2)")
+	if !strings.Contains(result, "2 row(s) returned") {
+		t.Errorf("Expected 2 rows, got:\n%s", result)
+	}
+
+	for _, sql := range []string{"SELECT * FROM repeat(0)", "SELECT * FROM empty()"} {
+		if result := execSQL(t, adapter, sql); result != "0 rows returned" {
+			t.Errorf("%s: expected no rows, got:\n%s", sql, result)
+		}
+	}
+}
+
+func TestEngineAdapter_CallTableProcedure(t *testing.T) {
+	adapter := newTableTestAdapter(t)
+
+	result := execSQL(t, adapter, "CALL rates()")
+	if !strings.Contains(result, "express") || !strings.Contains(result, "3 row(s) returned") {
+		t.Errorf("Expected the rates result set, got:\n%s", result)
+	}
+
+	rows, err := adapter.pagedEngine.CallProcedure("repeat", 3)
+	if err != nil {
+		t.Fatalf("CallProcedure() error = %v", err)
+	}
+	table, ok := rows.(*ProcedureRows)
+	if !ok || len(table.Rows) != 3 || len(table.Columns) != 3 || table.Rows[0]["weight"] != int64(1) {
+		t.Errorf("Unexpected result: %#v", rows)
+	}
+}
+
+func TestEngineAdapter_TableFunctionErrors(t *testing.T) {
+	adapter := newTableTestAdapter(t)
+	adapter.pagedEngine.wasmEngine.config.MaxEmittedRows = 3
+
+	tests := []struct {
+		name    string
+		sql     string
+		wantErr string
+	}{
+		{"scalar procedure", "SELECT * FROM answer()", "procedure 'answer' does not return a table"},
+		{"column argument", "SELECT * FROM repeat(n)", "arguments must be constants"},
+		{"unknown procedure", "SELECT * FROM missing()", "procedure 'missing' does not exist"},
+		{"nested value", "SELECT * FROM nested()", "column 'zone' must be a scalar value"},
+		{"too many rows", "SELECT * FROM repeat(4)", "procedure emitted more than 3 rows"},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			stmt, err := NewParser().Parse(tt.sql)
+			if err != nil {
+				t.Fatalf("Parse() error = %v", err)
+			}
+			_, err = adapter.Execute(stmt)
+			if err == nil || !strings.Contains(err.Error(), tt.wantErr) {
+				t.Errorf("Expected error containing %q, got: %v", tt.wantErr, err)
+			}
+		})
+	}
+}
+
+func TestPagedEngine_ExecuteQueryTableFunction(t *testing.T) {
+	adapter := newTableTestAdapter(t)
+
+	stmt, err := NewParser().Parse("SELECT * FROM rates() WHERE zone = 'A'")
+	if err != nil {
+		t.Fatalf("Parse() error = %v", err)
+	}
+	rows, err := adapter.pagedEngine.ExecuteQuery(stmt)
+	if err != nil {
+		t.Fatalf("ExecuteQuery() error = %v", err)
+	}
+	if len(rows) != 1 || rows[0]["rate"] != 4.5 {
+		t.Errorf("Expected zone A at 4.5, got %+v", rows)
+	}
+}
@@ -2,6 +2,7 @@ package mindb
 
 import (
 	"encoding/base64"
+	"errors"
 	"fmt"
 	"strings"
 	"testing"
@@ -23,6 +24,93 @@ var simpleAddWASM = []byte{
 	0x0a, 0x09, 0x01, 0x07, 0x00, 0x20, 0x00, 0x20, 0x01, 0x6a, 0x0b,
 }
 
+// WASM module that reports an error through the mindb_error host import.
+// Equivalent Rust (using the mindb-procedure SDK):
+//
+//	#[procedure]
+//	fn check_tier(tier: i32) -> Result<i32> {
+//	    if tier == 0 { return Err(ProcError::new("bad tier")); }
+//	    Ok(tier)
+//	}
+var guestErrorWASM = []byte{
+	0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x0b, 0x02, 0x60,
+	0x02, 0x7f, 0x7f, 0x00, 0x60, 0x01, 0x7f, 0x01, 0x7f, 0x02, 0x13, 0x01,
+	0x03, 0x65, 0x6e, 0x76, 0x0b, 0x6d, 0x69, 0x6e, 0x64, 0x62, 0x5f, 0x65,
+	0x72, 0x72, 0x6f, 0x72, 0x00, 0x00, 0x03, 0x02, 0x01, 0x01, 0x05, 0x03,
+	0x01, 0x00, 0x01, 0x07, 0x17, 0x02, 0x06, 0x6d, 0x65, 0x6d, 0x6f, 0x72,
+	0x79, 0x02, 0x00, 0x0a, 0x63, 0x68, 0x65, 0x63, 0x6b, 0x5f, 0x74, 0x69,
+	0x65, 0x72, 0x00, 0x01, 0x0a, 0x13, 0x01, 0x11, 0x00, 0x20, 0x00, 0x45,
+	0x04, 0x40, 0x41, 0x00, 0x41, 0x08, 0x10, 0x00, 0x00, 0x0b, 0x20, 0x00,
+	0x0b, 0x0b, 0x0e, 0x01, 0x00, 0x41, 0x00, 0x0b, 0x08, 0x62, 0x61, 0x64,
+	0x20, 0x74, 0x69, 0x65, 0x72,
+}
+
+// WASM module that raises typed errors through the mindb_raise host import.
+// Equivalent Rust (using the mindb-procedure SDK):
+//
+//	#[procedure]
+//	fn check_tier(tier: i32) -> Result<i32> {
+//	    if !(1..=3).contains(&tier) {
+//	        return Err(ProcError::new("invalid tier")
+//	            .with_code("22023")
+//	            .with_detail("tier must be between 1 and 3"));
+//	    }
+//	    Ok(tier)
+//	}
+//
+// bad_code() raises with the malformed code "2202".
+var guestRaiseWASM = []byte{
+	0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x12, 0x03, 0x60,
+	0x06, 0x7f, 0x7f, 0x7f, 0x7f, 0x7f, 0x7f, 0x00, 0x60, 0x01, 0x7f, 0x01,
+	0x7f, 0x60, 0x00, 0x00, 0x02, 0x13, 0x01, 0x03, 0x65, 0x6e, 0x76, 0x0b,
+	0x6d, 0x69, 0x6e, 0x64, 0x62, 0x5f, 0x72, 0x61, 0x69, 0x73, 0x65, 0x00,
+	0x00, 0x03, 0x03, 0x02, 0x01, 0x02, 0x05, 0x03, 0x01, 0x00, 0x01, 0x07,
+	0x22, 0x03, 0x06, 0x6d, 0x65, 0x6d, 0x6f, 0x72, 0x79, 0x02, 0x00, 0x0a,
+	0x63, 0x68, 0x65, 0x63, 0x6b, 0x5f, 0x74, 0x69, 0x65, 0x72, 0x00, 0x01,
+	0x08, 0x62, 0x61, 0x64, 0x5f, 0x63, 0x6f, 0x64, 0x65, 0x00, 0x02, 0x0a,
+	0x35, 0x02, 0x21, 0x00, 0x20, 0x00, 0x41, 0x01, 0x48, 0x20, 0x00, 0x41,
+	0x03, 0x4a, 0x72, 0x04, 0x40, 0x41, 0x00, 0x41, 0x05, 0x41, 0x05, 0x41,
+	0x0c, 0x41, 0x11, 0x41, 0x1c, 0x10, 0x00, 0x00, 0x0b, 0x20, 0x00, 0x0b,
+	0x11, 0x00, 0x41, 0x2d, 0x41, 0x04, 0x41, 0x05, 0x41, 0x0c, 0x41, 0x00,
+	0x41, 0x00, 0x10, 0x00, 0x00, 0x0b, 0x0b, 0x37, 0x01, 0x00, 0x41, 0x00,
+	0x0b, 0x31, 0x32, 0x32, 0x30, 0x32, 0x33, 0x69, 0x6e, 0x76, 0x61, 0x6c,
+	0x69, 0x64, 0x20, 0x74, 0x69, 0x65, 0x72, 0x74, 0x69, 0x65, 0x72, 0x20,
+	0x6d, 0x75, 0x73, 0x74, 0x20, 0x62, 0x65, 0x20, 0x62, 0x65, 0x74, 0x77,
+	0x65, 0x65, 0x6e, 0x20, 0x31, 0x20, 0x61, 0x6e, 0x64, 0x20, 0x33, 0x32,
+	0x32, 0x30, 0x32,
+
+}
+
+// WASM module exporting two functions, for CREATE MODULE. calculate_tax
+// carries SDK metadata; calculate_late_fee does not.
+//
+//	calculate_tax(amount f64, rate f64) -> f64 { amount * rate }
+//	calculate_late_fee(a i32, b i32) -> i32    { a - b }
+var businessRulesWASM = []byte{
+	0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x0d, 0x02, 0x60,
+	0x02, 0x7c, 0x7c, 0x01, 0x7c, 0x60, 0x02, 0x7f, 0x7f, 0x01, 0x7f, 0x03,
+	0x03, 0x02, 0x00, 0x01, 0x07, 0x26, 0x02, 0x0d, 0x63, 0x61, 0x6c, 0x63,
+	0x75, 0x6c, 0x61, 0x74, 0x65, 0x5f, 0x74, 0x61, 0x78, 0x00, 0x00, 0x12,
+	0x63, 0x61, 0x6c, 0x63, 0x75, 0x6c, 0x61, 0x74, 0x65, 0x5f, 0x6c, 0x61,
+	0x74, 0x65, 0x5f, 0x66, 0x65, 0x65, 0x00, 0x01, 0x0a, 0x11, 0x02, 0x07,
+	0x00, 0x20, 0x00, 0x20, 0x01, 0xa2, 0x0b, 0x07, 0x00, 0x20, 0x00, 0x20,
+	0x01, 0x6b, 0x0b, 0x00, 0xae, 0x01, 0x10, 0x6d, 0x69, 0x6e, 0x64, 0x62,
+	0x2e, 0x70, 0x72, 0x6f, 0x63, 0x65, 0x64, 0x75, 0x72, 0x65, 0x73, 0x7b,
+	0x22, 0x6e, 0x61, 0x6d, 0x65, 0x22, 0x3a, 0x22, 0x63, 0x61, 0x6c, 0x63,
+	0x75, 0x6c, 0x61, 0x74, 0x65, 0x5f, 0x74, 0x61, 0x78, 0x22, 0x2c, 0x22,
+	0x70, 0x61, 0x72, 0x61, 0x6d, 0x73, 0x22, 0x3a, 0x5b, 0x7b, 0x22, 0x6e,
+	0x61, 0x6d, 0x65, 0x22, 0x3a, 0x22, 0x61, 0x6d, 0x6f, 0x75, 0x6e, 0x74,
+	0x22, 0x2c, 0x22, 0x74, 0x79, 0x70, 0x65, 0x22, 0x3a, 0x22, 0x46, 0x4c,
+	0x4f, 0x41, 0x54, 0x22, 0x7d, 0x2c, 0x7b, 0x22, 0x6e, 0x61, 0x6d, 0x65,
+	0x22, 0x3a, 0x22, 0x72, 0x61, 0x74, 0x65, 0x22, 0x2c, 0x22, 0x74, 0x79,
+	0x70, 0x65, 0x22, 0x3a, 0x22, 0x46, 0x4c, 0x4f, 0x41, 0x54, 0x22, 0x7d,
+	0x5d, 0x2c, 0x22, 0x72, 0x65, 0x74, 0x75, 0x72, 0x6e, 0x73, 0x22, 0x3a,
+	0x22, 0x46, 0x4c, 0x4f, 0x41, 0x54, 0x22, 0x2c, 0x22, 0x64, 0x65, 0x73,
+	0x63, 0x72, 0x69, 0x70, 0x74, 0x69, 0x6f, 0x6e, 0x22, 0x3a, 0x22, 0x54,
+	0x61, 0x78, 0x20, 0x6f, 0x77, 0x65, 0x64, 0x20, 0x6f, 0x6e, 0x20, 0x61,
+	0x6e, 0x20, 0x61, 0x6d, 0x6f, 0x75, 0x6e, 0x74, 0x2e, 0x22, 0x7d, 0x0a,
+}
+
 func TestWASMEngine_Creation(t *testing.T) {
 	engine, err := NewWASMEngine(DefaultWASMConfig())
 	if err != nil {
@@ -538,6 +626,206 @@ func TestWASMEngine_LoadProcedureFromBase64_InvalidBase64(t *testing.T) {
 	}
 }
 
+func TestWASMEngine_GuestError(t *testing.T) {
+	engine, err := NewWASMEngine(DefaultWASMConfig())
+	if err != nil {
+		t.Fatalf("Failed to create WASM engine: %v", err)
+	}
+	defer engine.Close()
+
+	if err := engine.CompileModule("check_tier", guestErrorWASM); err != nil {
+		t.Fatalf("Failed to compile module: %v", err)
+	}
+
+	// Successful call: the import is linked even without a database context
+	result, err := engine.Execute("check_tier", "check_tier", int32(3))
+	if err != nil {
+		t.Fatalf("Failed to execute function: %v", err)
+	}
+	if result.(int32) != 3 {
+		t.Errorf("Expected 3, got %v", result)
+	}
+
+	// Failing call: the recorded message replaces the generic trap
+	_, err = engine.ExecuteWithContext("check_tier", "check_tier", &ExecutionContext{}, int32(0))
+	if err == nil {
+		t.Fatal("Expected error from guest, got nil")
+	}
+	if !strings.Contains(err.Error(), "procedure error: bad tier") {
+		t.Errorf("Expected guest error message, got: %v", err)
+	}
+	var procErr *ProcedureError
+	if !errors.As(err, &procErr) || procErr.Code != SQLStateRaiseException {
+		t.Errorf("Expected a %s procedure error, got: %#v", SQLStateRaiseException, procErr)
+	}
+}
+
+func TestWASMEngine_GuestRaise(t *testing.T) {
+	engine, err := NewWASMEngine(DefaultWASMConfig())
+	if err != nil {
+		t.Fatalf("Failed to create WASM engine: %v", err)
+	}
+	defer engine.Close()
+
+	if err := engine.CompileModule("check_tier", guestRaiseWASM); err != nil {
+		t.Fatalf("Failed to compile module: %v", err)
+	}
+
+	result, err := engine.Execute("check_tier", "check_tier", int32(2))
+	if err != nil || result != int32(2) {
+		t.Fatalf("Expected 2, got %v, %v", result, err)
+	}
+
+	// Raised errors keep their code, message and detail
+	_, err = engine.Execute("check_tier", "check_tier", int32(7))
+	var procErr *ProcedureError
+	if !errors.As(err, &procErr) {
+		t.Fatalf("Expected a procedure error, got: %v", err)
+	}
+	if procErr.Code != "22023" || procErr.Message != "invalid tier" || procErr.Detail != "tier must be between 1 and 3" || !procErr.Raised {
+		t.Errorf("Unexpected procedure error: %#v", procErr)
+	}
+	if err.Error() != "procedure error: invalid tier" {
+		t.Errorf("Unexpected message: %v", err)
+	}
+
+	// A malformed code traps, and nothing from the failed raise leaks into
+	// the report
+	_, err = engine.Execute("check_tier", "bad_code")
+	if !errors.As(err, &procErr) {
+		t.Fatalf("Expected a procedure error, got: %v", err)
+	}
+	if procErr.Code != SQLStateExternalRoutine || procErr.Raised || !strings.Contains(err.Error(), "invalid SQLSTATE code") {
+		t.Errorf("Expected a trap, got: %#v", procErr)
+	}
+}
+
+func TestPagedEngine_CreateModule(t *testing.T) {
+	dataDir := t.TempDir()
+
+	engine, err := NewPagedEngine(dataDir)
+	if err != nil {
+		t.Fatalf("Failed to create engine: %v", err)
+	}
+
+	mod := &StoredModule{Name: "business_rules", Language: "wasm", Code: businessRulesWASM}
+	if err := engine.CreateModule(mod); err != nil {
+		t.Fatalf("Failed to create module: %v", err)
+	}
+	if err := engine.CreateModule(&StoredModule{Name: "business_rules", Code: businessRulesWASM}); err == nil {
+		t.Error("Expected error creating duplicate module")
+	}
+
+	// Every export is registered, with SDK metadata where present
+	if len(mod.Functions) != 2 {
+		t.Fatalf("Expected 2 functions, got %d", len(mod.Functions))
+	}
+	tax := mod.Functions["calculate_tax"]
+	if tax == nil || len(tax.Params) != 2 || tax.Params[1].Name != "rate" || tax.ReturnType != "FLOAT" {
+		t.Errorf("Unexpected calculate_tax signature: %+v", tax)
+	}
+	if fee := mod.Functions["calculate_late_fee"]; fee == nil || fee.ReturnType != "INT" {
+		t.Errorf("Unexpected calculate_late_fee signature: %+v", fee)
+	}
+
+	result, err := engine.CallModuleFunction("business_rules", "calculate_tax", 100.0, 0.08)
+	if err != nil {
+		t.Fatalf("Failed to call calculate_tax: %v", err)
+	}
+	if result.(float64) != 8.0 {
+		t.Errorf("Expected 8, got %v", result)
+	}
+
+	result, err = engine.CallModuleFunction("business_rules", "calculate_late_fee", 30, 10)
+	if err != nil {
+		t.Fatalf("Failed to call calculate_late_fee: %v", err)
+	}
+	if result.(int32) != 20 {
+		t.Errorf("Expected 20, got %v", result)
+	}
+
+	if _, err := engine.CallModuleFunction("business_rules", "missing"); err == nil {
+		t.Error("Expected error calling unknown function")
+	}
+	engine.Close()
+
+	// Modules survive a restart
+	engine, err = NewPagedEngine(dataDir)
+	if err != nil {
+		t.Fatalf("Failed to reopen engine: %v", err)
+	}
+	defer engine.Close()
+
+	result, err = engine.CallModuleFunction("business_rules", "calculate_late_fee", 5, 2)
+	if err != nil {
+		t.Fatalf("Failed to call after reload: %v", err)
+	}
+	if result.(int32) != 3 {
+		t.Errorf("Expected 3, got %v", result)
+	}
+
+	if err := engine.DropModule("business_rules"); err != nil {
+		t.Fatalf("Failed to drop module: %v", err)
+	}
+	if _, err := engine.CallModuleFunction("business_rules", "calculate_tax", 1.0, 1.0); err == nil {
+		t.Error("Expected error calling dropped module")
+	}
+}
+
+func TestEngineAdapter_ModuleStatements(t *testing.T) {
+	adapter, err := NewEngineAdapter(t.TempDir(), false)
+	if err != nil {
+		t.Fatalf("Failed to create adapter: %v", err)
+	}
+	defer adapter.Close()
+
+	parser := NewParser()
+	exec := func(sql string) (string, error) {
+		stmt, err := parser.Parse(sql)
+		if err != nil {
+			t.Fatalf("Failed to parse %q: %v", sql, err)
+		}
+		return adapter.Execute(stmt)
+	}
+
+	code := base64.StdEncoding.EncodeToString(businessRulesWASM)
+	result, err := exec(fmt.Sprintf("CREATE MODULE business_rules LANGUAGE rust AS '%s'", code))
+	if err != nil {
+		t.Fatalf("CREATE MODULE failed: %v", err)
+	}
+	if !strings.Contains(result, "2 function(s)") {
+		t.Errorf("Unexpected result: %s", result)
+	}
+
+	result, err = exec("CALL business_rules.calculate_late_fee(30, 10)")
+	if err != nil {
+		t.Fatalf("CALL failed: %v", err)
+	}
+	if !strings.Contains(result, "20") {
+		t.Errorf("Expected 20 in result, got: %s", result)
+	}
+
+	// The HTTP API passes qualified names straight through
+	value, err := adapter.CallProcedureViaAdapter("business_rules.calculate_tax", 50.0, 0.1)
+	if err != nil {
+		t.Fatalf("Qualified call failed: %v", err)
+	}
+	if value.(float64) != 5.0 {
+		t.Errorf("Expected 5, got %v", value)
+	}
+
+	if _, err := exec("DROP MODULE business_rules"); err != nil {
+		t.Fatalf("DROP MODULE failed: %v", err)
+	}
+	result, err = exec("DROP MODULE IF EXISTS business_rules")
+	if err != nil || !strings.Contains(result, "skipping") {
+		t.Errorf("Expected DROP MODULE IF EXISTS to skip, got %q, %v", result, err)
+	}
+	if _, err := exec("CALL business_rules.calculate_late_fee(30, 10)"); err == nil {
+		t.Error("Expected error calling dropped module")
+	}
+}
+
 func BenchmarkWASMEngine_Execute(b *testing.B) {
 	engine, err := NewWASMEngine(DefaultWASMConfig())
 	if err != nil {
@@ -0,0 +1,588 @@
+package mindb
+
+import (
+	"bytes"
+	"encoding/json"
+	"fmt"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+	"time"
+)
+
+// Trigger timings and events
+const (
+	TriggerBefore = "BEFORE"
+	TriggerAfter  = "AFTER"
+
+	TriggerInsert = "INSERT"
+	TriggerUpdate = "UPDATE"
+	TriggerDelete = "DELETE"
+)
+
+// maxTriggerDepth bounds triggers fired from within trigger handlers, so a
+// handler that writes to its own table fails instead of recursing forever.
+// Depth is counted along each chain of calls, not across the engine.
+const maxTriggerDepth = 16
+
+// StoredTrigger runs a WASM procedure for each row inserted, updated or
+// deleted in a table. The handler takes no arguments; it reads the row
+// through the mindb_trigger_* imports and rejects the change by failing.
+type StoredTrigger struct {
+	Name      string
+	Database  string
+	Table     string
+	Timing    string   // BEFORE or AFTER
+	Events    []string // INSERT, UPDATE and/or DELETE
+	Procedure string   // Handler procedure, or function of Module
+	Module    string
+	CreatedAt time.Time
+}
+
+// key returns the trigger's key in e.triggers and the name of its metadata
+// file. Trigger names are unique per table.
+func (t *StoredTrigger) key() string {
+	return t.Database + "." + t.Table + "." + t.Name
+}
+
+// handler returns the handler's name as written in CREATE TRIGGER
+func (t *StoredTrigger) handler() string {
+	if t.Module != "" {
+		return t.Module + "." + t.Procedure
+	}
+	return t.Procedure
+}
+
+// firesOn reports whether the trigger fires at timing for event
+func (t *StoredTrigger) firesOn(timing, event string) bool {
+	if t.Timing != timing {
+		return false
+	}
+	for _, e := range t.Events {
+		if e == event {
+			return true
+		}
+	}
+	return false
+}
+
+// TriggerEvent is the row change a trigger handler is called for
+type TriggerEvent struct {
+	Trigger string
+	Table   string
+	Timing  string
+	Event   string
+	Old     Row // Row before the change; nil for INSERT
+	New     Row // Row after the change; nil for DELETE. BEFORE handlers may change it.
+
+	columns []Column // Columns of the table, the only ones New may be given
+}
+
+// hasColumn reports whether the table the event is on has a column
+func (ev *TriggerEvent) hasColumn(name string) bool {
+	for _, col := range ev.columns {
+		if col.Name == name {
+			return true
+		}
+	}
+	return false
+}
+
+// CreateTrigger stores a trigger on a table of the current database
+func (e *PagedEngine) CreateTrigger(trigger *StoredTrigger) error {
+	db, err := e.getCurrentDatabase()
+	if err != nil {
+		return err
+	}
+	db.mu.RLock()
+	_, exists := db.Tables[trigger.Table]
+	db.mu.RUnlock()
+	if !exists {
+		return fmt.Errorf("table '%s' does not exist", trigger.Table)
+	}
+
+	e.mu.Lock()
+	defer e.mu.Unlock()
+
+	// Check if trigger already exists on the table
+	trigger.Database = db.Name
+	if _, exists := e.triggers[trigger.key()]; exists {
+		return fmt.Errorf("trigger '%s' already exists on table '%s'", trigger.Name, trigger.Table)
+	}
+
+	if trigger.Timing != TriggerBefore && trigger.Timing != TriggerAfter {
+		return fmt.Errorf("invalid trigger timing: %s", trigger.Timing)
+	}
+	if len(trigger.Events) == 0 {
+		return fmt.Errorf("trigger '%s' has no events", trigger.Name)
+	}
+
+	// The handler must exist and take no arguments
+	proc, err := e.lookupHandler(trigger.Module, trigger.Procedure)
+	if err != nil {
+		return err
+	}
+	if len(proc.Params) > 0 {
+		return fmt.Errorf("trigger handler '%s' must take no arguments", trigger.handler())
+	}
+
+	trigger.CreatedAt = time.Now()
+
+	// Persist to disk
+	if err := e.saveTrigger(trigger); err != nil {
+		return fmt.Errorf("failed to persist trigger: %w", err)
+	}
+
+	// Store in memory
+	e.triggers[trigger.key()] = trigger
+	return nil
+}
+
+// DropTrigger drops a trigger of the current database. If table is given,
+// the trigger must be on it; otherwise its name must be on only one table.
+func (e *PagedEngine) DropTrigger(name, table string) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+
+	// Check if trigger exists
+	var trigger *StoredTrigger
+	for _, t := range e.triggers {
+		if t.Database != e.currentDB || t.Name != name || (table != "" && t.Table != table) {
+			continue
+		}
+		if trigger != nil {
+			return fmt.Errorf("trigger '%s' exists on more than one table; use DROP TRIGGER %s ON table", name, name)
+		}
+		trigger = t
+	}
+	if trigger == nil {
+		return fmt.Errorf("trigger '%s' does not exist", name)
+	}
+
+	// Delete from disk
+	if err := e.deleteTrigger(trigger); err != nil {
+		return fmt.Errorf("failed to delete trigger: %w", err)
+	}
+
+	// Remove from triggers map
+	delete(e.triggers, trigger.key())
+
+	return nil
+}
+
+// dropTableTriggers drops the triggers on a table being dropped
+func (e *PagedEngine) dropTableTriggers(database, table string) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+
+	return e.dropTriggers(database, table)
+}
+
+// dropTriggers drops the triggers on a table, or on every table of the
+// database if table is empty. The caller holds e.mu.
+func (e *PagedEngine) dropTriggers(database, table string) error {
+	for key, trigger := range e.triggers {
+		if trigger.Database != database || (table != "" && trigger.Table != table) {
+			continue
+		}
+		if err := e.deleteTrigger(trigger); err != nil {
+			return err
+		}
+		delete(e.triggers, key)
+	}
+
+	return nil
+}
+
+// ListTriggers returns all triggers, ordered by name, then database and
+// table
+func (e *PagedEngine) ListTriggers() []*StoredTrigger {
+	e.mu.RLock()
+	defer e.mu.RUnlock()
+
+	triggers := make([]*StoredTrigger, 0, len(e.triggers))
+	for _, trigger := range e.triggers {
+		triggers = append(triggers, trigger)
+	}
+	sort.Slice(triggers, func(i, j int) bool {
+		if triggers[i].Name != triggers[j].Name {
+			return triggers[i].Name < triggers[j].Name
+		}
+		return triggers[i].key() < triggers[j].key()
+	})
+
+	return triggers
+}
+
+// lookupHandler finds a trigger handler procedure or module function. The
+// caller holds e.mu.
+func (e *PagedEngine) lookupHandler(module, name string) (*StoredProcedure, error) {
+	if module == "" {
+		proc, exists := e.procedures[name]
+		if !exists {
+			return nil, fmt.Errorf("procedure '%s' does not exist", name)
+		}
+		return proc, nil
+	}
+
+	mod, exists := e.modules[module]
+	if !exists {
+		return nil, fmt.Errorf("module '%s' does not exist", module)
+	}
+	fn, exists := mod.Functions[name]
+	if !exists {
+		return nil, fmt.Errorf("module '%s' has no function '%s'", module, name)
+	}
+	return fn, nil
+}
+
+// triggersFor returns the triggers of a table in the current database that
+// fire at timing for event, ordered by name
+func (e *PagedEngine) triggersFor(table, timing, event string) []*StoredTrigger {
+	e.mu.RLock()
+	defer e.mu.RUnlock()
+
+	var triggers []*StoredTrigger
+	for _, trigger := range e.triggers {
+		if trigger.Database == e.currentDB && trigger.Table == table && trigger.firesOn(timing, event) {
+			triggers = append(triggers, trigger)
+		}
+	}
+	sort.Slice(triggers, func(i, j int) bool { return triggers[i].Name < triggers[j].Name })
+
+	return triggers
+}
+
+// hasTriggers reports whether any trigger fires for event on a table
+func (e *PagedEngine) hasTriggers(table, event string) bool {
+	return len(e.triggersFor(table, TriggerBefore, event)) > 0 ||
+		len(e.triggersFor(table, TriggerAfter, event)) > 0
+}
+
+// fireTriggers runs the triggers for one row change in name order and
+// returns the new row as changed by BEFORE handlers. An error from any
+// handler rejects the change. ctx is the context of the statement making
+// the change, which may itself run in trigger handlers. Must be called
+// without table locks held, since handlers may access the database.
+func (e *PagedEngine) fireTriggers(ctx *ExecutionContext, table, timing, event string, oldRow, newRow Row) (Row, error) {
+	triggers := e.triggersFor(table, timing, event)
+	if len(triggers) == 0 {
+		return newRow, nil
+	}
+
+	if ctx.TriggerDepth >= maxTriggerDepth {
+		return nil, fmt.Errorf("triggers on '%s' nested more than %d levels deep", table, maxTriggerDepth)
+	}
+
+	// BEFORE handlers may only set the table's columns in the new row
+	columns, err := e.tableColumns(table)
+	if err != nil {
+		return nil, err
+	}
+
+	for _, trigger := range triggers {
+		ev := &TriggerEvent{
+			Trigger: trigger.Name,
+			Table:   table,
+			Timing:  timing,
+			Event:   event,
+			Old:     copyRow(oldRow),
+			New:     copyRow(newRow),
+			columns: columns,
+		}
+		if err := e.runTrigger(ctx, trigger, ev); err != nil {
+			return nil, fmt.Errorf("trigger '%s' on %s: %w", trigger.Name, table, err)
+		}
+		if timing == TriggerBefore {
+			newRow = ev.New
+		}
+	}
+
+	return newRow, nil
+}
+
+// tableColumns returns the columns of a table in the current database
+func (e *PagedEngine) tableColumns(name string) ([]Column, error) {
+	db, err := e.getCurrentDatabase()
+	if err != nil {
+		return nil, err
+	}
+
+	db.mu.RLock()
+	table, exists := db.Tables[name]
+	db.mu.RUnlock()
+	if !exists {
+		return nil, fmt.Errorf("table '%s' does not exist", name)
+	}
+
+	table.mu.RLock()
+	defer table.mu.RUnlock()
+	return table.Columns, nil
+}
+
+// runTrigger calls a trigger's handler for one event, one level deeper than
+// the statement running in parent
+func (e *PagedEngine) runTrigger(parent *ExecutionContext, trigger *StoredTrigger, ev *TriggerEvent) error {
+	e.mu.RLock()
+	proc, err := e.lookupHandler(trigger.Module, trigger.Procedure)
+	e.mu.RUnlock()
+	if err != nil {
+		return err
+	}
+
+	ctx := e.procedureContext(parent, proc)
+	ctx.Trigger = ev
+	ctx.TriggerDepth = parent.TriggerDepth + 1
+
+	cacheKey, name := trigger.Procedure, trigger.Procedure
+	if trigger.Module != "" {
+		cacheKey = moduleCacheKey(trigger.Module)
+		name = trigger.Module + "." + trigger.Procedure
+	}
+	_, err = e.executeAudited(cacheKey, name, proc, ctx, nil)
+	return err
+}
+
+// copyRow returns a shallow copy of a row, or nil for nil
+func copyRow(row Row) Row {
+	if row == nil {
+		return nil
+	}
+	out := make(Row, len(row))
+	for k, v := range row {
+		out[k] = v
+	}
+	return out
+}
+
+// triggerRow is a row changed by an UPDATE or DELETE on a table with
+// triggers or CHECK constraints
+type triggerRow struct {
+	tid  TupleID
+	data []byte // The tuple as matched, to detect concurrent changes
+	old  Row
+	new  Row
+}
+
+// matchRows returns the visible rows of a table matching conditions
+func (e *PagedEngine) matchRows(table *PagedTable, plan *QueryPlan, conditions []Condition, snapshot *Snapshot) []triggerRow {
+	table.mu.RLock()
+	defer table.mu.RUnlock()
+
+	var matches []triggerRow
+	for _, tid := range NewQueryPlanner().ExecutePlan(plan, table, conditions) {
+		tupleData, err := table.HeapFile.GetTuple(tid)
+		if err != nil {
+			continue
+		}
+
+		tuple, err := DeserializeTuple(tupleData)
+		if err != nil {
+			continue
+		}
+
+		if !e.txnManager.IsVisible(tuple, snapshot) || !matchesConditions(tuple.Data, conditions) {
+			continue
+		}
+		matches = append(matches, triggerRow{tid: tid, data: tupleData, old: tuple.Data})
+	}
+
+	return matches
+}
+
+// updateRowsWithProcedures updates rows of a table with UPDATE triggers or
+// CHECK constraints, both of which may call procedures. All BEFORE triggers
+// and CHECK constraints run before any row is written, so a rejected row
+// leaves the table unchanged; AFTER triggers run once every row is written.
+// Rows changed by another statement while the procedures ran are skipped.
+func (e *PagedEngine) updateRowsWithProcedures(ctx *ExecutionContext, table *PagedTable, updates map[string]interface{}, conditions []Condition) (int, error) {
+	var snapshot *Snapshot
+	var tempTxn *Transaction
+	if e.currentTxn != nil {
+		snapshot = e.currentTxn.Snapshot
+	} else {
+		tempTxn, _ = e.txnManager.BeginTransaction()
+		snapshot = tempTxn.Snapshot
+		defer e.txnManager.CommitTransaction(tempTxn.ID)
+	}
+
+	validator := NewConstraintValidator()
+	rows := e.matchRows(table, NewQueryPlanner().PlanUpdate(table, conditions), conditions, snapshot)
+	for i := range rows {
+		newRow := copyRow(rows[i].old)
+		for col, val := range updates {
+			newRow[col] = val
+		}
+		newRow, err := e.fireTriggers(ctx, table.Name, TriggerBefore, TriggerUpdate, rows[i].old, newRow)
+		if err != nil {
+			return 0, err
+		}
+		if err := validator.ValidateChecks(ctx, table, newRow); err != nil {
+			return 0, err
+		}
+		rows[i].new = newRow
+	}
+
+	var written []triggerRow
+	table.mu.Lock()
+	for _, row := range rows {
+		tuple, err := e.readUnchangedTuple(table, row)
+		if err != nil {
+			continue
+		}
+		tuple.Data = row.new
+		if err := e.rewriteTuple(table, row.tid, tuple); err != nil {
+			continue
+		}
+		written = append(written, row)
+	}
+	table.mu.Unlock()
+
+	for _, row := range written {
+		if _, err := e.fireTriggers(ctx, table.Name, TriggerAfter, TriggerUpdate, row.old, row.new); err != nil {
+			return len(written), err
+		}
+	}
+
+	return len(written), nil
+}
+
+// deleteRowsWithTriggers deletes rows of a table with DELETE triggers. All
+// BEFORE triggers run before any row is deleted. Rows changed by another
+// statement while the triggers ran are skipped.
+func (e *PagedEngine) deleteRowsWithTriggers(ctx *ExecutionContext, table *PagedTable, conditions []Condition) (int, error) {
+	var snapshot *Snapshot
+	var txnID uint32
+	if e.currentTxn != nil {
+		snapshot = e.currentTxn.Snapshot
+		txnID = e.currentTxn.ID
+	} else {
+		tempTxn, _ := e.txnManager.BeginTransaction()
+		snapshot = tempTxn.Snapshot
+		txnID = tempTxn.ID
+		defer e.txnManager.CommitTransaction(tempTxn.ID)
+	}
+
+	rows := e.matchRows(table, NewQueryPlanner().PlanDelete(table, conditions), conditions, snapshot)
+	for _, row := range rows {
+		if _, err := e.fireTriggers(ctx, table.Name, TriggerBefore, TriggerDelete, row.old, nil); err != nil {
+			return 0, err
+		}
+	}
+
+	var deleted []triggerRow
+	table.mu.Lock()
+	for _, row := range rows {
+		tuple, err := e.readUnchangedTuple(table, row)
+		if err != nil {
+			continue
+		}
+
+		// MVCC delete: set Xmax instead of physical delete
+		newData, err := SerializeTupleWithHeader(tuple.Data, table.Columns, tuple.Header.Xmin, txnID)
+		if err != nil {
+			continue
+		}
+		if err := table.HeapFile.UpdateTuple(row.tid, newData); err != nil {
+			continue
+		}
+		deleted = append(deleted, row)
+	}
+	table.mu.Unlock()
+
+	for _, row := range deleted {
+		if _, err := e.fireTriggers(ctx, table.Name, TriggerAfter, TriggerDelete, row.old, nil); err != nil {
+			return len(deleted), err
+		}
+	}
+
+	return len(deleted), nil
+}
+
+// readUnchangedTuple re-reads a matched row under the table's write lock and
+// fails if it was updated or deleted since it was matched
+func (e *PagedEngine) readUnchangedTuple(table *PagedTable, row triggerRow) (*Tuple, error) {
+	tupleData, err := table.HeapFile.GetTuple(row.tid)
+	if err != nil {
+		return nil, err
+	}
+	if !bytes.Equal(tupleData, row.data) {
+		return nil, fmt.Errorf("row %v changed concurrently", row.tid)
+	}
+	return DeserializeTuple(tupleData)
+}
+
+// saveTrigger persists a new trigger to disk through the WAL
+func (e *PagedEngine) saveTrigger(trigger *StoredTrigger) error {
+	change, err := createObjectChange(objectTrigger, trigger.key(), trigger)
+	if err != nil {
+		return err
+	}
+	return e.logObjectChange(change)
+}
+
+// deleteTrigger removes a trigger from disk through the WAL
+func (e *PagedEngine) deleteTrigger(trigger *StoredTrigger) error {
+	change, err := dropObjectChange(objectTrigger, trigger.key(), trigger)
+	if err != nil {
+		return err
+	}
+	return e.logObjectChange(change)
+}
+
+// loadTriggers loads all triggers from disk
+func (e *PagedEngine) loadTriggers() error {
+	triggerDir := filepath.Join(e.dataDir, "triggers")
+
+	// Check if triggers directory exists
+	if _, err := os.Stat(triggerDir); os.IsNotExist(err) {
+		return nil // No triggers to load
+	}
+
+	// Read all trigger files
+	files, err := os.ReadDir(triggerDir)
+	if err != nil {
+		return fmt.Errorf("failed to read triggers directory: %w", err)
+	}
+
+	// Load each trigger, leaving out any that cannot be loaded
+	for _, file := range files {
+		if file.IsDir() || !strings.HasSuffix(file.Name(), ".json") {
+			continue
+		}
+
+		if err := e.loadTrigger(filepath.Join(triggerDir, file.Name())); err != nil {
+			fmt.Printf("Warning: failed to load trigger %s: %v\n", file.Name(), err)
+		}
+	}
+
+	return nil
+}
+
+// loadTrigger loads one trigger
+func (e *PagedEngine) loadTrigger(filename string) error {
+	data, err := os.ReadFile(filename)
+	if err != nil {
+		return err
+	}
+
+	var trigger StoredTrigger
+	if err := json.Unmarshal(data, &trigger); err != nil {
+		return err
+	}
+
+	// Triggers saved under their name alone are renamed to their key
+	if renamed := filepath.Join(filepath.Dir(filename), trigger.key()+".json"); renamed != filename {
+		if err := replaceFile(renamed, data); err != nil {
+			return err
+		}
+		if err := os.Remove(filename); err != nil {
+			return err
+		}
+	}
+
+	// Store in memory
+	e.triggers[trigger.key()] = &trigger
+	return nil
+}
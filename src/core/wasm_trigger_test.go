@@ -0,0 +1,385 @@
+package mindb
+
+import (
+	"strings"
+	"sync"
+	"testing"
+)
+
+// WASM module with trigger handlers, raising the host's message through
+// mindb_error when a host call fails. Equivalent to:
+//
+//	#[procedure]
+//	fn normalize() -> Result<()> {
+//	    trigger::set_new(&[("status", "checked".into())])
+//	}
+//
+//	#[procedure]
+//	fn reject() -> Result<()> {
+//	    Err(ProcError::new("card declined"))
+//	}
+//
+//	#[procedure]
+//	fn audit_old() -> Result<()> {
+//	    match trigger::old_row()? {
+//	        Some(row) => db::insert("audit", /* row's columns */),
+//	        None => Ok(()),
+//	    }
+//	}
+//
+// plus recurse() inserting {"id":99,"amount":1} into payments and
+// with_arg(i32) doing nothing.
+var triggerWASM = []byte{
+	0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x1f, 0x06, 0x60,
+	0x00, 0x01, 0x7f, 0x60, 0x02, 0x7f, 0x7f, 0x01, 0x7f, 0x60, 0x02, 0x7f,
+	0x7f, 0x00, 0x60, 0x04, 0x7f, 0x7f, 0x7f, 0x7f, 0x01, 0x7f, 0x60, 0x01,
+	0x7f, 0x00, 0x60, 0x00, 0x00, 0x02, 0x72, 0x05, 0x03, 0x65, 0x6e, 0x76,
+	0x11, 0x6d, 0x69, 0x6e, 0x64, 0x62, 0x5f, 0x74, 0x72, 0x69, 0x67, 0x67,
+	0x65, 0x72, 0x5f, 0x6f, 0x6c, 0x64, 0x00, 0x00, 0x03, 0x65, 0x6e, 0x76,
+	0x11, 0x6d, 0x69, 0x6e, 0x64, 0x62, 0x5f, 0x72, 0x65, 0x73, 0x75, 0x6c,
+	0x74, 0x5f, 0x72, 0x65, 0x61, 0x64, 0x00, 0x01, 0x03, 0x65, 0x6e, 0x76,
+	0x15, 0x6d, 0x69, 0x6e, 0x64, 0x62, 0x5f, 0x74, 0x72, 0x69, 0x67, 0x67,
+	0x65, 0x72, 0x5f, 0x73, 0x65, 0x74, 0x5f, 0x6e, 0x65, 0x77, 0x00, 0x01,
+	0x03, 0x65, 0x6e, 0x76, 0x0b, 0x6d, 0x69, 0x6e, 0x64, 0x62, 0x5f, 0x65,
+	0x72, 0x72, 0x6f, 0x72, 0x00, 0x02, 0x03, 0x65, 0x6e, 0x76, 0x0c, 0x6d,
+	0x69, 0x6e, 0x64, 0x62, 0x5f, 0x69, 0x6e, 0x73, 0x65, 0x72, 0x74, 0x00,
+	0x03, 0x03, 0x07, 0x06, 0x04, 0x05, 0x05, 0x05, 0x05, 0x04, 0x05, 0x03,
+	0x01, 0x00, 0x01, 0x07, 0x40, 0x06, 0x06, 0x6d, 0x65, 0x6d, 0x6f, 0x72,
+	0x79, 0x02, 0x00, 0x09, 0x6e, 0x6f, 0x72, 0x6d, 0x61, 0x6c, 0x69, 0x7a,
+	0x65, 0x00, 0x06, 0x06, 0x72, 0x65, 0x6a, 0x65, 0x63, 0x74, 0x00, 0x07,
+	0x09, 0x61, 0x75, 0x64, 0x69, 0x74, 0x5f, 0x6f, 0x6c, 0x64, 0x00, 0x08,
+	0x07, 0x72, 0x65, 0x63, 0x75, 0x72, 0x73, 0x65, 0x00, 0x09, 0x08, 0x77,
+	0x69, 0x74, 0x68, 0x5f, 0x61, 0x72, 0x67, 0x00, 0x0a, 0x0a, 0x6f, 0x06,
+	0x1a, 0x00, 0x20, 0x00, 0x41, 0x00, 0x48, 0x04, 0x40, 0x41, 0x80, 0xc0,
+	0x00, 0x41, 0x80, 0xc0, 0x00, 0x41, 0x80, 0x20, 0x10, 0x01, 0x10, 0x03,
+	0x00, 0x0b, 0x0b, 0x0b, 0x00, 0x41, 0xc0, 0x00, 0x41, 0x14, 0x10, 0x02,
+	0x10, 0x05, 0x0b, 0x0a, 0x00, 0x41, 0x80, 0x01, 0x41, 0x0d, 0x10, 0x03,
+	0x00, 0x0b, 0x27, 0x01, 0x01, 0x7f, 0x10, 0x00, 0x22, 0x00, 0x10, 0x05,
+	0x20, 0x00, 0x45, 0x04, 0x40, 0x0f, 0x0b, 0x41, 0x80, 0x20, 0x20, 0x00,
+	0x10, 0x01, 0x1a, 0x41, 0xa0, 0x01, 0x41, 0x05, 0x41, 0x80, 0x20, 0x20,
+	0x00, 0x10, 0x04, 0x10, 0x05, 0x0b, 0x10, 0x00, 0x41, 0xe0, 0x01, 0x41,
+	0x08, 0x41, 0xc0, 0x01, 0x41, 0x14, 0x10, 0x04, 0x10, 0x05, 0x0b, 0x02,
+	0x00, 0x0b, 0x0b, 0x61, 0x05, 0x00, 0x41, 0xc0, 0x00, 0x0b, 0x14, 0x7b,
+	0x22, 0x73, 0x74, 0x61, 0x74, 0x75, 0x73, 0x22, 0x3a, 0x22, 0x63, 0x68,
+	0x65, 0x63, 0x6b, 0x65, 0x64, 0x22, 0x7d, 0x00, 0x41, 0x80, 0x01, 0x0b,
+	0x0d, 0x63, 0x61, 0x72, 0x64, 0x20, 0x64, 0x65, 0x63, 0x6c, 0x69, 0x6e,
+	0x65, 0x64, 0x00, 0x41, 0xa0, 0x01, 0x0b, 0x05, 0x61, 0x75, 0x64, 0x69,
+	0x74, 0x00, 0x41, 0xc0, 0x01, 0x0b, 0x14, 0x7b, 0x22, 0x69, 0x64, 0x22,
+	0x3a, 0x39, 0x39, 0x2c, 0x22, 0x61, 0x6d, 0x6f, 0x75, 0x6e, 0x74, 0x22,
+	0x3a, 0x31, 0x7d, 0x00, 0x41, 0xe0, 0x01, 0x0b, 0x08, 0x70, 0x61, 0x79,
+	0x6d, 0x65, 0x6e, 0x74, 0x73,
+}
+
+// newTriggerTestAdapter returns an adapter with payments and audit tables
+// and the trigger handler procedures
+func newTriggerTestAdapter(t *testing.T, dataDir string) *EngineAdapter {
+	t.Helper()
+
+	adapter, err := NewEngineAdapter(dataDir, false)
+	if err != nil {
+		t.Fatalf("Failed to create adapter: %v", err)
+	}
+	t.Cleanup(func() { adapter.Close() })
+
+	if _, err := adapter.Execute(&Statement{Type: CreateDatabase, Database: "shop"}); err != nil {
+		t.Fatalf("CREATE DATABASE failed: %v", err)
+	}
+	if err := adapter.UseDatabase("shop"); err != nil {
+		t.Fatalf("USE DATABASE failed: %v", err)
+	}
+
+	for _, proc := range []*StoredProcedure{
+		{Name: "normalize"},
+		{Name: "reject"},
+		{Name: "audit_old"},
+		{Name: "recurse"},
+		{Name: "with_arg", Params: []Column{{Name: "n", DataType: "INT"}}},
+	} {
+		proc.Language = "wasm"
+		proc.Code = triggerWASM
+		proc.ReturnType = "VOID"
+		if err := adapter.pagedEngine.CreateProcedure(proc); err != nil {
+			t.Fatalf("Failed to create procedure %s: %v", proc.Name, err)
+		}
+	}
+
+	for _, sql := range []string{
+		"CREATE TABLE payments (id INT, amount INT, status TEXT)",
+		"CREATE TABLE audit (id INT, amount INT, status TEXT)",
+	} {
+		execSQL(t, adapter, sql)
+	}
+
+	return adapter
+}
+
+// execSQLError runs a statement that is expected to fail
+func execSQLError(t *testing.T, adapter *EngineAdapter, sql string) error {
+	t.Helper()
+
+	stmt, err := NewParser().Parse(sql)
+	if err != nil {
+		t.Fatalf("Parse(%q) error = %v", sql, err)
+	}
+	if _, err := adapter.Execute(stmt); err != nil {
+		return err
+	}
+	t.Fatalf("Expected %q to fail", sql)
+	return nil
+}
+
+func TestEngineAdapter_BeforeTriggerChangesRow(t *testing.T) {
+	adapter := newTriggerTestAdapter(t, t.TempDir())
+
+	execSQL(t, adapter, "CREATE TRIGGER check_payment BEFORE INSERT OR UPDATE ON payments EXECUTE PROCEDURE normalize()")
+	execSQL(t, adapter, "INSERT INTO payments (id, amount, status) VALUES (1, 10, 'new')")
+
+	result := execSQL(t, adapter, "SELECT status FROM payments WHERE id = 1")
+	if !strings.Contains(result, "| checked ") {
+		t.Errorf("Expected status set by the trigger, got:\n%s", result)
+	}
+
+	// UPDATE runs the trigger too
+	execSQL(t, adapter, "INSERT INTO payments (id, amount, status) VALUES (2, 20, 'new')")
+	execSQL(t, adapter, "UPDATE payments SET status = 'paid' WHERE id = 2")
+	result = execSQL(t, adapter, "SELECT status FROM payments WHERE status = 'checked'")
+	if !strings.Contains(result, "2 row(s) returned") {
+		t.Errorf("Expected both rows checked, got:\n%s", result)
+	}
+}
+
+func TestEngineAdapter_BeforeTriggerRejects(t *testing.T) {
+	adapter := newTriggerTestAdapter(t, t.TempDir())
+
+	execSQL(t, adapter, "INSERT INTO payments (id, amount, status) VALUES (1, 10, 'new')")
+	execSQL(t, adapter, "CREATE TRIGGER decline BEFORE INSERT OR UPDATE OR DELETE ON payments FOR EACH ROW EXECUTE PROCEDURE reject")
+
+	for _, sql := range []string{
+		"INSERT INTO payments (id, amount, status) VALUES (2, 20, 'new')",
+		"UPDATE payments SET status = 'paid'",
+		"DELETE FROM payments WHERE id = 1",
+	} {
+		err := execSQLError(t, adapter, sql)
+		if !strings.Contains(err.Error(), "trigger 'decline' on payments") || !strings.Contains(err.Error(), "card declined") {
+			t.Errorf("%s: unexpected error: %v", sql, err)
+		}
+	}
+
+	result := execSQL(t, adapter, "SELECT * FROM payments")
+	if !strings.Contains(result, "1 row(s) returned") || !strings.Contains(result, "| new ") {
+		t.Errorf("Expected the table unchanged, got:\n%s", result)
+	}
+
+	// Without the trigger the statements go through again
+	execSQL(t, adapter, "DROP TRIGGER decline ON payments")
+	execSQL(t, adapter, "INSERT INTO payments (id, amount, status) VALUES (2, 20, 'new')")
+	execSQL(t, adapter, "DROP TRIGGER IF EXISTS decline")
+}
+
+func TestEngineAdapter_AfterTriggerSeesOldRow(t *testing.T) {
+	adapter := newTriggerTestAdapter(t, t.TempDir())
+
+	execSQL(t, adapter, "INSERT INTO payments (id, amount, status) VALUES (1, 10, 'new')")
+	execSQL(t, adapter, "INSERT INTO payments (id, amount, status) VALUES (2, 20, 'new')")
+	execSQL(t, adapter, "CREATE TRIGGER audit_payments AFTER INSERT OR UPDATE OR DELETE ON payments EXECUTE PROCEDURE audit_old")
+
+	// INSERT has no old row, so nothing is audited
+	execSQL(t, adapter, "INSERT INTO payments (id, amount, status) VALUES (3, 30, 'new')")
+	execSQL(t, adapter, "UPDATE payments SET status = 'paid' WHERE id = 1")
+	execSQL(t, adapter, "DELETE FROM payments WHERE amount >= 20")
+
+	result := execSQL(t, adapter, "SELECT * FROM audit")
+	if !strings.Contains(result, "3 row(s) returned") {
+		t.Errorf("Expected 3 audited rows, got:\n%s", result)
+	}
+	if strings.Contains(result, "| paid ") {
+		t.Errorf("Expected the old rows in the audit, got:\n%s", result)
+	}
+
+	result = execSQL(t, adapter, "SELECT * FROM payments")
+	if !strings.Contains(result, "1 row(s) returned") || !strings.Contains(result, "| paid ") {
+		t.Errorf("Expected only the updated row left, got:\n%s", result)
+	}
+}
+
+func TestEngineAdapter_TriggerErrors(t *testing.T) {
+	adapter := newTriggerTestAdapter(t, t.TempDir())
+	execSQL(t, adapter, "INSERT INTO payments (id, amount, status) VALUES (1, 10, 'new')")
+	execSQL(t, adapter, "CREATE TRIGGER check_payment AFTER UPDATE ON payments EXECUTE PROCEDURE normalize")
+	execSQL(t, adapter, "CREATE TRIGGER loop BEFORE INSERT ON payments EXECUTE PROCEDURE recurse")
+	execSQL(t, adapter, "CREATE TABLE ledger (id INT, amount INT)")
+	execSQL(t, adapter, "CREATE TRIGGER tag BEFORE INSERT ON ledger EXECUTE PROCEDURE normalize")
+
+	tests := []struct {
+		name    string
+		sql     string
+		wantErr string
+	}{
+		{"missing table", "CREATE TRIGGER t BEFORE INSERT ON missing EXECUTE PROCEDURE normalize", "table 'missing' does not exist"},
+		{"missing procedure", "CREATE TRIGGER t BEFORE INSERT ON payments EXECUTE PROCEDURE missing", "procedure 'missing' does not exist"},
+		{"missing module", "CREATE TRIGGER t BEFORE INSERT ON payments EXECUTE PROCEDURE cards.check", "module 'cards' does not exist"},
+		{"handler with arguments", "CREATE TRIGGER t BEFORE INSERT ON payments EXECUTE PROCEDURE with_arg", "must take no arguments"},
+		{"duplicate name", "CREATE TRIGGER loop AFTER DELETE ON payments EXECUTE PROCEDURE audit_old", "trigger 'loop' already exists"},
+		{"drop on other table", "DROP TRIGGER loop ON audit", "trigger 'loop' does not exist"},
+		{"set new after update", "UPDATE payments SET amount = 5", "only BEFORE INSERT or UPDATE triggers can change the new row"},
+		{"recursion", "INSERT INTO payments (id, amount, status) VALUES (2, 20, 'new')", "nested more than 16 levels deep"},
+		{"set new unknown column", "INSERT INTO ledger (id, amount) VALUES (1, 5)", "column 'status' does not exist in table 'ledger'"},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			err := execSQLError(t, adapter, tt.sql)
+			if !strings.Contains(err.Error(), tt.wantErr) {
+				t.Errorf("Expected error containing %q, got: %v", tt.wantErr, err)
+			}
+		})
+	}
+}
+
+func TestEngineAdapter_TriggerPersistence(t *testing.T) {
+	dataDir := t.TempDir()
+	adapter := newTriggerTestAdapter(t, dataDir)
+	execSQL(t, adapter, "CREATE TRIGGER check_payment BEFORE INSERT ON payments EXECUTE PROCEDURE normalize")
+	execSQL(t, adapter, "CREATE TRIGGER decline BEFORE INSERT ON audit EXECUTE PROCEDURE reject")
+	adapter.Close()
+
+	reopened, err := NewEngineAdapter(dataDir, false)
+	if err != nil {
+		t.Fatalf("Failed to reopen adapter: %v", err)
+	}
+	defer reopened.Close()
+	if err := reopened.UseDatabase("shop"); err != nil {
+		t.Fatalf("USE DATABASE failed: %v", err)
+	}
+
+	triggers := reopened.pagedEngine.ListTriggers()
+	if len(triggers) != 2 || triggers[0].Name != "check_payment" || triggers[0].Database != "shop" ||
+		triggers[0].Timing != TriggerBefore || len(triggers[0].Events) != 1 || triggers[0].Events[0] != TriggerInsert {
+		t.Fatalf("Unexpected triggers after reload: %+v", triggers)
+	}
+
+	execSQL(t, reopened, "INSERT INTO payments (id, amount, status) VALUES (1, 10, 'new')")
+	result := execSQL(t, reopened, "SELECT status FROM payments")
+	if !strings.Contains(result, "| checked ") {
+		t.Errorf("Expected the trigger to run after reload, got:\n%s", result)
+	}
+
+	// Dropping a table drops its triggers
+	execSQL(t, reopened, "DROP TABLE audit")
+	triggers = reopened.pagedEngine.ListTriggers()
+	if len(triggers) != 1 || triggers[0].Name != "check_payment" {
+		t.Errorf("Expected only check_payment left, got: %+v", triggers)
+	}
+}
+
+func TestEngineAdapter_TriggerNamesPerTable(t *testing.T) {
+	adapter := newTriggerTestAdapter(t, t.TempDir())
+	execSQL(t, adapter, "CREATE TRIGGER check_payment BEFORE INSERT ON payments EXECUTE PROCEDURE normalize")
+
+	// The same name may be used on another table and in another database
+	execSQL(t, adapter, "CREATE TRIGGER check_payment BEFORE INSERT ON audit EXECUTE PROCEDURE normalize")
+	execSQL(t, adapter, "CREATE DATABASE branch")
+	if err := adapter.UseDatabase("branch"); err != nil {
+		t.Fatalf("USE DATABASE failed: %v", err)
+	}
+	execSQL(t, adapter, "CREATE TABLE payments (id INT, amount INT, status TEXT)")
+	execSQL(t, adapter, "CREATE TRIGGER check_payment BEFORE INSERT ON payments EXECUTE PROCEDURE reject")
+	if n := len(adapter.pagedEngine.ListTriggers()); n != 3 {
+		t.Fatalf("Expected 3 triggers, got %d", n)
+	}
+
+	err := execSQLError(t, adapter, "INSERT INTO payments (id, amount, status) VALUES (1, 10, 'new')")
+	if !strings.Contains(err.Error(), "card declined") {
+		t.Errorf("Expected the branch trigger to run, got: %v", err)
+	}
+
+	// Dropping a database drops its triggers
+	if err := adapter.UseDatabase("shop"); err != nil {
+		t.Fatalf("USE DATABASE failed: %v", err)
+	}
+	execSQL(t, adapter, "DROP DATABASE branch")
+	triggers := adapter.pagedEngine.ListTriggers()
+	if len(triggers) != 2 || triggers[0].Database != "shop" || triggers[1].Database != "shop" {
+		t.Fatalf("Expected only the shop triggers left, got: %+v", triggers)
+	}
+
+	err = execSQLError(t, adapter, "DROP TRIGGER check_payment")
+	if !strings.Contains(err.Error(), "more than one table") {
+		t.Errorf("Expected ambiguous trigger error, got: %v", err)
+	}
+	execSQL(t, adapter, "DROP TRIGGER check_payment ON audit")
+	execSQL(t, adapter, "INSERT INTO payments (id, amount, status) VALUES (1, 10, 'new')")
+	result := execSQL(t, adapter, "SELECT status FROM payments")
+	if !strings.Contains(result, "| checked ") {
+		t.Errorf("Expected the shop trigger to run, got:\n%s", result)
+	}
+}
+
+func TestPagedEngine_TriggerDepthPerChain(t *testing.T) {
+	adapter := newTriggerTestAdapter(t, t.TempDir())
+	engine := adapter.pagedEngine
+	execSQL(t, adapter, "CREATE TRIGGER check_payment BEFORE INSERT ON payments EXECUTE PROCEDURE normalize")
+
+	// Depth counts the handlers a statement is nested in...
+	ctx := engine.executionContext("")
+	ctx.TriggerDepth = maxTriggerDepth - 1
+	if err := engine.insertRowIn(ctx, "payments", Row{"id": 1, "amount": 10, "status": "new"}); err != nil {
+		t.Fatalf("Insert one level below the limit failed: %v", err)
+	}
+	ctx.TriggerDepth = maxTriggerDepth
+	err := engine.insertRowIn(ctx, "payments", Row{"id": 2, "amount": 20, "status": "new"})
+	if err == nil || !strings.Contains(err.Error(), "nested more than 16 levels deep") {
+		t.Errorf("Expected the depth limit error, got: %v", err)
+	}
+
+	// ...not the handlers running for other statements
+	var wg sync.WaitGroup
+	errs := make(chan error, 2*maxTriggerDepth)
+	for i := 0; i < 2*maxTriggerDepth; i++ {
+		wg.Add(1)
+		go func(id int) {
+			defer wg.Done()
+			errs <- engine.InsertRow("payments", Row{"id": 100 + id, "amount": 1, "status": "new"})
+		}(i)
+	}
+	wg.Wait()
+	close(errs)
+	for err := range errs {
+		if err != nil {
+			t.Errorf("Concurrent insert failed: %v", err)
+		}
+	}
+}
+
+func TestPagedEngine_TriggerSkipsChangedRows(t *testing.T) {
+	adapter := newTriggerTestAdapter(t, t.TempDir())
+	engine := adapter.pagedEngine
+	execSQL(t, adapter, "INSERT INTO audit (id, amount, status) VALUES (1, 10, 'new')")
+	execSQL(t, adapter, "INSERT INTO audit (id, amount, status) VALUES (2, 20, 'new')")
+
+	db, err := engine.getCurrentDatabase()
+	if err != nil {
+		t.Fatalf("getCurrentDatabase failed: %v", err)
+	}
+	table := db.Tables["audit"]
+	txn, _ := engine.txnManager.BeginTransaction()
+	rows := engine.matchRows(table, NewQueryPlanner().PlanUpdate(table, nil), nil, txn.Snapshot)
+	engine.txnManager.CommitTransaction(txn.ID)
+	if len(rows) != 2 {
+		t.Fatalf("Expected 2 matched rows, got %d", len(rows))
+	}
+
+	// Another statement changes row 1 after it was matched
+	execSQL(t, adapter, "UPDATE audit SET status = 'paid' WHERE id = 1")
+
+	for _, row := range rows {
+		_, err := engine.readUnchangedTuple(table, row)
+		changed := CompareValues(row.old["id"], 1) == 0
+		if changed && err == nil {
+			t.Error("Expected the changed row to be skipped")
+		}
+		if !changed && err != nil {
+			t.Errorf("Expected the unchanged row to be read, got: %v", err)
+		}
+	}
+}
@@ -0,0 +1,227 @@
+package mindb
+
+import (
+	"encoding/json"
+	"fmt"
+	"os"
+	"path/filepath"
+	"regexp"
+	"sort"
+	"strconv"
+	"strings"
+	"time"
+)
+
+// ReplaceProcedure creates a stored procedure, or replaces an existing one
+// with a new version. The previous version keeps serving calls until the new
+// one has compiled, so there is no point at which the procedure is missing.
+func (e *PagedEngine) ReplaceProcedure(proc *StoredProcedure) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+
+	proc.CreatedAt = time.Now()
+	if current, exists := e.procedures[proc.Name]; exists {
+		proc.CreatedAt = current.CreatedAt
+	}
+	return e.storeProcedure(proc)
+}
+
+// ReplaceProcedureCode creates a stored procedure, or replaces its bytecode
+// with a new version that keeps the settings given when it was created:
+// SECURITY, owner and NULL handling. Parameters, return type and
+// description come from the new module. Since the new code runs with the
+// owner's privileges if it is SECURITY DEFINER, only the owner, or root,
+// can replace it.
+func (e *PagedEngine) ReplaceProcedureCode(proc *StoredProcedure) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+
+	proc.CreatedAt = time.Now()
+	if current, exists := e.procedures[proc.Name]; exists {
+		if !e.ownsProcedure(current) {
+			return fmt.Errorf("access denied for user '%s' (not the owner of procedure %s)", e.currentUser, proc.Name)
+		}
+		proc.CreatedAt = current.CreatedAt
+		proc.Security = current.Security
+		proc.Owner = current.Owner
+		proc.OnNull = current.OnNull
+	}
+	return e.storeProcedure(proc)
+}
+
+// RollbackProcedure makes an earlier revision of a stored procedure the
+// current version. The revisions themselves are kept, so a later rollback
+// can return to any of them.
+func (e *PagedEngine) RollbackProcedure(name string, version int) error {
+	e.mu.Lock()
+	defer e.mu.Unlock()
+
+	current, exists := e.procedures[name]
+	if !exists {
+		return fmt.Errorf("procedure '%s' does not exist", name)
+	}
+
+	proc, err := loadProcedureVersion(e.dataDir, name, version)
+	if err != nil {
+		return err
+	}
+
+	module, err := e.wasmEngine.compile(proc.Code)
+	if err != nil {
+		return fmt.Errorf("failed to compile procedure: %w", err)
+	}
+
+	proc.CreatedAt = current.CreatedAt
+	proc.UpdatedAt = time.Now()
+	if err := e.logProcedureChange(&procedureChange{Name: name, Before: current, After: proc}); err != nil {
+		return err
+	}
+
+	e.wasmEngine.install(name, proc.Code, module)
+	e.procedures[name] = proc
+	return nil
+}
+
+// ProcedureVersions returns every revision of a stored procedure, oldest
+// first. Each revision's UpdatedAt is when it was created.
+func (e *PagedEngine) ProcedureVersions(name string) ([]*StoredProcedure, error) {
+	e.mu.RLock()
+	defer e.mu.RUnlock()
+
+	if _, exists := e.procedures[name]; !exists {
+		return nil, fmt.Errorf("procedure '%s' does not exist", name)
+	}
+
+	return procedureVersions(e.dataDir, name)
+}
+
+// nextProcedureVersion returns the number for a procedure's next revision
+func (e *PagedEngine) nextProcedureVersion(name string) (int, error) {
+	numbers, err := procedureVersionNumbers(e.dataDir, name)
+	if err != nil {
+		return 0, err
+	}
+	if len(numbers) == 0 {
+		return 1, nil
+	}
+	return numbers[len(numbers)-1] + 1, nil
+}
+
+// procedureNameRe matches the names a procedure can be stored under
+var procedureNameRe = regexp.MustCompile(`^[A-Za-z_]\w*$`)
+
+// ValidProcedureName reports whether name can be used for a stored
+// procedure: an identifier, since it also names the procedure's files
+func ValidProcedureName(name string) bool {
+	return procedureNameRe.MatchString(name)
+}
+
+// procedurePath returns the path of a procedure's file or directory in the
+// procedures directory: its current version with suffix ".json", the
+// directory of its revisions with none. Names that are not identifiers, or
+// would resolve outside the procedures directory, are refused.
+func procedurePath(dataDir, name, suffix string) (string, error) {
+	procDir := filepath.Join(dataDir, "procedures")
+	path := filepath.Join(procDir, name+suffix)
+	if !ValidProcedureName(name) || filepath.Dir(path) != procDir {
+		return "", fmt.Errorf("%w '%s'", ErrInvalidProcedureName, name)
+	}
+	return path, nil
+}
+
+// procedureVersionDir returns the directory holding a procedure's revisions
+func procedureVersionDir(dataDir string, name string) (string, error) {
+	return procedurePath(dataDir, name, "")
+}
+
+// writeProcedureVersion persists a procedure as revision proc.Version
+func writeProcedureVersion(dataDir string, proc *StoredProcedure) error {
+	versionDir, err := procedureVersionDir(dataDir, proc.Name)
+	if err != nil {
+		return err
+	}
+	if err := os.MkdirAll(versionDir, 0755); err != nil {
+		return fmt.Errorf("failed to create procedure versions directory: %w", err)
+	}
+
+	data, err := json.Marshal(proc)
+	if err != nil {
+		return fmt.Errorf("failed to marshal procedure: %w", err)
+	}
+
+	filename := filepath.Join(versionDir, strconv.Itoa(proc.Version)+".json")
+	if err := replaceFile(filename, data); err != nil {
+		return fmt.Errorf("failed to write procedure version file: %w", err)
+	}
+
+	return nil
+}
+
+// loadProcedureVersion reads a procedure's revision from disk
+func loadProcedureVersion(dataDir string, name string, version int) (*StoredProcedure, error) {
+	versionDir, err := procedureVersionDir(dataDir, name)
+	if err != nil {
+		return nil, err
+	}
+	data, err := os.ReadFile(filepath.Join(versionDir, strconv.Itoa(version)+".json"))
+	if os.IsNotExist(err) {
+		return nil, fmt.Errorf("procedure '%s' has no version %d", name, version)
+	}
+	if err != nil {
+		return nil, fmt.Errorf("failed to read procedure version file: %w", err)
+	}
+
+	proc, _, err := decodeProcedure(dataDir, data)
+	if err != nil {
+		return nil, fmt.Errorf("failed to load procedure version %d: %w", version, err)
+	}
+
+	return proc, nil
+}
+
+// procedureVersions reads all of a procedure's revisions, oldest first
+func procedureVersions(dataDir string, name string) ([]*StoredProcedure, error) {
+	numbers, err := procedureVersionNumbers(dataDir, name)
+	if err != nil {
+		return nil, err
+	}
+
+	var versions []*StoredProcedure
+	for _, version := range numbers {
+		proc, err := loadProcedureVersion(dataDir, name, version)
+		if err != nil {
+			return nil, err
+		}
+		versions = append(versions, proc)
+	}
+
+	return versions, nil
+}
+
+// procedureVersionNumbers lists the numbers of a procedure's revisions on
+// disk, in ascending order
+func procedureVersionNumbers(dataDir string, name string) ([]int, error) {
+	versionDir, err := procedureVersionDir(dataDir, name)
+	if err != nil {
+		return nil, err
+	}
+	files, err := os.ReadDir(versionDir)
+	if os.IsNotExist(err) {
+		return nil, nil
+	}
+	if err != nil {
+		return nil, fmt.Errorf("failed to read procedure versions directory: %w", err)
+	}
+
+	var numbers []int
+	for _, file := range files {
+		version, err := strconv.Atoi(strings.TrimSuffix(file.Name(), ".json"))
+		if file.IsDir() || !strings.HasSuffix(file.Name(), ".json") || err != nil {
+			continue
+		}
+		numbers = append(numbers, version)
+	}
+	sort.Ints(numbers)
+
+	return numbers, nil
+}
@@ -0,0 +1,276 @@
+package mindb
+
+import (
+	"encoding/base64"
+	"errors"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+// Two builds of a procedure, for versioning:
+//
+//	discount(amount i32) -> i32 { amount - 10 }  // discountV1WASM
+//	discount(amount i32) -> i32 { amount - 20 }  // discountV2WASM
+var discountV1WASM = []byte{
+	0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60,
+	0x01, 0x7f, 0x01, 0x7f, 0x03, 0x02, 0x01, 0x00, 0x07, 0x0c, 0x01, 0x08,
+	0x64, 0x69, 0x73, 0x63, 0x6f, 0x75, 0x6e, 0x74, 0x00, 0x00, 0x0a, 0x09,
+	0x01, 0x07, 0x00, 0x20, 0x00, 0x41, 0x0a, 0x6b, 0x0b,
+}
+
+var discountV2WASM = []byte{
+	0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60,
+	0x01, 0x7f, 0x01, 0x7f, 0x03, 0x02, 0x01, 0x00, 0x07, 0x0c, 0x01, 0x08,
+	0x64, 0x69, 0x73, 0x63, 0x6f, 0x75, 0x6e, 0x74, 0x00, 0x00, 0x0a, 0x09,
+	0x01, 0x07, 0x00, 0x20, 0x00, 0x41, 0x14, 0x6b, 0x0b,
+}
+
+// callDiscount calls the discount procedure with 100
+func callDiscount(t *testing.T, engine *PagedEngine) int32 {
+	t.Helper()
+
+	result, err := engine.CallProcedure("discount", 100)
+	if err != nil {
+		t.Fatalf("Failed to call discount: %v", err)
+	}
+	return result.(int32)
+}
+
+func TestPagedEngine_ReplaceProcedure(t *testing.T) {
+	dataDir := t.TempDir()
+
+	engine, err := NewPagedEngine(dataDir)
+	if err != nil {
+		t.Fatalf("Failed to create engine: %v", err)
+	}
+
+	params := []Column{{Name: "amount", DataType: "INT"}}
+	v1 := &StoredProcedure{Name: "discount", Language: "wasm", Code: discountV1WASM, Params: params, ReturnType: "INT"}
+	if err := engine.CreateProcedure(v1); err != nil {
+		t.Fatalf("Failed to create procedure: %v", err)
+	}
+	if v1.Version != 1 || callDiscount(t, engine) != 90 {
+		t.Fatalf("Expected version 1 to be current, got version %d", v1.Version)
+	}
+
+	v2 := &StoredProcedure{Name: "discount", Language: "wasm", Code: discountV2WASM, Params: params, ReturnType: "INT"}
+	if err := engine.ReplaceProcedure(v2); err != nil {
+		t.Fatalf("Failed to replace procedure: %v", err)
+	}
+	if v2.Version != 2 || !v2.CreatedAt.Equal(v1.CreatedAt) || callDiscount(t, engine) != 80 {
+		t.Errorf("Expected version 2 to be current, got %+v", v2)
+	}
+
+	// A build that does not compile leaves the current version in place
+	bad := &StoredProcedure{Name: "discount", Language: "wasm", Code: []byte("not wasm")}
+	if err := engine.ReplaceProcedure(bad); err == nil {
+		t.Error("Expected error replacing with invalid WASM")
+	}
+	if callDiscount(t, engine) != 80 {
+		t.Error("Expected version 2 to survive a failed replace")
+	}
+
+	if err := engine.RollbackProcedure("discount", 1); err != nil {
+		t.Fatalf("Failed to roll back: %v", err)
+	}
+	if callDiscount(t, engine) != 90 {
+		t.Error("Expected version 1 after rollback")
+	}
+	if err := engine.RollbackProcedure("discount", 5); err == nil || !strings.Contains(err.Error(), "has no version 5") {
+		t.Errorf("Expected missing version error, got: %v", err)
+	}
+	if err := engine.RollbackProcedure("missing", 1); err == nil {
+		t.Error("Expected error rolling back unknown procedure")
+	}
+
+	// Replacing after a rollback adds a new version rather than reusing one
+	v3 := &StoredProcedure{Name: "discount", Language: "wasm", Code: discountV2WASM, Params: params, ReturnType: "INT"}
+	if err := engine.ReplaceProcedure(v3); err != nil {
+		t.Fatalf("Failed to replace procedure: %v", err)
+	}
+	if v3.Version != 3 {
+		t.Errorf("Expected version 3, got %d", v3.Version)
+	}
+	if err := engine.RollbackProcedure("discount", 1); err != nil {
+		t.Fatalf("Failed to roll back: %v", err)
+	}
+	engine.Close()
+
+	// The current version and every revision survive a restart
+	engine, err = NewPagedEngine(dataDir)
+	if err != nil {
+		t.Fatalf("Failed to reopen engine: %v", err)
+	}
+	defer engine.Close()
+
+	proc, err := engine.GetProcedure("discount")
+	if err != nil {
+		t.Fatalf("Failed to get procedure: %v", err)
+	}
+	if proc.Version != 1 || callDiscount(t, engine) != 90 {
+		t.Errorf("Expected version 1 after reload, got version %d", proc.Version)
+	}
+
+	versions, err := engine.ProcedureVersions("discount")
+	if err != nil {
+		t.Fatalf("Failed to list versions: %v", err)
+	}
+	if len(versions) != 3 || versions[0].Version != 1 || versions[2].Version != 3 {
+		t.Fatalf("Unexpected versions: %+v", versions)
+	}
+	if versions[2].UpdatedAt.Before(versions[0].UpdatedAt) {
+		t.Error("Expected revisions to record when they were created")
+	}
+
+	// Dropping a procedure removes its revisions
+	if err := engine.DropProcedure("discount"); err != nil {
+		t.Fatalf("Failed to drop procedure: %v", err)
+	}
+	if _, err := os.Stat(filepath.Join(dataDir, "procedures", "discount")); !os.IsNotExist(err) {
+		t.Errorf("Expected versions directory to be removed, got: %v", err)
+	}
+}
+
+func TestPagedEngine_ReplaceProcedureCode(t *testing.T) {
+	engine, err := NewPagedEngine(t.TempDir())
+	if err != nil {
+		t.Fatalf("Failed to create engine: %v", err)
+	}
+	defer engine.Close()
+
+	params := []Column{{Name: "amount", DataType: "INT"}}
+	engine.currentUser = "owner@%"
+	if err := engine.CreateProcedure(&StoredProcedure{Name: "discount", Language: "wasm", Code: discountV1WASM, Params: params, ReturnType: "INT", OnNull: OnNullCalled, Security: SecurityDefiner}); err != nil {
+		t.Fatalf("Failed to create procedure: %v", err)
+	}
+
+	// Only the owner can replace the code, which would otherwise run with
+	// the owner's privileges
+	engine.currentUser = "deployer@%"
+	if err := engine.ReplaceProcedureCode(&StoredProcedure{Name: "discount", Language: "wasm", Code: discountV2WASM, Params: params, ReturnType: "INT"}); err == nil || !strings.Contains(err.Error(), "not the owner of procedure discount") {
+		t.Fatalf("Expected a replace by another user to be refused, got %v", err)
+	}
+
+	// A new build keeps the settings of the version it replaces
+	engine.currentUser = "owner@%"
+	if err := engine.ReplaceProcedureCode(&StoredProcedure{Name: "discount", Language: "wasm", Code: discountV2WASM, Params: params, ReturnType: "INT"}); err != nil {
+		t.Fatalf("Failed to replace procedure code: %v", err)
+	}
+
+	proc, err := engine.GetProcedure("discount")
+	if err != nil {
+		t.Fatalf("Failed to get procedure: %v", err)
+	}
+	if proc.Version != 2 || proc.Security != SecurityDefiner || proc.Owner != "owner@%" || proc.OnNull != OnNullCalled {
+		t.Errorf("Expected version 2 with the settings of version 1, got %+v", proc)
+	}
+}
+
+func TestEngineAdapter_ReplaceProcedureCodePrivileges(t *testing.T) {
+	adapter, err := NewEngineAdapter(t.TempDir(), false)
+	if err != nil {
+		t.Fatalf("Failed to create adapter: %v", err)
+	}
+	defer adapter.Close()
+
+	execSQL(t, adapter, "CREATE DATABASE shop")
+	if err := adapter.UseDatabase("shop"); err != nil {
+		t.Fatalf("USE DATABASE failed: %v", err)
+	}
+	params := []Column{{Name: "amount", DataType: "INT"}}
+	if err := adapter.pagedEngine.CreateProcedure(&StoredProcedure{Name: "discount", Language: "wasm", Code: discountV1WASM, Params: params, ReturnType: "INT", Security: SecurityDefiner}); err != nil {
+		t.Fatalf("Failed to create procedure: %v", err)
+	}
+	um := adapter.pagedEngine.userManager
+	um.CreateUser("clerk", "secret", "%")
+
+	// Without CREATE, and then without owning root's procedure, an upload is
+	// refused
+	adapter.SetCurrentUser("clerk", "%")
+	upload := &StoredProcedure{Name: "discount", Language: "wasm", Code: discountV2WASM, Params: params, ReturnType: "INT"}
+	if err := adapter.ReplaceProcedureCodeViaAdapter(upload); err == nil || !strings.Contains(err.Error(), "missing CREATE privilege") {
+		t.Errorf("Expected the upload to need CREATE, got %v", err)
+	}
+	um.GrantPrivileges("clerk", "%", "shop", "*", []Privilege{PrivilegeCreate})
+	if err := adapter.ReplaceProcedureCodeViaAdapter(upload); err == nil || !strings.Contains(err.Error(), "not the owner") {
+		t.Errorf("Expected the upload to need ownership, got %v", err)
+	}
+	if proc, _ := adapter.pagedEngine.GetProcedure("discount"); proc.Version != 1 {
+		t.Errorf("Expected version 1 to remain, got version %d", proc.Version)
+	}
+
+	// A new procedure is the uploader's own
+	if err := adapter.ReplaceProcedureCodeViaAdapter(&StoredProcedure{Name: "clerk_discount", Language: "wasm", Code: discountV2WASM, Params: params, ReturnType: "INT"}); err != nil {
+		t.Fatalf("Failed to upload a new procedure: %v", err)
+	}
+	if proc, _ := adapter.pagedEngine.GetProcedure("clerk_discount"); proc.Owner != "clerk@%" {
+		t.Errorf("Expected clerk to own the new procedure, got %q", proc.Owner)
+	}
+}
+
+func TestPagedEngine_InvalidProcedureName(t *testing.T) {
+	dataDir := t.TempDir()
+	engine, err := NewPagedEngine(dataDir)
+	if err != nil {
+		t.Fatalf("Failed to create engine: %v", err)
+	}
+	defer engine.Close()
+
+	// Names that would put files, or a committed drop's RemoveAll, outside
+	// the procedure's own directory
+	for _, name := range []string{"..", ".", "../discount", "a/b", "1discount", ""} {
+		proc := &StoredProcedure{Name: name, Language: "wasm", Code: discountV1WASM, ReturnType: "INT"}
+		if err := engine.CreateProcedure(proc); !errors.Is(err, ErrInvalidProcedureName) {
+			t.Errorf("Expected CreateProcedure(%q) to be refused, got %v", name, err)
+		}
+		if err := engine.ReplaceProcedureCode(proc); !errors.Is(err, ErrInvalidProcedureName) {
+			t.Errorf("Expected ReplaceProcedureCode(%q) to be refused, got %v", name, err)
+		}
+		if _, err := procedureVersionDir(dataDir, name); !errors.Is(err, ErrInvalidProcedureName) {
+			t.Errorf("Expected no versions directory for %q, got %v", name, err)
+		}
+	}
+
+	entries, err := os.ReadDir(filepath.Join(dataDir, "procedures"))
+	if err != nil && !os.IsNotExist(err) {
+		t.Fatalf("Failed to read procedures directory: %v", err)
+	}
+	if len(entries) != 0 {
+		t.Errorf("Expected nothing stored, got %d entries", len(entries))
+	}
+}
+
+func TestEngineAdapter_CreateOrReplaceProcedure(t *testing.T) {
+	adapter, err := NewEngineAdapter(t.TempDir(), false)
+	if err != nil {
+		t.Fatalf("Failed to create adapter: %v", err)
+	}
+	defer adapter.Close()
+
+	v1 := base64.StdEncoding.EncodeToString(discountV1WASM)
+	v2 := base64.StdEncoding.EncodeToString(discountV2WASM)
+
+	execSQL(t, adapter, "CREATE PROCEDURE discount(amount INT) RETURNS INT LANGUAGE wasm AS '"+v1+"'")
+	err = execSQLError(t, adapter, "CREATE PROCEDURE discount(amount INT) RETURNS INT LANGUAGE wasm AS '"+v2+"'")
+	if !strings.Contains(err.Error(), "already exists") {
+		t.Errorf("Expected already exists error, got: %v", err)
+	}
+
+	result := execSQL(t, adapter, "CREATE OR REPLACE PROCEDURE discount(amount INT) RETURNS INT LANGUAGE wasm AS '"+v2+"'")
+	if !strings.Contains(result, "version 2") {
+		t.Errorf("Expected version 2, got: %s", result)
+	}
+	if result := execSQL(t, adapter, "CALL discount(100)"); !strings.Contains(result, "| 80 ") {
+		t.Errorf("Expected the new version, got:\n%s", result)
+	}
+
+	execSQL(t, adapter, "ALTER PROCEDURE discount ROLLBACK TO VERSION 1")
+	if result := execSQL(t, adapter, "CALL discount(100)"); !strings.Contains(result, "| 90 ") {
+		t.Errorf("Expected the rolled back version, got:\n%s", result)
+	}
+
+	execSQL(t, adapter, "DROP PROCEDURE discount")
+	execSQL(t, adapter, "DROP PROCEDURE IF EXISTS discount")
+}
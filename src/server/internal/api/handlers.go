@@ -3,11 +3,13 @@ package api
 import (
 	"context"
 	"encoding/json"
+	"errors"
 	"net/http"
 	"time"
 
 	"github.com/go-chi/chi/v5"
 	"github.com/rs/zerolog"
+	"github.com/sausheong/mindb/src/core"
 	"github.com/sausheong/mindb/src/server/internal/db"
 	"github.com/sausheong/mindb/src/server/internal/semaphore"
 	"github.com/sausheong/mindb/src/server/internal/txmanager"
@@ -80,6 +82,9 @@ func (h *Handlers) QueryHandler() http.HandlerFunc {
 				writeError(w, http.StatusRequestTimeout, ErrCodeTimeout, "query timeout")
 				return
 			}
+			if writeProcedureError(w, err) {
+				return
+			}
 			writeError(w, http.StatusBadRequest, ErrCodeInvalidSQL, err.Error())
 			return
 		}
@@ -147,6 +152,9 @@ func (h *Handlers) ExecuteHandler() http.HandlerFunc {
 				writeError(w, http.StatusRequestTimeout, ErrCodeTimeout, "execution timeout")
 				return
 			}
+			if writeProcedureError(w, err) {
+				return
+			}
 			writeError(w, http.StatusBadRequest, ErrCodeInvalidSQL, err.Error())
 			return
 		}
@@ -258,6 +266,9 @@ func (h *Handlers) TxExecHandler() http.HandlerFunc {
 				writeError(w, http.StatusRequestTimeout, ErrCodeTimeout, "execution timeout")
 				return
 			}
+			if writeProcedureError(w, err) {
+				return
+			}
 			writeError(w, http.StatusBadRequest, ErrCodeInvalidSQL, err.Error())
 			return
 		}
@@ -388,6 +399,33 @@ func writeError(w http.ResponseWriter, status int, code, message string) {
 	writeJSON(w, status, resp)
 }
 
+// writeProcedureError writes err if a procedure call failed with it, with
+// its SQLSTATE code, and reports whether it did. Errors the procedure raised
+// are the client's to handle (422); traps and exhausted fuel are not (500).
+func writeProcedureError(w http.ResponseWriter, err error) bool {
+	var procErr *mindb.ProcedureError
+	if !errors.As(err, &procErr) {
+		return false
+	}
+
+	status, code := http.StatusInternalServerError, ErrCodeProcedureTrap
+	switch {
+	case procErr.Raised:
+		status, code = http.StatusUnprocessableEntity, ErrCodeProcedure
+	case procErr.Code == mindb.SQLStateQueryCanceled:
+		status, code = http.StatusRequestTimeout, ErrCodeTimeout
+	}
+	writeJSON(w, status, ErrorResponse{
+		Error: ErrorDetail{
+			Code:     code,
+			Message:  procErr.Message,
+			SQLState: procErr.Code,
+			Detail:   procErr.Detail,
+		},
+	})
+	return true
+}
+
 func getClientID(r *http.Request) string {
 	// Use remote address as client ID
 	// In production, you might use a session ID or authenticated user ID
@@ -466,6 +504,10 @@ func (h *Handlers) CreateProcedureHandler() http.HandlerFunc {
 			writeError(w, http.StatusBadRequest, ErrCodeBadRequest, "procedure name is required")
 			return
 		}
+		if !mindb.ValidProcedureName(req.Name) {
+			writeError(w, http.StatusBadRequest, ErrCodeBadRequest, "procedure name must be an identifier")
+			return
+		}
 		if req.WasmBase64 == "" {
 			writeError(w, http.StatusBadRequest, ErrCodeBadRequest, "wasm_base64 is required")
 			return
@@ -596,11 +638,17 @@ func (h *Handlers) ListProceduresHandler() http.HandlerFunc {
 					}
 				}
 				
+				onNull, _ := procMap["on_null"].(string)
+				security, _ := procMap["security"].(string)
+				owner, _ := procMap["owner"].(string)
 				procInfos[i] = ProcedureInfo{
 					Name:        procMap["name"].(string),
 					Language:    procMap["language"].(string),
 					Params:      params,
 					ReturnType:  procMap["return_type"].(string),
+					OnNull:      onNull,
+					Security:    security,
+					Owner:       owner,
 					Description: procMap["description"].(string),
 					CreatedAt:   procMap["created_at"].(string),
 					UpdatedAt:   procMap["updated_at"].(string),
@@ -635,9 +683,12 @@ func (h *Handlers) CallProcedureHandler() http.HandlerFunc {
 			return
 		}
 
-		// Parse request
+		// Parse request, keeping numbers exact so BIGINT arguments beyond
+		// 2^53 are not rounded through float64
 		var req CallProcedureRequest
-		if err := json.NewDecoder(r.Body).Decode(&req); err != nil {
+		decoder := json.NewDecoder(r.Body)
+		decoder.UseNumber()
+		if err := decoder.Decode(&req); err != nil {
 			writeError(w, http.StatusBadRequest, ErrCodeBadRequest, "invalid JSON: "+err.Error())
 			return
 		}
@@ -653,6 +704,9 @@ func (h *Handlers) CallProcedureHandler() http.HandlerFunc {
 		result, err := h.db.CallProcedure(ctx, dbName, procName, req.Args...)
 		if err != nil {
 			h.logger.Error().Err(err).Str("procedure", procName).Msg("call_procedure_failed")
+			if writeProcedureError(w, err) {
+				return
+			}
 			writeError(w, http.StatusInternalServerError, ErrCodeInternal, "failed to call procedure: "+err.Error())
 			return
 		}
@@ -3,7 +3,11 @@ package api
 import (
 	"bytes"
 	"context"
+	"crypto/sha256"
+	"encoding/base64"
+	"encoding/hex"
 	"encoding/json"
+	"errors"
 	"fmt"
 	"net/http"
 	"net/http/httptest"
@@ -13,6 +17,7 @@ import (
 
 	"github.com/go-chi/chi/v5"
 	"github.com/rs/zerolog"
+	"github.com/sausheong/mindb/src/core"
 	"github.com/sausheong/mindb/src/server/internal/db"
 	"github.com/sausheong/mindb/src/server/internal/semaphore"
 	"github.com/sausheong/mindb/src/server/internal/txmanager"
@@ -1117,6 +1122,28 @@ func TestCreateProcedureHandler_EmptyName(t *testing.T) {
 	}
 }
 
+func TestCreateProcedureHandler_InvalidName(t *testing.T) {
+	h := newTestHandlers(t)
+	handler := h.CreateProcedureHandler()
+	
+	procReq := CreateProcedureRequest{
+		Name:       "..",
+		Language:   "wasm",
+		WasmBase64: "AGFzbQEAAAA=",
+	}
+	body, _ := json.Marshal(procReq)
+	
+	req := httptest.NewRequest("POST", "/procedures", bytes.NewReader(body))
+	req.Header.Set("Content-Type", "application/json")
+	w := httptest.NewRecorder()
+	
+	handler(w, req)
+	
+	if w.Code != http.StatusBadRequest {
+		t.Errorf("Expected status 400, got %d", w.Code)
+	}
+}
+
 func TestDropProcedureHandler_Success(t *testing.T) {
 	h := newTestHandlers(t)
 	
@@ -2121,3 +2148,170 @@ func TestTxRollbackHandler_WithDatabase(t *testing.T) {
 		t.Errorf("Expected status 200, got %d", w.Code)
 	}
 }
+
+// WASM module exporting calculate_tax(amount FLOAT, state_code INT) FLOAT,
+// with its signature recorded in a mindb.procedures custom section
+var calculateTaxWASM = []byte{
+	0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x07, 0x01, 0x60,
+	0x02, 0x7c, 0x7f, 0x01, 0x7c, 0x03, 0x02, 0x01, 0x00, 0x07, 0x11, 0x01,
+	0x0d, 0x63, 0x61, 0x6c, 0x63, 0x75, 0x6c, 0x61, 0x74, 0x65, 0x5f, 0x74,
+	0x61, 0x78, 0x00, 0x00, 0x0a, 0x06, 0x01, 0x04, 0x00, 0x20, 0x00, 0x0b,
+	0x00, 0xb2, 0x01, 0x10, 0x6d, 0x69, 0x6e, 0x64, 0x62, 0x2e, 0x70, 0x72,
+	0x6f, 0x63, 0x65, 0x64, 0x75, 0x72, 0x65, 0x73, 0x7b, 0x22, 0x6e, 0x61,
+	0x6d, 0x65, 0x22, 0x3a, 0x22, 0x63, 0x61, 0x6c, 0x63, 0x75, 0x6c, 0x61,
+	0x74, 0x65, 0x5f, 0x74, 0x61, 0x78, 0x22, 0x2c, 0x22, 0x70, 0x61, 0x72,
+	0x61, 0x6d, 0x73, 0x22, 0x3a, 0x5b, 0x7b, 0x22, 0x6e, 0x61, 0x6d, 0x65,
+	0x22, 0x3a, 0x22, 0x61, 0x6d, 0x6f, 0x75, 0x6e, 0x74, 0x22, 0x2c, 0x22,
+	0x74, 0x79, 0x70, 0x65, 0x22, 0x3a, 0x22, 0x46, 0x4c, 0x4f, 0x41, 0x54,
+	0x22, 0x7d, 0x2c, 0x7b, 0x22, 0x6e, 0x61, 0x6d, 0x65, 0x22, 0x3a, 0x22,
+	0x73, 0x74, 0x61, 0x74, 0x65, 0x5f, 0x63, 0x6f, 0x64, 0x65, 0x22, 0x2c,
+	0x22, 0x74, 0x79, 0x70, 0x65, 0x22, 0x3a, 0x22, 0x49, 0x4e, 0x54, 0x22,
+	0x7d, 0x5d, 0x2c, 0x22, 0x72, 0x65, 0x74, 0x75, 0x72, 0x6e, 0x73, 0x22,
+	0x3a, 0x22, 0x46, 0x4c, 0x4f, 0x41, 0x54, 0x22, 0x2c, 0x22, 0x64, 0x65,
+	0x73, 0x63, 0x72, 0x69, 0x70, 0x74, 0x69, 0x6f, 0x6e, 0x22, 0x3a, 0x22,
+	0x53, 0x61, 0x6c, 0x65, 0x73, 0x20, 0x74, 0x61, 0x78, 0x20, 0x66, 0x6f,
+	0x72, 0x20, 0x61, 0x20, 0x73, 0x74, 0x61, 0x74, 0x65, 0x2e, 0x22, 0x7d,
+	0x0a,
+}
+
+// newUploadRequest builds a PUT /procedures/:name/module request
+func newUploadRequest(name string, body []byte) *http.Request {
+	req := httptest.NewRequest("PUT", "/procedures/"+name+"/module", bytes.NewReader(body))
+	req.Header.Set("Content-Type", "application/wasm")
+	rctx := chi.NewRouteContext()
+	rctx.URLParams.Add("name", name)
+	return req.WithContext(context.WithValue(req.Context(), chi.RouteCtxKey, rctx))
+}
+
+func TestUploadProcedureModuleHandler_Success(t *testing.T) {
+	h := newTestHandlers(t)
+	handler := h.UploadProcedureModuleHandler(1 << 20)
+
+	sum := sha256.Sum256(calculateTaxWASM)
+	req := newUploadRequest("calculate_tax", calculateTaxWASM)
+	req.Header.Set("Content-Digest", "sha-256=:"+base64.StdEncoding.EncodeToString(sum[:])+":")
+	w := httptest.NewRecorder()
+	handler(w, req)
+
+	if w.Code != http.StatusCreated {
+		t.Fatalf("Expected status 201, got %d: %s", w.Code, w.Body.String())
+	}
+	var resp UploadProcedureResponse
+	json.NewDecoder(w.Body).Decode(&resp)
+	if resp.Version != 1 || resp.Size != int64(len(calculateTaxWASM)) || resp.SHA256 != hex.EncodeToString(sum[:]) {
+		t.Errorf("Unexpected response: %+v", resp)
+	}
+
+	// Metadata comes from the module's signature section
+	procs, err := h.db.ListProcedures(context.Background(), "")
+	if err != nil || len(procs) != 1 {
+		t.Fatalf("Expected 1 procedure, got %d (%v)", len(procs), err)
+	}
+	proc := procs[0].(map[string]interface{})
+	if proc["return_type"] != "FLOAT" || proc["description"] != "Sales tax for a state." || len(proc["params"].([]interface{})) != 2 {
+		t.Errorf("Expected metadata from the signature section, got %v", proc)
+	}
+
+	// Uploading again adds a version
+	req = newUploadRequest("calculate_tax", calculateTaxWASM)
+	req.Header.Set("X-Checksum-Sha256", hex.EncodeToString(sum[:]))
+	w = httptest.NewRecorder()
+	handler(w, req)
+
+	if w.Code != http.StatusOK {
+		t.Fatalf("Expected status 200, got %d: %s", w.Code, w.Body.String())
+	}
+	json.NewDecoder(w.Body).Decode(&resp)
+	if resp.Version != 2 {
+		t.Errorf("Expected version 2, got %d", resp.Version)
+	}
+}
+
+func TestUploadProcedureModuleHandler_Rejects(t *testing.T) {
+	h := newTestHandlers(t)
+	handler := h.UploadProcedureModuleHandler(64)
+
+	tests := []struct {
+		name   string
+		req    *http.Request
+		status int
+	}{
+		{"too large", newUploadRequest("calculate_tax", calculateTaxWASM), http.StatusRequestEntityTooLarge},
+		{"empty", newUploadRequest("calculate_tax", nil), http.StatusBadRequest},
+		{"wrong type", newUploadRequest("calculate_tax", calculateTaxWASM[:8]), http.StatusUnsupportedMediaType},
+		{"bad checksum", newUploadRequest("calculate_tax", calculateTaxWASM[:8]), http.StatusBadRequest},
+		{"not wasm", newUploadRequest("calculate_tax", []byte("not wasm")), http.StatusBadRequest},
+		{"no signature", newUploadRequest("calculate_tax", calculateTaxWASM[:8]), http.StatusUnprocessableEntity},
+		{"traversal name", newUploadRequest("..", calculateTaxWASM[:8]), http.StatusBadRequest},
+	}
+	tests[2].req.Header.Set("Content-Type", "application/json")
+	tests[3].req.Header.Set("X-Checksum-Sha256", hex.EncodeToString(make([]byte, sha256.Size)))
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			w := httptest.NewRecorder()
+			handler(w, tt.req)
+			if w.Code != tt.status {
+				t.Errorf("Expected status %d, got %d: %s", tt.status, w.Code, w.Body.String())
+			}
+		})
+	}
+
+	// A body without Content-Length is cut off at the limit too
+	req := newUploadRequest("calculate_tax", calculateTaxWASM)
+	req.ContentLength = -1
+	w := httptest.NewRecorder()
+	handler(w, req)
+	if w.Code != http.StatusRequestEntityTooLarge {
+		t.Errorf("Expected status 413 for a streamed body, got %d", w.Code)
+	}
+}
+
+func TestWriteProcedureError(t *testing.T) {
+	tests := []struct {
+		name   string
+		err    error
+		status int
+		want   ErrorDetail
+	}{
+		{
+			"raised",
+			fmt.Errorf("query error: %w", &mindb.ProcedureError{Code: "22023", Message: "invalid tier", Detail: "tier 7", Raised: true}),
+			http.StatusUnprocessableEntity,
+			ErrorDetail{Code: ErrCodeProcedure, Message: "invalid tier", SQLState: "22023", Detail: "tier 7"},
+		},
+		{
+			"trap",
+			&mindb.ProcedureError{Code: mindb.SQLStateExternalRoutine, Message: "execution error: wasm trap: unreachable"},
+			http.StatusInternalServerError,
+			ErrorDetail{Code: ErrCodeProcedureTrap, Message: "execution error: wasm trap: unreachable", SQLState: mindb.SQLStateExternalRoutine},
+		},
+		{
+			"timeout",
+			&mindb.ProcedureError{Code: mindb.SQLStateQueryCanceled, Message: "execution timeout after 5s"},
+			http.StatusRequestTimeout,
+			ErrorDetail{Code: ErrCodeTimeout, Message: "execution timeout after 5s", SQLState: mindb.SQLStateQueryCanceled},
+		},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			w := httptest.NewRecorder()
+			if !writeProcedureError(w, tt.err) {
+				t.Fatal("Expected the procedure error to be written")
+			}
+			if w.Code != tt.status {
+				t.Errorf("Expected status %d, got %d", tt.status, w.Code)
+			}
+			var resp ErrorResponse
+			json.NewDecoder(w.Body).Decode(&resp)
+			if resp.Error != tt.want {
+				t.Errorf("Expected %+v, got %+v", tt.want, resp.Error)
+			}
+		})
+	}
+
+	if writeProcedureError(httptest.NewRecorder(), errors.New("table not found")) {
+		t.Error("Expected other errors to be left to the caller")
+	}
+}
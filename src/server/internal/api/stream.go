@@ -59,6 +59,9 @@ func (h *Handlers) StreamHandler() http.HandlerFunc {
 				writeError(w, http.StatusRequestTimeout, ErrCodeTimeout, "query timeout")
 				return
 			}
+			if writeProcedureError(w, err) {
+				return
+			}
 			writeError(w, http.StatusBadRequest, ErrCodeInvalidSQL, err.Error())
 			return
 		}
@@ -51,8 +51,10 @@ type ErrorResponse struct {
 
 // ErrorDetail contains error details
 type ErrorDetail struct {
-	Code    string `json:"code"`
-	Message string `json:"message"`
+	Code     string `json:"code"`
+	Message  string `json:"message"`
+	SQLState string `json:"sqlstate,omitempty"` // SQLSTATE-style code of procedure errors
+	Detail   string `json:"detail,omitempty"`
 }
 
 // Error codes
@@ -65,6 +67,11 @@ const (
 	ErrCodeUnauthorized   = "UNAUTHORIZED"
 	ErrCodeTooManyTx      = "TOO_MANY_TRANSACTIONS"
 	ErrCodeInvalidSQL     = "INVALID_SQL"
+	ErrCodeTooLarge       = "PAYLOAD_TOO_LARGE"
+	ErrCodeUnsupportedType = "UNSUPPORTED_MEDIA_TYPE"
+	ErrCodeInvalidModule  = "INVALID_MODULE" // The uploaded module has no usable procedure
+	ErrCodeProcedure      = "PROCEDURE_ERROR" // Raised by the procedure; see sqlstate
+	ErrCodeProcedureTrap  = "PROCEDURE_TRAP"  // The procedure trapped or ran out of fuel
 )
 
 // Timeout returns the timeout duration or default
@@ -123,6 +130,17 @@ type CreateProcedureResponse struct {
 	LatencyMS int64  `json:"latency_ms"`
 }
 
+// UploadProcedureResponse represents the response for uploading a
+// procedure's module
+type UploadProcedureResponse struct {
+	Name      string `json:"name"`
+	Version   int    `json:"version"`
+	Size      int64  `json:"size"`
+	SHA256    string `json:"sha256"`
+	Message   string `json:"message"`
+	LatencyMS int64  `json:"latency_ms"`
+}
+
 // DropProcedureRequest represents a request to drop a stored procedure
 type DropProcedureRequest struct {
 	Name     string `json:"name"`
@@ -149,6 +167,9 @@ type ProcedureInfo struct {
 	Language    string   `json:"language"`
 	Params      []Param  `json:"params"`
 	ReturnType  string   `json:"return_type"`
+	OnNull      string   `json:"on_null,omitempty"` // "STRICT" or "CALLED"; empty rejects NULL arguments
+	Security    string   `json:"security,omitempty"` // "DEFINER" or "INVOKER"; empty means INVOKER
+	Owner       string   `json:"owner,omitempty"`
 	Description string   `json:"description,omitempty"`
 	CreatedAt   string   `json:"created_at"`
 	UpdatedAt   string   `json:"updated_at"`
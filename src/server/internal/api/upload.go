@@ -0,0 +1,166 @@
+package api
+
+import (
+	"bytes"
+	"context"
+	"crypto/sha256"
+	"encoding/base64"
+	"encoding/hex"
+	"errors"
+	"fmt"
+	"io"
+	"mime"
+	"net/http"
+	"strings"
+	"time"
+
+	"github.com/go-chi/chi/v5"
+	"github.com/sausheong/mindb/src/core"
+	"github.com/sausheong/mindb/src/server/internal/db"
+)
+
+// UploadProcedureModuleHandler handles PUT /procedures/:name/module. The body
+// is the raw module as application/wasm, up to maxBytes long. A SHA-256 sent
+// in Content-Digest or X-Checksum-Sha256 is checked against the body. The
+// procedure is created, or replaced with a new version, using the signature
+// the module records for it.
+func (h *Handlers) UploadProcedureModuleHandler(maxBytes int64) http.HandlerFunc {
+	return func(w http.ResponseWriter, r *http.Request) {
+		start := time.Now()
+
+		// Get procedure name from URL
+		procName := chi.URLParam(r, "name")
+		if procName == "" {
+			writeError(w, http.StatusBadRequest, ErrCodeBadRequest, "procedure name is required")
+			return
+		}
+		if !mindb.ValidProcedureName(procName) {
+			writeError(w, http.StatusBadRequest, ErrCodeBadRequest, "procedure name must be an identifier")
+			return
+		}
+
+		if mediaType, _, _ := mime.ParseMediaType(r.Header.Get("Content-Type")); mediaType != "application/wasm" {
+			writeError(w, http.StatusUnsupportedMediaType, ErrCodeUnsupportedType, "Content-Type must be application/wasm")
+			return
+		}
+		if r.ContentLength > maxBytes {
+			writeError(w, http.StatusRequestEntityTooLarge, ErrCodeTooLarge, fmt.Sprintf("module exceeds the %d byte limit", maxBytes))
+			return
+		}
+
+		// Read the body, hashing it as it arrives
+		var module bytes.Buffer
+		if r.ContentLength > 0 {
+			module.Grow(int(r.ContentLength))
+		}
+		hash := sha256.New()
+		size, err := io.Copy(io.MultiWriter(&module, hash), http.MaxBytesReader(w, r.Body, maxBytes))
+		if err != nil {
+			var tooLarge *http.MaxBytesError
+			if errors.As(err, &tooLarge) {
+				writeError(w, http.StatusRequestEntityTooLarge, ErrCodeTooLarge, fmt.Sprintf("module exceeds the %d byte limit", maxBytes))
+				return
+			}
+			writeError(w, http.StatusBadRequest, ErrCodeBadRequest, "failed to read module: "+err.Error())
+			return
+		}
+		if size == 0 {
+			writeError(w, http.StatusBadRequest, ErrCodeBadRequest, "module body is empty")
+			return
+		}
+
+		sum := hash.Sum(nil)
+		if err := verifyChecksums(r.Header, sum); err != nil {
+			writeError(w, http.StatusBadRequest, ErrCodeBadRequest, err.Error())
+			return
+		}
+
+		// Get database from header
+		dbName := r.Header.Get("X-Mindb-Database")
+
+		// Create execution context with timeout
+		ctx, cancel := context.WithTimeout(r.Context(), h.stmtTimeout)
+		defer cancel()
+
+		// Create or replace the procedure via database adapter
+		version, err := h.db.UploadProcedure(ctx, dbName, procName, module.Bytes())
+		if err != nil {
+			status, code := uploadErrorStatus(err)
+			if status == http.StatusInternalServerError {
+				h.logger.Error().Err(err).Str("procedure", procName).Msg("upload_procedure_failed")
+			}
+			writeError(w, status, code, "failed to upload procedure: "+err.Error())
+			return
+		}
+
+		// Write response
+		status := http.StatusOK
+		if version == 1 {
+			status = http.StatusCreated
+		}
+		resp := UploadProcedureResponse{
+			Name:      procName,
+			Version:   version,
+			Size:      size,
+			SHA256:    hex.EncodeToString(sum),
+			Message:   fmt.Sprintf("Procedure version %d uploaded successfully", version),
+			LatencyMS: time.Since(start).Milliseconds(),
+		}
+		writeJSON(w, status, resp)
+
+		h.logger.Info().
+			Str("procedure", procName).
+			Int("version", version).
+			Int64("size", size).
+			Int64("latency_ms", time.Since(start).Milliseconds()).
+			Msg("procedure_uploaded")
+	}
+}
+
+// uploadErrorStatus maps an UploadProcedure error to a response status and
+// error code. A body that is not WASM, or a name that is not an identifier,
+// is a bad request (400); a module without the procedure's signature, or
+// that does not compile, cannot be stored (422).
+func uploadErrorStatus(err error) (int, string) {
+	switch {
+	case errors.Is(err, db.ErrInvalidModule), errors.Is(err, mindb.ErrInvalidProcedureName):
+		return http.StatusBadRequest, ErrCodeBadRequest
+	case errors.Is(err, db.ErrNoSignature), errors.Is(err, mindb.ErrModuleCompile):
+		return http.StatusUnprocessableEntity, ErrCodeInvalidModule
+	default:
+		return http.StatusInternalServerError, ErrCodeInternal
+	}
+}
+
+// verifyChecksums checks a body's SHA-256 against the Content-Digest
+// (RFC 9530) and X-Checksum-Sha256 headers, whichever were sent. Other
+// Content-Digest algorithms are ignored.
+func verifyChecksums(header http.Header, sum []byte) error {
+	if digest := header.Get("Content-Digest"); digest != "" {
+		for _, field := range strings.Split(digest, ",") {
+			algorithm, value, ok := strings.Cut(strings.TrimSpace(field), "=")
+			if !ok || !strings.EqualFold(algorithm, "sha-256") {
+				continue
+			}
+			expected, err := base64.StdEncoding.DecodeString(strings.Trim(value, ":"))
+			if err != nil || len(expected) != sha256.Size {
+				return fmt.Errorf("invalid sha-256 value in Content-Digest")
+			}
+			if !bytes.Equal(expected, sum) {
+				return fmt.Errorf("module does not match Content-Digest")
+			}
+		}
+	}
+
+	if checksum := header.Get("X-Checksum-Sha256"); checksum != "" {
+		expected, err := hex.DecodeString(checksum)
+		if err != nil || len(expected) != sha256.Size {
+			return fmt.Errorf("invalid X-Checksum-Sha256 value")
+		}
+		if !bytes.Equal(expected, sum) {
+			return fmt.Errorf("module does not match X-Checksum-Sha256")
+		}
+	}
+
+	return nil
+}
@@ -32,6 +32,7 @@ type Config struct {
 	TxIdleTimeout    time.Duration
 	MaxOpenTx        int
 	MaxTxPerClient   int
+	MaxModuleSize    int64 // Largest WASM module accepted by PUT /procedures/{name}/module
 	
 	// Auth
 	AuthDisabled    bool
@@ -68,6 +69,7 @@ func LoadFromEnv() (*Config, error) {
 		TxIdleTimeout:   getDuration("TX_IDLE_TIMEOUT_MS", 60000*time.Millisecond),
 		MaxOpenTx:       getInt("MAX_OPEN_TX", 100),
 		MaxTxPerClient:  getInt("MAX_TX_PER_CLIENT", 5),
+		MaxModuleSize:   int64(getInt("MAX_MODULE_SIZE", 64<<20)),
 		AuthDisabled:      getBool("AUTH_DISABLED", false),
 		APIKey:            os.Getenv("API_KEY"),
 		SessionTimeout:    getDuration("SESSION_TIMEOUT", 15*time.Minute),
@@ -3,12 +3,21 @@ package db
 import (
 	"context"
 	"encoding/base64"
+	"errors"
 	"fmt"
 	"strings"
 
+	chimiddleware "github.com/go-chi/chi/v5/middleware"
+	"github.com/rs/zerolog"
 	"github.com/sausheong/mindb/src/core"
 )
 
+// Errors UploadProcedure wraps when the module itself is at fault
+var (
+	ErrInvalidModule = errors.New("invalid procedure module")    // Not WASM, or unreadable metadata
+	ErrNoSignature   = errors.New("module records no signature") // No #[procedure] signature for the name
+)
+
 // Adapter wraps mindb.EngineAdapter with context-aware operations
 type Adapter struct {
 	engine *mindb.EngineAdapter
@@ -51,8 +60,8 @@ func (a *Adapter) Query(ctx context.Context, sql string, args []interface{}, lim
 		return nil, nil, fmt.Errorf("parse error: %w", err)
 	}
 
-	// Execute query
-	result, err := a.engine.Execute(stmt)
+	// Execute query; the request ID tags the log lines of procedures it runs
+	result, err := a.engine.ExecuteRequest(chimiddleware.GetReqID(ctx), stmt)
 	if err != nil {
 		return nil, nil, fmt.Errorf("query error: %w", err)
 	}
@@ -96,8 +105,8 @@ func (a *Adapter) Execute(ctx context.Context, sql string, args []interface{}, d
 		return 0, nil, fmt.Errorf("parse error: %w", err)
 	}
 
-	// Execute statement
-	result, err := a.engine.Execute(stmt)
+	// Execute statement; the request ID tags the log lines of procedures it runs
+	result, err := a.engine.ExecuteRequest(chimiddleware.GetReqID(ctx), stmt)
 	if err != nil {
 		return 0, nil, fmt.Errorf("execution error: %w", err)
 	}
@@ -287,6 +296,49 @@ func (a *Adapter) CreateProcedure(ctx context.Context, database, name, language,
 	return a.engine.CreateProcedureViaAdapter(proc)
 }
 
+// UploadProcedure creates a stored procedure from raw WASM bytecode, or
+// replaces it with a new version, and returns the version number. The
+// parameters, return type and description come from the signature the
+// module records for the procedure.
+// A module that cannot be used fails with ErrInvalidModule,
+// ErrNoSignature or mindb.ErrModuleCompile.
+func (a *Adapter) UploadProcedure(ctx context.Context, database, name string, wasmBytes []byte) (int, error) {
+	// Check context
+	select {
+	case <-ctx.Done():
+		return 0, ctx.Err()
+	default:
+	}
+
+	// Switch database if specified
+	if database != "" {
+		if err := a.engine.UseDatabase(database); err != nil {
+			return 0, fmt.Errorf("use database error: %w", err)
+		}
+	}
+
+	signatures, err := mindb.ParseProcedureMetadata(wasmBytes)
+	if err != nil {
+		return 0, fmt.Errorf("%w: %w", ErrInvalidModule, err)
+	}
+	if sig, ok := signatures[name]; !ok || sig.Kind != "" {
+		return 0, fmt.Errorf("%w for procedure '%s'", ErrNoSignature, name)
+	}
+
+	// A new version keeps the SECURITY, owner and NULL handling of the one
+	// it replaces
+	proc := &mindb.StoredProcedure{
+		Name:     name,
+		Language: "wasm",
+		Code:     wasmBytes,
+	}
+	if err := a.engine.ReplaceProcedureCodeViaAdapter(proc); err != nil {
+		return 0, err
+	}
+
+	return proc.Version, nil
+}
+
 // DropProcedure drops a stored procedure
 func (a *Adapter) DropProcedure(ctx context.Context, database, name string) error {
 	// Check context
@@ -341,6 +393,9 @@ func (a *Adapter) ListProcedures(ctx context.Context, database string) ([]interf
 			"language":    proc.Language,
 			"params":      params,
 			"return_type": proc.ReturnType,
+			"on_null":     proc.OnNull,
+			"security":    proc.Security,
+			"owner":       proc.Owner,
 			"description": proc.Description,
 			"created_at":  proc.CreatedAt.Format("2006-01-02T15:04:05Z07:00"),
 			"updated_at":  proc.UpdatedAt.Format("2006-01-02T15:04:05Z07:00"),
@@ -366,7 +421,8 @@ func (a *Adapter) CallProcedure(ctx context.Context, database, name string, args
 		}
 	}
 
-	return a.engine.CallProcedureViaAdapter(name, args...)
+	// The request ID tags the log lines of procedures this call runs
+	return a.engine.CallProcedureRequest(chimiddleware.GetReqID(ctx), name, args...)
 }
 
 // Authenticate authenticates a user
@@ -379,6 +435,11 @@ func (a *Adapter) SetCurrentUser(username, host string) {
 	a.engine.SetCurrentUser(username, host)
 }
 
+// SetLogger sets the logger stored procedures write to
+func (a *Adapter) SetLogger(logger zerolog.Logger) {
+	a.engine.SetLogger(logger)
+}
+
 // LogLoginSuccess logs a successful login
 func (a *Adapter) LogLoginSuccess(username, host string) {
 	a.engine.LogLoginSuccess(username, host)
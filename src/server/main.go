@@ -74,6 +74,7 @@ func run() error {
 		}
 	}()
 
+	database.SetLogger(logger.With().Str("component", "procedure").Logger())
 	logger.Info().Msg("database initialized")
 
 	// Create transaction manager
@@ -138,6 +139,7 @@ func run() error {
 	r.Delete("/procedures/{name}", handlers.DropProcedureHandler())    // Drop procedure
 	r.Get("/procedures", handlers.ListProceduresHandler())             // List procedures
 	r.Post("/procedures/{name}/call", handlers.CallProcedureHandler()) // Call procedure
+	r.Put("/procedures/{name}/module", handlers.UploadProcedureModuleHandler(cfg.MaxModuleSize)) // Upload raw module
 
 	// Streaming
 	r.Get("/stream", handlers.StreamHandler())